@@ -8,8 +8,8 @@ use std::thread::sleep;
 fn main() {
     let mut sense_hat = SenseHat::new().unwrap();
 
-    sense_hat.clear(Some((255, 127, 0)));
-    let mut gamma = sense_hat.gamma();
+    sense_hat.clear(Some((255, 127, 0))).unwrap();
+    let mut gamma = sense_hat.gamma().unwrap();
     println!("{:?}", gamma);
     sleep(Duration::from_secs(2));
 
@@ -18,9 +18,9 @@ fn main() {
     println!("{:?}", gamma);
     sleep(Duration::from_secs(2));
 
-    sense_hat.low_light(true);
+    sense_hat.low_light(true).unwrap();
     println!("{:?}", gamma);
     sleep(Duration::from_secs(2));
 
-    sense_hat.low_light(false);
+    sense_hat.low_light(false).unwrap();
 }
\ No newline at end of file
@@ -8,4 +8,6 @@ fn main() {
     println!("It's {} on the humidity sensor", temp);
     let temp = sense_hat.get_temperature_from_pressure().unwrap();
     println!("It's {} on the pressure sensor", temp);
+    let temp = sense_hat.get_temperature_from_imu().unwrap();
+    println!("It's {} on the IMU", temp);
 }
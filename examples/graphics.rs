@@ -0,0 +1,37 @@
+extern crate embedded_graphics;
+extern crate sensehat;
+
+use embedded_graphics::mono_font::ascii::FONT_6X10;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::Rgb888;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Circle, PrimitiveStyle};
+use embedded_graphics::text::{Baseline, Text};
+use sensehat::Display;
+
+use std::thread::sleep;
+use std::time::Duration;
+
+fn main() {
+    let mut display = Display::new().unwrap();
+
+    // A circle filling the matrix.
+    DrawTarget::clear(&mut display, Rgb888::BLACK).unwrap();
+    Circle::new(Point::zero(), 8)
+        .into_styled(PrimitiveStyle::with_stroke(Rgb888::CYAN, 1))
+        .draw(&mut display)
+        .unwrap();
+    sleep(Duration::from_secs(2));
+
+    // Text scrolled past, a column at a time.
+    let message = "Hello!";
+    let style = MonoTextStyle::new(&FONT_6X10, Rgb888::YELLOW);
+    let width = 6 * message.len() as i32;
+    for x in (-width..8).rev() {
+        DrawTarget::clear(&mut display, Rgb888::BLACK).unwrap();
+        Text::with_baseline(message, Point::new(x, -1), style, Baseline::Top)
+            .draw(&mut display)
+            .unwrap();
+        sleep(Duration::from_millis(80));
+    }
+}
@@ -1,7 +1,27 @@
-extern crate sensehat;
-
-use sensehat::*;
-
-fn main() {
-    let mut sense_hat = SenseHat::new().expect("Failed to create Sense Hat");
+extern crate sensehat;
+
+use sensehat::*;
+
+fn main() {
+    let mut sense_hat = SenseHat::new().expect("Failed to create Sense Hat");
+    let imu = sense_hat.imu().expect("IMU not available");
+    match imu.read_accelerometer() {
+        Ok([x, y, z]) => println!("Acceleration: x {:.3}g, y {:.3}g, z {:.3}g", x, y, z),
+        Err(e) => println!("Couldn't read the accelerometer: {}", e),
+    }
+    match imu.read_gyroscope() {
+        Ok([x, y, z]) => println!("Angular rate: x {:.1}dps, y {:.1}dps, z {:.1}dps", x, y, z),
+        Err(e) => println!("Couldn't read the gyroscope: {}", e),
+    }
+    match imu.read_magnetometer() {
+        Ok([x, y, z]) => println!("Magnetic field: x {:.1}uT, y {:.1}uT, z {:.1}uT", x, y, z),
+        Err(e) => println!("Couldn't read the magnetometer: {}", e),
+    }
+    loop {
+        match imu.get_orientation() {
+            Ok(o) => println!("Orientation: pitch {}, roll {}, yaw {}", o.pitch, o.roll, o.yaw),
+            Err(e) => println!("Couldn't read the orientation: {}", e),
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,16 @@
+extern crate sensehat;
+
+use sensehat::Joystick;
+
+use std::time::Duration;
+use std::thread::sleep;
+
+fn main() {
+    let mut joystick = Joystick::new().expect("Couldn't find the joystick");
+    loop {
+        for event in joystick.get_events().unwrap() {
+            println!("{:?} {:?}", event.direction, event.action);
+        }
+        sleep(Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,48 @@
+extern crate mio;
+extern crate sensehat;
+
+use mio::{Events, Interest, Poll, Token};
+use mio::net::TcpListener;
+use sensehat::Joystick;
+
+use std::io::Write;
+
+const JOYSTICK: Token = Token(0);
+const LISTENER: Token = Token(1);
+
+fn main() {
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let mut joystick = Joystick::new().unwrap();
+    poll.registry().register(&mut joystick, JOYSTICK, Interest::READABLE).unwrap();
+
+    let mut listener = TcpListener::bind("0.0.0.0:9000".parse().unwrap()).unwrap();
+    poll.registry().register(&mut listener, LISTENER, Interest::READABLE).unwrap();
+
+    let mut clients = Vec::new();
+
+    loop {
+        poll.poll(&mut events, None).unwrap();
+        for event in events.iter() {
+            match event.token() {
+                JOYSTICK => {
+                    for stick in joystick.drain_events().unwrap() {
+                        let line = format!("{:?} {:?}\n", stick.direction, stick.action);
+                        // Forget about clients which have gone away
+                        clients.retain(|client: &mio::net::TcpStream| {
+                            (&*client).write_all(line.as_bytes()).is_ok()
+                        });
+                    }
+                }
+                LISTENER => {
+                    while let Ok((client, address)) = listener.accept() {
+                        println!("{} connected", address);
+                        clients.push(client);
+                    }
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+}
@@ -0,0 +1,17 @@
+extern crate sensehat;
+
+use sensehat::recorder::JsonLinesRecorder;
+use sensehat::SenseHat;
+
+use std::io;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+fn main() {
+    let mut sense_hat = SenseHat::new().expect("Couldn't create Sense Hat object");
+    let stop = AtomicBool::new(false);
+    let mut recorder = JsonLinesRecorder::new(io::stdout());
+    recorder
+        .run(&mut sense_hat, Duration::from_secs(1), &stop)
+        .expect("Couldn't write readings");
+}
@@ -8,8 +8,8 @@ use std::thread::sleep;
 fn main() {
     let mut sense_hat = SenseHat::new().unwrap();
 
-    sense_hat.clear(None);
-    sense_hat.set_rotation(Orientation::Deg270, false);
+    sense_hat.clear(None).unwrap();
+    sense_hat.set_rotation(Orientation::Deg270, false).unwrap();
 
     let color = (255, 0, 0);
     for i in 0..8 {
@@ -17,11 +17,11 @@ fn main() {
     }
 
     sleep(Duration::from_millis(1000));
-    sense_hat.set_rotation(Orientation::Deg0, true);
+    sense_hat.set_rotation(Orientation::Deg0, true).unwrap();
     sleep(Duration::from_millis(1000));
-    sense_hat.set_rotation(Orientation::Deg180, true);
+    sense_hat.set_rotation(Orientation::Deg180, true).unwrap();
     sleep(Duration::from_millis(1000));
-    sense_hat.set_rotation(Orientation::Deg90, true);
+    sense_hat.set_rotation(Orientation::Deg90, true).unwrap();
     sleep(Duration::from_millis(1000));
-    sense_hat.clear(None);
+    sense_hat.clear(None).unwrap();
 }
\ No newline at end of file
@@ -0,0 +1,48 @@
+extern crate sensehat;
+
+use sensehat::*;
+
+use std::thread;
+use std::time::Duration;
+
+fn main() {
+    let simulator = SimulatorDisplay::new();
+    let layout = RasterLayout::default();
+    let mut window = SimulatorWindow::open("Sense HAT", &simulator, layout).unwrap();
+    let mut joystick = window.joystick().unwrap();
+
+    // The window has to stay on the main thread, so draw from another.
+    // The arrow keys move the dot, and Enter changes its colour.
+    thread::spawn(move || {
+        let mut sense_hat = SenseHat::builder().led_matrix(simulator).build().unwrap();
+        let colours = [(255, 0, 0), (0, 255, 0), (0, 0, 255), (255, 255, 255)];
+        let (mut x, mut y, mut colour) = (3, 3, 0);
+        loop {
+            sense_hat.clear(None).unwrap();
+            // Stops once the window is closed.
+            if sense_hat.set_pixel(x, y, colours[colour]).is_err() {
+                return;
+            }
+            let event = match joystick.wait_for_event(Some(Duration::from_millis(100))) {
+                Ok(Some(event)) => event,
+                Ok(None) => continue,
+                Err(_) => return,
+            };
+            // Holding an arrow key keeps the dot moving.
+            let repeat = match event.action {
+                Action::Pressed => false,
+                Action::Held(_) => true,
+                _ => continue,
+            };
+            match event.direction {
+                Direction::Up => y = y.saturating_sub(1),
+                Direction::Down => y = (y + 1).min(7),
+                Direction::Left => x = x.saturating_sub(1),
+                Direction::Right => x = (x + 1).min(7),
+                Direction::Enter if !repeat => colour = (colour + 1) % colours.len(),
+                Direction::Enter => {}
+            }
+        }
+    });
+    window.run().unwrap();
+}
@@ -0,0 +1,63 @@
+extern crate sensehat;
+
+use sensehat::{Direction, Joystick, SenseHat};
+
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::thread::sleep;
+
+fn main() {
+    let mut sense_hat = SenseHat::new().unwrap();
+    let mut joystick = Joystick::new().unwrap();
+
+    let green = (0, 255, 0);
+    let red = (255, 0, 0);
+
+    let mut seed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().subsec_nanos() as usize;
+    let mut random = move || {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        (seed >> 16) % 8
+    };
+
+    let mut snake: VecDeque<(usize, usize)> = VecDeque::new();
+    snake.push_back((3, 3));
+    let mut heading = Direction::Right;
+    let mut apple = (random(), random());
+
+    loop {
+        // Use the most recent direction pressed since the last step
+        while let Some(event) = joystick.poll_event().unwrap() {
+            if event.direction != Direction::Enter {
+                heading = event.direction;
+            }
+        }
+
+        let (x, y) = *snake.front().unwrap();
+        let head = match heading {
+            Direction::Up => (x, (y + 7) % 8),
+            Direction::Down => (x, (y + 1) % 8),
+            Direction::Left => ((x + 7) % 8, y),
+            _ => ((x + 1) % 8, y),
+        };
+        if snake.contains(&head) {
+            break;
+        }
+        snake.push_front(head);
+        if head == apple {
+            apple = (random(), random());
+        } else {
+            snake.pop_back();
+        }
+
+        sense_hat.clear(None).unwrap();
+        for &(x, y) in snake.iter() {
+            sense_hat.set_pixel(x, y, green).unwrap();
+        }
+        sense_hat.set_pixel(apple.0, apple.1, red).unwrap();
+
+        sleep(Duration::from_millis(250));
+    }
+
+    println!("Game over, your snake was {} long", snake.len());
+    sense_hat.clear(None).unwrap();
+}
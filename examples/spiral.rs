@@ -13,8 +13,8 @@ fn main() {
     let b = (0, 0, 255);
     let w = (255, 255, 255);
     let l = [r, g, b, w];
-    sense_hat.clear(None);
-    sense_hat.low_light(true);
+    sense_hat.clear(None).unwrap();
+    sense_hat.low_light(true).unwrap();
 
     loop {
         for x in l.iter() {
@@ -0,0 +1,671 @@
+use display::{Display, Pixel};
+use rle;
+use {SenseHatError, SenseHatResult};
+
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The most colours a palette can have, as indices are a byte.
+const MAX_COLOURS: usize = 256;
+
+/// What an animation file starts with.
+const MAGIC: &[u8; 4] = b"SHAN";
+
+/// The version of the animation file format written.
+const VERSION: u16 = 1;
+
+/// The flag saying an animation file has a palette.
+const FLAG_PALETTE: u16 = 1;
+
+/// An 8x8 image stored as a palette and, for each pixel, the index of its
+/// colour in the palette, row by row from the top left.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::PalettedFrame;
+///
+/// let red = (255, 0, 0);
+/// let mut pixels = [(0, 0, 0); 64];
+/// for pixel in pixels.iter_mut().step_by(3) {
+///     *pixel = red;
+/// }
+/// let frame = PalettedFrame::from_pixels(&pixels);
+/// // Each colour is only kept once, in the order they're first used.
+/// assert_eq!(frame.palette, [red, (0, 0, 0)]);
+/// assert_eq!(frame.indices[..4], [0, 1, 1, 0]);
+/// assert_eq!(frame.to_pixels().unwrap()[..], pixels[..]);
+///
+/// let broken = PalettedFrame { palette: vec![red], indices: [1; 64] };
+/// assert!(broken.to_pixels().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PalettedFrame {
+    pub palette: Vec<Pixel>,
+    pub indices: [u8; 64],
+}
+
+/// A sequence of 8x8 frames, each shown for its own time, all sharing one
+/// palette of up to 256 colours.
+///
+/// Frames are kept as palette indices, so a frame takes 64 bytes rather
+/// than 192. `Display::play_animation` shows one.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use sensehat::{art_indices, Animation};
+///
+/// const OFF: [u8; 64] = [0; 64];
+/// const DOT: [u8; 64] = art_indices(
+///     "........
+///      ........
+///      ........
+///      ...##...
+///      ...##...
+///      ........
+///      ........
+///      ........",
+///     ".#",
+/// );
+/// let palette = vec![(0, 0, 0), (0, 255, 0)];
+/// let durations = vec![Duration::from_millis(500); 2];
+/// let blink = Animation::from_paletted(vec![DOT, OFF], palette, durations).unwrap();
+///
+/// assert_eq!(blink.len(), 2);
+/// assert_eq!(blink.frame(0).unwrap()[27], (0, 255, 0));
+/// assert_eq!(blink.total_duration(), Duration::from_secs(1));
+///
+/// // An animation can be built up a frame at a time too, sharing colours.
+/// let mut built = Animation::new();
+/// for (pixels, duration) in blink.iter() {
+///     built.push(&pixels, duration).unwrap();
+/// }
+/// assert_eq!(built, blink);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Animation {
+    palette: Vec<Pixel>,
+    frames: Vec<([u8; 64], Duration)>,
+}
+
+impl PalettedFrame {
+    /// Makes a paletted frame out of an image, with each colour in the
+    /// palette once. An image can't have more than 64 colours, so it
+    /// always fits.
+    pub fn from_pixels(pixels: &[Pixel; 64]) -> Self {
+        let mut palette = Vec::new();
+        let mut indices = [0; 64];
+        for (index, &pixel) in indices.iter_mut().zip(pixels.iter()) {
+            *index = palette_index(&mut palette, pixel).expect("64 colours fit in a palette");
+        }
+        PalettedFrame { palette, indices }
+    }
+
+    /// Returns the image. Returns `InvalidPaletteIndex` if an index is past
+    /// the end of the palette.
+    pub fn to_pixels(&self) -> SenseHatResult<[Pixel; 64]> {
+        look_up(&self.palette, &self.indices)
+    }
+}
+
+impl Animation {
+    /// Makes an animation with no frames.
+    pub fn new() -> Self {
+        Animation::default()
+    }
+
+    /// Makes an animation out of frames of palette indices, with how long
+    /// each is shown for.
+    ///
+    /// Returns `TooManyColours` if the palette has more than 256 colours,
+    /// `InvalidPaletteIndex` if a frame uses an index past the end of the
+    /// palette, and an io `InvalidInput` error if there isn't one duration
+    /// for each frame.
+    pub fn from_paletted(
+        frames: Vec<[u8; 64]>,
+        palette: Vec<Pixel>,
+        durations: Vec<Duration>,
+    ) -> SenseHatResult<Self> {
+        if palette.len() > MAX_COLOURS {
+            return Err(SenseHatError::TooManyColours(palette.len()));
+        }
+        if frames.len() != durations.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} frames but {} durations", frames.len(), durations.len()),
+            )
+            .into());
+        }
+        for indices in &frames {
+            check_indices(&palette, indices)?;
+        }
+        Ok(Animation {
+            palette,
+            frames: frames.into_iter().zip(durations).collect(),
+        })
+    }
+
+    /// Adds a frame to the end, shown for `duration`, adding any colours
+    /// it uses to the palette. Returns `TooManyColours`, leaving the
+    /// animation as it was, if that would make more than 256.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use sensehat::{Animation, ErrorKind};
+    ///
+    /// let mut animation = Animation::new();
+    /// let tick = Duration::from_millis(10);
+    /// for frame in 0..4u8 {
+    ///     let mut pixels = [(0, 0, 0); 64];
+    ///     for (i, pixel) in pixels.iter_mut().enumerate() {
+    ///         *pixel = (frame, i as u8, 0);
+    ///     }
+    ///     animation.push(&pixels, tick).unwrap();
+    ///     // The same frame again adds no colours.
+    ///     animation.push(&pixels, tick).unwrap();
+    /// }
+    /// assert_eq!((animation.len(), animation.palette().len()), (8, 256));
+    ///
+    /// let err = animation.push(&[(9, 9, 9); 64], tick).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::TooManyColours);
+    /// assert_eq!((animation.len(), animation.palette().len()), (8, 256));
+    /// ```
+    pub fn push(&mut self, pixels: &[Pixel; 64], duration: Duration) -> SenseHatResult<()> {
+        let colours = self.palette.len();
+        let mut indices = [0; 64];
+        for (index, &pixel) in indices.iter_mut().zip(pixels.iter()) {
+            match palette_index(&mut self.palette, pixel) {
+                Some(i) => *index = i,
+                None => {
+                    self.palette.truncate(colours);
+                    let mut new = Vec::new();
+                    for &pixel in pixels.iter() {
+                        if !self.palette.contains(&pixel) && !new.contains(&pixel) {
+                            new.push(pixel);
+                        }
+                    }
+                    return Err(SenseHatError::TooManyColours(colours + new.len()));
+                }
+            }
+        }
+        self.frames.push((indices, duration));
+        Ok(())
+    }
+
+    /// Returns the colours the frames use.
+    pub fn palette(&self) -> &[Pixel] {
+        &self.palette
+    }
+
+    /// Returns how many frames there are.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns true if there are no frames.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Returns the image for a frame, or None if there aren't that many.
+    pub fn frame(&self, index: usize) -> Option<[Pixel; 64]> {
+        self.frames.get(index).map(|(indices, _)| self.pixels(indices))
+    }
+
+    /// Returns the palette indices for a frame, or None if there aren't
+    /// that many.
+    pub fn indices(&self, index: usize) -> Option<&[u8; 64]> {
+        self.frames.get(index).map(|(indices, _)| indices)
+    }
+
+    /// Returns how long a frame is shown for, or None if there aren't that
+    /// many.
+    pub fn duration(&self, index: usize) -> Option<Duration> {
+        self.frames.get(index).map(|&(_, duration)| duration)
+    }
+
+    /// Returns how long the whole animation takes.
+    pub fn total_duration(&self) -> Duration {
+        self.frames.iter().map(|&(_, duration)| duration).sum()
+    }
+
+    /// Returns every frame's image, with how long it's shown for.
+    pub fn iter(&self) -> impl Iterator<Item = ([Pixel; 64], Duration)> + '_ {
+        self.frames
+            .iter()
+            .map(move |&(ref indices, duration)| (self.pixels(indices), duration))
+    }
+
+    /// Returns which frame is showing `elapsed` after the animation
+    /// started, or None once it's over. Frames with no duration are never
+    /// showing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use sensehat::Animation;
+    ///
+    /// let ms = Duration::from_millis;
+    /// let mut animation = Animation::new();
+    /// for &duration in &[100, 0, 50] {
+    ///     animation.push(&[(0, 0, 0); 64], ms(duration)).unwrap();
+    /// }
+    /// assert_eq!(animation.frame_at(ms(0)), Some(0));
+    /// assert_eq!(animation.frame_at(ms(99)), Some(0));
+    /// assert_eq!(animation.frame_at(ms(100)), Some(2));
+    /// assert_eq!(animation.frame_at(ms(150)), None);
+    /// ```
+    pub fn frame_at(&self, elapsed: Duration) -> Option<usize> {
+        let mut end = Duration::default();
+        for (index, &(_, duration)) in self.frames.iter().enumerate() {
+            end += duration;
+            if elapsed < end {
+                return Some(index);
+            }
+        }
+        None
+    }
+
+    /// Saves the animation to a file, replacing it if it's there. See
+    /// `write_to` for the format.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> SenseHatResult<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Loads an animation saved by `save`.
+    pub fn load<P: AsRef<Path>>(path: P) -> SenseHatResult<Self> {
+        Animation::read_from(File::open(path)?)
+    }
+
+    /// Writes the animation out in the animation file format.
+    ///
+    /// A file is a header, perhaps a palette, then each frame in turn,
+    /// with every number little-endian. The header is 12 bytes:
+    ///
+    /// | Bytes  | Contents                                          |
+    /// |--------|---------------------------------------------------|
+    /// | 0..4   | `SHAN`                                            |
+    /// | 4..6   | The format version, 1 (u16)                       |
+    /// | 6..8   | Flags (u16): 1 if there's a palette               |
+    /// | 8..12  | How many frames there are (u32)                   |
+    ///
+    /// If there's a palette, it's how many colours it has (u16, up to
+    /// 256), then three bytes for each colour, red, green and blue.
+    ///
+    /// Each frame is how long it's shown for in milliseconds (u32), how
+    /// many bytes its pixels take (u16), then its pixels run-length
+    /// encoded as the `rle` module does. Decoded, they're a palette index
+    /// a pixel if there's a palette, or red, green and blue bytes a pixel
+    /// if not. Either way they're row by row from the top left.
+    ///
+    /// Animations are always written with a palette, and durations are
+    /// rounded down to the millisecond. Returns an io `InvalidInput` error
+    /// if a frame is longer than a u32 of milliseconds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use sensehat::Animation;
+    ///
+    /// let fixture = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/blink.shan"));
+    ///
+    /// let mut pixels = [(0, 0, 0); 64];
+    /// pixels[27] = (255, 0, 0);
+    /// let mut blink = Animation::new();
+    /// blink.push(&pixels, Duration::from_millis(250)).unwrap();
+    /// blink.push(&[(0, 0, 0); 64], Duration::from_millis(750)).unwrap();
+    ///
+    /// // The format doesn't change without anyone noticing.
+    /// let mut written = Vec::new();
+    /// blink.write_to(&mut written).unwrap();
+    /// assert_eq!(written, &fixture[..]);
+    /// assert_eq!(Animation::read_from(&fixture[..]).unwrap(), blink);
+    ///
+    /// // Random animations round trip.
+    /// let mut seed = 7u32;
+    /// let mut random = || {
+    ///     seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+    ///     (seed >> 16) as u8
+    /// };
+    /// for frames in 0..10 {
+    ///     let mut animation = Animation::new();
+    ///     for _ in 0..frames {
+    ///         let mut pixels = [(0, 0, 0); 64];
+    ///         for pixel in pixels.iter_mut() {
+    ///             // A few colours, so there are runs.
+    ///             let level = random() & 0xc0;
+    ///             *pixel = (level, 0, 255 - level);
+    ///         }
+    ///         let duration = Duration::from_millis(u64::from(random()) * 10);
+    ///         animation.push(&pixels, duration).unwrap();
+    ///     }
+    ///     let mut written = Vec::new();
+    ///     animation.write_to(&mut written).unwrap();
+    ///     assert_eq!(Animation::read_from(&written[..]).unwrap(), animation);
+    /// }
+    ///
+    /// // Files which aren't right are turned away.
+    /// assert!(Animation::read_from(&fixture[..fixture.len() - 1]).is_err());
+    /// assert!(Animation::read_from(&b"SHAN\x02\x00"[..]).is_err());
+    /// let mut unknown = fixture.to_vec();
+    /// unknown[4] = 9;
+    /// let err = Animation::read_from(&unknown[..]).unwrap_err();
+    /// assert!(err.to_string().contains("version 9"), "{}", err);
+    /// let mut extra = fixture.to_vec();
+    /// extra.push(0);
+    /// assert!(Animation::read_from(&extra[..]).is_err());
+    /// ```
+    pub fn write_to<W: Write>(&self, mut writer: W) -> SenseHatResult<()> {
+        let mut data = Vec::new();
+        data.extend_from_slice(MAGIC);
+        data.extend_from_slice(&VERSION.to_le_bytes());
+        data.extend_from_slice(&FLAG_PALETTE.to_le_bytes());
+        data.extend_from_slice(&(self.frames.len() as u32).to_le_bytes());
+        data.extend_from_slice(&(self.palette.len() as u16).to_le_bytes());
+        for &(r, g, b) in &self.palette {
+            data.extend_from_slice(&[r, g, b]);
+        }
+        for (indices, duration) in &self.frames {
+            let millis = match u32::try_from(duration.as_millis()) {
+                Ok(millis) => millis,
+                Err(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("frame of {:?} is too long to save", duration),
+                    )
+                    .into())
+                }
+            };
+            let encoded = rle::encode(indices);
+            data.extend_from_slice(&millis.to_le_bytes());
+            data.extend_from_slice(&(encoded.len() as u16).to_le_bytes());
+            data.extend_from_slice(&encoded);
+        }
+        writer.write_all(&data)?;
+        Ok(())
+    }
+
+    /// Reads an animation in the format `write_to` writes. Returns an io
+    /// `InvalidData` error saying what's wrong if it isn't one, is cut
+    /// short, or is a version this doesn't understand.
+    pub fn read_from<R: Read>(mut reader: R) -> SenseHatResult<Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let mut data = &data[..];
+        if take(&mut data, 4, "magic")? != MAGIC {
+            return Err(invalid_animation("not an animation file"));
+        }
+        let version = read_u16(&mut data, "version")?;
+        if version != VERSION {
+            return Err(invalid_animation(&format!("unsupported version {}", version)));
+        }
+        let flags = read_u16(&mut data, "flags")?;
+        if flags & !FLAG_PALETTE != 0 {
+            return Err(invalid_animation(&format!("unknown flags {:#x}", flags)));
+        }
+        let count = read_u32(&mut data, "frame count")?;
+        let palette = if flags & FLAG_PALETTE != 0 {
+            let colours = read_u16(&mut data, "palette size")? as usize;
+            if colours > MAX_COLOURS {
+                return Err(invalid_animation(&format!("palette of {} colours", colours)));
+            }
+            let colours = take(&mut data, colours * 3, "palette")?;
+            Some(colours.chunks(3).map(|c| (c[0], c[1], c[2])).collect::<Vec<_>>())
+        } else {
+            None
+        };
+        let mut animation = Animation::new();
+        for frame in 0..count {
+            let what = format!("frame {}", frame);
+            let duration = Duration::from_millis(u64::from(read_u32(&mut data, &what)?));
+            let len = read_u16(&mut data, &what)? as usize;
+            let decoded = rle::decode(take(&mut data, len, &what)?)
+                .map_err(|err| invalid_animation(&format!("{}: {}", what, err)))?;
+            match palette {
+                Some(ref palette) => {
+                    let indices = <[u8; 64]>::try_from(&decoded[..])
+                        .map_err(|_| invalid_animation(&format!("{} isn't 64 pixels", what)))?;
+                    check_indices(palette, &indices)
+                        .map_err(|err| invalid_animation(&format!("{}: {}", what, err)))?;
+                    animation.frames.push((indices, duration));
+                }
+                None => {
+                    if decoded.len() != 64 * 3 {
+                        return Err(invalid_animation(&format!("{} isn't 64 pixels", what)));
+                    }
+                    let mut pixels = [(0, 0, 0); 64];
+                    for (pixel, c) in pixels.iter_mut().zip(decoded.chunks(3)) {
+                        *pixel = (c[0], c[1], c[2]);
+                    }
+                    animation.push(&pixels, duration)?;
+                }
+            }
+        }
+        if let Some(palette) = palette {
+            animation.palette = palette;
+        }
+        if !data.is_empty() {
+            return Err(invalid_animation("extra bytes after the last frame"));
+        }
+        Ok(animation)
+    }
+
+    /// Helper function.
+    ///
+    /// Looks up a frame's indices, which are known to be in the palette.
+    fn pixels(&self, indices: &[u8; 64]) -> [Pixel; 64] {
+        look_up(&self.palette, indices).expect("frame indices are checked against the palette")
+    }
+}
+
+impl Display {
+    /// Plays an animation on the LED matrix, blocking until it's over.
+    ///
+    /// Each frame is due when the ones before it have had their time, from
+    /// when playing started, so drawing doesn't make the animation drift.
+    /// If drawing falls behind, frames whose time has already gone by are
+    /// skipped. The last frame is left showing.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use sensehat::{Animation, Display};
+    ///
+    /// let mut display = Display::new().unwrap();
+    /// let mut fade = Animation::new();
+    /// for level in (0..=255).rev().step_by(5) {
+    ///     fade.push(&[(level, 0, level); 64], Duration::from_millis(20)).unwrap();
+    /// }
+    /// display.play_animation(&fade).unwrap();
+    /// ```
+    pub fn play_animation(&mut self, animation: &Animation) -> SenseHatResult<()> {
+        let start = Instant::now();
+        let mut shown = None;
+        loop {
+            let elapsed = start.elapsed();
+            let index = match animation.frame_at(elapsed) {
+                Some(index) => index,
+                None => break,
+            };
+            if shown != Some(index) {
+                self.set_pixels(&animation.pixels(&animation.frames[index].0))?;
+                shown = Some(index);
+            }
+            let end: Duration = animation.frames[..=index].iter().map(|&(_, d)| d).sum();
+            thread::sleep(end.saturating_sub(start.elapsed()));
+        }
+        // Even if there wasn't time for it, say because every frame has
+        // no duration.
+        if let Some(last) = animation.len().checked_sub(1) {
+            if shown != Some(last) {
+                self.set_pixels(&animation.pixels(&animation.frames[last].0))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Turns a block of ASCII art into palette indices, for writing frames out
+/// in source. Each character of `art` is one pixel, row by row from the
+/// top left, and its index is where it comes in `keys`. Whitespace is
+/// skipped, so the art can be laid out in lines and indented.
+///
+/// It's a `const fn`, so in a `const` a mistake stops the build. Panics if
+/// there aren't 64 pixels, or a character isn't in `keys`. Only ASCII can
+/// be used.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{art_indices, PalettedFrame};
+///
+/// const SMILEY: [u8; 64] = art_indices(
+///     "..yyyy..
+///      .yyyyyy.
+///      yybyybyy
+///      yyyyyyyy
+///      ybyyyyby
+///      yybbbbyy
+///      .yyyyyy.
+///      ..yyyy..",
+///     ".yb",
+/// );
+/// assert_eq!(SMILEY[..8], [0, 0, 1, 1, 1, 1, 0, 0]);
+/// assert_eq!(SMILEY[18], 2);
+///
+/// let frame = PalettedFrame {
+///     palette: vec![(0, 0, 0), (255, 255, 0), (0, 0, 255)],
+///     indices: SMILEY,
+/// };
+/// assert_eq!(frame.to_pixels().unwrap()[18], (0, 0, 255));
+/// ```
+///
+/// ```should_panic
+/// sensehat::art_indices("........", ".");
+/// ```
+pub const fn art_indices(art: &str, keys: &str) -> [u8; 64] {
+    let art = art.as_bytes();
+    let keys = keys.as_bytes();
+    let mut indices = [0; 64];
+    let mut count = 0;
+    let mut i = 0;
+    while i < art.len() {
+        let c = art[i];
+        i += 1;
+        if c.is_ascii_whitespace() {
+            continue;
+        }
+        if count == 64 {
+            panic!("art has more than 64 pixels");
+        }
+        let mut key = 0;
+        while key < keys.len() && keys[key] != c {
+            key += 1;
+        }
+        if key == keys.len() {
+            panic!("art uses a character which isn't a key");
+        }
+        if key >= MAX_COLOURS {
+            panic!("art key is past the 256th");
+        }
+        indices[count] = key as u8;
+        count += 1;
+    }
+    if count != 64 {
+        panic!("art has fewer than 64 pixels");
+    }
+    indices
+}
+
+/// Helper function.
+///
+/// Takes the next `len` bytes of an animation file, or returns an error
+/// saying the file stopped in the middle of `what`.
+fn take<'a>(data: &mut &'a [u8], len: usize, what: &str) -> SenseHatResult<&'a [u8]> {
+    if data.len() < len {
+        return Err(invalid_animation(&format!("truncated in {}", what)));
+    }
+    let (taken, rest) = data.split_at(len);
+    *data = rest;
+    Ok(taken)
+}
+
+/// Helper function.
+///
+/// Reads a little-endian u16 from an animation file.
+fn read_u16(data: &mut &[u8], what: &str) -> SenseHatResult<u16> {
+    let bytes = take(data, 2, what)?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+/// Helper function.
+///
+/// Reads a little-endian u32 from an animation file.
+fn read_u32(data: &mut &[u8], what: &str) -> SenseHatResult<u32> {
+    let bytes = take(data, 4, what)?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Helper function.
+///
+/// Makes the error for a file which isn't an animation this version can
+/// read.
+fn invalid_animation(problem: &str) -> SenseHatError {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("bad animation file: {}", problem),
+    )
+    .into()
+}
+
+/// Helper function.
+///
+/// Returns the index of a colour in the palette, adding it if it isn't
+/// there, or None if the palette is full.
+fn palette_index(palette: &mut Vec<Pixel>, pixel: Pixel) -> Option<u8> {
+    if let Some(index) = palette.iter().position(|&colour| colour == pixel) {
+        return Some(index as u8);
+    }
+    if palette.len() == MAX_COLOURS {
+        return None;
+    }
+    palette.push(pixel);
+    Some((palette.len() - 1) as u8)
+}
+
+/// Helper function.
+///
+/// Returns `InvalidPaletteIndex` for the first index past the end of the
+/// palette.
+fn check_indices(palette: &[Pixel], indices: &[u8; 64]) -> SenseHatResult<()> {
+    match indices.iter().find(|&&index| index as usize >= palette.len()) {
+        Some(&index) => Err(SenseHatError::InvalidPaletteIndex(index)),
+        None => Ok(()),
+    }
+}
+
+/// Helper function.
+///
+/// Looks up each index in the palette.
+fn look_up(palette: &[Pixel], indices: &[u8; 64]) -> SenseHatResult<[Pixel; 64]> {
+    check_indices(palette, indices)?;
+    let mut pixels = [(0, 0, 0); 64];
+    for (pixel, &index) in pixels.iter_mut().zip(indices.iter()) {
+        *pixel = palette[index as usize];
+    }
+    Ok(pixels)
+}
@@ -0,0 +1,126 @@
+use display::Pixel;
+use {SenseHatError, SenseHatResult};
+
+use std::time::{Duration, Instant};
+
+/// One blinking pixel.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Blink {
+    x: usize,
+    y: usize,
+    on: Pixel,
+    off: Pixel,
+    period: Duration,
+    start: Instant,
+}
+
+impl Blink {
+    /// Helper function.
+    ///
+    /// Returns the colour the pixel should be at `now`: `on` for the first
+    /// half of each period, and `off` for the second.
+    fn pixel_at(&self, now: Instant) -> Pixel {
+        let period = self.period.as_nanos();
+        if period == 0 {
+            return self.on;
+        }
+        let elapsed = now.saturating_duration_since(self.start).as_nanos();
+        if elapsed % period < period / 2 {
+            self.on
+        } else {
+            self.off
+        }
+    }
+}
+
+/// Works out which colour each blinking pixel should be at any time, as
+/// used by `Display::set_blink` and `Display::tick`.
+///
+/// Each pixel is `on` for the first half of its period and `off` for the
+/// second, counting from when it was added. Nothing here reads the clock,
+/// so the times are whatever the caller passes in.
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use sensehat::BlinkSchedule;
+///
+/// let (red, black) = ((255, 0, 0), (0, 0, 0));
+/// let start = Instant::now();
+/// let at = |ms| start + Duration::from_millis(ms);
+///
+/// let mut schedule = BlinkSchedule::new();
+/// schedule.set(3, 4, red, black, Duration::from_millis(1000), start).unwrap();
+/// assert_eq!(schedule.pixels_at(at(0)), vec![(3, 4, red)]);
+/// assert_eq!(schedule.pixels_at(at(499)), vec![(3, 4, red)]);
+/// assert_eq!(schedule.pixels_at(at(500)), vec![(3, 4, black)]);
+/// assert_eq!(schedule.pixels_at(at(1200)), vec![(3, 4, red)]);
+///
+/// assert!(schedule.clear(3, 4));
+/// assert!(schedule.pixels_at(at(1200)).is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlinkSchedule {
+    blinks: Vec<Blink>,
+}
+
+impl BlinkSchedule {
+    pub fn new() -> Self {
+        BlinkSchedule::default()
+    }
+
+    /// Makes the pixel at (x, y) blink between `on` and `off` every
+    /// `period`, starting with `on` at `now`. Replaces any blink already
+    /// at that pixel. Returns an error if the coordinates are out of
+    /// bounds.
+    pub fn set(
+        &mut self,
+        x: usize,
+        y: usize,
+        on: Pixel,
+        off: Pixel,
+        period: Duration,
+        now: Instant,
+    ) -> SenseHatResult<()> {
+        if x > 7 || y > 7 {
+            return Err(SenseHatError::OutOfBounds);
+        }
+        self.clear(x, y);
+        self.blinks.push(Blink {
+            x,
+            y,
+            on,
+            off,
+            period,
+            start: now,
+        });
+        Ok(())
+    }
+
+    /// Stops the pixel at (x, y) blinking. Returns false if it wasn't.
+    pub fn clear(&mut self, x: usize, y: usize) -> bool {
+        let before = self.blinks.len();
+        self.blinks.retain(|blink| (blink.x, blink.y) != (x, y));
+        self.blinks.len() != before
+    }
+
+    /// Stops every pixel blinking.
+    pub fn clear_all(&mut self) {
+        self.blinks.clear();
+    }
+
+    /// Returns true if no pixels are blinking.
+    pub fn is_empty(&self) -> bool {
+        self.blinks.is_empty()
+    }
+
+    /// Returns the (x, y) coordinate and colour of each blinking pixel at
+    /// `now`, in the order they were added.
+    pub fn pixels_at(&self, now: Instant) -> Vec<(usize, usize, Pixel)> {
+        self.blinks
+            .iter()
+            .map(|blink| (blink.x, blink.y, blink.pixel_at(now)))
+            .collect()
+    }
+}
@@ -0,0 +1,421 @@
+use {SenseHatError, SenseHatResult};
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+/// How many of the eight octants around the centre of the samples need
+/// samples in them for the calibration to be trusted.
+const REQUIRED_OCTANTS: usize = 8;
+
+/// The hard and soft iron correction for the magnetometer.
+///
+/// Corrected readings are `(raw - offset) * scale` on each axis.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CompassCalibration {
+    /// The hard iron offset, in microtesla.
+    pub offset: [f64; 3],
+    /// The soft iron scale factors.
+    pub scale: [f64; 3],
+}
+
+impl CompassCalibration {
+    /// Works out the calibration from magnetometer readings, in microtesla,
+    /// taken while the board was rotated in every direction.
+    ///
+    /// The offset is the centre of the per-axis minimum and maximum, and
+    /// the scale stretches each axis to the average range. Returns
+    /// `InsufficientCoverage` unless there are samples in every octant
+    /// around that centre.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::CompassCalibration;
+    ///
+    /// // A 50uT field, offset by hard iron and squashed on the Z axis.
+    /// let mut samples = Vec::new();
+    /// for i in 0..36 {
+    ///     for j in 0..18 {
+    ///         let (a, b) = (i as f64 * 10.0f64.to_radians(), j as f64 * 10.0f64.to_radians());
+    ///         samples.push([
+    ///             50.0 * a.cos() * b.sin() + 10.0,
+    ///             50.0 * a.sin() * b.sin() - 5.0,
+    ///             25.0 * b.cos() + 20.0,
+    ///         ]);
+    ///     }
+    /// }
+    /// let cal = CompassCalibration::from_samples(&samples).unwrap();
+    /// assert!((cal.offset[0] - 10.0).abs() < 1.0);
+    /// assert!((cal.offset[2] - 20.0).abs() < 1.0);
+    /// assert!((cal.scale[2] / cal.scale[0] - 2.0).abs() < 0.1);
+    /// ```
+    pub fn from_samples(samples: &[[f64; 3]]) -> SenseHatResult<Self> {
+        let mut min = [f64::INFINITY; 3];
+        let mut max = [f64::NEG_INFINITY; 3];
+        for sample in samples {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(sample[axis]);
+                max[axis] = max[axis].max(sample[axis]);
+            }
+        }
+
+        let mut offset = [0.0; 3];
+        let mut radius = [0.0; 3];
+        for axis in 0..3 {
+            offset[axis] = (max[axis] + min[axis]) / 2.0;
+            radius[axis] = (max[axis] - min[axis]) / 2.0;
+        }
+
+        let mut octants = [false; 8];
+        for sample in samples {
+            let mut octant = 0;
+            for axis in 0..3 {
+                if sample[axis] >= offset[axis] {
+                    octant |= 1 << axis;
+                }
+            }
+            octants[octant] = true;
+        }
+        let covered = octants.iter().filter(|&&seen| seen).count();
+        if covered < REQUIRED_OCTANTS || radius.iter().any(|&r| r <= 0.0 || !r.is_finite()) {
+            return Err(SenseHatError::InsufficientCoverage {
+                octants: covered as u8,
+            });
+        }
+
+        let average = (radius[0] + radius[1] + radius[2]) / 3.0;
+        Ok(CompassCalibration {
+            offset,
+            scale: [average / radius[0], average / radius[1], average / radius[2]],
+        })
+    }
+
+    /// Works out the calibration from the smallest and largest readings on
+    /// each axis, in microtesla, the way RTIMULib does.
+    ///
+    /// The offset is the centre of each axis's range, and the scale
+    /// stretches each axis to the widest one, rather than to the average as
+    /// `from_samples` does, so headings are the same but field strengths
+    /// can differ by a constant factor.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::CompassCalibration;
+    ///
+    /// let cal = CompassCalibration::from_min_max([-40.0, -20.0, 0.0], [60.0, 30.0, 50.0]);
+    /// assert_eq!(cal.offset, [10.0, 5.0, 25.0]);
+    /// assert_eq!(cal.scale, [1.0, 2.0, 2.0]);
+    /// ```
+    pub fn from_min_max(min: [f64; 3], max: [f64; 3]) -> Self {
+        let mut offset = [0.0; 3];
+        let mut radius = [0.0; 3];
+        for axis in 0..3 {
+            offset[axis] = (max[axis] + min[axis]) / 2.0;
+            radius[axis] = (max[axis] - min[axis]) / 2.0;
+        }
+        let widest = radius[0].max(radius[1]).max(radius[2]);
+        CompassCalibration {
+            offset,
+            scale: [widest / radius[0], widest / radius[1], widest / radius[2]],
+        }
+    }
+
+    /// Returns true if this is the default, which corrects nothing, as it
+    /// is until the compass is calibrated.
+    pub fn is_identity(&self) -> bool {
+        *self == CompassCalibration::default()
+    }
+
+    /// Returns a reading with the calibration applied.
+    pub fn apply(&self, raw: [f64; 3]) -> [f64; 3] {
+        [
+            (raw[0] - self.offset[0]) * self.scale[0],
+            (raw[1] - self.offset[1]) * self.scale[1],
+            (raw[2] - self.offset[2]) * self.scale[2],
+        ]
+    }
+
+    /// Helper function.
+    ///
+    /// Writes the calibration in the format `Imu::save_calibration`
+    /// documents.
+    pub(crate) fn save(&self, path: &Path) -> SenseHatResult<()> {
+        let mut values = Vec::new();
+        for (name, axes) in &[("offset", self.offset), ("scale", self.scale)] {
+            for (axis, &value) in ["x", "y", "z"].iter().zip(axes.iter()) {
+                values.push((format!("compass_{}_{}", name, axis), value));
+            }
+        }
+        save_values(path, "Sense Hat compass calibration", &values)
+    }
+
+    /// Helper function.
+    ///
+    /// Reads a calibration written by `save`.
+    pub(crate) fn load(path: &Path) -> SenseHatResult<Self> {
+        let mut calibration = CompassCalibration::default();
+        let mut seen = 0;
+        for (key, value) in load_values(path)? {
+            let (values, axis) = match key.as_str() {
+                "compass_offset_x" => (&mut calibration.offset, 0),
+                "compass_offset_y" => (&mut calibration.offset, 1),
+                "compass_offset_z" => (&mut calibration.offset, 2),
+                "compass_scale_x" => (&mut calibration.scale, 0),
+                "compass_scale_y" => (&mut calibration.scale, 1),
+                "compass_scale_z" => (&mut calibration.scale, 2),
+                _ => return Err(invalid_data(&format!("{}={}", key, value))),
+            };
+            values[axis] = value;
+            seen += 1;
+        }
+        if seen == 0 {
+            return Err(invalid_data("no calibration values"));
+        }
+        Ok(calibration)
+    }
+}
+
+impl Default for CompassCalibration {
+    /// No correction at all.
+    fn default() -> Self {
+        CompassCalibration {
+            offset: [0.0; 3],
+            scale: [1.0; 3],
+        }
+    }
+}
+
+/// The magnetic field strengths, in microtesla, `check_compass` takes to be
+/// the Earth's.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MagneticFieldLimits {
+    /// The weakest field which counts, in microtesla.
+    pub min: f64,
+    /// The strongest field which counts, in microtesla.
+    pub max: f64,
+}
+
+impl Default for MagneticFieldLimits {
+    /// 20 to 80 microtesla, a little either side of the 25 to 65 the
+    /// Earth's field ranges over.
+    fn default() -> Self {
+        MagneticFieldLimits {
+            min: 20.0,
+            max: 80.0,
+        }
+    }
+}
+
+/// How far a compass heading can be trusted, from `check_compass`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CompassStatus {
+    /// The compass is calibrated and the field looks like the Earth's.
+    Calibrated,
+    /// The field looks like the Earth's, but the compass has never been
+    /// calibrated, so headings can be well out.
+    Uncalibrated,
+    /// The field, of this many microtesla, is too strong or too weak to be
+    /// the Earth's, so the heading is nonsense whatever the calibration.
+    Interference { magnitude: f64 },
+}
+
+/// Works out whether a compass heading taken from the magnetic field
+/// `field`, in microtesla, can be trusted, as
+/// `Imu::get_compass_heading_checked` does.
+///
+/// Interference wins over calibration, as no calibration fixes it.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{check_compass, CompassStatus, MagneticFieldLimits};
+///
+/// let limits = MagneticFieldLimits::default();
+/// // About the Earth's field in the UK.
+/// let earth = [17.0, -1.0, -45.0];
+/// assert_eq!(check_compass(earth, true, &limits), CompassStatus::Calibrated);
+/// assert_eq!(check_compass(earth, false, &limits), CompassStatus::Uncalibrated);
+/// assert_eq!(check_compass([0.0, 0.0, 25.0], true, &limits), CompassStatus::Calibrated);
+/// assert_eq!(check_compass([0.0, 0.0, -65.0], true, &limits), CompassStatus::Calibrated);
+///
+/// // Next to a speaker magnet, or shielded by steel.
+/// assert_eq!(
+///     check_compass([300.0, 0.0, 400.0], true, &limits),
+///     CompassStatus::Interference { magnitude: 500.0 }
+/// );
+/// assert_eq!(
+///     check_compass([3.0, 4.0, 0.0], false, &limits),
+///     CompassStatus::Interference { magnitude: 5.0 }
+/// );
+///
+/// // The limits can be changed, say for somewhere the field is unusual.
+/// let wide = MagneticFieldLimits { min: 1.0, max: 1000.0 };
+/// assert_eq!(check_compass([300.0, 0.0, 400.0], true, &wide), CompassStatus::Calibrated);
+/// ```
+pub fn check_compass(
+    field: [f64; 3],
+    calibrated: bool,
+    limits: &MagneticFieldLimits,
+) -> CompassStatus {
+    let magnitude = (field[0] * field[0] + field[1] * field[1] + field[2] * field[2]).sqrt();
+    if !(limits.min..=limits.max).contains(&magnitude) {
+        CompassStatus::Interference { magnitude }
+    } else if calibrated {
+        CompassStatus::Calibrated
+    } else {
+        CompassStatus::Uncalibrated
+    }
+}
+
+/// A soft iron correction for the magnetometer, from an ellipsoid fitted to
+/// readings by RTIMULib's calibration tool. It's applied after the
+/// `CompassCalibration`.
+///
+/// Corrected readings are `matrix * (reading - offset)`.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::CompassEllipsoid;
+///
+/// let ellipsoid = CompassEllipsoid {
+///     offset: [1.0, 0.0, 0.0],
+///     matrix: [[2.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 1.0, 1.0]],
+/// };
+/// assert_eq!(ellipsoid.apply([2.0, 3.0, 4.0]), [2.0, 3.0, 7.0]);
+/// assert_eq!(CompassEllipsoid::default().apply([2.0, 3.0, 4.0]), [2.0, 3.0, 4.0]);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CompassEllipsoid {
+    /// The centre of the ellipsoid, in microtesla.
+    pub offset: [f64; 3],
+    /// The matrix which turns the ellipsoid into a sphere, by rows.
+    pub matrix: [[f64; 3]; 3],
+}
+
+impl CompassEllipsoid {
+    /// Returns a reading with the correction applied.
+    pub fn apply(&self, reading: [f64; 3]) -> [f64; 3] {
+        let v = [
+            reading[0] - self.offset[0],
+            reading[1] - self.offset[1],
+            reading[2] - self.offset[2],
+        ];
+        let row = |r: [f64; 3]| r[0] * v[0] + r[1] * v[1] + r[2] * v[2];
+        [row(self.matrix[0]), row(self.matrix[1]), row(self.matrix[2])]
+    }
+}
+
+impl Default for CompassEllipsoid {
+    /// No correction at all.
+    fn default() -> Self {
+        CompassEllipsoid {
+            offset: [0.0; 3],
+            matrix: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        }
+    }
+}
+
+/// The accelerometer correction RTIMULib uses: the smallest and largest
+/// readings on each axis, in g, from when it was pointed straight down and
+/// straight up.
+///
+/// Corrected readings are divided by the largest reading on axes reading
+/// positive, and by minus the smallest on axes reading negative, so each
+/// axis reads exactly 1g either way up.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::AccelCalibration;
+///
+/// let cal = AccelCalibration { min: [-0.5, -1.0, -1.0], max: [2.0, 1.0, 1.0] };
+/// assert_eq!(cal.apply([-0.25, 0.5, 1.0]), [-0.5, 0.5, 1.0]);
+/// assert_eq!(cal.apply([1.0, -0.5, 0.0]), [0.5, -0.5, 0.0]);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AccelCalibration {
+    /// The reading on each axis pointing straight down, in g.
+    pub min: [f64; 3],
+    /// The reading on each axis pointing straight up, in g.
+    pub max: [f64; 3],
+}
+
+impl AccelCalibration {
+    /// Returns a reading with the calibration applied.
+    pub fn apply(&self, raw: [f64; 3]) -> [f64; 3] {
+        let mut corrected = raw;
+        for axis in 0..3 {
+            corrected[axis] = if raw[axis] >= 0.0 {
+                raw[axis] / self.max[axis]
+            } else {
+                raw[axis] / -self.min[axis]
+            };
+        }
+        corrected
+    }
+}
+
+impl Default for AccelCalibration {
+    /// No correction at all.
+    fn default() -> Self {
+        AccelCalibration {
+            min: [-1.0; 3],
+            max: [1.0; 3],
+        }
+    }
+}
+
+/// Helper function.
+///
+/// Writes a file of `key=value` lines under a `#` comment, as
+/// `Imu::save_calibration` and `Imu::save_tare` do.
+pub(crate) fn save_values(
+    path: &Path,
+    comment: &str,
+    values: &[(String, f64)],
+) -> SenseHatResult<()> {
+    let mut file = File::create(path)?;
+    writeln!(file, "# {}", comment)?;
+    for (key, value) in values {
+        writeln!(file, "{}={}", key, value)?;
+    }
+    Ok(())
+}
+
+/// Helper function.
+///
+/// Reads the `key=value` lines written by `save_values`, skipping blank
+/// lines and comments.
+pub(crate) fn load_values(path: &Path) -> SenseHatResult<Vec<(String, f64)>> {
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+
+    let mut values = Vec::new();
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.splitn(2, '=');
+        let key = parts.next().unwrap_or("").trim();
+        let value = parts
+            .next()
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .ok_or_else(|| invalid_data(line))?;
+        values.push((key.to_string(), value));
+    }
+    Ok(values)
+}
+
+/// Helper function.
+///
+/// Makes the error for a calibration file we can't parse.
+pub(crate) fn invalid_data(line: &str) -> SenseHatError {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("bad calibration line: {}", line),
+    )
+    .into()
+}
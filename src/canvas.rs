@@ -0,0 +1,154 @@
+use display::{Display, Pixel};
+use {SenseHatError, SenseHatResult};
+
+/// An image of any size, to show part of on the LED matrix with
+/// `Display::show_viewport`, say a map to scroll around with the joystick.
+///
+/// (0, 0) is the top left, like on the display.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::Canvas;
+///
+/// let red = (255, 0, 0);
+/// let mut canvas = Canvas::new(32, 32);
+/// canvas.set_pixel(31, 31, red).unwrap();
+/// assert!(canvas.set_pixel(32, 0, red).is_err());
+///
+/// // Offsets past the edge are clamped, so the window stays on the canvas.
+/// let corner = canvas.viewport(100, 100, false);
+/// assert_eq!(corner, canvas.viewport(24, 24, false));
+/// assert_eq!(corner[63], red);
+/// assert_eq!(canvas.viewport(-5, 0, false), canvas.viewport(0, 0, false));
+///
+/// // Or wrap around, so the bottom right corner is just above and left
+/// // of the top left.
+/// let wrapped = canvas.viewport(-1, -1, true);
+/// assert_eq!(wrapped[0], red);
+/// assert_eq!(wrapped, canvas.viewport(31, 31, true));
+/// assert_eq!(wrapped, canvas.viewport(63, -33, true));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    /// Row by row, from the top left.
+    pixels: Vec<Pixel>,
+}
+
+impl Canvas {
+    /// Makes a blank canvas `width` pixels across and `height` down.
+    pub fn new(width: usize, height: usize) -> Self {
+        Canvas {
+            width,
+            height,
+            pixels: vec![(0, 0, 0); width * height],
+        }
+    }
+
+    /// Returns how many pixels across the canvas is.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns how many pixels down the canvas is.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Sets a single pixel at the given coordinate.
+    /// Returns an error if the coordinates are out of bounds.
+    pub fn set_pixel(&mut self, x: usize, y: usize, p: Pixel) -> SenseHatResult<()> {
+        if x >= self.width || y >= self.height {
+            return Err(SenseHatError::OutOfBounds);
+        }
+        self.pixels[x + self.width * y] = p;
+        Ok(())
+    }
+
+    /// Returns a single pixel value at the given coordinate.
+    /// Returns an error if the coordinates are out of bounds.
+    pub fn get_pixel(&self, x: usize, y: usize) -> SenseHatResult<Pixel> {
+        if x >= self.width || y >= self.height {
+            return Err(SenseHatError::OutOfBounds);
+        }
+        Ok(self.pixels[x + self.width * y])
+    }
+
+    /// Sets the whole canvas to a single color, defaults to blank/off.
+    pub fn clear(&mut self, color: Option<Pixel>) {
+        let color = color.unwrap_or((0, 0, 0));
+        for p in self.pixels.iter_mut() {
+            *p = color;
+        }
+    }
+
+    /// Returns the 8x8 window of the canvas with its top left at (x, y).
+    ///
+    /// If `wrap` is false, the offset is clamped so the window stays on
+    /// the canvas. If it's true, the canvas repeats in every direction, so
+    /// any offset works. Pixels past the edge of a canvas smaller than 8x8
+    /// are blank.
+    pub fn viewport(&self, x: isize, y: isize, wrap: bool) -> [Pixel; 64] {
+        let mut out = [(0, 0, 0); 64];
+        if self.pixels.is_empty() {
+            return out;
+        }
+        let (left, top) = if wrap {
+            (x, y)
+        } else {
+            (clamp_offset(x, self.width), clamp_offset(y, self.height))
+        };
+        for (i, p) in out.iter_mut().enumerate() {
+            let cx = left + (i % 8) as isize;
+            let cy = top + (i / 8) as isize;
+            let (cx, cy) = if wrap {
+                (
+                    cx.rem_euclid(self.width as isize) as usize,
+                    cy.rem_euclid(self.height as isize) as usize,
+                )
+            } else {
+                (cx as usize, cy as usize)
+            };
+            if let Ok(pixel) = self.get_pixel(cx, cy) {
+                *p = pixel;
+            }
+        }
+        out
+    }
+}
+
+impl Display {
+    /// Shows the 8x8 window of `canvas` with its top left at (x, y),
+    /// clamping or wrapping at the edges as `Canvas::viewport` does.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{Canvas, Display};
+    ///
+    /// let mut display = Display::new().unwrap();
+    /// let map = Canvas::new(32, 32);
+    /// for x in 0..24 {
+    ///     display.show_viewport(&map, x, 0, false).unwrap();
+    /// }
+    /// ```
+    pub fn show_viewport(
+        &mut self,
+        canvas: &Canvas,
+        x: isize,
+        y: isize,
+        wrap: bool,
+    ) -> SenseHatResult<()> {
+        self.set_pixels(&canvas.viewport(x, y, wrap))
+    }
+}
+
+/// Helper function.
+///
+/// Clamps the offset of a window 8 pixels wide so it stays on a canvas
+/// `size` pixels wide, or at 0 if the canvas is narrower than that.
+fn clamp_offset(offset: isize, size: usize) -> isize {
+    offset.min(size.saturating_sub(8) as isize).max(0)
+}
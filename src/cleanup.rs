@@ -0,0 +1,236 @@
+#[cfg(feature = "display")]
+use device::SenseHat;
+use {SenseHatError, SenseHatResult};
+
+use libc::{self, c_int, c_void};
+
+use std::io;
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+#[cfg(feature = "display")]
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+
+/// The signals the handler catches.
+const SIGNALS: [c_int; 2] = [libc::SIGINT, libc::SIGTERM];
+
+/// Whether a handler is installed, as there can only be one.
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+/// The end of the pipe the signal handler writes the signal to, or -1.
+static WAKE_FD: AtomicI32 = AtomicI32::new(-1);
+/// How the signals were handled before the handler was installed, until
+/// they're put back.
+static PREVIOUS: Mutex<Option<[libc::sigaction; 2]>> = Mutex::new(None);
+
+/// Keeps the handler installed by `install_termination_handler` or
+/// `install_cleanup_handler`.
+///
+/// Dropping the guard uninstalls the handler, putting back however SIGINT
+/// and SIGTERM were handled before.
+pub struct CleanupGuard {
+    read_fd: c_int,
+    write_fd: c_int,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// Runs `cleanup` when the process gets SIGINT or SIGTERM, say from
+/// Ctrl-C, then handles the signal as it would have been before, so by
+/// default the process still stops with the right status.
+///
+/// `cleanup` runs on a thread of its own, not in the signal handler, so it
+/// can lock mutexes and write to devices. If the program had its own
+/// handlers for the signals, they're called after it, and the next signal
+/// goes straight to them.
+///
+/// Only one handler can be installed at a time. Returns
+/// `CleanupHandlerInstalled` if there already is one.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{install_termination_handler, ErrorKind};
+///
+/// let guard = install_termination_handler(|| println!("bye")).unwrap();
+/// let again = install_termination_handler(|| ()).map(|_| ()).unwrap_err();
+/// assert_eq!(again.kind(), ErrorKind::CleanupHandlerInstalled);
+///
+/// // Dropping the guard uninstalls it, so there can be another.
+/// drop(guard);
+/// let _guard = install_termination_handler(|| ()).unwrap();
+/// ```
+pub fn install_termination_handler<F>(cleanup: F) -> SenseHatResult<CleanupGuard>
+where
+    F: FnOnce() + Send + 'static,
+{
+    if INSTALLED.swap(true, Ordering::SeqCst) {
+        return Err(SenseHatError::CleanupHandlerInstalled);
+    }
+    match start(cleanup) {
+        Ok(guard) => Ok(guard),
+        Err(err) => {
+            INSTALLED.store(false, Ordering::SeqCst);
+            Err(err)
+        }
+    }
+}
+
+/// Blanks the LED matrix and resets its gamma when the process gets
+/// SIGINT or SIGTERM, so stopping a program with Ctrl-C doesn't leave the
+/// LEDs lit. Does what `cleanup` does, through
+/// `install_termination_handler`.
+///
+/// # Example
+///
+/// ```no_run
+/// use sensehat::{install_cleanup_handler, SenseHat};
+/// use std::sync::{Arc, Mutex};
+///
+/// let sense = Arc::new(Mutex::new(SenseHat::new().unwrap()));
+/// let _guard = install_cleanup_handler(&sense).unwrap();
+/// sense.lock().unwrap().clear(Some((255, 255, 255))).unwrap();
+/// // Ctrl-C now turns the LEDs off on the way out.
+/// loop {
+///     std::thread::park();
+/// }
+/// ```
+#[cfg(feature = "display")]
+pub fn install_cleanup_handler(hat: &Arc<Mutex<SenseHat>>) -> SenseHatResult<CleanupGuard> {
+    let hat = hat.clone();
+    install_termination_handler(move || {
+        // Leave the LEDs off even if another thread panicked holding the
+        // lock.
+        let mut hat = hat.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let _ = cleanup(&mut hat);
+    })
+}
+
+/// Blanks the LED matrix and resets its gamma, as the handler installed by
+/// `install_cleanup_handler` does. Both are tried even if one fails, and
+/// the first error is returned.
+#[cfg(feature = "display")]
+pub fn cleanup(hat: &mut SenseHat) -> SenseHatResult<()> {
+    let cleared = hat.clear(None);
+    let reset = hat.reset_gamma();
+    cleared.and(reset)
+}
+
+impl Drop for CleanupGuard {
+    fn drop(&mut self) {
+        restore();
+        // A zero byte tells the thread to stop.
+        wake(self.write_fd, 0);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        WAKE_FD.store(-1, Ordering::SeqCst);
+        unsafe {
+            libc::close(self.read_fd);
+            libc::close(self.write_fd);
+        }
+        INSTALLED.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Helper function.
+///
+/// Opens the pipe, installs the signal handlers and starts the thread
+/// which runs `cleanup`.
+fn start<F>(cleanup: F) -> SenseHatResult<CleanupGuard>
+where
+    F: FnOnce() + Send + 'static,
+{
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    let (read_fd, write_fd) = (fds[0], fds[1]);
+    WAKE_FD.store(write_fd, Ordering::SeqCst);
+
+    let mut saved: [libc::sigaction; 2] = unsafe { mem::zeroed() };
+    for (i, &signal) in SIGNALS.iter().enumerate() {
+        let mut action: libc::sigaction = unsafe { mem::zeroed() };
+        action.sa_sigaction = on_signal as extern "C" fn(c_int) as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART;
+        unsafe { libc::sigemptyset(&mut action.sa_mask) };
+        if unsafe { libc::sigaction(signal, &action, &mut saved[i]) } != 0 {
+            let err = io::Error::last_os_error();
+            for (&signal, old) in SIGNALS.iter().zip(&saved).take(i) {
+                unsafe { libc::sigaction(signal, old, ptr::null_mut()) };
+            }
+            WAKE_FD.store(-1, Ordering::SeqCst);
+            unsafe {
+                libc::close(read_fd);
+                libc::close(write_fd);
+            }
+            return Err(err.into());
+        }
+    }
+    *PREVIOUS.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(saved);
+
+    let thread = thread::spawn(move || {
+        let signal = wait(read_fd);
+        if signal == 0 {
+            return;
+        }
+        cleanup();
+        restore();
+        unsafe { libc::raise(signal) };
+    });
+    Ok(CleanupGuard {
+        read_fd,
+        write_fd,
+        thread: Some(thread),
+    })
+}
+
+/// Helper function.
+///
+/// The signal handler. Only passes the signal on to the thread, as almost
+/// nothing else is safe in a signal handler.
+extern "C" fn on_signal(signal: c_int) {
+    let errno = unsafe { *libc::__errno_location() };
+    wake(WAKE_FD.load(Ordering::SeqCst), signal as u8);
+    unsafe { *libc::__errno_location() = errno };
+}
+
+/// Helper function.
+///
+/// Writes a byte to the pipe, if it's open.
+fn wake(fd: c_int, byte: u8) {
+    if fd >= 0 {
+        unsafe { libc::write(fd, &byte as *const u8 as *const c_void, 1) };
+    }
+}
+
+/// Helper function.
+///
+/// Waits for a byte on the pipe, returning it as the signal, or 0 if the
+/// pipe couldn't be read.
+fn wait(fd: c_int) -> c_int {
+    let mut byte = 0u8;
+    loop {
+        let read = unsafe { libc::read(fd, &mut byte as *mut u8 as *mut c_void, 1) };
+        if read == 1 {
+            return byte as c_int;
+        }
+        if read < 0 && io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+            continue;
+        }
+        return 0;
+    }
+}
+
+/// Helper function.
+///
+/// Puts back how the signals were handled before, if they haven't been
+/// already.
+fn restore() {
+    let saved = PREVIOUS.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+    if let Some(saved) = saved {
+        for (&signal, old) in SIGNALS.iter().zip(&saved) {
+            unsafe { libc::sigaction(signal, old, ptr::null_mut()) };
+        }
+    }
+}
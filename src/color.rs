@@ -0,0 +1,336 @@
+use display::{convert_from_pixel, convert_to_pixel, Pixel};
+
+/// Color maths on `Pixel`s, for fades and effects.
+///
+/// All of these saturate rather than wrap, and factors outside the range
+/// they expect are clamped to it, with NaN treated as 0, so they never
+/// panic.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{Pixel, PixelExt};
+///
+/// let red: Pixel = (255, 0, 0);
+/// let blue: Pixel = (0, 0, 255);
+///
+/// assert_eq!(red.lerp(blue, 0.0), red);
+/// assert_eq!(red.lerp(blue, 1.0), blue);
+/// assert_eq!(red.lerp(blue, 0.5), (128, 0, 128));
+///
+/// assert_eq!(red.scale(1.0), red);
+/// assert_eq!(red.scale(0.5), (128, 0, 0));
+/// assert_eq!((200, 100, 0).scale(2.0), (255, 200, 0));
+/// assert_eq!(red.scale(-1.0), (0, 0, 0));
+///
+/// assert_eq!(red.blend_over(blue, 1.0), red);
+/// assert_eq!(red.blend_over(blue, 0.0), blue);
+///
+/// assert_eq!((255, 255, 255).luminance(), 255);
+/// assert!((0, 255, 0).luminance() > (255, 0, 0).luminance());
+/// assert!((255, 0, 0).luminance() > (0, 0, 255).luminance());
+/// ```
+pub trait PixelExt {
+    /// Returns the pixel `t` of the way from this one to `other`, with `t`
+    /// clamped between 0 and 1.
+    fn lerp(self, other: Pixel, t: f32) -> Pixel;
+
+    /// Returns the pixel with each channel multiplied by `factor`, which is
+    /// at least 0.
+    fn scale(self, factor: f32) -> Pixel;
+
+    /// Returns this pixel drawn over `under` with the given opacity,
+    /// clamped between 0 (just `under`) and 1 (just this pixel).
+    fn blend_over(self, under: Pixel, alpha: f32) -> Pixel;
+
+    /// Returns the brightness of the pixel, using the Rec. 601 weights.
+    fn luminance(self) -> u8;
+
+    /// Returns the pixel with this hue, in degrees, saturation and value.
+    /// The hue wraps around, so -90 and 270 are the same, and the
+    /// saturation and value are clamped between 0 and 1.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::{Pixel, PixelExt};
+    ///
+    /// assert_eq!(Pixel::from_hsv(0.0, 1.0, 1.0), (255, 0, 0));
+    /// assert_eq!(Pixel::from_hsv(60.0, 1.0, 1.0), (255, 255, 0));
+    /// assert_eq!(Pixel::from_hsv(120.0, 1.0, 1.0), (0, 255, 0));
+    /// assert_eq!(Pixel::from_hsv(180.0, 1.0, 1.0), (0, 255, 255));
+    /// assert_eq!(Pixel::from_hsv(240.0, 1.0, 1.0), (0, 0, 255));
+    /// assert_eq!(Pixel::from_hsv(-60.0, 1.0, 1.0), (255, 0, 255));
+    /// assert_eq!(Pixel::from_hsv(720.0, 0.0, 0.5), (128, 128, 128));
+    ///
+    /// // Round trips stay within one step per channel.
+    /// for hue in 0..360 {
+    ///     let pixel = Pixel::from_hsv(hue as f32, 0.7, 0.9);
+    ///     let (h, s, v) = pixel.to_hsv();
+    ///     let back = Pixel::from_hsv(h, s, v);
+    ///     assert!((pixel.0 as i32 - back.0 as i32).abs() <= 1);
+    ///     assert!((pixel.1 as i32 - back.1 as i32).abs() <= 1);
+    ///     assert!((pixel.2 as i32 - back.2 as i32).abs() <= 1);
+    /// }
+    /// ```
+    fn from_hsv(h: f32, s: f32, v: f32) -> Pixel;
+
+    /// Returns the hue of the pixel in degrees, from 0 up to 360, and its
+    /// saturation and value, between 0 and 1. Greys have a hue of 0.
+    fn to_hsv(self) -> (f32, f32, f32);
+
+    /// Returns the pixel with its hue turned by `degrees` around the color
+    /// wheel. Greys, and whole turns, are returned unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::PixelExt;
+    ///
+    /// assert_eq!((255, 0, 0).rotate_hue(120.0), (0, 255, 0));
+    /// assert_eq!((255, 0, 0).rotate_hue(-120.0), (0, 0, 255));
+    /// assert_eq!((100, 100, 100).rotate_hue(90.0), (100, 100, 100));
+    /// assert_eq!((12, 34, 56).rotate_hue(360.0), (12, 34, 56));
+    /// ```
+    fn rotate_hue(self, degrees: f32) -> Pixel;
+
+    /// Returns the pixel as the rgb565 value the LED matrix stores, with
+    /// each channel rounded to the nearest level it has.
+    ///
+    /// Channels used to be truncated instead, so `get_pixels` now reads
+    /// some colors back a level different from before.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::PixelExt;
+    ///
+    /// // Red 5 is nearest level 1 (8), where truncating gives level 0.
+    /// assert_eq!((5, 0, 0).to_rgb565(), 1 << 11);
+    /// assert_eq!((4, 0, 0).to_rgb565(), 0);
+    /// // Red 250 is nearest level 30 (247), where truncating gives 31.
+    /// assert_eq!((250, 0, 0).to_rgb565(), 30 << 11);
+    /// assert_eq!((251, 0, 0).to_rgb565(), 31 << 11);
+    /// // Green has twice as many levels, so 3 is nearest level 1 (4).
+    /// assert_eq!((0, 3, 0).to_rgb565(), 1 << 5);
+    /// ```
+    fn to_rgb565(self) -> u16;
+
+    /// Returns the pixel for an rgb565 value, as `get_pixels` reads it back
+    /// from the LED matrix.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::{Pixel, PixelExt};
+    ///
+    /// assert_eq!(Pixel::from_rgb565(0xF800), (255, 0, 0));
+    /// assert_eq!(Pixel::from_rgb565(0x07E0), (0, 255, 0));
+    /// assert_eq!(Pixel::from_rgb565(0x001F), (0, 0, 255));
+    /// assert_eq!(Pixel::from_rgb565(0xFFFF), (255, 255, 255));
+    /// assert_eq!(Pixel::from_rgb565(0x0000), (0, 0, 0));
+    /// assert_eq!((255, 255, 255).to_rgb565(), 0xFFFF);
+    ///
+    /// for value in 0..=u16::MAX {
+    ///     assert_eq!(Pixel::from_rgb565(value).to_rgb565(), value);
+    /// }
+    /// ```
+    fn from_rgb565(value: u16) -> Pixel;
+}
+
+impl PixelExt for Pixel {
+    fn lerp(self, other: Pixel, t: f32) -> Pixel {
+        let t = unit(t);
+        let channel = |from: u8, to: u8| {
+            let from = from as f32;
+            saturate(from + (to as f32 - from) * t)
+        };
+        (
+            channel(self.0, other.0),
+            channel(self.1, other.1),
+            channel(self.2, other.2),
+        )
+    }
+
+    fn scale(self, factor: f32) -> Pixel {
+        let factor = if factor > 0.0 { factor } else { 0.0 };
+        let channel = |value: u8| saturate(value as f32 * factor);
+        (channel(self.0), channel(self.1), channel(self.2))
+    }
+
+    fn blend_over(self, under: Pixel, alpha: f32) -> Pixel {
+        under.lerp(self, alpha)
+    }
+
+    fn luminance(self) -> u8 {
+        let weighted = 299 * self.0 as u32 + 587 * self.1 as u32 + 114 * self.2 as u32;
+        ((weighted + 500) / 1000) as u8
+    }
+
+    fn from_hsv(h: f32, s: f32, v: f32) -> Pixel {
+        let h = if h.is_finite() { h.rem_euclid(360.0) } else { 0.0 };
+        let (s, v) = (unit(s), unit(v));
+        let chroma = v * s;
+        let sector = h / 60.0;
+        let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+        let (r, g, b) = match sector as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = v - chroma;
+        let channel = |value: f32| saturate((value + m) * 255.0);
+        (channel(r), channel(g), channel(b))
+    }
+
+    fn to_hsv(self) -> (f32, f32, f32) {
+        let (r, g, b) = (
+            self.0 as f32 / 255.0,
+            self.1 as f32 / 255.0,
+            self.2 as f32 / 255.0,
+        );
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let chroma = max - min;
+        let h = if chroma == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / chroma).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / chroma + 2.0)
+        } else {
+            60.0 * ((r - g) / chroma + 4.0)
+        };
+        let s = if max == 0.0 { 0.0 } else { chroma / max };
+        (h % 360.0, s, max)
+    }
+
+    fn rotate_hue(self, degrees: f32) -> Pixel {
+        let turn = if degrees.is_finite() { degrees.rem_euclid(360.0) } else { 0.0 };
+        if turn == 0.0 || (self.0 == self.1 && self.1 == self.2) {
+            return self;
+        }
+        let (h, s, v) = self.to_hsv();
+        Pixel::from_hsv(h + turn, s, v)
+    }
+
+    fn to_rgb565(self) -> u16 {
+        convert_from_pixel(self)
+    }
+
+    fn from_rgb565(value: u16) -> Pixel {
+        convert_to_pixel(value)
+    }
+}
+
+/// Helper function.
+///
+/// Clamps a factor between 0 and 1, treating NaN as 0.
+fn unit(t: f32) -> f32 {
+    if t > 0.0 {
+        t.min(1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Helper function.
+///
+/// Rounds a channel value to the nearest u8, saturating at 0 and 255.
+fn saturate(value: f32) -> u8 {
+    // Casting a float to an int saturates, and NaN becomes 0.
+    value.round() as u8
+}
+
+/// Which way `Display::fill_gradient` runs a gradient across the LED matrix.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GradientDirection {
+    /// From the left column to the right.
+    Horizontal,
+    /// From the top row to the bottom.
+    Vertical,
+    /// From the top left corner to the bottom right.
+    Diagonal,
+}
+
+/// How `gradient` interpolates between its two colors.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GradientSpace {
+    /// Straight between the red, green and blue values, which goes through
+    /// grey between opposite colors.
+    Rgb,
+    /// Around the color wheel the short way, keeping colors saturated.
+    Hsv,
+}
+
+/// Returns `steps` colors going evenly from `from` to `to`, both included.
+///
+/// No steps gives no colors, and one step gives just `from`.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{gradient, GradientSpace};
+///
+/// let (red, blue) = ((255, 0, 0), (0, 0, 255));
+///
+/// let colors = gradient(red, blue, 5, GradientSpace::Rgb);
+/// assert_eq!(colors.len(), 5);
+/// assert_eq!(colors[0], red);
+/// assert_eq!(colors[2], (128, 0, 128));
+/// assert_eq!(colors[4], blue);
+/// assert!(colors.windows(2).all(|pair| pair[0].0 >= pair[1].0 && pair[0].2 <= pair[1].2));
+///
+/// // Through magenta, rather than the dull purple in the middle.
+/// let colors = gradient(red, blue, 3, GradientSpace::Hsv);
+/// assert_eq!(colors, vec![red, (255, 0, 255), blue]);
+///
+/// assert!(gradient(red, blue, 0, GradientSpace::Rgb).is_empty());
+/// assert_eq!(gradient(red, blue, 1, GradientSpace::Rgb), vec![red]);
+/// ```
+pub fn gradient(from: Pixel, to: Pixel, steps: usize, space: GradientSpace) -> Vec<Pixel> {
+    match steps {
+        0 => Vec::new(),
+        1 => vec![from],
+        _ => (0..steps)
+            .map(|step| match step {
+                0 => from,
+                _ if step == steps - 1 => to,
+                _ => interpolate(from, to, step as f32 / (steps - 1) as f32, space),
+            })
+            .collect(),
+    }
+}
+
+/// Helper function.
+///
+/// Returns the color `t` of the way from `from` to `to`.
+fn interpolate(from: Pixel, to: Pixel, t: f32, space: GradientSpace) -> Pixel {
+    match space {
+        GradientSpace::Rgb => from.lerp(to, t),
+        GradientSpace::Hsv => {
+            let (mut from_h, from_s, from_v) = from.to_hsv();
+            let (mut to_h, to_s, to_v) = to.to_hsv();
+            // Greys have no hue of their own, so take the other one's.
+            if from_s == 0.0 {
+                from_h = to_h;
+            } else if to_s == 0.0 {
+                to_h = from_h;
+            }
+            let mut delta = to_h - from_h;
+            if delta > 180.0 {
+                delta -= 360.0;
+            } else if delta < -180.0 {
+                delta += 360.0;
+            }
+            Pixel::from_hsv(
+                from_h + delta * t,
+                from_s + (to_s - from_s) * t,
+                from_v + (to_v - from_v) * t,
+            )
+        }
+    }
+}
@@ -0,0 +1,527 @@
+//! A wrapper with the same method names and arguments as the Python
+//! `sense_hat` library, for porting Python Sense Hat programs.
+//!
+//! Each method does what the Python one does, through the rest of this
+//! crate, and `SenseHatCompat::inner_mut` gets at everything else.
+//!
+//! | Python                                   | Rust                                           |
+//! |------------------------------------------|------------------------------------------------|
+//! | `SenseHat()`                             | `SenseHatCompat::new()?`                       |
+//! | `set_rotation(r, redraw)`                | `set_rotation(r, redraw)?`                     |
+//! | `flip_h(redraw)`, `flip_v(redraw)`       | `flip_h(redraw)?`, `flip_v(redraw)?`           |
+//! | `set_pixels(pixel_list)`                 | `set_pixels(&pixel_list)?`                     |
+//! | `get_pixels()`                           | `get_pixels()?`                                |
+//! | `set_pixel(x, y, r, g, b)`               | `set_pixel(x, y, r, g, b)?`                    |
+//! | `set_pixel(x, y, pixel)`                 | `set_pixel_colour(x, y, pixel)?`               |
+//! | `get_pixel(x, y)`                        | `get_pixel(x, y)?`                             |
+//! | `clear()`, `clear(colour)`               | `clear(None)?`, `clear(Some(colour))?`         |
+//! | `show_message(text, speed, text, back)`  | `show_message(text, speed, text, back)?`       |
+//! | `show_letter(s, text, back)`             | `show_letter(s, text, back)?`                  |
+//! | `low_light`, `low_light = on`            | `low_light()?`, `set_low_light(on)?`           |
+//! | `gamma`, `gamma = table`                | `gamma()?`, `set_gamma(&table)?`               |
+//! | `gamma_reset()`                          | `gamma_reset()?`                               |
+//! | `get_humidity()`                         | `get_humidity()`                               |
+//! | `get_temperature()`                      | `get_temperature()`                            |
+//! | `get_temperature_from_humidity()`        | `get_temperature_from_humidity()`              |
+//! | `get_temperature_from_pressure()`        | `get_temperature_from_pressure()`              |
+//! | `get_pressure()`                         | `get_pressure()`                               |
+//! | `set_imu_config(compass, gyro, accel)`   | `set_imu_config(compass, gyro, accel)?`        |
+//! | `get_orientation_radians()`              | `get_orientation_radians()`                    |
+//! | `get_orientation_degrees()`              | `get_orientation_degrees()`                    |
+//! | `get_orientation()`                      | `get_orientation()`                            |
+//! | `get_compass()`                          | `get_compass()`                                |
+//! | `get_compass_raw()`                      | `get_compass_raw()`                            |
+//! | `get_gyroscope()`                        | `get_gyroscope()`                              |
+//! | `get_gyroscope_raw()`                    | `get_gyroscope_raw()`                          |
+//! | `get_accelerometer()`                    | `get_accelerometer()`                          |
+//! | `get_accelerometer_raw()`                | `get_accelerometer_raw()`                      |
+//!
+//! Where the Python library raises `ValueError`, these return an error.
+//! Where it quietly returns 0 for a sensor reading which isn't ready, so do
+//! these, and where it returns the last IMU reading, so do these. Colours
+//! read back from the LED matrix have their low bits dropped, like the
+//! Python library's, so `[255, 255, 255]` reads back as `[248, 252, 248]`.
+
+use device::SenseHat;
+use display::{convert_from_pixel, convert_to_pixel, Orientation, Pixel};
+use text::{render_message, TextStyle};
+use {SenseHatError, SenseHatResult};
+
+use std::f64::consts::PI;
+use std::io;
+use std::time::Duration;
+
+/// A colour as the Python library has them, red, green and blue.
+pub type Colour = [u8; 3];
+
+/// Pitch, roll and yaw, like the dicts the Python library's orientation
+/// methods return.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct PitchRollYaw {
+    pub pitch: f64,
+    pub roll: f64,
+    pub yaw: f64,
+}
+
+/// X, Y and Z, like the dicts the Python library's raw IMU methods return.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Xyz {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+/// Which of the IMU's sensors the Python library's orientation methods
+/// use, as `set_imu_config` takes them.
+const COMPASS_ONLY: (bool, bool, bool) = (true, false, false);
+const GYRO_ONLY: (bool, bool, bool) = (false, true, false);
+const ACCEL_ONLY: (bool, bool, bool) = (false, false, true);
+
+/// A Sense Hat with the Python library's API. See the module docs.
+///
+/// # Example
+///
+/// ```no_run
+/// use sensehat::compat::SenseHatCompat;
+///
+/// let mut sense = SenseHatCompat::new().unwrap();
+/// sense.set_rotation(180, true).unwrap();
+/// sense.set_pixel(0, 0, 255, 0, 0).unwrap();
+/// let temp = sense.get_temperature();
+/// sense.show_message(&format!("{:.1}C", temp), 0.1, [255, 255, 255], [0, 0, 0]).unwrap();
+/// ```
+pub struct SenseHatCompat {
+    hat: SenseHat,
+    /// The sensors the IMU was last set to use, as the Python library
+    /// only changes them when they're different.
+    imu_config: Option<(bool, bool, bool)>,
+    /// The last orientation read, in radians, for when there isn't a new
+    /// one.
+    orientation: PitchRollYaw,
+    /// The last raw readings, for when there isn't a new one.
+    compass_raw: Xyz,
+    gyroscope_raw: Xyz,
+    accelerometer_raw: Xyz,
+}
+
+impl SenseHatCompat {
+    /// Opens the Sense Hat, like the Python library's `SenseHat()`.
+    pub fn new() -> SenseHatResult<Self> {
+        Ok(SenseHatCompat::from(SenseHat::new()?))
+    }
+
+    /// Returns the `SenseHat` this wraps.
+    pub fn inner(&self) -> &SenseHat {
+        &self.hat
+    }
+
+    /// Returns the `SenseHat` this wraps, for anything the Python library
+    /// doesn't have.
+    pub fn inner_mut(&mut self) -> &mut SenseHat {
+        &mut self.hat
+    }
+
+    /// Rotates the image on the LED matrix by `r` degrees, which has to be
+    /// 0, 90, 180 or 270, or it returns `InvalidRotation`.
+    pub fn set_rotation(&mut self, r: u32, redraw: bool) -> SenseHatResult<()> {
+        self.hat.set_rotation(rotation_from_degrees(r)?, redraw)
+    }
+
+    /// Flips the image on the LED matrix left to right, returning the
+    /// flipped pixels.
+    pub fn flip_h(&mut self, redraw: bool) -> SenseHatResult<Vec<Colour>> {
+        Ok(self
+            .hat
+            .flip_h(redraw)?
+            .iter()
+            .map(|&p| colour_of(p))
+            .collect())
+    }
+
+    /// Flips the image on the LED matrix top to bottom, returning the
+    /// flipped pixels.
+    pub fn flip_v(&mut self, redraw: bool) -> SenseHatResult<Vec<Colour>> {
+        Ok(self
+            .hat
+            .flip_v(redraw)?
+            .iter()
+            .map(|&p| colour_of(p))
+            .collect())
+    }
+
+    /// Shows 64 pixels, row by row from the top left. Returns an io
+    /// `InvalidInput` error if there aren't 64.
+    pub fn set_pixels(&mut self, pixel_list: &[Colour]) -> SenseHatResult<()> {
+        if pixel_list.len() != 64 {
+            return Err(invalid_input(format!(
+                "pixel list must have 64 elements, found {}",
+                pixel_list.len()
+            )));
+        }
+        let mut pixels = [(0, 0, 0); 64];
+        for (p, &colour) in pixels.iter_mut().zip(pixel_list) {
+            *p = pixel_for(colour);
+        }
+        self.hat.set_pixels(&pixels)
+    }
+
+    /// Returns the 64 pixels on the LED matrix, row by row from the top
+    /// left.
+    pub fn get_pixels(&self) -> SenseHatResult<Vec<Colour>> {
+        Ok(self
+            .hat
+            .get_pixels()?
+            .iter()
+            .map(|&p| colour_of(p))
+            .collect())
+    }
+
+    /// Sets the pixel at (x, y) to the colour (r, g, b). Returns
+    /// `OutOfBounds` if x or y is more than 7.
+    pub fn set_pixel(&mut self, x: usize, y: usize, r: u8, g: u8, b: u8) -> SenseHatResult<()> {
+        self.set_pixel_colour(x, y, [r, g, b])
+    }
+
+    /// Sets the pixel at (x, y) to a colour, like the Python library's
+    /// `set_pixel(x, y, pixel)`.
+    pub fn set_pixel_colour(&mut self, x: usize, y: usize, pixel: Colour) -> SenseHatResult<()> {
+        self.hat.set_pixel(x, y, pixel_for(pixel))
+    }
+
+    /// Returns the colour of the pixel at (x, y).
+    pub fn get_pixel(&self, x: usize, y: usize) -> SenseHatResult<Colour> {
+        Ok(colour_of(self.hat.get_pixel(x, y)?))
+    }
+
+    /// Sets every pixel to a colour, black if it's None.
+    pub fn clear(&mut self, colour: Option<Colour>) -> SenseHatResult<()> {
+        self.hat.clear(Some(pixel_for(colour.unwrap_or([0, 0, 0]))))
+    }
+
+    /// Scrolls `text_string` across the LED matrix from right to left,
+    /// moving a column every `scroll_speed` seconds, blocking until it's
+    /// done.
+    pub fn show_message(
+        &mut self,
+        text_string: &str,
+        scroll_speed: f64,
+        text_colour: Colour,
+        back_colour: Colour,
+    ) -> SenseHatResult<()> {
+        let speed = Duration::from_secs_f64(scroll_speed.max(0.0));
+        let style = TextStyle {
+            speed,
+            ..TextStyle::default()
+        };
+        let (fg, bg) = (pixel_for(text_colour), pixel_for(back_colour));
+        self.hat.play_animation(&render_message(text_string, &style, fg, bg))
+    }
+
+    /// Shows a single character on the LED matrix. Returns an io
+    /// `InvalidInput` error if `s` isn't one character.
+    pub fn show_letter(
+        &mut self,
+        s: &str,
+        text_colour: Colour,
+        back_colour: Colour,
+    ) -> SenseHatResult<()> {
+        if s.chars().count() != 1 {
+            return Err(invalid_input(format!(
+                "only one character may be passed, found {:?}",
+                s
+            )));
+        }
+        // The Python library leaves one blank column before the letter.
+        let style = TextStyle {
+            leading_cols: 1,
+            ..TextStyle::default()
+        };
+        let (fg, bg) = (pixel_for(text_colour), pixel_for(back_colour));
+        let frame = render_message(s, &style, fg, bg).frame(0).expect("a message has frames");
+        self.hat.set_pixels(&frame)
+    }
+
+    /// Returns true if the LED matrix is dimmed.
+    pub fn low_light(&self) -> SenseHatResult<bool> {
+        self.hat.is_low_light()
+    }
+
+    /// Dims the LED matrix, or brings it back to full brightness.
+    pub fn set_low_light(&mut self, low_light: bool) -> SenseHatResult<()> {
+        self.hat.low_light(low_light)
+    }
+
+    /// Returns the LED matrix's gamma table.
+    pub fn gamma(&self) -> SenseHatResult<[u8; 32]> {
+        self.hat.gamma()
+    }
+
+    /// Sets the LED matrix's gamma table.
+    pub fn set_gamma(&mut self, buffer: &[u8; 32]) -> SenseHatResult<()> {
+        self.hat.set_gamma(buffer)
+    }
+
+    /// Puts the LED matrix's gamma table back to the default.
+    pub fn gamma_reset(&mut self) -> SenseHatResult<()> {
+        self.hat.reset_gamma()
+    }
+
+    /// Returns the relative humidity in percent, or 0 if there's no new
+    /// reading.
+    pub fn get_humidity(&mut self) -> f64 {
+        self.hat
+            .get_humidity()
+            .map(|h| h.as_percent())
+            .unwrap_or(0.0)
+    }
+
+    /// Returns the temperature in °C from the humidity sensor, or 0 if
+    /// there's no new reading.
+    pub fn get_temperature(&mut self) -> f64 {
+        self.get_temperature_from_humidity()
+    }
+
+    /// Returns the temperature in °C from the humidity sensor, or 0 if
+    /// there's no new reading.
+    pub fn get_temperature_from_humidity(&mut self) -> f64 {
+        self.hat
+            .get_temperature_from_humidity()
+            .map(|t| t.as_celsius())
+            .unwrap_or(0.0)
+    }
+
+    /// Returns the temperature in °C from the barometer, or 0 if there's
+    /// no new reading.
+    pub fn get_temperature_from_pressure(&mut self) -> f64 {
+        self.hat
+            .get_temperature_from_pressure()
+            .map(|t| t.as_celsius())
+            .unwrap_or(0.0)
+    }
+
+    /// Returns the air pressure in millibars, or 0 if there's no new
+    /// reading.
+    pub fn get_pressure(&mut self) -> f64 {
+        self.hat
+            .get_pressure()
+            .map(|p| p.as_hectopascals())
+            .unwrap_or(0.0)
+    }
+
+    /// Turns the IMU's compass, gyroscope and accelerometer on or off, for
+    /// working out its orientation.
+    pub fn set_imu_config(
+        &mut self,
+        compass_enabled: bool,
+        gyro_enabled: bool,
+        accel_enabled: bool,
+    ) -> SenseHatResult<()> {
+        let config = (compass_enabled, gyro_enabled, accel_enabled);
+        if self.imu_config != Some(config) {
+            self.hat
+                .imu()?
+                .set_config(compass_enabled, gyro_enabled, accel_enabled)?;
+            self.imu_config = Some(config);
+        }
+        Ok(())
+    }
+
+    /// Returns the orientation in radians, or the last one if there's no
+    /// new reading.
+    pub fn get_orientation_radians(&mut self) -> PitchRollYaw {
+        if let Ok((pitch, roll, yaw)) = self.hat.imu().and_then(|imu| imu.get_orientation_radians())
+        {
+            self.orientation = PitchRollYaw { pitch, roll, yaw };
+        }
+        self.orientation
+    }
+
+    /// Returns the orientation in degrees, from 0 to 360, or the last one
+    /// if there's no new reading.
+    pub fn get_orientation_degrees(&mut self) -> PitchRollYaw {
+        let radians = self.get_orientation_radians();
+        PitchRollYaw {
+            pitch: python_degrees(radians.pitch),
+            roll: python_degrees(radians.roll),
+            yaw: python_degrees(radians.yaw),
+        }
+    }
+
+    /// The same as `get_orientation_degrees`.
+    pub fn get_orientation(&mut self) -> PitchRollYaw {
+        self.get_orientation_degrees()
+    }
+
+    /// Returns the compass heading in degrees, from 0 to 360, using only
+    /// the compass.
+    pub fn get_compass(&mut self) -> f64 {
+        self.use_only(COMPASS_ONLY);
+        self.get_orientation_degrees().yaw
+    }
+
+    /// Returns the magnetic field in microtesla.
+    pub fn get_compass_raw(&mut self) -> Xyz {
+        if let Ok(field) = self.hat.imu().and_then(|imu| imu.read_magnetometer()) {
+            self.compass_raw = xyz(field);
+        }
+        self.compass_raw
+    }
+
+    /// Returns the orientation in degrees, using only the gyroscope.
+    pub fn get_gyroscope(&mut self) -> PitchRollYaw {
+        self.use_only(GYRO_ONLY);
+        self.get_orientation_degrees()
+    }
+
+    /// Returns the angular rate in radians per second.
+    pub fn get_gyroscope_raw(&mut self) -> Xyz {
+        if let Ok(rate) = self.hat.imu().and_then(|imu| imu.read_gyroscope()) {
+            let [x, y, z] = rate;
+            self.gyroscope_raw = xyz([x * PI / 180.0, y * PI / 180.0, z * PI / 180.0]);
+        }
+        self.gyroscope_raw
+    }
+
+    /// Returns the orientation in degrees, using only the accelerometer.
+    pub fn get_accelerometer(&mut self) -> PitchRollYaw {
+        self.use_only(ACCEL_ONLY);
+        self.get_orientation_degrees()
+    }
+
+    /// Returns the acceleration in g.
+    pub fn get_accelerometer_raw(&mut self) -> Xyz {
+        if let Ok(acceleration) = self.hat.imu().and_then(|imu| imu.read_accelerometer()) {
+            self.accelerometer_raw = xyz(acceleration);
+        }
+        self.accelerometer_raw
+    }
+
+    /// Helper function.
+    ///
+    /// Switches the IMU to only the given sensors, as the Python library's
+    /// `get_compass`, `get_gyroscope` and `get_accelerometer` do. Failing
+    /// leaves the last orientation to be returned, as it does too.
+    fn use_only(&mut self, (compass, gyro, accel): (bool, bool, bool)) {
+        let _ = self.set_imu_config(compass, gyro, accel);
+    }
+}
+
+impl From<SenseHat> for SenseHatCompat {
+    fn from(hat: SenseHat) -> Self {
+        SenseHatCompat {
+            hat,
+            imu_config: None,
+            orientation: PitchRollYaw::default(),
+            compass_raw: Xyz::default(),
+            gyroscope_raw: Xyz::default(),
+            accelerometer_raw: Xyz::default(),
+        }
+    }
+}
+
+/// Returns the rotation for `r` degrees, as the Python library's
+/// `set_rotation` takes it, or `InvalidRotation` if it isn't 0, 90, 180
+/// or 270.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::compat::rotation_from_degrees;
+/// use sensehat::Orientation;
+///
+/// assert_eq!(rotation_from_degrees(90).unwrap(), Orientation::Deg90);
+/// assert!(rotation_from_degrees(45).is_err());
+/// assert!(rotation_from_degrees(360).is_err());
+/// ```
+pub fn rotation_from_degrees(r: u32) -> SenseHatResult<Orientation> {
+    match r {
+        0 => Ok(Orientation::Deg0),
+        90 => Ok(Orientation::Deg90),
+        180 => Ok(Orientation::Deg180),
+        270 => Ok(Orientation::Deg270),
+        _ => Err(SenseHatError::InvalidRotation(r)),
+    }
+}
+
+/// Packs a colour into rgb565 the way the Python library does, dropping
+/// the low bits of each channel.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::compat::{from_python_rgb565, to_python_rgb565};
+///
+/// assert_eq!(to_python_rgb565([255, 255, 255]), 0xffff);
+/// assert_eq!(to_python_rgb565([7, 3, 7]), 0);
+/// assert_eq!(to_python_rgb565([8, 4, 8]), 0x0821);
+///
+/// // So colours don't read back quite as they were written.
+/// assert_eq!(from_python_rgb565(to_python_rgb565([255, 255, 255])), [248, 252, 248]);
+/// assert_eq!(from_python_rgb565(to_python_rgb565([100, 150, 200])), [96, 148, 200]);
+/// ```
+pub fn to_python_rgb565(colour: Colour) -> u16 {
+    let [r, g, b] = colour;
+    ((r as u16 >> 3) << 11) | ((g as u16 >> 2) << 5) | (b as u16 >> 3)
+}
+
+/// Unpacks an rgb565 value the way the Python library does, leaving the
+/// low bits of each channel 0.
+pub fn from_python_rgb565(value: u16) -> Colour {
+    [
+        (((value >> 11) & 0x1f) << 3) as u8,
+        (((value >> 5) & 0x3f) << 2) as u8,
+        ((value & 0x1f) << 3) as u8,
+    ]
+}
+
+/// Converts an angle in radians to degrees from 0 to 360, the way the
+/// Python library's `get_orientation_degrees` does.
+///
+/// # Example
+///
+/// ```
+/// use std::f64::consts::PI;
+/// use sensehat::compat::python_degrees;
+///
+/// assert_eq!(python_degrees(0.0), 0.0);
+/// assert_eq!(python_degrees(PI / 2.0), 90.0);
+/// assert_eq!(python_degrees(-PI / 2.0), 270.0);
+/// ```
+pub fn python_degrees(radians: f64) -> f64 {
+    let degrees = radians.to_degrees();
+    if degrees >= 0.0 {
+        degrees
+    } else {
+        degrees + 360.0
+    }
+}
+
+/// Helper function.
+///
+/// Returns the pixel which the display stores as the same rgb565 value the
+/// Python library would write for a colour.
+fn pixel_for(colour: Colour) -> Pixel {
+    convert_to_pixel(to_python_rgb565(colour))
+}
+
+/// Helper function.
+///
+/// Returns the colour the Python library would read back for a pixel.
+fn colour_of(pixel: Pixel) -> Colour {
+    from_python_rgb565(convert_from_pixel(pixel))
+}
+
+/// Helper function.
+///
+/// Makes an `Xyz` from an array.
+fn xyz([x, y, z]: [f64; 3]) -> Xyz {
+    Xyz { x, y, z }
+}
+
+/// Helper function.
+///
+/// Makes the error for an argument the Python library would raise
+/// `ValueError` for.
+fn invalid_input(message: String) -> SenseHatError {
+    io::Error::new(io::ErrorKind::InvalidInput, message).into()
+}
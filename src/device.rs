@@ -1,412 +1,2369 @@
-use {SenseHatError, SenseHatResult};
-
-pub use measurements::Temperature;
-pub use measurements::Pressure;
-
-use i2cdev::core::I2CDevice;
-use i2cdev::linux::LinuxI2CDevice;
-use byteorder::{ByteOrder, LittleEndian};
-
-use display::{Display, Pixel, Orientation};
-use imu::Imu;
-
-use std::fmt;
-
-/// Represents a relative humidity reading from the humidity sensor
-pub struct RelativeHumidity {
-    value: f64,
-}
-
-/// Represents the SenseHat itself
-pub struct SenseHat {
-    // LPS25H pressure sensor
-    pressure_dev: LinuxI2CDevice,
-    // HT221 humidity sensor
-    humidity_dev: LinuxI2CDevice,
-    // The 8x8 LED display
-    display: Display,
-    // The IMU
-    imu: Imu,
-    temp_m: f64,
-    temp_c: f64,
-    hum_m: f64,
-    hum_c: f64,
-}
-
-// Registers for the HT221 humidity sensor
-const HTS221_AV_CONF: u8 = 0x10;
-const HTS221_CTRL1: u8 = 0x20;
-const HTS221_STATUS: u8 = 0x27;
-const HTS221_HUMIDITY_OUT_L: u8 = 0x28;
-const HTS221_HUMIDITY_OUT_H: u8 = 0x29;
-const HTS221_TEMP_OUT_L: u8 = 0x2a;
-const HTS221_TEMP_OUT_H: u8 = 0x2b;
-const HTS221_H0_H_2: u8 = 0x30;
-const HTS221_H1_H_2: u8 = 0x31;
-const HTS221_T0_C_8: u8 = 0x32;
-const HTS221_T1_C_8: u8 = 0x33;
-const HTS221_T1_T0: u8 = 0x35;
-const HTS221_H0_T0_OUT: u8 = 0x36;
-const HTS221_H1_T0_OUT: u8 = 0x3a;
-const HTS221_T0_OUT: u8 = 0x3c;
-const HTS221_T1_OUT: u8 = 0x3e;
-
-// Registers for the LPS25H pressure sensor
-const LPS25H_RES_CONF: u8 = 0x10;
-const LPS25H_CTRL_REG_1: u8 = 0x20;
-const LPS25H_CTRL_REG_2: u8 = 0x21;
-const LPS25H_STATUS_REG: u8 = 0x27;
-const LPS25H_PRESS_OUT_XL: u8 = 0x28;
-const LPS25H_PRESS_OUT_L: u8 = 0x29;
-const LPS25H_PRESS_OUT_H: u8 = 0x2a;
-const LPS25H_TEMP_OUT_L: u8 = 0x2b;
-const LPS25H_TEMP_OUT_H: u8 = 0x2c;
-const LPS25H_FIFO_CTRL: u8 = 0x2e;
-
-impl SenseHat {
-    /// Try and create a new SenseHat object.
-    ///
-    /// Will open the relevant I2C devices and then attempt to initialise the
-    /// chips on the Sense Hat.
-    pub fn new() -> SenseHatResult<SenseHat> {
-        let mut hat = SenseHat {
-            pressure_dev: LinuxI2CDevice::new("/dev/i2c-1", 0x5c)?,
-            humidity_dev: LinuxI2CDevice::new("/dev/i2c-1", 0x5f)?,
-            display: Display::new()?,
-            imu: Imu::new()?,
-            temp_m: 0.0,
-            temp_c: 0.0,
-            hum_m: 0.0,
-            hum_c: 0.0,
-        };
-
-        hat.init_pressure()?;
-        hat.init_humidity()?;
-
-        Ok(hat)
-    }
-
-    /// Init sequence from https://github.com/RPi-Distro/RTIMULib
-    fn init_pressure(&mut self) -> SenseHatResult<()> {
-        self.pressure_dev.smbus_write_byte_data(LPS25H_CTRL_REG_1, 0xc4)?;
-        self.pressure_dev.smbus_write_byte_data(LPS25H_RES_CONF, 0x05)?;
-        self.pressure_dev.smbus_write_byte_data(LPS25H_FIFO_CTRL, 0xc0)?;
-        self.pressure_dev.smbus_write_byte_data(LPS25H_CTRL_REG_2, 0x40)?;
-        Ok(())
-    }
-
-    /// Init sequence from https://github.com/RPi-Distro/RTIMULib
-    fn init_humidity(&mut self) -> SenseHatResult<()> {
-        // Init
-        self.humidity_dev.smbus_write_byte_data(HTS221_CTRL1, 0x87)?;
-        self.humidity_dev.smbus_write_byte_data(HTS221_AV_CONF, 0x1b)?;
-
-        // Get cal
-        let mut buf = [0u8; 2];
-        buf[0] = self.humidity_dev.smbus_read_byte_data(HTS221_T0_C_8)?;
-        buf[1] = self.humidity_dev.smbus_read_byte_data(HTS221_T1_T0)? & 0x03;
-        let t0 = (LittleEndian::read_i16(&buf) as f64) / 8.0;
-        buf[0] = self.humidity_dev.smbus_read_byte_data(HTS221_T1_C_8)?;
-        buf[1] = (self.humidity_dev.smbus_read_byte_data(HTS221_T1_T0)? & 0x0C) >> 2;
-        let t1 = (LittleEndian::read_i16(&buf) as f64) / 8.0;
-
-        buf[0] = self.humidity_dev.smbus_read_byte_data(HTS221_T0_OUT)?;
-        buf[1] = self.humidity_dev.smbus_read_byte_data(HTS221_T0_OUT + 1)?;
-        let t0_out = LittleEndian::read_i16(&buf) as f64;
-
-        buf[0] = self.humidity_dev.smbus_read_byte_data(HTS221_T1_OUT)?;
-        buf[1] = self.humidity_dev.smbus_read_byte_data(HTS221_T1_OUT + 1)?;
-        let t1_out = LittleEndian::read_i16(&buf) as f64;
-
-        buf[0] = self.humidity_dev.smbus_read_byte_data(HTS221_H0_H_2)?;
-        let h0 = (buf[0] as f64) / 2.0;
-
-        buf[0] = self.humidity_dev.smbus_read_byte_data(HTS221_H1_H_2)?;
-        let h1 = (buf[0] as f64) / 2.0;
-
-        buf[0] = self.humidity_dev.smbus_read_byte_data(HTS221_H0_T0_OUT)?;
-        buf[1] = self.humidity_dev.smbus_read_byte_data(HTS221_H0_T0_OUT + 1)?;
-        let h0_t0_out = LittleEndian::read_i16(&buf) as f64;
-
-        buf[0] = self.humidity_dev.smbus_read_byte_data(HTS221_H1_T0_OUT)?;
-        buf[1] = self.humidity_dev.smbus_read_byte_data(HTS221_H1_T0_OUT + 1)?;
-        let h1_t0_out = LittleEndian::read_i16(&buf) as f64;
-
-        self.temp_m = (t1 - t0) / (t1_out - t0_out);
-        self.temp_c = t0 - (self.temp_m * t0_out);
-        self.hum_m = (h1 - h0) / (h1_t0_out - h0_t0_out);
-        self.hum_c = h0 - (self.hum_m * h0_t0_out);
-
-        Ok(())
-    }
-
-    /// Returns a Temperature reading from the barometer.  It's less accurate
-    /// than the barometer (+/- 2 degrees C), but over a wider range.
-    pub fn get_temperature_from_pressure(&mut self) -> SenseHatResult<Temperature> {
-        let status = self.pressure_dev.smbus_read_byte_data(LPS25H_STATUS_REG)?;
-        if (status & 1) != 0 {
-            let mut buf = [0u8; 2];
-            buf[0] = self.pressure_dev.smbus_read_byte_data(LPS25H_TEMP_OUT_L)?;
-            buf[1] = self.pressure_dev.smbus_read_byte_data(LPS25H_TEMP_OUT_H)?;
-            let celcius = ((LittleEndian::read_i16(&buf) as f64) / 480.0) + 42.5;
-            Ok(Temperature::from_celsius(celcius))
-        } else {
-            Err(SenseHatError::NotReady)
-        }
-    }
-
-    /// Returns a Pressure value from the barometer
-    pub fn get_pressure(&mut self) -> SenseHatResult<Pressure> {
-        let status = self.pressure_dev.smbus_read_byte_data(LPS25H_STATUS_REG)?;
-        if (status & 2) != 0 {
-            let mut buf = [0u8; 4];
-            buf[0] = self.pressure_dev.smbus_read_byte_data(LPS25H_PRESS_OUT_XL)?;
-            buf[1] = self.pressure_dev.smbus_read_byte_data(LPS25H_PRESS_OUT_L)?;
-            buf[2] = self.pressure_dev.smbus_read_byte_data(LPS25H_PRESS_OUT_H)?;
-            let hectopascals = (LittleEndian::read_u32(&buf) as f64) / 4096.0;
-            Ok(Pressure::from_hectopascals(hectopascals))
-        } else {
-            Err(SenseHatError::NotReady)
-        }
-    }
-
-    /// Returns a Temperature reading from the humidity sensor. It's more
-    /// accurate than the barometer (+/- 0.5 degrees C), but over a smaller
-    /// range.
-    pub fn get_temperature_from_humidity(&mut self) -> SenseHatResult<Temperature> {
-        let status = self.humidity_dev.smbus_read_byte_data(HTS221_STATUS)?;
-        if (status & 1) != 0 {
-            let mut buf = [0u8; 2];
-            buf[0] = self.humidity_dev.smbus_read_byte_data(HTS221_TEMP_OUT_L)?;
-            buf[1] = self.humidity_dev.smbus_read_byte_data(HTS221_TEMP_OUT_H)?;
-            let celcius = ((LittleEndian::read_i16(&buf) as f64) * self.temp_m) + self.temp_c;
-            Ok(Temperature::from_celsius(celcius))
-        } else {
-            Err(SenseHatError::NotReady)
-        }
-    }
-
-    /// Returns a RelativeHumidity value in percent between 0 and 100
-    pub fn get_humidity(&mut self) -> SenseHatResult<RelativeHumidity> {
-        let status = self.humidity_dev.smbus_read_byte_data(HTS221_STATUS)?;
-        if (status & 2) != 0 {
-            let mut buf = [0u8; 2];
-            buf[0] = self.humidity_dev.smbus_read_byte_data(HTS221_HUMIDITY_OUT_L)?;
-            buf[1] = self.humidity_dev.smbus_read_byte_data(HTS221_HUMIDITY_OUT_H)?;
-            let percent = ((LittleEndian::read_i16(&buf) as f64) * self.hum_m) + self.hum_c;
-            Ok(RelativeHumidity::from_percent(percent))
-        } else {
-            Err(SenseHatError::NotReady)
-        }
-    }
-
-    /// If you're using the Pi upside down or sideways you can use this function
-    /// to correct the orientation of the image being shown.
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use sensehat::{SenseHat, Orientation};
-    ///
-    /// let sense = SenseHat::new().unwrap();
-    /// sense.set_rotation(Orientation::Deg180);
-    /// ```
-    pub fn set_rotation(&mut self, ori: Orientation, redraw: bool) {
-        self.display.set_rotation(ori, redraw);
-    }
-
-    /// Flips the image on the LED matrix horizontally.
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use sensehat::{SenseHat, Pixel};
-    ///
-    /// let sense = SenseHat::new().unwrap();
-    /// // Flip the frame and redraw it.
-    /// sense.flip_h(true);
-    /// // Flip the frame, but don't redraw it
-    /// let pixels: [Pixel; 64] = sense.slip(false);
-    /// ```
-    pub fn flip_h(&mut self, redraw: bool) -> [Pixel; 64] {
-        self.display.flip_h(redraw)
-    }
-
-    /// Flips the image on the LED matrix vertically.
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use sensehat::{SenseHat, Pixel};
-    ///
-    /// let sense = SenseHat::new().unwrap();
-    /// // Flip the frame and redraw it.
-    /// sense.flip_v(true);
-    /// // Flip the frame, but don't redraw it
-    /// let pixels: [Pixel; 64] = sense.flip_v(false);
-    /// ```
-    pub fn flip_v(&mut self, redraw: bool) -> [Pixel; 64] {
-        self.display.flip_v(redraw)
-    }
-
-    /// Updates the entire LED matrix based on a 64 length array of pixel values.
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use sensehat::{SenseHat, Pixel};
-    ///
-    /// let sense = SenseHat::new().unwrap();
-    /// let r: Pixel = (255, 0, 0);
-    /// let w: Pixel = (255, 255, 255);
-    ///
-    /// let question_mark: [Pixel; 64] = [
-    ///    w, w, w, r, r, w, w, w,
-    ///    w, w, r, w, w, r, w, w,
-    ///    w, w, w, w, w, r, w, w,
-    ///    w, w, w, w, r, w, w, w,
-    ///    w, w, w, r, w, w, w, w,
-    ///    w, w, w, r, w, w, w, w,
-    ///    w, w, w, w, w, w, w, w,
-    ///    w, w, w, r, w, w, w, w];
-    ///
-    /// sense.set_pixels(&question_mark);
-    /// ```
-    pub fn set_pixels(&mut self, pixels: &[Pixel; 64]) {
-        self.display.set_pixels(pixels);
-    }
-
-    /// Returns an array of pixels representing the currently displayed image.
-    ///
-    /// # Note
-    ///
-    /// You will notice that the pixel values you pass into `set_pixels` sometimes
-    /// change when you read them back with `get_pixels`. This is because we specify
-    /// each pixel element as 8 bit numbers (0 to 255) but when they're passed into the
-    /// Linux frame buffer for the LED matrix the numbers are bit shifted down to fit
-    /// into RGB 565. 5 bits for red, 6 bits for green and 5 bits for blue. The loss of
-    /// binary precision when performing this conversion (3 bits lost for red, 2 for
-    /// green and 3 for blue) accounts for the discrepancies you see.
-    ///
-    /// The `get_pixels` function provides a correct representation of how the pixels
-    /// end up in frame buffer memory after you've called `set_pixels`.
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use sensehat::{SenseHat, Pixel};
-    ///
-    /// let sense = SenseHat::new().unwrap();
-    /// let pixel_list: [Pixel; 64] = sense.get_pixels();
-    /// ```
-    pub fn get_pixels(&self) -> [Pixel; 64] {
-        self.display.get_pixels()
-    }
-
-    /// Sets an individual LED matrix pixel at the specified X-Y coordinate to the
-    /// specified colour. Returns an error if `x` or `y` is greater than 7.
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use sensehat::SenseHat;
-    ///
-    /// let sense = SenseHat::new().unwrap();
-    ///
-    /// // examples using (x, y, (r, g, b))
-    /// sense.set_pixel(0, 0, (255, 0, 0)).unwrap();
-    /// sense.set_pixel(0, 7, (0, 255, 0)).unwrap();
-    /// sense.set_pixel(7, 0, (0, 0, 255)).unwrap();
-    /// sense.set_pixel(7, 7, (255, 0, 255)).unwrap();
-    ///
-    /// let red = (255, 0, 0);
-    /// let green = (0, 255, 0);
-    /// let blue = (0, 0, 255);
-    ///
-    /// // examples using (x, y, Pixel)
-    /// sense.set_pixel(0, 0, red).unwrap();
-    /// sense.set_pixel(0, 0, green).unwrap();
-    /// sense.set_pixel(0, 0, blue).unwrap();
-    /// ```
-    pub fn set_pixel(&mut self, x: usize, y: usize, p: Pixel) -> SenseHatResult<()> {
-        self.display.set_pixel(x, y, p)
-    }
-
-    /// Returns a single pixel at the given (`x`, `y`) coordinate from
-    /// the currently displayed image. Returns an error if `x` or `y` is greater than 7.
-    ///
-    /// # Note
-    ///
-    /// Please read the note under `get_pixels`.
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use sensehat::{SenseHat, Pixel};
-    ///
-    /// let sense = SenseHat::new().unwrap();
-    /// let top_left_pixel: Pixel = sense.get_pixel(0, 0).unwrap();
-    /// ```
-    pub fn get_pixel(&self, x: usize, y: usize) -> SenseHatResult<Pixel> {
-        self.display.get_pixel(x, y)
-    }
-
-    /// Sets the entire LED matrix to a single colour. If the given `Option` is `None`,
-    /// it will turn the screen blank / off.
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use sensehat::SenseHat;
-    /// use std::time::Duration;
-    /// use std::thread::sleep;
-    ///
-    /// let sense = SenseHat::new().unwrap();
-    ///
-    /// let red = (255, 0, 0);
-    ///
-    /// sense.clear(None); // None turns the screen off
-    /// sleep(Duration::from_millis(1000));
-    /// sense.clear(Some(red)); // turns the whole screen red
-    ///
-    /// ```
-    pub fn clear(&mut self, color: Option<Pixel>) {
-        self.display.clear(color);
-    }
-
-    pub fn gamma(&self) -> [u8; 32] {
-        self.display.gamma()
-    }
-
-    pub fn set_gamma(&mut self, buffer: &[u8; 32]) -> SenseHatResult<()> {
-        self.display.set_gamma(&buffer)
-    }
-
-    pub fn reset_gamma(&mut self) {
-        self.display.reset_gamma()
-    }
-
-    pub fn is_low_light(&self) -> bool {
-        self.display.is_low_light()
-    }
-
-    pub fn low_light(&mut self, set_low: bool) {
-        self.display.low_light(set_low);
-    }
-}
-
-impl RelativeHumidity {
-    pub fn from_percent(pc: f64) -> Self {
-        RelativeHumidity { value: pc }
-    }
-
-    pub fn as_percent(&self) -> f64 {
-        self.value
-    }
-}
-
-impl fmt::Display for RelativeHumidity {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:.1}%", self.as_percent())
-    }
-}
+use {Component, SenseHatError, SenseHatResult};
+#[cfg(feature = "sensors")]
+use {Measurement, Sensor};
+
+#[cfg(feature = "sensors")]
+pub use measurements::Temperature;
+#[cfg(feature = "sensors")]
+pub use measurements::Pressure;
+
+#[cfg(feature = "sensors")]
+use i2cdev::core::I2CDevice;
+#[cfg(feature = "sensors")]
+use i2c::{self, BoxedI2CDevice, TracedI2CDevice};
+#[cfg(feature = "sensors")]
+use byteorder::{ByteOrder, LittleEndian};
+
+#[cfg(feature = "display")]
+use animation::Animation;
+#[cfg(feature = "display")]
+use display::{Display, LedMatrix, Pixel, Orientation};
+#[cfg(feature = "display")]
+use color::{GradientDirection, GradientSpace};
+#[cfg(feature = "display")]
+use canvas::Canvas;
+#[cfg(feature = "display")]
+use text::{format_value, MessageMetrics, TextBackground, TextStyle};
+#[cfg(feature = "sensors")]
+use humidity::HumidityCalibration;
+#[cfg(feature = "sensors")]
+use imu::{Imu, ImuChip, ImuConfig};
+#[cfg(feature = "sensors")]
+use settings::Settings;
+#[cfg(feature = "sensors")]
+use selftest::{is_plausible, SelfTestCheck, SelfTestReport};
+#[cfg(feature = "sensors")]
+use smoothing::Ewma;
+#[cfg(feature = "sensors")]
+use outlier::{OutlierFilter, OutlierPolicy};
+#[cfg(feature = "sensors")]
+use reading::{Environment, Reading};
+#[cfg(feature = "sensors")]
+use pressure::{
+    pressure_from_raw, pressure_offset_from_registers, pressure_offset_to_registers, pressure_reference_to_registers,
+    pressure_threshold_to_registers, PressureIntMode, PressureIntSource,
+};
+
+#[cfg(feature = "sensors")]
+use std::fmt;
+#[cfg(feature = "sensors")]
+use std::io;
+#[cfg(feature = "display")]
+use std::path::Path;
+use std::sync::Arc;
+#[cfg(feature = "sensors")]
+use std::thread;
+#[cfg(any(feature = "display", feature = "sensors"))]
+use std::time::{Duration, Instant};
+#[cfg(feature = "sensors")]
+use std::time::SystemTime;
+
+/// The status of each component of the Sense Hat, as returned by
+/// `SenseHat::health`.
+#[derive(Debug, Clone)]
+pub struct HealthReport {
+    /// Every component in this build of the crate, with its status.
+    pub components: Vec<(Component, ComponentStatus)>,
+}
+
+/// Whether a component of the Sense Hat initialised successfully.
+#[derive(Debug, Clone)]
+pub enum ComponentStatus {
+    Available,
+    /// The component failed to initialise, for this reason.
+    Failed(Arc<SenseHatError>),
+}
+
+/// Represents a relative humidity reading from the humidity sensor
+#[cfg(feature = "sensors")]
+pub struct RelativeHumidity {
+    value: f64,
+}
+
+/// The readiness and overrun flags of the two environmental sensors, as
+/// returned by `SenseHat::sensor_status`.
+#[cfg(feature = "sensors")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SensorStatus {
+    /// The HTS221 humidity sensor's flags.
+    pub humidity: HumidityStatus,
+    /// The LPS25H pressure sensor's flags.
+    pub pressure: PressureStatus,
+}
+
+/// The flags in the HTS221 humidity sensor's STATUS register.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::HumidityStatus;
+///
+/// let status = HumidityStatus::from_register(0x02);
+/// assert!(!status.temperature_ready);
+/// assert!(status.humidity_ready);
+/// assert_eq!(status.raw, 0x02);
+/// ```
+#[cfg(feature = "sensors")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HumidityStatus {
+    /// A new temperature reading is ready.
+    pub temperature_ready: bool,
+    /// A new humidity reading is ready.
+    pub humidity_ready: bool,
+    /// The STATUS register as read.
+    pub raw: u8,
+}
+
+/// The flags in the LPS25H pressure sensor's STATUS_REG register.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::PressureStatus;
+///
+/// let status = PressureStatus::from_register(0x23);
+/// assert!(status.temperature_ready);
+/// assert!(!status.temperature_overrun);
+/// assert!(status.pressure_ready);
+/// assert!(status.pressure_overrun);
+/// assert_eq!(status.raw, 0x23);
+///
+/// assert!(PressureStatus::from_register(0x10).temperature_overrun);
+/// assert_eq!(PressureStatus::from_register(0x00), PressureStatus {
+///     temperature_ready: false,
+///     temperature_overrun: false,
+///     pressure_ready: false,
+///     pressure_overrun: false,
+///     raw: 0,
+/// });
+/// ```
+#[cfg(feature = "sensors")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PressureStatus {
+    /// A new temperature reading is ready.
+    pub temperature_ready: bool,
+    /// A temperature reading was overwritten before it was read.
+    pub temperature_overrun: bool,
+    /// A new pressure reading is ready.
+    pub pressure_ready: bool,
+    /// A pressure reading was overwritten before it was read.
+    pub pressure_overrun: bool,
+    /// The STATUS_REG register as read.
+    pub raw: u8,
+}
+
+#[cfg(feature = "sensors")]
+impl HumidityStatus {
+    /// Decodes the value of the STATUS register.
+    pub fn from_register(raw: u8) -> Self {
+        HumidityStatus {
+            temperature_ready: raw & 0x01 != 0,
+            humidity_ready: raw & 0x02 != 0,
+            raw,
+        }
+    }
+}
+
+#[cfg(feature = "sensors")]
+impl PressureStatus {
+    /// Decodes the value of the STATUS_REG register.
+    pub fn from_register(raw: u8) -> Self {
+        PressureStatus {
+            temperature_ready: raw & 0x01 != 0,
+            pressure_ready: raw & 0x02 != 0,
+            temperature_overrun: raw & 0x10 != 0,
+            pressure_overrun: raw & 0x20 != 0,
+            raw,
+        }
+    }
+}
+
+/// How hard the environmental sensors work, as set by
+/// `SenseHat::set_power_mode`.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::PowerMode;
+///
+/// // The HTS221's CTRL_REG1 and AV_CONF.
+/// assert_eq!(PowerMode::Performance.hts221_registers(), (0x87, 0x1b));
+/// assert_eq!(PowerMode::LowPower.hts221_registers(), (0x85, 0x00));
+/// assert_eq!(PowerMode::Standby.hts221_registers(), (0x07, 0x1b));
+///
+/// // The LPS25H's CTRL_REG1 and RES_CONF, keeping the bits of CTRL_REG1
+/// // which aren't about power.
+/// assert_eq!(PowerMode::Performance.lps25h_registers(0x0c), (0xcc, 0x05));
+/// assert_eq!(PowerMode::LowPower.lps25h_registers(0xc4), (0x94, 0x00));
+/// assert_eq!(PowerMode::Standby.lps25h_registers(0xcc), (0x4c, 0x05));
+/// ```
+#[cfg(feature = "sensors")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PowerMode {
+    /// Continuous readings, 12.5 a second from the humidity sensor and 25
+    /// from the barometer, averaged as much as they can be. The default.
+    Performance,
+    /// One reading a second from each, with less averaging, so noisier.
+    LowPower,
+    /// The humidity sensor, barometer and IMU powered down. The LED matrix
+    /// isn't affected.
+    Standby,
+}
+
+#[cfg(feature = "sensors")]
+impl PowerMode {
+    /// Returns the values of the HTS221 humidity sensor's CTRL_REG1 and
+    /// AV_CONF registers for this mode.
+    pub fn hts221_registers(self) -> (u8, u8) {
+        match self {
+            PowerMode::Performance => (0x87, 0x1b),
+            PowerMode::LowPower => (0x85, 0x00),
+            PowerMode::Standby => (0x07, 0x1b),
+        }
+    }
+
+    /// Returns the values of the LPS25H pressure sensor's CTRL_REG1 and
+    /// RES_CONF registers for this mode, given what CTRL_REG1 is now. Only
+    /// the power down and data rate bits of CTRL_REG1 are changed.
+    pub fn lps25h_registers(self, ctrl_reg_1: u8) -> (u8, u8) {
+        let rest = ctrl_reg_1 & 0x0f;
+        match self {
+            PowerMode::Performance => (0xc0 | rest, 0x05),
+            PowerMode::LowPower => (0x90 | rest, 0x00),
+            PowerMode::Standby => (0x40 | rest, 0x05),
+        }
+    }
+}
+
+/// Returns the value of the HTS221 humidity sensor's CTRL_REG3 register
+/// for the given data ready signal settings, as written by
+/// `SenseHat::set_humidity_drdy`.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::humidity_drdy_ctrl_reg3;
+///
+/// assert_eq!(humidity_drdy_ctrl_reg3(false, false, false), 0x00);
+/// assert_eq!(humidity_drdy_ctrl_reg3(true, false, false), 0x04);
+/// assert_eq!(humidity_drdy_ctrl_reg3(true, true, false), 0x84);
+/// assert_eq!(humidity_drdy_ctrl_reg3(true, false, true), 0x44);
+/// assert_eq!(humidity_drdy_ctrl_reg3(true, true, true), 0xc4);
+/// ```
+#[cfg(feature = "sensors")]
+pub fn humidity_drdy_ctrl_reg3(enabled: bool, active_low: bool, open_drain: bool) -> u8 {
+    let mut value = 0;
+    if active_low {
+        value |= 0x80;
+    }
+    if open_drain {
+        value |= 0x40;
+    }
+    if enabled {
+        value |= 0x04;
+    }
+    value
+}
+
+/// A component of the Sense Hat, or the reason it failed to initialise.
+#[cfg(any(feature = "display", feature = "sensors"))]
+type Slot<T> = Result<T, Arc<SenseHatError>>;
+
+/// Represents the SenseHat itself
+pub struct SenseHat {
+    // LPS25H pressure sensor
+    #[cfg(feature = "sensors")]
+    pressure_dev: Slot<TracedI2CDevice<BoxedI2CDevice>>,
+    // HT221 humidity sensor
+    #[cfg(feature = "sensors")]
+    humidity_dev: Slot<TracedI2CDevice<BoxedI2CDevice>>,
+    // The 8x8 LED display
+    #[cfg(feature = "display")]
+    display: Slot<Display>,
+    // The IMU
+    #[cfg(feature = "sensors")]
+    imu: Slot<Imu>,
+    #[cfg(feature = "sensors")]
+    humidity_calibration: HumidityCalibration,
+    #[cfg(feature = "sensors")]
+    power_mode: PowerMode,
+    /// Which of the IMU's sensors were on before going into standby.
+    #[cfg(feature = "sensors")]
+    imu_config: Option<ImuConfig>,
+    #[cfg(feature = "sensors")]
+    smoothing: Option<Smoothing>,
+    #[cfg(feature = "sensors")]
+    smoothing_max_age: Option<Duration>,
+    #[cfg(feature = "sensors")]
+    outliers: Option<Outliers>,
+}
+
+/// Builds a `SenseHat` from devices it's given, rather than the ones on
+/// the Sense Hat, such as `MockI2CDevice`s and a `MemoryMatrix` for trying
+/// out code off the hardware. Components which aren't given are opened as
+/// `SenseHat::new` opens them.
+#[derive(Default)]
+pub struct SenseHatBuilder {
+    #[cfg(feature = "sensors")]
+    pressure_dev: Option<BoxedI2CDevice>,
+    #[cfg(feature = "sensors")]
+    humidity_dev: Option<BoxedI2CDevice>,
+    /// The accelerometer and gyro, and the magnetometer.
+    #[cfg(feature = "sensors")]
+    imu_devs: Option<(BoxedI2CDevice, BoxedI2CDevice)>,
+    #[cfg(feature = "display")]
+    display: Option<Display>,
+    strict: bool,
+}
+
+/// The averages `SenseHat::set_smoothing` keeps, one for each reading.
+#[cfg(feature = "sensors")]
+struct Smoothing {
+    temperature_from_humidity: Ewma,
+    temperature_from_pressure: Ewma,
+    humidity: Ewma,
+    pressure: Ewma,
+}
+
+/// The filters `SenseHat::set_outlier_policy` checks readings with, one
+/// for each reading.
+#[cfg(feature = "sensors")]
+struct Outliers {
+    temperature_from_humidity: OutlierFilter,
+    temperature_from_pressure: OutlierFilter,
+    humidity: OutlierFilter,
+    pressure: OutlierFilter,
+    retry: bool,
+}
+
+/// The I2C addresses and registers of the humidity sensor and barometer,
+/// and how long to wait for them.
+#[cfg(feature = "sensors")]
+mod registers {
+    use std::time::Duration;
+
+    /// The I2C address of the LPS25H pressure sensor.
+    pub const LPS25H_ADDRESS: u16 = 0x5c;
+    /// The I2C address of the HTS221 humidity sensor.
+    pub const HTS221_ADDRESS: u16 = 0x5f;
+
+    // Registers for the HT221 humidity sensor
+    pub const HTS221_WHO_AM_I: u8 = 0x0f;
+    pub const HTS221_ID: u8 = 0xbc;
+    pub const HTS221_AV_CONF: u8 = 0x10;
+    pub const HTS221_CTRL1: u8 = 0x20;
+    pub const HTS221_CTRL2: u8 = 0x21;
+    pub const HTS221_CTRL3: u8 = 0x22;
+    pub const HTS221_STATUS: u8 = 0x27;
+    pub const HTS221_HUMIDITY_OUT_L: u8 = 0x28;
+    pub const HTS221_HUMIDITY_OUT_H: u8 = 0x29;
+    pub const HTS221_TEMP_OUT_L: u8 = 0x2a;
+    pub const HTS221_TEMP_OUT_H: u8 = 0x2b;
+    /// The first of the 16 calibration registers.
+    pub const HTS221_CALIBRATION: u8 = 0x30;
+
+    // Registers for the LPS25H pressure sensor
+    pub const LPS25H_REF_P_XL: u8 = 0x08;
+    pub const LPS25H_WHO_AM_I: u8 = 0x0f;
+    pub const LPS25H_ID: u8 = 0xbd;
+    pub const LPS25H_RES_CONF: u8 = 0x10;
+    pub const LPS25H_CTRL_REG_1: u8 = 0x20;
+    pub const LPS25H_CTRL_REG_2: u8 = 0x21;
+    pub const LPS25H_CTRL_REG_3: u8 = 0x22;
+    pub const LPS25H_INTERRUPT_CFG: u8 = 0x24;
+    pub const LPS25H_INT_SOURCE: u8 = 0x25;
+    pub const LPS25H_STATUS_REG: u8 = 0x27;
+    pub const LPS25H_PRESS_OUT_XL: u8 = 0x28;
+    pub const LPS25H_PRESS_OUT_L: u8 = 0x29;
+    pub const LPS25H_PRESS_OUT_H: u8 = 0x2a;
+    pub const LPS25H_TEMP_OUT_L: u8 = 0x2b;
+    pub const LPS25H_TEMP_OUT_H: u8 = 0x2c;
+    pub const LPS25H_FIFO_CTRL: u8 = 0x2e;
+    pub const LPS25H_THS_P_L: u8 = 0x30;
+    pub const LPS25H_THS_P_H: u8 = 0x31;
+    pub const LPS25H_RPDS_L: u8 = 0x39;
+    pub const LPS25H_RPDS_H: u8 = 0x3a;
+    /// Set in CTRL_REG_1 to compare the pressure against the reference, for
+    /// the threshold interrupt.
+    pub const LPS25H_DIFF_EN: u8 = 0x08;
+    /// Set in CTRL_REG2 of the humidity sensor and barometer to reload their
+    /// trimming parameters. It clears itself once they're loaded.
+    pub const BOOT: u8 = 0x80;
+    /// How long to wait for a sensor to finish booting.
+    pub const BOOT_TIMEOUT: Duration = Duration::from_millis(100);
+    /// How many times `self_test` and `get_environment` try a reading which
+    /// isn't ready yet.
+    pub const NOT_READY_ATTEMPTS: u32 = 20;
+    /// How long they wait between tries.
+    pub const NOT_READY_DELAY: Duration = Duration::from_millis(50);
+    /// Set in a register address to read several registers in one go.
+    pub const LPS25H_AUTO_INCREMENT: u8 = 0x80;
+}
+
+#[cfg(feature = "sensors")]
+use self::registers::*;
+
+impl SenseHat {
+    /// Try and create a new SenseHat object.
+    ///
+    /// Will open the relevant I2C devices and then attempt to initialise the
+    /// chips on the Sense Hat. If a component fails to initialise, the rest
+    /// of the Sense Hat is still usable and the methods which need the
+    /// failed component return `ComponentNotAvailable`. Use `health` to see
+    /// which components are working.
+    pub fn new() -> SenseHatResult<SenseHat> {
+        SenseHat::builder().build()
+    }
+
+    /// Like `new`, but fails if any component fails to initialise.
+    pub fn new_strict() -> SenseHatResult<SenseHat> {
+        SenseHat::builder().strict(true).build()
+    }
+
+    /// Returns a builder for a `SenseHat` using devices of your choosing.
+    pub fn builder() -> SenseHatBuilder {
+        SenseHatBuilder::default()
+    }
+
+    /// Reports which components of the Sense Hat initialised successfully.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(feature = "sensors")]
+    /// # {
+    /// use sensehat::{Component, MockI2CDevice, SenseHat, SenseHatError};
+    ///
+    /// // A humidity sensor which has died.
+    /// let (pressure, humidity) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// humidity.set_failing(true);
+    /// let mut sense = SenseHat::builder()
+    ///     .pressure_device(pressure.clone())
+    ///     .humidity_device(humidity)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let health = sense.health();
+    /// assert!(!health.is_healthy());
+    /// assert!(health.status(Component::Pressure).unwrap().is_available());
+    /// assert!(!health.status(Component::Humidity).unwrap().is_available());
+    ///
+    /// // The rest of the Sense Hat still works.
+    /// pressure.set_registers(0x27, &[0x03, 0x00, 0x00, 0x40]);
+    /// assert_eq!(sense.get_pressure().unwrap().as_hectopascals(), 1024.0);
+    /// match sense.get_humidity() {
+    ///     Err(SenseHatError::ComponentNotAvailable { component: Component::Humidity, cause }) => {
+    ///         assert_eq!(cause.to_string(), "I2C error: mock I2C device is failing");
+    ///     }
+    ///     other => panic!("{:?}", other.map(|h| h.as_percent())),
+    /// }
+    /// # }
+    /// ```
+    #[allow(clippy::vec_init_then_push)]
+    pub fn health(&self) -> HealthReport {
+        #[cfg_attr(not(any(feature = "display", feature = "sensors")), allow(unused_mut))]
+        let mut components = Vec::new();
+        #[cfg(feature = "sensors")]
+        {
+            components.push((Component::Pressure, status(&self.pressure_dev)));
+            components.push((Component::Humidity, status(&self.humidity_dev)));
+        }
+        #[cfg(feature = "display")]
+        components.push((Component::Display, status(&self.display)));
+        #[cfg(feature = "sensors")]
+        components.push((Component::Imu, status(&self.imu)));
+        HealthReport { components }
+    }
+}
+
+impl SenseHatBuilder {
+    /// Talks to the LPS25H pressure sensor through `dev`.
+    #[cfg(feature = "sensors")]
+    pub fn pressure_device<D>(mut self, dev: D) -> Self
+    where
+        D: I2CDevice + Send + 'static,
+        SenseHatError: From<D::Error>,
+    {
+        self.pressure_dev = Some(BoxedI2CDevice::new(dev));
+        self
+    }
+
+    /// Talks to the HTS221 humidity sensor through `dev`.
+    #[cfg(feature = "sensors")]
+    pub fn humidity_device<D>(mut self, dev: D) -> Self
+    where
+        D: I2CDevice + Send + 'static,
+        SenseHatError: From<D::Error>,
+    {
+        self.humidity_dev = Some(BoxedI2CDevice::new(dev));
+        self
+    }
+
+    /// Talks to the LSM9DS1 IMU through `accel_gyro`, for the accelerometer
+    /// and gyro, and `magnetometer`.
+    #[cfg(feature = "sensors")]
+    pub fn imu_devices<A, M>(mut self, accel_gyro: A, magnetometer: M) -> Self
+    where
+        A: I2CDevice + Send + 'static,
+        M: I2CDevice + Send + 'static,
+        SenseHatError: From<A::Error> + From<M::Error>,
+    {
+        self.imu_devs = Some((BoxedI2CDevice::new(accel_gyro), BoxedI2CDevice::new(magnetometer)));
+        self
+    }
+
+    /// Draws on `matrix`, such as a `MemoryMatrix`, rather than the Sense
+    /// Hat's framebuffer.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::{MemoryMatrix, SenseHat};
+    ///
+    /// let matrix = MemoryMatrix::new();
+    /// let mut sense = SenseHat::builder().led_matrix(matrix.clone()).build().unwrap();
+    /// sense.set_pixel(0, 7, (0, 255, 0)).unwrap();
+    /// assert_eq!(matrix.pixels()[8 * 7], (0, 255, 0));
+    /// ```
+    #[cfg(feature = "display")]
+    pub fn led_matrix<M: LedMatrix + 'static>(mut self, matrix: M) -> Self {
+        self.display = Some(Display::with_matrix(matrix));
+        self
+    }
+
+    /// Whether `build` fails if any component fails to initialise, as
+    /// `SenseHat::new_strict` does. By default it doesn't, as
+    /// `SenseHat::new` doesn't.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(all(feature = "display", feature = "sensors"))]
+    /// # {
+    /// use sensehat::{Component, MemoryMatrix, MockI2CDevice, SenseHat, SenseHatBuilder};
+    ///
+    /// // Every component, with the IMU's chips answering with their ids.
+    /// fn builder(humidity: &MockI2CDevice) -> SenseHatBuilder {
+    ///     let (accel_gyro, mag) = (MockI2CDevice::new(), MockI2CDevice::new());
+    ///     accel_gyro.set_register(0x0f, 0x68);
+    ///     mag.set_register(0x0f, 0x3d);
+    ///     SenseHat::builder()
+    ///         .pressure_device(MockI2CDevice::new())
+    ///         .humidity_device(humidity.clone())
+    ///         .imu_devices(accel_gyro, mag)
+    ///         .led_matrix(MemoryMatrix::new())
+    /// }
+    ///
+    /// let humidity = MockI2CDevice::new();
+    /// let sense = builder(&humidity).strict(true).build().unwrap();
+    /// assert!(sense.health().is_healthy());
+    ///
+    /// // In strict mode a failing component fails the build...
+    /// humidity.set_failing(true);
+    /// match builder(&humidity).strict(true).build() {
+    ///     Err(err) => assert_eq!(err.to_string(), "I2C error: mock I2C device is failing"),
+    ///     Ok(_) => panic!("a failing humidity sensor should fail a strict build"),
+    /// }
+    ///
+    /// // ...and otherwise only that component is unavailable.
+    /// let health = builder(&humidity).build().unwrap().health();
+    /// assert!(!health.status(Component::Humidity).unwrap().is_available());
+    /// for &component in &[Component::Pressure, Component::Display, Component::Imu] {
+    ///     assert!(health.status(component).unwrap().is_available());
+    /// }
+    /// # }
+    /// ```
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Opens and initialises every component. In strict mode the first
+    /// failure is returned, otherwise it is recorded against the component.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(feature = "sensors")]
+    /// # {
+    /// use sensehat::{Component, MockI2CDevice, SenseHat};
+    ///
+    /// let pressure = MockI2CDevice::new();
+    /// let mut sense = SenseHat::builder().pressure_device(pressure.clone()).build().unwrap();
+    /// // The LPS25H init sequence from RTIMULib.
+    /// assert_eq!(pressure.writes(), [(0x20, 0xc4), (0x10, 0x05), (0x2e, 0xc0), (0x21, 0x40)]);
+    ///
+    /// // 1013.25 hPa is 4150272 / 4096.
+    /// pressure.set_registers(0x27, &[0x03, 0x00, 0x54, 0x3f]);
+    /// assert_eq!(sense.get_pressure().unwrap().as_hectopascals(), 1013.25);
+    ///
+    /// // A sensor which doesn't answer.
+    /// pressure.set_failing(true);
+    /// let sense = SenseHat::builder().pressure_device(pressure).build().unwrap();
+    /// assert!(!sense.health().status(Component::Pressure).unwrap().is_available());
+    /// # }
+    /// ```
+    #[cfg_attr(not(any(feature = "display", feature = "sensors")), allow(unused_variables))]
+    pub fn build(self) -> SenseHatResult<SenseHat> {
+        let strict = self.strict;
+        #[cfg(feature = "sensors")]
+        let pressure_dev = match self.pressure_dev {
+            Some(dev) => Ok(TracedI2CDevice::new(dev, LPS25H_ADDRESS)),
+            None => i2c::open(LPS25H_ADDRESS),
+        };
+        #[cfg(feature = "sensors")]
+        let humidity_dev = match self.humidity_dev {
+            Some(dev) => Ok(TracedI2CDevice::new(dev, HTS221_ADDRESS)),
+            None => i2c::open(HTS221_ADDRESS),
+        };
+        #[cfg(feature = "sensors")]
+        let imu = match self.imu_devs {
+            Some((accel_gyro, magnetometer)) => {
+                Imu::from_devices(accel_gyro, magnetometer, Settings::default())
+            }
+            None => Imu::new(),
+        };
+        #[cfg_attr(not(feature = "sensors"), allow(unused_mut))]
+        let mut hat = SenseHat {
+            #[cfg(feature = "sensors")]
+            pressure_dev: slot(pressure_dev, strict)?,
+            #[cfg(feature = "sensors")]
+            humidity_dev: slot(humidity_dev, strict)?,
+            #[cfg(feature = "display")]
+            display: slot(self.display.map_or_else(Display::new, Ok), strict)?,
+            #[cfg(feature = "sensors")]
+            imu: slot(imu, strict)?,
+            #[cfg(feature = "sensors")]
+            humidity_calibration: HumidityCalibration::default(),
+            #[cfg(feature = "sensors")]
+            power_mode: PowerMode::Performance,
+            #[cfg(feature = "sensors")]
+            imu_config: None,
+            #[cfg(feature = "sensors")]
+            smoothing: None,
+            #[cfg(feature = "sensors")]
+            smoothing_max_age: None,
+            #[cfg(feature = "sensors")]
+            outliers: None,
+        };
+
+        #[cfg(feature = "sensors")]
+        {
+            if hat.pressure_dev.is_ok() {
+                if let Err(err) = hat.init_pressure() {
+                    hat.pressure_dev = Err(Arc::new(slot_err(err, strict)?));
+                }
+            }
+            if hat.humidity_dev.is_ok() {
+                if let Err(err) = hat.init_humidity() {
+                    hat.humidity_dev = Err(Arc::new(slot_err(err, strict)?));
+                }
+            }
+        }
+
+        Ok(hat)
+    }
+}
+
+impl HealthReport {
+    /// Returns true if every component initialised successfully.
+    pub fn is_healthy(&self) -> bool {
+        self.components.iter().all(|(_, status)| status.is_available())
+    }
+
+    /// Returns the status of the given component, or `None` if this build
+    /// of the crate doesn't include it.
+    pub fn status(&self, component: Component) -> Option<&ComponentStatus> {
+        self.components
+            .iter()
+            .find(|&&(c, _)| c == component)
+            .map(|(_, status)| status)
+    }
+}
+
+impl ComponentStatus {
+    /// Returns true if the component is working.
+    pub fn is_available(&self) -> bool {
+        matches!(*self, ComponentStatus::Available)
+    }
+}
+
+/// Helper function.
+///
+/// In strict mode returns the result as is, otherwise turns a failure into
+/// a failed component.
+#[cfg(any(feature = "display", feature = "sensors"))]
+fn slot<T>(result: SenseHatResult<T>, strict: bool) -> SenseHatResult<Slot<T>> {
+    match result {
+        Ok(value) => Ok(Ok(value)),
+        Err(err) => Ok(Err(Arc::new(slot_err(err, strict)?))),
+    }
+}
+
+/// Helper function.
+///
+/// In strict mode returns the error, otherwise hands it back to be recorded.
+#[cfg(any(feature = "display", feature = "sensors"))]
+fn slot_err(err: SenseHatError, strict: bool) -> SenseHatResult<SenseHatError> {
+    if strict {
+        Err(err)
+    } else {
+        debug!("carrying on without a component which failed to initialise: {}", err);
+        Ok(err)
+    }
+}
+
+/// Helper function.
+///
+/// Reads the status of a component.
+#[cfg(any(feature = "display", feature = "sensors"))]
+fn status<T>(slot: &Slot<T>) -> ComponentStatus {
+    match *slot {
+        Ok(_) => ComponentStatus::Available,
+        Err(ref cause) => ComponentStatus::Failed(cause.clone()),
+    }
+}
+
+/// Helper function.
+///
+/// Borrows a component, or returns `ComponentNotAvailable` if it failed to
+/// initialise.
+#[cfg(feature = "display")]
+fn component<T>(slot: &Slot<T>, component: Component) -> SenseHatResult<&T> {
+    match *slot {
+        Ok(ref value) => Ok(value),
+        Err(ref cause) => Err(SenseHatError::ComponentNotAvailable {
+            component,
+            cause: cause.clone(),
+        }),
+    }
+}
+
+/// Helper function.
+///
+/// Mutably borrows a component, or returns `ComponentNotAvailable` if it
+/// failed to initialise.
+#[cfg(any(feature = "display", feature = "sensors"))]
+fn component_mut<T>(slot: &mut Slot<T>, component: Component) -> SenseHatResult<&mut T> {
+    match *slot {
+        Ok(ref mut value) => Ok(value),
+        Err(ref cause) => Err(SenseHatError::ComponentNotAvailable {
+            component,
+            cause: cause.clone(),
+        }),
+    }
+}
+
+/// Helper function.
+///
+/// Writes the humidity sensor's registers for a power mode.
+#[cfg(feature = "sensors")]
+fn apply_hts221_power_mode(
+    dev: &mut TracedI2CDevice<BoxedI2CDevice>,
+    mode: PowerMode,
+) -> SenseHatResult<()> {
+    let (ctrl1, av_conf) = mode.hts221_registers();
+    dev.smbus_write_byte_data(HTS221_AV_CONF, av_conf)?;
+    dev.smbus_write_byte_data(HTS221_CTRL1, ctrl1)?;
+    Ok(())
+}
+
+/// Helper function.
+///
+/// Writes the barometer's registers for a power mode.
+#[cfg(feature = "sensors")]
+fn apply_lps25h_power_mode(
+    dev: &mut TracedI2CDevice<BoxedI2CDevice>,
+    mode: PowerMode,
+) -> SenseHatResult<()> {
+    let current = dev.smbus_read_byte_data(LPS25H_CTRL_REG_1)?;
+    let (ctrl_reg_1, res_conf) = mode.lps25h_registers(current);
+    dev.smbus_write_byte_data(LPS25H_RES_CONF, res_conf)?;
+    dev.smbus_write_byte_data(LPS25H_CTRL_REG_1, ctrl_reg_1)?;
+    Ok(())
+}
+
+/// Helper function.
+///
+/// Sets the BOOT bit in a sensor's CTRL_REG2, and waits for it to clear
+/// itself, which it does once the trimming parameters are reloaded.
+#[cfg(feature = "sensors")]
+fn boot(dev: &mut TracedI2CDevice<BoxedI2CDevice>, ctrl_reg_2: u8) -> SenseHatResult<()> {
+    let value = dev.smbus_read_byte_data(ctrl_reg_2)?;
+    dev.smbus_write_byte_data(ctrl_reg_2, value | BOOT)?;
+    let deadline = Instant::now() + BOOT_TIMEOUT;
+    while dev.smbus_read_byte_data(ctrl_reg_2)? & BOOT != 0 {
+        if Instant::now() >= deadline {
+            return Err(SenseHatError::Timeout);
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+    Ok(())
+}
+
+/// Helper function.
+///
+/// Checks the WHO_AM_I register of a sensor, for `self_test`.
+#[cfg(feature = "sensors")]
+fn who_am_i_check(
+    slot: &mut Slot<TracedI2CDevice<BoxedI2CDevice>>,
+    component: Component,
+    register: u8,
+    expected: u8,
+) -> SelfTestCheck {
+    let id = component_mut(slot, component).and_then(|dev| dev.smbus_read_byte_data(register));
+    let (observed, passed) = match id {
+        Ok(id) if id == expected => (format!("{:#04x}", id), true),
+        Ok(id) => (format!("{:#04x}, expected {:#04x}", id, expected), false),
+        Err(err) => (err.to_string(), false),
+    };
+    SelfTestCheck {
+        component,
+        check: "WHO_AM_I".to_owned(),
+        observed,
+        passed,
+    }
+}
+
+/// Helper function.
+///
+/// Checks a reading is believable, for `self_test`.
+#[cfg(feature = "sensors")]
+fn reading_check(
+    component: Component,
+    measurement: Measurement,
+    reading: SenseHatResult<f64>,
+    unit: &str,
+) -> SelfTestCheck {
+    let (observed, passed) = match reading {
+        Ok(value) => (format!("{:.1}{}", value, unit), is_plausible(measurement, value)),
+        Err(err) => (err.to_string(), false),
+    };
+    SelfTestCheck {
+        component,
+        check: measurement.to_string(),
+        observed,
+        passed,
+    }
+}
+
+/// Helper function.
+///
+/// Tries a reading a few times while it isn't ready, for `self_test` and
+/// `get_environment`.
+#[cfg(feature = "sensors")]
+fn retry_not_ready<T, F>(mut read: F) -> SenseHatResult<T>
+where
+    F: FnMut() -> SenseHatResult<T>,
+{
+    let mut attempt = 1;
+    loop {
+        match read() {
+            Err(ref err) if err.is_not_ready() && attempt < NOT_READY_ATTEMPTS => {
+                attempt += 1;
+                thread::sleep(NOT_READY_DELAY);
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(feature = "sensors")]
+impl SenseHat {
+    /// Init sequence from https://github.com/RPi-Distro/RTIMULib
+    fn init_pressure(&mut self) -> SenseHatResult<()> {
+        let pressure_dev = component_mut(&mut self.pressure_dev, Component::Pressure)?;
+        pressure_dev.smbus_write_byte_data(LPS25H_CTRL_REG_1, 0xc4)?;
+        pressure_dev.smbus_write_byte_data(LPS25H_RES_CONF, 0x05)?;
+        pressure_dev.smbus_write_byte_data(LPS25H_FIFO_CTRL, 0xc0)?;
+        pressure_dev.smbus_write_byte_data(LPS25H_CTRL_REG_2, 0x40)?;
+        debug!("initialised the LPS25H pressure sensor");
+        Ok(())
+    }
+
+    /// Init sequence from https://github.com/RPi-Distro/RTIMULib
+    fn init_humidity(&mut self) -> SenseHatResult<()> {
+        let humidity_dev = component_mut(&mut self.humidity_dev, Component::Humidity)?;
+
+        // Init
+        humidity_dev.smbus_write_byte_data(HTS221_CTRL1, 0x87)?;
+        humidity_dev.smbus_write_byte_data(HTS221_AV_CONF, 0x1b)?;
+
+        self.reload_humidity_calibration()?;
+        debug!("initialised the HTS221 humidity sensor: {:?}", self.humidity_calibration);
+        Ok(())
+    }
+
+    /// Puts the humidity sensor, barometer and IMU into the given power
+    /// mode. Putting them in `Standby` turns the IMU's sensors off, and
+    /// coming out of it turns back on the ones that were on. The IMU is
+    /// left as it is in `LowPower`. Components which failed to initialise
+    /// are skipped.
+    ///
+    /// While in `Standby`, reading the humidity, pressure or temperature
+    /// returns `Standby`, and reading the IMU returns `ComponentDisabled`.
+    ///
+    /// If a component fails to switch, those already switched are put back
+    /// in the previous mode, as far as they can be, and the error is
+    /// returned, leaving them all in `power_mode`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{PowerMode, SenseHat};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// sense.set_power_mode(PowerMode::Standby).unwrap();
+    /// assert!(sense.get_pressure().is_err());
+    /// sense.set_power_mode(PowerMode::Performance).unwrap();
+    /// ```
+    ///
+    /// Off the hardware, with the chips mocked and the barometer failing
+    /// after the humidity sensor has gone into standby:
+    ///
+    /// ```
+    /// use sensehat::{MockI2CDevice, PowerMode, SenseHat};
+    ///
+    /// let (humidity, pressure) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// let (accel_gyro, mag) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// accel_gyro.set_register(0x0f, 0x68);
+    /// mag.set_register(0x0f, 0x3d);
+    /// let mut sense = SenseHat::builder()
+    ///     .humidity_device(humidity.clone())
+    ///     .pressure_device(pressure.clone())
+    ///     .imu_devices(accel_gyro, mag)
+    ///     .build()
+    ///     .unwrap();
+    /// let imu_config = sense.imu().unwrap().config();
+    ///
+    /// humidity.clear_writes();
+    /// pressure.set_failing(true);
+    /// assert!(sense.set_power_mode(PowerMode::Standby).is_err());
+    /// // The humidity sensor went into standby, then back into performance.
+    /// assert_eq!(humidity.writes(), [(0x10, 0x1b), (0x20, 0x07), (0x10, 0x1b), (0x20, 0x87)]);
+    /// assert_eq!(sense.power_mode(), PowerMode::Performance);
+    /// assert_eq!(sense.imu().unwrap().config(), imu_config);
+    ///
+    /// pressure.set_failing(false);
+    /// sense.set_power_mode(PowerMode::Standby).unwrap();
+    /// assert_eq!(humidity.register(0x20), 0x07);
+    /// ```
+    pub fn set_power_mode(&mut self, mode: PowerMode) -> SenseHatResult<()> {
+        if mode == self.power_mode {
+            return Ok(());
+        }
+        let previous = self.power_mode;
+        let mut switched = Vec::new();
+        for &component in &[Component::Humidity, Component::Pressure, Component::Imu] {
+            if let Err(err) = self.apply_power_mode(component, mode) {
+                for &component in switched.iter().rev() {
+                    // The error switching `component` is the one to return.
+                    let _ = self.apply_power_mode(component, previous);
+                }
+                return Err(err);
+            }
+            switched.push(component);
+        }
+        self.power_mode = mode;
+        Ok(())
+    }
+
+    /// Helper function.
+    ///
+    /// Puts one component into a power mode, if it initialised.
+    fn apply_power_mode(&mut self, component: Component, mode: PowerMode) -> SenseHatResult<()> {
+        match component {
+            Component::Humidity => {
+                if let Ok(ref mut humidity_dev) = self.humidity_dev {
+                    apply_hts221_power_mode(humidity_dev, mode)?;
+                }
+            }
+            Component::Pressure => {
+                if let Ok(ref mut pressure_dev) = self.pressure_dev {
+                    apply_lps25h_power_mode(pressure_dev, mode)?;
+                }
+            }
+            Component::Imu => {
+                if let Ok(ref mut imu) = self.imu {
+                    if mode == PowerMode::Standby {
+                        let config = imu.config();
+                        imu.set_config(false, false, false)?;
+                        self.imu_config = Some(config);
+                    } else if let Some(config) = self.imu_config {
+                        imu.set_config(config.compass, config.gyro, config.accel)?;
+                        self.imu_config = None;
+                    }
+                }
+            }
+            Component::Display => {}
+        }
+        Ok(())
+    }
+
+    /// Returns the humidity sensor's factory calibration, which turns its
+    /// raw readings into °C and %rH.
+    pub fn humidity_calibration(&self) -> &HumidityCalibration {
+        &self.humidity_calibration
+    }
+
+    /// Reads the humidity sensor's factory calibration again, and uses it
+    /// for the readings from now on. It's read when the Sense Hat is
+    /// opened, and after `reset_humidity_sensor`, so this is only needed if
+    /// something else has reset the sensor.
+    pub fn reload_humidity_calibration(&mut self) -> SenseHatResult<HumidityCalibration> {
+        let humidity_dev = component_mut(&mut self.humidity_dev, Component::Humidity)?;
+        let mut registers = [0u8; 16];
+        for (i, register) in registers.iter_mut().enumerate() {
+            *register = humidity_dev.smbus_read_byte_data(HTS221_CALIBRATION + i as u8)?;
+        }
+        self.humidity_calibration = HumidityCalibration::from_registers(&registers);
+        Ok(self.humidity_calibration)
+    }
+
+    /// Reboots the humidity sensor, reloading its trimming parameters, for
+    /// when it's stuck returning the same values, say after a brown-out.
+    /// Then initialises it again, reading its calibration again as it may
+    /// have changed, and puts it back in the current power mode.
+    ///
+    /// Returns `Timeout` if it doesn't finish rebooting within 100ms.
+    pub fn reset_humidity_sensor(&mut self) -> SenseHatResult<()> {
+        boot(component_mut(&mut self.humidity_dev, Component::Humidity)?, HTS221_CTRL2)?;
+        self.init_humidity()?;
+        let humidity_dev = component_mut(&mut self.humidity_dev, Component::Humidity)?;
+        apply_hts221_power_mode(humidity_dev, self.power_mode)
+    }
+
+    /// Reboots the barometer, reloading its trimming parameters, then
+    /// initialises it again and puts it back in the current power mode.
+    /// The pressure offset, reference and interrupt settings aren't set
+    /// again.
+    ///
+    /// Returns `Timeout` if it doesn't finish rebooting within 100ms.
+    pub fn reset_pressure_sensor(&mut self) -> SenseHatResult<()> {
+        boot(component_mut(&mut self.pressure_dev, Component::Pressure)?, LPS25H_CTRL_REG_2)?;
+        self.init_pressure()?;
+        let pressure_dev = component_mut(&mut self.pressure_dev, Component::Pressure)?;
+        apply_lps25h_power_mode(pressure_dev, self.power_mode)
+    }
+
+    /// Reboots the humidity sensor and the barometer, with
+    /// `reset_humidity_sensor` and `reset_pressure_sensor`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// sense.reset_sensors().unwrap();
+    /// println!("{}", sense.get_humidity().unwrap());
+    /// ```
+    ///
+    /// Off the hardware, with the sensors mocked. Each clears BOOT once it
+    /// has rebooted, and is then initialised again.
+    ///
+    /// ```
+    /// use sensehat::{MockI2CDevice, SenseHat, SenseHatError};
+    /// use std::thread;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// // Clears BOOT in CTRL_REG2 the first time it's set, as the chips do.
+    /// fn reboots(chip: &MockI2CDevice) -> thread::JoinHandle<()> {
+    ///     let chip = chip.clone();
+    ///     thread::spawn(move || {
+    ///         let deadline = Instant::now() + Duration::from_secs(5);
+    ///         while chip.register(0x21) & 0x80 == 0 && Instant::now() < deadline {
+    ///             thread::sleep(Duration::from_millis(1));
+    ///         }
+    ///         chip.set_register(0x21, chip.register(0x21) & !0x80);
+    ///     })
+    /// }
+    ///
+    /// let (pressure, humidity) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// let mut sense = SenseHat::builder()
+    ///     .pressure_device(pressure.clone())
+    ///     .humidity_device(humidity.clone())
+    ///     .build()
+    ///     .unwrap();
+    /// pressure.clear_writes();
+    /// humidity.clear_writes();
+    /// let chips = [reboots(&pressure), reboots(&humidity)];
+    /// sense.reset_sensors().unwrap();
+    /// for chip in chips {
+    ///     chip.join().unwrap();
+    /// }
+    /// // BOOT, the init sequence, then the power mode.
+    /// assert_eq!(
+    ///     humidity.writes(),
+    ///     [(0x21, 0x80), (0x20, 0x87), (0x10, 0x1b), (0x10, 0x1b), (0x20, 0x87)]
+    /// );
+    /// assert_eq!(
+    ///     pressure.writes(),
+    ///     [
+    ///         (0x21, 0xc0),
+    ///         (0x20, 0xc4),
+    ///         (0x10, 0x05),
+    ///         (0x2e, 0xc0),
+    ///         (0x21, 0x40),
+    ///         (0x10, 0x05),
+    ///         (0x20, 0xc4),
+    ///     ]
+    /// );
+    ///
+    /// // A sensor which never finishes rebooting.
+    /// let started = Instant::now();
+    /// match sense.reset_pressure_sensor() {
+    ///     Err(SenseHatError::Timeout) => {}
+    ///     other => panic!("{:?}", other),
+    /// }
+    /// assert!(started.elapsed() >= Duration::from_millis(100));
+    ///
+    /// humidity.set_failing(true);
+    /// assert!(sense.reset_humidity_sensor().is_err());
+    /// ```
+    pub fn reset_sensors(&mut self) -> SenseHatResult<()> {
+        self.reset_humidity_sensor()?;
+        self.reset_pressure_sensor()
+    }
+
+    /// Checks each sensor is the chip it should be, and that a reading of
+    /// each of its measurements is believable, as `is_plausible` says,
+    /// for telling a broken sensor apart from a bug. Readings which aren't
+    /// ready are waited for, for up to a second. Nothing is reconfigured.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// print!("{}", sense.self_test().unwrap());
+    /// ```
+    pub fn self_test(&mut self) -> SenseHatResult<SelfTestReport> {
+        let mut checks = vec![who_am_i_check(
+            &mut self.humidity_dev,
+            Component::Humidity,
+            HTS221_WHO_AM_I,
+            HTS221_ID,
+        )];
+        let reading = retry_not_ready(|| self.get_humidity_unsmoothed()).map(|h| h.as_percent());
+        checks.push(reading_check(Component::Humidity, Measurement::Humidity, reading, "%"));
+        let reading =
+            retry_not_ready(|| self.get_temperature_from_humidity_unsmoothed()).map(|t| t.as_celsius());
+        checks.push(reading_check(Component::Humidity, Measurement::Temperature, reading, " °C"));
+
+        checks.push(who_am_i_check(
+            &mut self.pressure_dev,
+            Component::Pressure,
+            LPS25H_WHO_AM_I,
+            LPS25H_ID,
+        ));
+        let reading = retry_not_ready(|| self.get_pressure_unsmoothed()).map(|p| p.as_hectopascals());
+        checks.push(reading_check(Component::Pressure, Measurement::Pressure, reading, " hPa"));
+        let reading =
+            retry_not_ready(|| self.get_temperature_from_pressure_unsmoothed()).map(|t| t.as_celsius());
+        checks.push(reading_check(Component::Pressure, Measurement::Temperature, reading, " °C"));
+
+        checks.push(match self.imu {
+            Ok(_) => SelfTestCheck {
+                component: Component::Imu,
+                check: "WHO_AM_I".to_owned(),
+                observed: format!("{:#04x}", ImuChip::Lsm9ds1.id()),
+                passed: true,
+            },
+            Err(ref cause) => SelfTestCheck {
+                component: Component::Imu,
+                check: "WHO_AM_I".to_owned(),
+                observed: cause.to_string(),
+                passed: false,
+            },
+        });
+        Ok(SelfTestReport { checks })
+    }
+
+    /// Returns the power mode set by `set_power_mode`.
+    pub fn power_mode(&self) -> PowerMode {
+        self.power_mode
+    }
+
+    /// Smooths the temperature, humidity and pressure readings with an
+    /// exponentially weighted moving average, or stops smoothing them if
+    /// `alpha` is None.
+    ///
+    /// Each reading moves its average `alpha` of the way towards it, so
+    /// smaller values smooth more. The first reading of each starts it off,
+    /// as does one more than `set_smoothing_max_age` after the last. Every
+    /// average starts again each time this is called. The `_unsmoothed`
+    /// and `_raw` getters aren't smoothed.
+    ///
+    /// Returns `InvalidSmoothing` unless `alpha` is more than 0 and no more
+    /// than 1.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// sense.set_smoothing(Some(0.2)).unwrap();
+    /// sense.set_smoothing_max_age(Some(Duration::from_secs(60)));
+    /// println!("{}", sense.get_temperature_from_humidity().unwrap());
+    /// ```
+    pub fn set_smoothing(&mut self, alpha: Option<f64>) -> SenseHatResult<()> {
+        self.smoothing = match alpha {
+            Some(alpha) => {
+                let mut ewma = Ewma::new(alpha)?;
+                ewma.set_max_age(self.smoothing_max_age);
+                Some(Smoothing {
+                    temperature_from_humidity: ewma,
+                    temperature_from_pressure: ewma,
+                    humidity: ewma,
+                    pressure: ewma,
+                })
+            }
+            None => None,
+        };
+        Ok(())
+    }
+
+    /// Returns the smoothing factor set with `set_smoothing`, or None if
+    /// readings aren't smoothed.
+    pub fn smoothing(&self) -> Option<f64> {
+        self.smoothing.as_ref().map(|s| s.humidity.alpha())
+    }
+
+    /// Sets how long a gap between readings makes `set_smoothing` start
+    /// the average off again, rather than averaging with stale readings.
+    /// None, the default, means never.
+    pub fn set_smoothing_max_age(&mut self, max_age: Option<Duration>) {
+        self.smoothing_max_age = max_age;
+        if let Some(ref mut s) = self.smoothing {
+            for ewma in &mut [
+                &mut s.temperature_from_humidity,
+                &mut s.temperature_from_pressure,
+                &mut s.humidity,
+                &mut s.pressure,
+            ] {
+                ewma.set_max_age(max_age);
+            }
+        }
+    }
+
+    /// Rejects temperature, humidity and pressure readings which break the
+    /// rules of `policy`, as they're probably I2C glitches, so they don't
+    /// throw off averages. Rejected readings return `OutlierRejected`, or
+    /// are read once more first if `policy.retry` is set. Every reading
+    /// but the `_raw` ones is checked, before it's smoothed. Calling this
+    /// again forgets the readings so far, and the default policy turns
+    /// checking off.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{OutlierPolicy, OutlierRule, SenseHat};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// sense.set_outlier_policy(OutlierPolicy {
+    ///     temperature: vec![
+    ///         OutlierRule::Range { min: -40.0, max: 85.0 },
+    ///         OutlierRule::JumpLimit { max_delta_per_second: 2.0 },
+    ///     ],
+    ///     retry: true,
+    ///     ..OutlierPolicy::default()
+    /// });
+    /// println!("{}", sense.get_temperature_from_humidity().unwrap());
+    /// ```
+    pub fn set_outlier_policy(&mut self, policy: OutlierPolicy) {
+        self.outliers = if policy == OutlierPolicy::default() {
+            None
+        } else {
+            Some(Outliers {
+                temperature_from_humidity: OutlierFilter::new(policy.temperature.clone()),
+                temperature_from_pressure: OutlierFilter::new(policy.temperature),
+                humidity: OutlierFilter::new(policy.humidity),
+                pressure: OutlierFilter::new(policy.pressure),
+                retry: policy.retry,
+            })
+        };
+    }
+
+    /// Helper function.
+    ///
+    /// Takes a reading with `read` and checks it with `check`, reading
+    /// again once if it's rejected and the policy says to retry.
+    fn read_checked<T>(
+        &mut self,
+        read: fn(&mut SenseHat) -> SenseHatResult<T>,
+        check: fn(&mut Outliers, T, Instant) -> SenseHatResult<T>,
+    ) -> SenseHatResult<T> {
+        let retry = match self.outliers {
+            Some(ref outliers) => outliers.retry,
+            None => return read(self),
+        };
+        let checked = |hat: &mut SenseHat, reading| match hat.outliers {
+            Some(ref mut outliers) => check(outliers, reading, Instant::now()),
+            None => Ok(reading),
+        };
+        let reading = read(self)?;
+        match checked(self, reading) {
+            Err(SenseHatError::OutlierRejected { .. }) if retry => {
+                let reading = read(self)?;
+                checked(self, reading)
+            }
+            result => result,
+        }
+    }
+
+    /// Helper function.
+    ///
+    /// Adds a reading to the average `pick` chooses and returns it, or
+    /// returns the reading as it is if smoothing is off.
+    fn smooth(&mut self, pick: fn(&mut Smoothing) -> &mut Ewma, value: f64) -> f64 {
+        match self.smoothing {
+            Some(ref mut s) => pick(s).update(value, Instant::now()),
+            None => value,
+        }
+    }
+
+    /// Helper function.
+    ///
+    /// Returns `Standby` if the sensors are powered down.
+    fn check_awake(&self, sensor: Sensor, measurement: Measurement) -> SenseHatResult<()> {
+        if self.power_mode == PowerMode::Standby {
+            return Err(SenseHatError::Standby {
+                sensor,
+                measurement,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns a Temperature reading from the barometer.  It's less accurate
+    /// than the barometer (+/- 2 degrees C), but over a wider range.
+    /// Smoothed, if `set_smoothing` is on.
+    pub fn get_temperature_from_pressure(&mut self) -> SenseHatResult<Temperature> {
+        let celsius = self.get_temperature_from_pressure_unsmoothed()?.as_celsius();
+        let celsius = self.smooth(|s| &mut s.temperature_from_pressure, celsius);
+        Ok(Temperature::from_celsius(celsius))
+    }
+
+    /// Returns a Temperature reading from the barometer, ignoring
+    /// `set_smoothing`.
+    pub fn get_temperature_from_pressure_unsmoothed(&mut self) -> SenseHatResult<Temperature> {
+        let celsius = self.read_checked(
+            SenseHat::read_temperature_from_pressure,
+            |o, celsius, now| o.temperature_from_pressure.filter(celsius, now),
+        )?;
+        Ok(Temperature::from_celsius(celsius))
+    }
+
+    /// Helper function.
+    ///
+    /// Reads the barometer's temperature, in °C.
+    fn read_temperature_from_pressure(&mut self) -> SenseHatResult<f64> {
+        self.check_awake(Sensor::Pressure, Measurement::Temperature)?;
+        let pressure_dev = component_mut(&mut self.pressure_dev, Component::Pressure)?;
+        let status = pressure_dev.smbus_read_byte_data(LPS25H_STATUS_REG)?;
+        if (status & 1) != 0 {
+            let mut buf = [0u8; 2];
+            buf[0] = pressure_dev.smbus_read_byte_data(LPS25H_TEMP_OUT_L)?;
+            buf[1] = pressure_dev.smbus_read_byte_data(LPS25H_TEMP_OUT_H)?;
+            Ok(((LittleEndian::read_i16(&buf) as f64) / 480.0) + 42.5)
+        } else {
+            Err(SenseHatError::NotReady {
+                sensor: Sensor::Pressure,
+                measurement: Measurement::Temperature,
+            })
+        }
+    }
+
+    /// Returns a Pressure value from the barometer. Smoothed, if
+    /// `set_smoothing` is on.
+    pub fn get_pressure(&mut self) -> SenseHatResult<Pressure> {
+        let hectopascals = self.get_pressure_unsmoothed()?.as_hectopascals();
+        let hectopascals = self.smooth(|s| &mut s.pressure, hectopascals);
+        Ok(Pressure::from_hectopascals(hectopascals))
+    }
+
+    /// Returns a Pressure value from the barometer, ignoring
+    /// `set_smoothing`.
+    pub fn get_pressure_unsmoothed(&mut self) -> SenseHatResult<Pressure> {
+        let hectopascals = self.read_checked(
+            |hat| Ok(pressure_from_raw(hat.get_pressure_raw()?).as_hectopascals()),
+            |o, hectopascals, now| o.pressure.filter(hectopascals, now),
+        )?;
+        Ok(Pressure::from_hectopascals(hectopascals))
+    }
+
+    /// Returns the barometer's raw pressure reading, before it's turned
+    /// into hPa by `pressure_from_raw`.
+    pub fn get_pressure_raw(&mut self) -> SenseHatResult<i32> {
+        self.check_awake(Sensor::Pressure, Measurement::Pressure)?;
+        let pressure_dev = component_mut(&mut self.pressure_dev, Component::Pressure)?;
+        let status = pressure_dev.smbus_read_byte_data(LPS25H_STATUS_REG)?;
+        if (status & 2) != 0 {
+            let mut buf = [0u8; 4];
+            buf[0] = pressure_dev.smbus_read_byte_data(LPS25H_PRESS_OUT_XL)?;
+            buf[1] = pressure_dev.smbus_read_byte_data(LPS25H_PRESS_OUT_L)?;
+            buf[2] = pressure_dev.smbus_read_byte_data(LPS25H_PRESS_OUT_H)?;
+            Ok(LittleEndian::read_i32(&buf))
+        } else {
+            Err(SenseHatError::NotReady {
+                sensor: Sensor::Pressure,
+                measurement: Measurement::Pressure,
+            })
+        }
+    }
+
+    /// Sets the offset the barometer adds to every pressure it reads, to
+    /// correct for its error once it's soldered to the board, rounded to
+    /// the nearest 1/16 hPa. Returns `InvalidPressureOffset` if it's not
+    /// between -2048 and 2047.9375 hPa.
+    pub fn set_pressure_offset(&mut self, offset: Pressure) -> SenseHatResult<()> {
+        let registers = pressure_offset_to_registers(offset)?;
+        let pressure_dev = component_mut(&mut self.pressure_dev, Component::Pressure)?;
+        pressure_dev.smbus_write_byte_data(LPS25H_RPDS_L, registers[0])?;
+        pressure_dev.smbus_write_byte_data(LPS25H_RPDS_H, registers[1])?;
+        // Readings jump by the change in offset, which isn't a glitch.
+        if let Some(ref mut outliers) = self.outliers {
+            outliers.pressure.reset();
+        }
+        Ok(())
+    }
+
+    /// Returns the offset the barometer adds to every pressure it reads.
+    pub fn pressure_offset(&mut self) -> SenseHatResult<Pressure> {
+        let pressure_dev = component_mut(&mut self.pressure_dev, Component::Pressure)?;
+        let registers = [
+            pressure_dev.smbus_read_byte_data(LPS25H_RPDS_L)?,
+            pressure_dev.smbus_read_byte_data(LPS25H_RPDS_H)?,
+        ];
+        Ok(pressure_offset_from_registers(registers))
+    }
+
+    /// Reads the pressure, and changes the barometer's offset so it reads
+    /// `known` instead, say the pressure from a nearby weather station.
+    /// Returns the new offset.
+    ///
+    /// Returns `NotReady` without changing anything if there's no new
+    /// pressure to compare against yet.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{Pressure, SenseHat};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let offset = sense.calibrate_pressure(Pressure::from_hectopascals(1013.2)).unwrap();
+    /// println!("offset is now {}", offset);
+    /// ```
+    pub fn calibrate_pressure(&mut self, known: Pressure) -> SenseHatResult<Pressure> {
+        let measured = self.get_pressure_unsmoothed()?;
+        let current = self.pressure_offset()?;
+        let offset = Pressure::from_hectopascals(
+            current.as_hectopascals() + known.as_hectopascals() - measured.as_hectopascals(),
+        );
+        self.set_pressure_offset(offset)?;
+        self.pressure_offset()
+    }
+
+    /// Sets the reference pressure the barometer's threshold interrupt
+    /// compares against, rounded to the nearest 1/4096 hPa. See
+    /// `set_pressure_interrupt`.
+    pub fn set_pressure_reference(&mut self, reference: Pressure) -> SenseHatResult<()> {
+        let registers = pressure_reference_to_registers(reference)?;
+        let pressure_dev = component_mut(&mut self.pressure_dev, Component::Pressure)?;
+        for (i, &value) in registers.iter().enumerate() {
+            pressure_dev.smbus_write_byte_data(LPS25H_REF_P_XL + i as u8, value)?;
+        }
+        Ok(())
+    }
+
+    /// Makes the barometer latch an interrupt when the pressure crosses a
+    /// threshold, to be picked up with `pressure_interrupt_source`.
+    ///
+    /// The barometer compares the pressure minus the reference pressure,
+    /// from `set_pressure_reference`, against plus or minus `threshold`.
+    /// The reference starts at 0, so `High` on its own fires when the
+    /// pressure goes above `threshold`. To be told when the pressure drops
+    /// below a value, set the reference to that value and use `Low` with a
+    /// threshold of 0. `Disabled` turns the comparison off again.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{Pressure, PressureIntMode, SenseHat};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// sense.set_pressure_reference(Pressure::from_hectopascals(990.0)).unwrap();
+    /// sense
+    ///     .set_pressure_interrupt(Pressure::from_hectopascals(0.0), PressureIntMode::Low)
+    ///     .unwrap();
+    /// if sense.pressure_interrupt_source().unwrap().low {
+    ///     println!("storm coming");
+    /// }
+    /// ```
+    pub fn set_pressure_interrupt(
+        &mut self,
+        threshold: Pressure,
+        mode: PressureIntMode,
+    ) -> SenseHatResult<()> {
+        let registers = pressure_threshold_to_registers(threshold)?;
+        let pressure_dev = component_mut(&mut self.pressure_dev, Component::Pressure)?;
+        pressure_dev.smbus_write_byte_data(LPS25H_THS_P_L, registers[0])?;
+        pressure_dev.smbus_write_byte_data(LPS25H_THS_P_H, registers[1])?;
+        pressure_dev.smbus_write_byte_data(LPS25H_INTERRUPT_CFG, mode.interrupt_cfg())?;
+        pressure_dev.smbus_write_byte_data(LPS25H_CTRL_REG_3, mode.ctrl_reg3())?;
+        let ctrl_reg_1 = pressure_dev.smbus_read_byte_data(LPS25H_CTRL_REG_1)?;
+        let ctrl_reg_1 = match mode {
+            PressureIntMode::Disabled => ctrl_reg_1 & !LPS25H_DIFF_EN,
+            _ => ctrl_reg_1 | LPS25H_DIFF_EN,
+        };
+        pressure_dev.smbus_write_byte_data(LPS25H_CTRL_REG_1, ctrl_reg_1)?;
+        Ok(())
+    }
+
+    /// Returns which pressure threshold crossings have happened since this
+    /// was last called. Reading it clears the latched interrupt.
+    pub fn pressure_interrupt_source(&mut self) -> SenseHatResult<PressureIntSource> {
+        let pressure_dev = component_mut(&mut self.pressure_dev, Component::Pressure)?;
+        let source = pressure_dev.smbus_read_byte_data(LPS25H_INT_SOURCE)?;
+        Ok(PressureIntSource::from_register(source))
+    }
+
+    /// Returns a Pressure value and a Temperature reading from the
+    /// barometer, both from the same conversion, read in one go so the
+    /// barometer can't start the next conversion in between.
+    ///
+    /// Both have to be ready. If only one is, this returns `NotReady` for
+    /// the other, and reads nothing, so the one that was ready still is for
+    /// the next call.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let (pressure, temperature) = sense.get_pressure_and_temperature().unwrap();
+    /// println!("{} at {}", pressure, temperature);
+    /// ```
+    ///
+    /// Off the hardware, with the barometer mocked:
+    ///
+    /// ```
+    /// use sensehat::{Measurement, MockI2CDevice, SenseHat, SenseHatError};
+    ///
+    /// let pressure = MockI2CDevice::new();
+    /// let mut sense = SenseHat::builder().pressure_device(pressure.clone()).build().unwrap();
+    /// // 1013.25 hPa, and 22.5C, which is 42.5C - 9600 / 480.
+    /// pressure.set_registers(0x28, &[0x00, 0x54, 0x3f, 0x80, 0xda]);
+    ///
+    /// let not_ready = |sense: &mut SenseHat| match sense.get_pressure_and_temperature() {
+    ///     Err(SenseHatError::NotReady { measurement, .. }) => measurement,
+    ///     other => panic!("{:?}", other),
+    /// };
+    /// pressure.set_register(0x27, 0x00);
+    /// assert_eq!(not_ready(&mut sense), Measurement::Pressure);
+    /// // Only the pressure, or only the temperature.
+    /// pressure.set_register(0x27, 0x02);
+    /// assert_eq!(not_ready(&mut sense), Measurement::Temperature);
+    /// pressure.set_register(0x27, 0x01);
+    /// assert_eq!(not_ready(&mut sense), Measurement::Pressure);
+    ///
+    /// pressure.set_register(0x27, 0x03);
+    /// let (hpa, celsius) = sense.get_pressure_and_temperature().unwrap();
+    /// assert_eq!((hpa.as_hectopascals(), celsius.as_celsius()), (1013.25, 22.5));
+    ///
+    /// pressure.set_failing(true);
+    /// match sense.get_pressure_and_temperature() {
+    ///     Err(SenseHatError::I2CError(_)) => {}
+    ///     other => panic!("{:?}", other),
+    /// }
+    /// ```
+    pub fn get_pressure_and_temperature(&mut self) -> SenseHatResult<(Pressure, Temperature)> {
+        let (hectopascals, celsius) = self.read_checked(
+            SenseHat::read_pressure_and_temperature,
+            |o, (hectopascals, celsius), now| {
+                let rejected =
+                    |value| move |reason| SenseHatError::OutlierRejected { value, reason };
+                o.pressure.check(hectopascals, now).map_err(rejected(hectopascals))?;
+                o.temperature_from_pressure.check(celsius, now).map_err(rejected(celsius))?;
+                o.pressure.accept(hectopascals, now);
+                o.temperature_from_pressure.accept(celsius, now);
+                Ok((hectopascals, celsius))
+            },
+        )?;
+        let hectopascals = self.smooth(|s| &mut s.pressure, hectopascals);
+        let celsius = self.smooth(|s| &mut s.temperature_from_pressure, celsius);
+        Ok((Pressure::from_hectopascals(hectopascals), Temperature::from_celsius(celsius)))
+    }
+
+    /// Helper function.
+    ///
+    /// Reads the barometer's pressure and temperature in one go, in hPa and
+    /// °C.
+    fn read_pressure_and_temperature(&mut self) -> SenseHatResult<(f64, f64)> {
+        self.check_awake(Sensor::Pressure, Measurement::Pressure)?;
+        let pressure_dev = component_mut(&mut self.pressure_dev, Component::Pressure)?;
+        let status = pressure_dev.smbus_read_byte_data(LPS25H_STATUS_REG)?;
+        let not_ready = |measurement| SenseHatError::NotReady {
+            sensor: Sensor::Pressure,
+            measurement,
+        };
+        if (status & 2) == 0 {
+            return Err(not_ready(Measurement::Pressure));
+        }
+        if (status & 1) == 0 {
+            return Err(not_ready(Measurement::Temperature));
+        }
+        let buf = pressure_dev
+            .smbus_read_i2c_block_data(LPS25H_PRESS_OUT_XL | LPS25H_AUTO_INCREMENT, 5)?;
+        if buf.len() < 5 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "short I2C read").into());
+        }
+        let raw = LittleEndian::read_u24(&buf[0..3]) as i32;
+        let celsius = ((LittleEndian::read_i16(&buf[3..5]) as f64) / 480.0) + 42.5;
+        Ok((pressure_from_raw(raw).as_hectopascals(), celsius))
+    }
+
+    /// Reads the readiness and overrun flags of the humidity and pressure
+    /// sensors, without reading any data, so the flags are left as they
+    /// were.
+    pub fn sensor_status(&mut self) -> SenseHatResult<SensorStatus> {
+        let humidity_dev = component_mut(&mut self.humidity_dev, Component::Humidity)?;
+        let humidity = humidity_dev.smbus_read_byte_data(HTS221_STATUS)?;
+        let pressure_dev = component_mut(&mut self.pressure_dev, Component::Pressure)?;
+        let pressure = pressure_dev.smbus_read_byte_data(LPS25H_STATUS_REG)?;
+        Ok(SensorStatus {
+            humidity: HumidityStatus::from_register(humidity),
+            pressure: PressureStatus::from_register(pressure),
+        })
+    }
+
+    /// Sets up the humidity sensor's data ready pin: whether it signals new
+    /// readings at all, whether it's active low rather than high, and
+    /// whether it's open drain rather than push-pull.
+    pub fn set_humidity_drdy(
+        &mut self,
+        enabled: bool,
+        active_low: bool,
+        open_drain: bool,
+    ) -> SenseHatResult<()> {
+        let humidity_dev = component_mut(&mut self.humidity_dev, Component::Humidity)?;
+        let value = humidity_drdy_ctrl_reg3(enabled, active_low, open_drain);
+        humidity_dev.smbus_write_byte_data(HTS221_CTRL3, value)?;
+        Ok(())
+    }
+
+    /// Returns whether the humidity sensor has a new temperature and a new
+    /// humidity reading ready, in that order, reading only its status
+    /// register, so it's a cheap thing to poll.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// while !sense.humidity_data_ready().unwrap().1 {}
+    /// println!("{}", sense.get_humidity().unwrap());
+    /// ```
+    pub fn humidity_data_ready(&mut self) -> SenseHatResult<(bool, bool)> {
+        let humidity_dev = component_mut(&mut self.humidity_dev, Component::Humidity)?;
+        let status = humidity_dev.smbus_read_byte_data(HTS221_STATUS)?;
+        let status = HumidityStatus::from_register(status);
+        Ok((status.temperature_ready, status.humidity_ready))
+    }
+
+    /// Returns a Temperature reading from the humidity sensor. It's more
+    /// accurate than the barometer (+/- 0.5 degrees C), but over a smaller
+    /// range. Smoothed, if `set_smoothing` is on.
+    pub fn get_temperature_from_humidity(&mut self) -> SenseHatResult<Temperature> {
+        let celsius = self.get_temperature_from_humidity_unsmoothed()?.as_celsius();
+        let celsius = self.smooth(|s| &mut s.temperature_from_humidity, celsius);
+        Ok(Temperature::from_celsius(celsius))
+    }
+
+    /// Returns a Temperature reading from the humidity sensor, ignoring
+    /// `set_smoothing`.
+    pub fn get_temperature_from_humidity_unsmoothed(&mut self) -> SenseHatResult<Temperature> {
+        let celsius = self.read_checked(
+            |hat| {
+                let raw = hat.get_temperature_from_humidity_raw()?;
+                Ok(hat.humidity_calibration.celsius(raw))
+            },
+            |o, celsius, now| o.temperature_from_humidity.filter(celsius, now),
+        )?;
+        Ok(Temperature::from_celsius(celsius))
+    }
+
+    /// Returns the humidity sensor's raw temperature reading, before it's
+    /// turned into °C with the `humidity_calibration`.
+    pub fn get_temperature_from_humidity_raw(&mut self) -> SenseHatResult<i16> {
+        self.check_awake(Sensor::Humidity, Measurement::Temperature)?;
+        let humidity_dev = component_mut(&mut self.humidity_dev, Component::Humidity)?;
+        let status = humidity_dev.smbus_read_byte_data(HTS221_STATUS)?;
+        if (status & 1) != 0 {
+            let mut buf = [0u8; 2];
+            buf[0] = humidity_dev.smbus_read_byte_data(HTS221_TEMP_OUT_L)?;
+            buf[1] = humidity_dev.smbus_read_byte_data(HTS221_TEMP_OUT_H)?;
+            Ok(LittleEndian::read_i16(&buf))
+        } else {
+            Err(SenseHatError::NotReady {
+                sensor: Sensor::Humidity,
+                measurement: Measurement::Temperature,
+            })
+        }
+    }
+
+    /// Returns a RelativeHumidity value in percent between 0 and 100.
+    /// Smoothed, if `set_smoothing` is on.
+    pub fn get_humidity(&mut self) -> SenseHatResult<RelativeHumidity> {
+        let percent = self.get_humidity_unsmoothed()?.as_percent();
+        let percent = self.smooth(|s| &mut s.humidity, percent);
+        Ok(RelativeHumidity::from_percent(percent))
+    }
+
+    /// Returns a RelativeHumidity value in percent between 0 and 100,
+    /// ignoring `set_smoothing`.
+    pub fn get_humidity_unsmoothed(&mut self) -> SenseHatResult<RelativeHumidity> {
+        let percent = self.read_checked(
+            |hat| {
+                let raw = hat.get_humidity_raw()?;
+                Ok(hat.humidity_calibration.percent(raw))
+            },
+            |o, percent, now| o.humidity.filter(percent, now),
+        )?;
+        Ok(RelativeHumidity::from_percent(percent))
+    }
+
+    /// Returns the humidity sensor's raw humidity reading, before it's
+    /// turned into %rH with the `humidity_calibration`.
+    pub fn get_humidity_raw(&mut self) -> SenseHatResult<i16> {
+        self.check_awake(Sensor::Humidity, Measurement::Humidity)?;
+        let humidity_dev = component_mut(&mut self.humidity_dev, Component::Humidity)?;
+        let status = humidity_dev.smbus_read_byte_data(HTS221_STATUS)?;
+        if (status & 2) != 0 {
+            let mut buf = [0u8; 2];
+            buf[0] = humidity_dev.smbus_read_byte_data(HTS221_HUMIDITY_OUT_L)?;
+            buf[1] = humidity_dev.smbus_read_byte_data(HTS221_HUMIDITY_OUT_H)?;
+            Ok(LittleEndian::read_i16(&buf))
+        } else {
+            Err(SenseHatError::NotReady {
+                sensor: Sensor::Humidity,
+                measurement: Measurement::Humidity,
+            })
+        }
+    }
+
+    /// Returns a Temperature reading from the IMU. It's the least accurate of
+    /// the three, and reads high as the Raspberry Pi warms the Sense Hat up,
+    /// but is a third opinion when the other two disagree.
+    pub fn get_temperature_from_imu(&mut self) -> SenseHatResult<Temperature> {
+        self.imu()?.get_temperature()
+    }
+
+    /// Returns the compass heading in degrees, from 0 up to 360, like the
+    /// Python API's `get_compass()`. See `Imu::get_north`, which this
+    /// calls, for how long the first call takes. Returns
+    /// `ComponentNotAvailable` if the IMU isn't working.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// println!("heading: {:.0}°", sense.get_north().unwrap());
+    /// ```
+    pub fn get_north(&mut self) -> SenseHatResult<f64> {
+        self.imu()?.get_north()
+    }
+
+    /// The same as `get_north`, with the Python API's name.
+    pub fn get_compass(&mut self) -> SenseHatResult<f64> {
+        self.get_north()
+    }
+
+    /// Like `get_temperature_from_humidity`, but with when it was read.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let temperature = sense.get_temperature_from_humidity_timestamped().unwrap();
+    /// println!("{} at {}", temperature.as_celsius(), temperature.taken_at_rfc3339());
+    /// ```
+    pub fn get_temperature_from_humidity_timestamped(
+        &mut self,
+    ) -> SenseHatResult<Reading<Temperature>> {
+        self.timestamped(Sensor::Humidity, SenseHat::get_temperature_from_humidity)
+    }
+
+    /// Like `get_humidity`, but with when it was read.
+    pub fn get_humidity_timestamped(&mut self) -> SenseHatResult<Reading<RelativeHumidity>> {
+        self.timestamped(Sensor::Humidity, SenseHat::get_humidity)
+    }
+
+    /// Like `get_temperature_from_pressure`, but with when it was read.
+    pub fn get_temperature_from_pressure_timestamped(
+        &mut self,
+    ) -> SenseHatResult<Reading<Temperature>> {
+        self.timestamped(Sensor::Pressure, SenseHat::get_temperature_from_pressure)
+    }
+
+    /// Like `get_pressure`, but with when it was read.
+    pub fn get_pressure_timestamped(&mut self) -> SenseHatResult<Reading<Pressure>> {
+        self.timestamped(Sensor::Pressure, SenseHat::get_pressure)
+    }
+
+    /// Like `get_pressure_and_temperature`, but with when they were read.
+    pub fn get_pressure_and_temperature_timestamped(
+        &mut self,
+    ) -> SenseHatResult<Reading<(Pressure, Temperature)>> {
+        self.timestamped(Sensor::Pressure, SenseHat::get_pressure_and_temperature)
+    }
+
+    /// Like `get_temperature_from_imu`, but with when it was read.
+    pub fn get_temperature_from_imu_timestamped(&mut self) -> SenseHatResult<Reading<Temperature>> {
+        self.timestamped(Sensor::Imu, SenseHat::get_temperature_from_imu)
+    }
+
+    /// Reads the temperature, from the humidity sensor, the humidity and
+    /// the pressure, waiting a little for any which aren't ready yet, as
+    /// the sensors make new readings several times a second. The IMU's
+    /// temperature is read too, but only as a bonus: if it can't be read,
+    /// `imu_temperature` is None rather than the whole reading failing.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let environment = sense.get_environment().unwrap();
+    /// println!("{:.1}°C, {:.0}%", environment.temperature, environment.humidity);
+    /// ```
+    ///
+    /// Off the hardware, with the chips mocked:
+    ///
+    /// ```
+    /// use sensehat::{MockI2CDevice, SenseHat};
+    ///
+    /// let (pressure, humidity) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// // The humidity sensor's calibration, then 20.875°C and 75%rH.
+    /// humidity.set_registers(0x30, &[66, 150, 0xa7, 0x22, 0x00, 0x04, 0x00, 0x00]);
+    /// humidity.set_registers(0x38, &[0x00, 0x00, 0x30, 0xdf, 0x18, 0xfc, 0x1e, 0x14]);
+    /// humidity.set_registers(0x27, &[0x03, 0x30, 0xdf, 0x18, 0xfc]);
+    /// pressure.set_registers(0x27, &[0x03, 0x00, 0x54, 0x3f]);
+    /// let (accel_gyro, mag) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// accel_gyro.set_register(0x0f, 0x68);
+    /// mag.set_register(0x0f, 0x3d);
+    /// // 80 / 16 + 25 = 30°C, and the temperature ready.
+    /// accel_gyro.set_registers(0x15, &[80, 0, 0x04]);
+    ///
+    /// let mut sense = SenseHat::builder()
+    ///     .pressure_device(pressure.clone())
+    ///     .humidity_device(humidity.clone())
+    ///     .imu_devices(accel_gyro.clone(), mag)
+    ///     .build()
+    ///     .unwrap();
+    /// let environment = sense.get_environment().unwrap();
+    /// assert_eq!(environment.temperature, 20.875);
+    /// assert!((environment.humidity - 75.0).abs() < 1e-9);
+    /// assert_eq!(environment.pressure, 1013.25);
+    /// assert_eq!(environment.imu_temperature, Some(30.0));
+    ///
+    /// // A failing IMU doesn't stop the rest being read.
+    /// accel_gyro.set_failing(true);
+    /// let environment = sense.get_environment().unwrap();
+    /// assert_eq!(environment.imu_temperature, None);
+    /// assert_eq!(environment.pressure, 1013.25);
+    /// ```
+    pub fn get_environment(&mut self) -> SenseHatResult<Environment> {
+        let temperature = retry_not_ready(|| self.get_temperature_from_humidity())?;
+        let humidity = retry_not_ready(|| self.get_humidity())?;
+        let pressure = retry_not_ready(|| self.get_pressure())?;
+        let imu_temperature = retry_not_ready(|| self.get_temperature_from_imu()).ok();
+        Ok(Environment {
+            taken_at: SystemTime::now(),
+            temperature: temperature.as_celsius(),
+            humidity: humidity.as_percent(),
+            pressure: pressure.as_hectopascals(),
+            imu_temperature: imu_temperature.map(|t| t.as_celsius()),
+        })
+    }
+
+    /// Helper function.
+    ///
+    /// Takes a reading with `read`, stamped with the time it finished.
+    fn timestamped<T>(
+        &mut self,
+        sensor: Sensor,
+        read: fn(&mut SenseHat) -> SenseHatResult<T>,
+    ) -> SenseHatResult<Reading<T>> {
+        let value = read(self)?;
+        Ok(Reading::new(value, SystemTime::now(), sensor))
+    }
+
+    /// Returns the IMU, for reading the accelerometer, gyroscope and
+    /// magnetometer.
+    pub fn imu(&mut self) -> SenseHatResult<&mut Imu> {
+        component_mut(&mut self.imu, Component::Imu)
+    }
+}
+
+#[cfg(feature = "display")]
+impl SenseHat {
+    /// If you're using the Pi upside down or sideways you can use this function
+    /// to correct the orientation of the image being shown.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{SenseHat, Orientation};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// sense.set_rotation(Orientation::Deg180, true).unwrap();
+    /// ```
+    pub fn set_rotation(&mut self, ori: Orientation, redraw: bool) -> SenseHatResult<()> {
+        self.display_mut()?.set_rotation(ori, redraw)
+    }
+
+    /// Mirrors the image left to right when it's drawn, for a Sense Hat
+    /// seen in a mirror. See `Display::set_mirror`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{SenseHat, Orientation};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// sense.set_rotation(Orientation::Deg90, false).unwrap();
+    /// sense.set_mirror(true, true).unwrap();
+    /// ```
+    pub fn set_mirror(&mut self, mirrored: bool, redraw: bool) -> SenseHatResult<()> {
+        self.display_mut()?.set_mirror(mirrored, redraw)
+    }
+
+    /// Flips the image on the LED matrix horizontally.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{SenseHat, Pixel};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// // Flip the frame and redraw it.
+    /// sense.flip_h(true).unwrap();
+    /// // Flip the frame, but don't redraw it
+    /// let pixels: [Pixel; 64] = sense.flip_h(false).unwrap();
+    /// ```
+    pub fn flip_h(&mut self, redraw: bool) -> SenseHatResult<[Pixel; 64]> {
+        self.display_mut()?.flip_h(redraw)
+    }
+
+    /// Flips the image on the LED matrix vertically.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{SenseHat, Pixel};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// // Flip the frame and redraw it.
+    /// sense.flip_v(true).unwrap();
+    /// // Flip the frame, but don't redraw it
+    /// let pixels: [Pixel; 64] = sense.flip_v(false).unwrap();
+    /// ```
+    pub fn flip_v(&mut self, redraw: bool) -> SenseHatResult<[Pixel; 64]> {
+        self.display_mut()?.flip_v(redraw)
+    }
+
+    /// Turns the hue of every pixel on the LED matrix by `degrees`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::thread::sleep;
+    /// use std::time::Duration;
+    /// use sensehat::{GradientDirection, GradientSpace, SenseHat};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// sense
+    ///     .fill_gradient((255, 0, 0), (0, 0, 255), GradientDirection::Diagonal, GradientSpace::Hsv)
+    ///     .unwrap();
+    /// loop {
+    ///     sense.rotate_hue(10.0, true).unwrap();
+    ///     sleep(Duration::from_millis(50));
+    /// }
+    /// ```
+    pub fn rotate_hue(&mut self, degrees: f32, redraw: bool) -> SenseHatResult<[Pixel; 64]> {
+        self.display_mut()?.rotate_hue(degrees, redraw)
+    }
+
+    /// Updates the entire LED matrix based on a 64 length array of pixel values.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{SenseHat, Pixel};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let r: Pixel = (255, 0, 0);
+    /// let w: Pixel = (255, 255, 255);
+    ///
+    /// let question_mark: [Pixel; 64] = [
+    ///    w, w, w, r, r, w, w, w,
+    ///    w, w, r, w, w, r, w, w,
+    ///    w, w, w, w, w, r, w, w,
+    ///    w, w, w, w, r, w, w, w,
+    ///    w, w, w, r, w, w, w, w,
+    ///    w, w, w, r, w, w, w, w,
+    ///    w, w, w, w, w, w, w, w,
+    ///    w, w, w, r, w, w, w, w];
+    ///
+    /// sense.set_pixels(&question_mark).unwrap();
+    /// ```
+    pub fn set_pixels(&mut self, pixels: &[Pixel; 64]) -> SenseHatResult<()> {
+        self.display_mut()?.set_pixels(pixels)
+    }
+
+    /// Returns an array of pixels representing the currently displayed image.
+    ///
+    /// # Note
+    ///
+    /// You will notice that the pixel values you pass into `set_pixels` sometimes
+    /// change when you read them back with `get_pixels`. This is because we specify
+    /// each pixel element as 8 bit numbers (0 to 255) but when they're passed into the
+    /// Linux frame buffer for the LED matrix the numbers are rounded to the nearest
+    /// level that fits into RGB 565. 5 bits for red, 6 bits for green and 5 bits for blue. The loss of
+    /// binary precision when performing this conversion (3 bits lost for red, 2 for
+    /// green and 3 for blue) accounts for the discrepancies you see.
+    ///
+    /// The `get_pixels` function provides a correct representation of how the pixels
+    /// end up in frame buffer memory after you've called `set_pixels`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{SenseHat, Pixel};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let pixel_list: [Pixel; 64] = sense.get_pixels().unwrap();
+    /// ```
+    pub fn get_pixels(&self) -> SenseHatResult<[Pixel; 64]> {
+        Ok(self.display()?.get_pixels())
+    }
+
+    /// Sets an individual LED matrix pixel at the specified X-Y coordinate to the
+    /// specified colour. Returns an error if `x` or `y` is greater than 7.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    ///
+    /// // examples using (x, y, (r, g, b))
+    /// sense.set_pixel(0, 0, (255, 0, 0)).unwrap();
+    /// sense.set_pixel(0, 7, (0, 255, 0)).unwrap();
+    /// sense.set_pixel(7, 0, (0, 0, 255)).unwrap();
+    /// sense.set_pixel(7, 7, (255, 0, 255)).unwrap();
+    ///
+    /// let red = (255, 0, 0);
+    /// let green = (0, 255, 0);
+    /// let blue = (0, 0, 255);
+    ///
+    /// // examples using (x, y, Pixel)
+    /// sense.set_pixel(0, 0, red).unwrap();
+    /// sense.set_pixel(0, 0, green).unwrap();
+    /// sense.set_pixel(0, 0, blue).unwrap();
+    /// ```
+    pub fn set_pixel(&mut self, x: usize, y: usize, p: Pixel) -> SenseHatResult<()> {
+        self.display_mut()?.set_pixel(x, y, p)
+    }
+
+    /// Returns a single pixel at the given (`x`, `y`) coordinate from
+    /// the currently displayed image, before it's rotated or mirrored, so
+    /// it's the pixel `set_pixel` set at (`x`, `y`). Returns an error if `x` or `y` is greater than 7.
+    ///
+    /// # Note
+    ///
+    /// Please read the note under `get_pixels`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{SenseHat, Pixel};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let top_left_pixel: Pixel = sense.get_pixel(0, 0).unwrap();
+    /// ```
+    pub fn get_pixel(&self, x: usize, y: usize) -> SenseHatResult<Pixel> {
+        self.display()?.get_pixel(x, y)
+    }
+
+    /// Sets the entire LED matrix to a single colour. If the given `Option` is `None`,
+    /// it will turn the screen blank / off.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    /// use std::time::Duration;
+    /// use std::thread::sleep;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    ///
+    /// let red = (255, 0, 0);
+    ///
+    /// sense.clear(None).unwrap(); // None turns the screen off
+    /// sleep(Duration::from_millis(1000));
+    /// sense.clear(Some(red)).unwrap(); // turns the whole screen red
+    ///
+    /// ```
+    pub fn clear(&mut self, color: Option<Pixel>) -> SenseHatResult<()> {
+        self.display_mut()?.clear(color)
+    }
+
+    /// Keeps the image from before each of the last `depth` changes, for
+    /// `undo`. See `Display::set_history_depth`.
+    pub fn set_history_depth(&mut self, depth: usize) -> SenseHatResult<()> {
+        self.display_mut()?.set_history_depth(depth);
+        Ok(())
+    }
+
+    /// Puts back the image from before the last change. Returns
+    /// `NothingToUndo` if there's no history left.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// sense.set_history_depth(8).unwrap();
+    /// sense.clear(Some((0, 0, 255))).unwrap();
+    /// sense.undo().unwrap();
+    /// ```
+    pub fn undo(&mut self) -> SenseHatResult<()> {
+        self.display_mut()?.undo()
+    }
+
+    /// Scrolls `text` across the LED matrix in the given color. See
+    /// `Display::show_message`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use sensehat::{SenseHat, TextStyle};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let fast = TextStyle { speed: Duration::from_millis(50), ..TextStyle::default() };
+    /// sense.show_message("Hello world", (255, 0, 0), &fast).unwrap();
+    /// ```
+    pub fn show_message(
+        &mut self,
+        text: &str,
+        color: Pixel,
+        style: &TextStyle,
+    ) -> SenseHatResult<MessageMetrics> {
+        self.display_mut()?.show_message(text, color, style)
+    }
+
+    /// Scrolls a number across the LED matrix, rounded to `decimals`
+    /// places with `suffix` after it, in `fg` on `bg`, laid out and
+    /// scrolled the way `style` says. NaN and infinite values are shown as
+    /// `--`. See `format_value`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{SenseHat, TextStyle};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let style = TextStyle::default();
+    /// // Shows "21.3C".
+    /// sense.show_value(21.34, 1, "C", &style, (255, 128, 0), (0, 0, 0)).unwrap();
+    /// ```
+    pub fn show_value(
+        &mut self,
+        value: f64,
+        decimals: u8,
+        suffix: &str,
+        style: &TextStyle,
+        fg: Pixel,
+        bg: Pixel,
+    ) -> SenseHatResult<()> {
+        let text = format_value(value, decimals, false, suffix);
+        let style = TextStyle {
+            background: TextBackground::Solid(bg),
+            ..*style
+        };
+        self.show_message(&text, fg, &style).map(|_| ())
+    }
+
+    /// Scrolls a temperature across the LED matrix to a tenth of a degree,
+    /// like `21.3C`, in white on black with the default style.
+    #[cfg(feature = "sensors")]
+    pub fn show_temperature(&mut self, temperature: &Temperature) -> SenseHatResult<()> {
+        let style = TextStyle::default();
+        self.show_value(temperature.as_celsius(), 1, "C", &style, (255, 255, 255), (0, 0, 0))
+    }
+
+    /// Scrolls a pressure across the LED matrix to the hectopascal, like
+    /// `1013 hPa`, in white on black with the default style.
+    #[cfg(feature = "sensors")]
+    pub fn show_pressure(&mut self, pressure: &Pressure) -> SenseHatResult<()> {
+        let style = TextStyle::default();
+        self.show_value(pressure.as_hectopascals(), 0, " hPa", &style, (255, 255, 255), (0, 0, 0))
+    }
+
+    /// Scrolls a relative humidity across the LED matrix to the percent,
+    /// like `45%`, in white on black with the default style.
+    #[cfg(feature = "sensors")]
+    pub fn show_humidity(&mut self, humidity: &RelativeHumidity) -> SenseHatResult<()> {
+        let style = TextStyle::default();
+        self.show_value(humidity.as_percent(), 0, "%", &style, (255, 255, 255), (0, 0, 0))
+    }
+
+    /// Plays an animation on the LED matrix, blocking until it's over. See
+    /// `Display::play_animation`.
+    pub fn play_animation(&mut self, animation: &Animation) -> SenseHatResult<()> {
+        self.display_mut()?.play_animation(animation)
+    }
+
+    /// Shows the 8x8 window of `canvas` with its top left at (x, y). See
+    /// `Display::show_viewport`.
+    pub fn show_viewport(
+        &mut self,
+        canvas: &Canvas,
+        x: isize,
+        y: isize,
+        wrap: bool,
+    ) -> SenseHatResult<()> {
+        self.display_mut()?.show_viewport(canvas, x, y, wrap)
+    }
+
+    /// Fills the LED matrix with a gradient from `from` to `to`. See
+    /// `Display::fill_gradient`.
+    pub fn fill_gradient(
+        &mut self,
+        from: Pixel,
+        to: Pixel,
+        direction: GradientDirection,
+        space: GradientSpace,
+    ) -> SenseHatResult<()> {
+        self.display_mut()?.fill_gradient(from, to, direction, space)
+    }
+
+    /// Makes the pixel at (x, y) blink between `on` and `off` every
+    /// `period`, on top of the image. See `Display::set_blink`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::thread::sleep;
+    /// use std::time::{Duration, Instant};
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// sense.set_blink(7, 0, (0, 255, 0), (0, 0, 0), Duration::from_millis(500)).unwrap();
+    /// loop {
+    ///     sense.tick(Instant::now()).unwrap();
+    ///     sleep(Duration::from_millis(50));
+    /// }
+    /// ```
+    pub fn set_blink(
+        &mut self,
+        x: usize,
+        y: usize,
+        on: Pixel,
+        off: Pixel,
+        period: Duration,
+    ) -> SenseHatResult<()> {
+        self.display_mut()?.set_blink(x, y, on, off, period)
+    }
+
+    /// Stops the pixel at (x, y) blinking.
+    pub fn clear_blink(&mut self, x: usize, y: usize) -> SenseHatResult<()> {
+        self.display_mut()?.clear_blink(x, y)
+    }
+
+    /// Updates the blinking pixels to how they should be at `now`.
+    pub fn tick(&mut self, now: Instant) -> SenseHatResult<()> {
+        self.display_mut()?.tick(now)
+    }
+
+    pub fn gamma(&self) -> SenseHatResult<[u8; 32]> {
+        Ok(self.display()?.gamma())
+    }
+
+    pub fn set_gamma(&mut self, buffer: &[u8; 32]) -> SenseHatResult<()> {
+        self.display_mut()?.set_gamma(buffer)
+    }
+
+    pub fn reset_gamma(&mut self) -> SenseHatResult<()> {
+        self.display_mut()?.reset_gamma();
+        Ok(())
+    }
+
+    /// Saves the current gamma settings as the profile `name` in `dir`. See
+    /// `Display::save_gamma_profile`.
+    pub fn save_gamma_profile(&self, name: &str, dir: &Path) -> SenseHatResult<()> {
+        self.display()?.save_gamma_profile(name, dir)
+    }
+
+    /// Loads and applies the gamma profile `name` from `dir`.
+    pub fn load_gamma_profile(&mut self, name: &str, dir: &Path) -> SenseHatResult<()> {
+        self.display_mut()?.load_gamma_profile(name, dir)
+    }
+
+    pub fn is_low_light(&self) -> SenseHatResult<bool> {
+        Ok(self.display()?.is_low_light())
+    }
+
+    pub fn low_light(&mut self, set_low: bool) -> SenseHatResult<()> {
+        self.display_mut()?.low_light(set_low);
+        Ok(())
+    }
+
+    /// Helper function.
+    ///
+    /// Borrows the display, if it initialised successfully.
+    fn display(&self) -> SenseHatResult<&Display> {
+        component(&self.display, Component::Display)
+    }
+
+    /// Helper function.
+    ///
+    /// Mutably borrows the display, if it initialised successfully.
+    fn display_mut(&mut self) -> SenseHatResult<&mut Display> {
+        component_mut(&mut self.display, Component::Display)
+    }
+}
+
+#[cfg(feature = "sensors")]
+impl RelativeHumidity {
+    pub fn from_percent(pc: f64) -> Self {
+        RelativeHumidity { value: pc }
+    }
+
+    pub fn as_percent(&self) -> f64 {
+        self.value
+    }
+}
+
+#[cfg(feature = "sensors")]
+impl fmt::Display for RelativeHumidity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.1}%", self.as_percent())
+    }
+}
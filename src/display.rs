@@ -1,290 +1,1313 @@
-use {SenseHatError, SenseHatResult};
-
-use libc::{ioctl, c_ulong};
-use framebuffer::Framebuffer;
-use byteorder::{ByteOrder, LittleEndian};
-use glob::glob;
-
-use std::fmt;
-use std::os::unix::io::AsRawFd;
-
-const SENSE_HAT_FBIOGET_GAMMA: c_ulong = 61696;
-const SENSE_HAT_FBIOSET_GAMMA: c_ulong = 61697;
-const SENSE_HAT_FBIORESET_GAMMA: c_ulong = 61698;
-const SENSE_HAT_GAMMA_DEFAULT: c_ulong = 0;
-const SENSE_HAT_GAMMA_LOW: c_ulong = 1;
-
-/// A rgb888 color pixel.
-///
-/// A pixel on the sensehat LED matrix is actually a hex565.
-/// That means a pixel is 16-bit instead of 24-bit.
-/// (5 for red, 6 for green, 5 for blue, 5+6+5=16)
-pub type Pixel = (u8, u8, u8);
-
-/// The image orientation.
-/// 0°, 90°, 180°, 270°
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub enum Orientation {
-    Deg0,
-    Deg90,
-    Deg180,
-    Deg270,
-}
-
-/// Represents the LED matrix.
-pub struct Display {
-    framebuffer: Framebuffer,
-    frame: [u8; 128],
-    orientation: Orientation,
-}
-
-impl Display {
-    /// Try to create a new Display object.
-    ///
-    /// Will open the sensehat framebuffer and map it to memory.
-    pub fn new() -> SenseHatResult<Self> {
-        // The id of the sensehat framebuffer
-        let rpi_sense_fb = b"RPi-Sense FB";
-        
-        // Iterator for framebuffers located in /dev
-        let path = glob("/dev/fb*")?;
-
-        // Locates the sensehat framebuffer
-        let framebuffer = path.filter_map(Result::ok)
-            .filter_map(|file| Framebuffer::new(&file.to_string_lossy()).ok())
-            .filter(|fb| {
-                let id = fb.fix_screen_info.id;
-                rpi_sense_fb[..] == id[..rpi_sense_fb.len()]
-            })
-            .next();
-        match framebuffer {
-            Some(fb) => Ok(Self {
-                framebuffer: fb,
-                frame: [0; 128],
-                orientation: Orientation::Deg0,
-                }),
-            None => Err(SenseHatError::MissingFramebuffer),
-        }
-    }
-
-    /// Helper function.
-    ///
-    /// Rotates and draws the LED matrix display based on the orientation.
-    fn draw(&mut self) {
-        if self.orientation == Orientation::Deg0 {
-            self.framebuffer.write_frame(&self.frame);
-        } else {
-            let mut temp = [0; 128];
-            let mut i = 0;
-            for y in 0..8 {
-                for x in 0..8 {
-                    let cor = self.map_position(x, y);
-                    let pixel = LittleEndian::read_u16(&self.frame[i..]);
-                    LittleEndian::write_u16(&mut temp[cor..], pixel);
-                    i += 2;
-                }
-            }
-            self.framebuffer.write_frame(&temp);
-        }
-    }
-
-    /// Helper function.
-    ///
-    /// Function for mapping a (x, y) coordinate on the
-    /// 2D LED matrix to a 1D position on the frame.
-    /// A pixel in the frame is actually 16-bit, but since we can
-    /// only write to the framebuffer with u8 slices, we have to
-    /// split up each pixel in two. This function returns the position
-    /// of the 8 MSB of a pixel.
-    fn map_position(&self, x: usize, y: usize) -> usize {
-        use self::Orientation::*;
-        match self.orientation {
-            Deg0 => 2 * (x + 8 * y),
-            Deg90 => 2 * ((7 - y) + 8 * x),
-            Deg180 => 126 - 2 * (x + 8 * y),
-            Deg270 => 2 * (y + 8 * (7 - x)),
-        }
-    }
-
-    /// Sets the orientation of the display. The default orientation is with
-    /// the HDMI port facing downwards on the Raspberry Pi 3 model B.
-    pub fn set_rotation(&mut self, ori: Orientation, redraw: bool) {
-        self.orientation = ori;
-        if redraw {
-            self.draw();
-        }
-    }
-
-    /// Flips the pixels on the LED matrix horizontaly.
-    /// Returns a list of the LED pixels.
-    pub fn flip_h(&mut self, redraw: bool) -> [Pixel; 64] {
-        let mut pixels = self.get_pixels();
-        for slice in pixels[..].chunks_mut(8) {
-            slice.reverse();
-        }
-        if redraw {
-            self.set_pixels(&pixels);   
-        }
-        pixels
-    }
-
-    /// Flips the pixels on the LED matrix vertically.
-    /// Returns a list of the LED pixels.
-    pub fn flip_v(&mut self, redraw: bool) -> [Pixel; 64] {
-        let mut pixels = self.get_pixels();
-        for i in 0..8 {
-            for j in 0..4 {
-                let offset = j * 8;
-                pixels.swap(i + offset, i + 56 - offset);
-            }
-        }
-        if redraw {
-            self.set_pixels(&pixels);
-        }
-        pixels
-    }
-
-    /// Updates the entire LED matrix based on a 64 length array of pixel values.
-    /// A pixel is a triplet of u8's (red, green, blue).
-    pub fn set_pixels(&mut self, pixels: &[Pixel; 64]) {
-        for (pos, pixel) in self.frame[..]
-            .chunks_mut(2)
-            .zip(pixels.iter()
-                       .map(|&p| convert_from_pixel(p)))
-        {
-            LittleEndian::write_u16(pos, pixel);
-        }
-        self.draw();
-    }
-
-    /// Get a vector of all `Pixel`s on the currently displayed image.
-    pub fn get_pixels(&self) -> [Pixel; 64] {
-        let mut pixels = [(0, 0, 0); 64];
-        for (index, value) in pixels
-            .iter_mut()
-            .zip(self.frame[..]
-                     .chunks(2)
-                     .map(LittleEndian::read_u16)
-                     .map(convert_to_pixel))
-        {
-            *index = value;
-        }
-        pixels
-    }
-
-    /// Sets a single LED matrix pixel at the given (x, y) coordinate
-    /// to the given color.
-    /// Returns an error if the coordinates are out of bounds.
-    pub fn set_pixel(&mut self, x: usize, y: usize, p: Pixel) -> SenseHatResult<()> {
-        if x > 7 || y > 7 {
-            return Err(SenseHatError::OutOfBounds);
-        }
-        let pos = 2 * (x + 8 * y);
-        let pixel = convert_from_pixel(p);
-        LittleEndian::write_u16(&mut self.frame[pos..], pixel);
-        self.draw();
-        Ok(())
-    }
-
-    /// Returns a single pixel value at the given coordinate.
-    /// Returns an error if the coordinates are out of bounds.
-    pub fn get_pixel(&self, x: usize, y: usize) -> SenseHatResult<Pixel> {
-        if x > 7 || y > 7 {
-            return Err(SenseHatError::OutOfBounds);
-        }
-        let pos = self.map_position(x, y);
-        let value = LittleEndian::read_u16(&self.frame[pos..]);
-        let pixel = convert_to_pixel(value);
-        Ok(pixel)
-    }
-
-    /// Sets the entire LED matrix to a single color, defaults to blank/off.
-    pub fn clear(&mut self, color: Option<Pixel>) {
-        match color {
-            Some(c) => {
-                let pixel = convert_from_pixel(c);
-                for pos in self.frame[..].chunks_mut(2) {
-                    LittleEndian::write_u16(pos, pixel);
-                }
-            }
-            None => {
-                for p in self.frame.iter_mut() { *p = 0 }
-            }
-        }
-        self.framebuffer.write_frame(&self.frame);
-    }
-
-    /// Retuns the current gamma settings.
-    pub fn gamma(&self) -> [u8; 32] {
-        let mut buffer = [0u8; 32];
-        unsafe {
-            let fd = self.framebuffer.device.as_raw_fd();
-            ioctl(fd, SENSE_HAT_FBIOGET_GAMMA, &mut buffer);
-            // TODO: Maybe check ioctl return value for errors.
-        }
-        buffer
-    }
-
-    /// Changes the gamma settings.
-    pub fn set_gamma(&mut self, buffer: &[u8; 32]) -> SenseHatResult<()> {
-        if !buffer.iter().all(|&x| x <= 31) {
-            return Err(SenseHatError::InvalidGamma);
-        }
-        unsafe {
-            let fd = self.framebuffer.device.as_raw_fd();
-            ioctl(fd, SENSE_HAT_FBIOSET_GAMMA, buffer);
-            // TODO: Maybe check ioctl return value for errors.
-        }
-        Ok(())
-    }
-
-    /// Resets the LED matrix gamma correction to default.
-    pub fn reset_gamma(&mut self) {
-        unsafe {
-            let fd = self.framebuffer.device.as_raw_fd();
-            ioctl(fd, SENSE_HAT_FBIORESET_GAMMA, SENSE_HAT_GAMMA_DEFAULT);
-            // TODO: Maybe check ioctl return value for errors.
-        }
-    }
-
-    /// Checks if the display is set to low light mode.
-    pub fn is_low_light(&self) -> bool {
-        let low: [u8; 32] = [0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 3, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 10, 10];
-        let cur_gamma = self.gamma();
-        cur_gamma == low
-    }
-
-    /// Enables or disables low light mode.
-    pub fn low_light(&mut self, set_low: bool) {
-        unsafe {
-            let fd = self.framebuffer.device.as_raw_fd();
-            let cmd = if set_low { SENSE_HAT_GAMMA_LOW } else { SENSE_HAT_GAMMA_DEFAULT };
-            ioctl(fd, SENSE_HAT_FBIORESET_GAMMA, cmd);
-        }
-    }
-}
-
-/// Converts a rgb888 pixel into a rgb565 pixel.
-fn convert_from_pixel(p: Pixel) -> u16 {
-    let r = (p.0 >> 3) as u16;
-    let g = (p.1 >> 2) as u16;
-    let b = (p.2 >> 3) as u16;
-    (r << 11) | (g << 5) | b
-}
-
-/// Converts a rgb565 pixel to a rgb888 pixel.
-fn convert_to_pixel(val: u16) -> Pixel {
-    let (msb, lsb) = ((val >> 8) as u8, val as u8);
-    let r = msb & 0xF8;
-    let g = ((msb & 0x07) << 3) | (lsb & 0xE0);
-    let b = lsb & 0x1F;
-    (r, g << 2, b << 3)
-}
-
-impl fmt::Debug for Display {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Display {{ framebuffer: {:?} orientation: {:?} }}",
-            self.framebuffer,
-            self.orientation)
-    }
-}
+use {SenseHatError, SenseHatResult};
+use blink::BlinkSchedule;
+use color::{gradient, GradientDirection, GradientSpace, PixelExt};
+use gamma;
+use history::FrameHistory;
+use transform;
+
+use libc::{ioctl, c_ulong};
+use framebuffer::Framebuffer;
+use byteorder::{ByteOrder, LittleEndian};
+use glob::glob;
+
+use std::fmt;
+use std::fs::{self, File};
+use std::ops::Range;
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const SENSE_HAT_FBIOGET_GAMMA: c_ulong = 61696;
+const SENSE_HAT_FBIOSET_GAMMA: c_ulong = 61697;
+const SENSE_HAT_FBIORESET_GAMMA: c_ulong = 61698;
+const SENSE_HAT_GAMMA_DEFAULT: c_ulong = 0;
+const SENSE_HAT_GAMMA_LOW: c_ulong = 1;
+/// The gamma table the Sense Hat driver starts with.
+pub(crate) const DEFAULT_GAMMA: [u8; 32] = [
+    0, 0, 0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 14, 15, 17, 18, 20, 21, 23,
+    25, 27, 29, 31,
+];
+/// The gamma table the Sense Hat driver uses in low light mode.
+pub(crate) const LOW_LIGHT_GAMMA: [u8; 32] = [
+    0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 3, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 10,
+    10,
+];
+
+/// The id of the sensehat framebuffer.
+const RPI_SENSE_FB: &str = "RPi-Sense FB";
+/// Where sysfs lists the framebuffers.
+const SYSFS_GRAPHICS: &str = "/sys/class/graphics";
+/// How many times to try reopening the framebuffer after it went away.
+const REOPEN_ATTEMPTS: u32 = 3;
+/// How long to wait between attempts to reopen the framebuffer, to give
+/// the kernel module time to come back.
+const REOPEN_DELAY: Duration = Duration::from_millis(100);
+/// How often drawing checks the framebuffer hasn't gone away. Checking
+/// takes two system calls, which is too many to make on every draw of an
+/// animation.
+const STALE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A rgb888 color pixel.
+///
+/// A pixel on the sensehat LED matrix is actually a hex565.
+/// That means a pixel is 16-bit instead of 24-bit.
+/// (5 for red, 6 for green, 5 for blue, 5+6+5=16)
+pub type Pixel = (u8, u8, u8);
+
+/// The image orientation.
+/// 0°, 90°, 180°, 270°
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Orientation {
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+impl Orientation {
+    /// Returns where the (x, y) coordinate of the image ends up on the LED
+    /// matrix with this rotation, after mirroring it left to right if
+    /// `mirrored` is set. Between them the four rotations and the mirror
+    /// give every way of putting the image on the matrix.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::Orientation;
+    ///
+    /// assert_eq!(Orientation::Deg0.map(1, 2, false), (1, 2));
+    /// assert_eq!(Orientation::Deg90.map(1, 2, false), (5, 1));
+    /// assert_eq!(Orientation::Deg180.map(1, 2, false), (6, 5));
+    /// assert_eq!(Orientation::Deg270.map(1, 2, false), (2, 6));
+    /// assert_eq!(Orientation::Deg0.map(1, 2, true), (6, 2));
+    /// assert_eq!(Orientation::Deg90.map(1, 2, true), (5, 6));
+    /// assert_eq!(Orientation::Deg180.map(1, 2, true), (1, 5));
+    /// assert_eq!(Orientation::Deg270.map(1, 2, true), (2, 1));
+    ///
+    /// // Every pixel of the image lands on a different LED.
+    /// for &ori in &[Orientation::Deg0, Orientation::Deg90, Orientation::Deg180, Orientation::Deg270] {
+    ///     for &mirrored in &[false, true] {
+    ///         let mut lit = [false; 64];
+    ///         for i in 0..64 {
+    ///             let (x, y) = ori.map(i % 8, i / 8, mirrored);
+    ///             assert!(x < 8 && y < 8 && !lit[x + 8 * y]);
+    ///             lit[x + 8 * y] = true;
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn map(self, x: usize, y: usize, mirrored: bool) -> (usize, usize) {
+        use self::Orientation::*;
+        let x = if mirrored { 7 - x } else { x };
+        match self {
+            Deg0 => (x, y),
+            Deg90 => (7 - y, x),
+            Deg180 => (7 - x, 7 - y),
+            Deg270 => (y, 7 - x),
+        }
+    }
+}
+
+/// Represents the LED matrix.
+///
+/// The image is always kept as it was set, with (0, 0) at the top left.
+/// The rotation and mirroring only change how it's drawn, so `set_pixel`,
+/// `get_pixel`, `get_pixels` and the flips all work on the image as set,
+/// whatever the orientation.
+///
+/// If the framebuffer goes away, say because the kernel module was
+/// reloaded, the first draw a second or more after the last check finds
+/// and reopens it and redraws the image, as does `force_full_redraw`
+/// straight away. Methods which draw return `MissingFramebuffer` if it
+/// doesn't come back.
+///
+/// It can draw on something other than the framebuffer, such as a
+/// `MemoryMatrix`, with `with_matrix`.
+///
+/// # Example
+///
+/// Every accessor works on the image as set, in every orientation,
+/// mirrored or not, while the LEDs show it rotated:
+///
+/// ```
+/// use sensehat::{Display, MemoryMatrix, Orientation};
+///
+/// // Checks the LEDs show the image, rotated, and that get_pixel agrees
+/// // with get_pixels.
+/// fn assert_drawn(display: &Display, matrix: &MemoryMatrix, ori: Orientation, mirrored: bool) {
+///     let (image, leds) = (display.get_pixels(), matrix.pixels());
+///     for i in 0..64 {
+///         let (x, y) = ori.map(i % 8, i / 8, mirrored);
+///         assert_eq!(leds[x + 8 * y], image[i], "{:?} mirrored {}", ori, mirrored);
+///         assert_eq!(display.get_pixel(i % 8, i / 8).unwrap(), image[i]);
+///     }
+/// }
+///
+/// // A different colour for every pixel.
+/// let mut image = [(0, 0, 0); 64];
+/// for (i, pixel) in image.iter_mut().enumerate() {
+///     *pixel = (32 * (i % 8) as u8, 32 * (i / 8) as u8, 0);
+/// }
+/// use sensehat::Orientation::*;
+/// let orientations = [Deg0, Deg90, Deg180, Deg270];
+/// for &ori in &orientations {
+///     for &mirrored in &[false, true] {
+///         let matrix = MemoryMatrix::new();
+///         let mut display = Display::with_matrix(matrix.clone());
+///         display.set_rotation(ori, false).unwrap();
+///         display.set_mirror(mirrored, false).unwrap();
+///
+///         display.set_pixels(&image).unwrap();
+///         let set = display.get_pixels();
+///         assert_drawn(&display, &matrix, ori, mirrored);
+///
+///         display.set_pixel(1, 2, (255, 255, 255)).unwrap();
+///         assert_eq!(display.get_pixels()[1 + 8 * 2], (255, 255, 255));
+///         assert_eq!(display.get_pixels()[2 + 8 * 1], set[2 + 8 * 1]);
+///         assert_drawn(&display, &matrix, ori, mirrored);
+///
+///         let before = display.get_pixels();
+///         let flipped = display.flip_h(true).unwrap();
+///         assert_eq!(display.get_pixels(), flipped);
+///         for i in 0..64 {
+///             assert_eq!(flipped[i], before[7 - i % 8 + 8 * (i / 8)]);
+///         }
+///         assert_drawn(&display, &matrix, ori, mirrored);
+///
+///         let flipped = display.flip_v(true).unwrap();
+///         for i in 0..64 {
+///             assert_eq!(flipped[i], before[7 - i % 8 + 8 * (7 - i / 8)]);
+///         }
+///         assert_drawn(&display, &matrix, ori, mirrored);
+///
+///         // Flipping without redrawing changes neither the image nor the LEDs.
+///         let leds = matrix.pixels();
+///         display.flip_h(false).unwrap();
+///         assert_eq!(display.get_pixels(), flipped);
+///         assert_eq!(matrix.pixels(), leds);
+///
+///         // Turning the display turns the LEDs, not the image.
+///         for &turned in &orientations {
+///             display.set_rotation(turned, true).unwrap();
+///             assert_eq!(display.get_pixels(), flipped);
+///             assert_drawn(&display, &matrix, turned, mirrored);
+///         }
+///         display.set_rotation(ori, true).unwrap();
+///
+///         display.clear(Some((0, 0, 255))).unwrap();
+///         assert!(display.get_pixels().iter().all(|&p| p == (0, 0, 255)));
+///         assert!(matrix.pixels().iter().all(|&p| p == (0, 0, 255)));
+///         display.clear(None).unwrap();
+///         assert_eq!(matrix.pixels(), [(0, 0, 0); 64]);
+///         assert_drawn(&display, &matrix, ori, mirrored);
+///     }
+/// }
+/// ```
+pub struct Display {
+    matrix: Box<dyn LedMatrix>,
+    frame: [u8; 128],
+    /// What was last written to the matrix, after rotating, or None if it
+    /// isn't known and the next draw has to write everything.
+    shadow: Option<[u8; 128]>,
+    orientation: Orientation,
+    /// Whether the image is mirrored left to right when it's drawn.
+    mirrored: bool,
+    blinks: BlinkSchedule,
+    /// The blinking pixels as they were last drawn.
+    blink_pixels: Vec<(usize, usize, Pixel)>,
+    /// The images before each change, for `undo`.
+    history: FrameHistory,
+}
+
+impl Display {
+    /// Try to create a new Display object.
+    ///
+    /// Will open the sensehat framebuffer and map it to memory. The
+    /// framebuffer is found through `/sys/class/graphics`, like
+    /// `find_framebuffer_at`, falling back to opening every `/dev/fb*` to
+    /// check its id if sysfs isn't there.
+    pub fn new() -> SenseHatResult<Self> {
+        Ok(Display::with_matrix(SenseHatFramebuffer::open()?))
+    }
+
+    /// Makes a Display which draws on `matrix` rather than the Sense Hat's
+    /// framebuffer. See `MemoryMatrix` for an example.
+    pub fn with_matrix<M: LedMatrix + 'static>(matrix: M) -> Self {
+        Self {
+            matrix: Box::new(matrix),
+            frame: [0; 128],
+            shadow: None,
+            orientation: Orientation::Deg0,
+            mirrored: false,
+            blinks: BlinkSchedule::new(),
+            blink_pixels: Vec::new(),
+            history: FrameHistory::new(0),
+        }
+    }
+
+    /// Helper function.
+    ///
+    /// Rotates, mirrors if needed, and draws the LED matrix display based
+    /// on the orientation, with the blinking pixels on top. Only the
+    /// pixels which changed since the last draw are written.
+    fn draw(&mut self) -> SenseHatResult<()> {
+        let mut frame = self.frame;
+        for &(x, y, p) in &self.blink_pixels {
+            LittleEndian::write_u16(&mut frame[2 * (x + 8 * y)..], convert_from_pixel(p));
+        }
+        if self.orientation != Orientation::Deg0 || self.mirrored {
+            let mut temp = [0; 128];
+            let mut i = 0;
+            for y in 0..8 {
+                for x in 0..8 {
+                    let cor = self.map_position(x, y);
+                    let pixel = LittleEndian::read_u16(&frame[i..]);
+                    LittleEndian::write_u16(&mut temp[cor..], pixel);
+                    i += 2;
+                }
+            }
+            frame = temp;
+        }
+        #[allow(clippy::single_range_in_vec_init)]
+        let changed = match self.shadow {
+            Some(ref shadow) => changed_ranges(shadow, &frame),
+            None => vec![0..128],
+        };
+        self.matrix.write(&frame, &changed)?;
+        self.shadow = Some(frame);
+        Ok(())
+    }
+
+    /// Redraws the whole LED matrix, rather than only the pixels which
+    /// changed since the last draw, reopening the framebuffer first if it
+    /// went away. Useful if something else has written to the framebuffer.
+    pub fn force_full_redraw(&mut self) -> SenseHatResult<()> {
+        self.matrix.refresh()?;
+        self.shadow = None;
+        self.draw()
+    }
+
+    /// Helper function.
+    ///
+    /// Function for mapping a (x, y) coordinate on the
+    /// 2D LED matrix to a 1D position on the frame.
+    /// A pixel in the frame is actually 16-bit, but since we can
+    /// only write to the framebuffer with u8 slices, we have to
+    /// split up each pixel in two. This function returns the position
+    /// of the 8 MSB of a pixel.
+    fn map_position(&self, x: usize, y: usize) -> usize {
+        let (x, y) = self.orientation.map(x, y, self.mirrored);
+        2 * (x + 8 * y)
+    }
+
+    /// Sets the orientation of the display. The default orientation is with
+    /// the HDMI port facing downwards on the Raspberry Pi 3 model B.
+    /// This only changes how the image is drawn, not the image itself.
+    pub fn set_rotation(&mut self, ori: Orientation, redraw: bool) -> SenseHatResult<()> {
+        self.orientation = ori;
+        if redraw {
+            self.draw()?;
+        }
+        Ok(())
+    }
+
+    /// Mirrors the image left to right when it's drawn, on top of the
+    /// rotation, for looking at the LED matrix in a mirror. Unlike
+    /// `flip_h` this doesn't change the image, so `get_pixels` returns it
+    /// unmirrored.
+    pub fn set_mirror(&mut self, mirrored: bool, redraw: bool) -> SenseHatResult<()> {
+        self.mirrored = mirrored;
+        if redraw {
+            self.draw()?;
+        }
+        Ok(())
+    }
+
+    /// Returns whether the image is mirrored when it's drawn.
+    pub fn is_mirrored(&self) -> bool {
+        self.mirrored
+    }
+
+    /// Flips the pixels on the LED matrix horizontaly.
+    /// Returns a list of the LED pixels.
+    pub fn flip_h(&mut self, redraw: bool) -> SenseHatResult<[Pixel; 64]> {
+        let pixels = transform::flip_h(&self.get_pixels());
+        if redraw {
+            self.set_pixels(&pixels)?;
+        }
+        Ok(pixels)
+    }
+
+    /// Flips the pixels on the LED matrix vertically.
+    /// Returns a list of the LED pixels.
+    pub fn flip_v(&mut self, redraw: bool) -> SenseHatResult<[Pixel; 64]> {
+        let pixels = transform::flip_v(&self.get_pixels());
+        if redraw {
+            self.set_pixels(&pixels)?;
+        }
+        Ok(pixels)
+    }
+
+    /// Turns the hue of every pixel on the LED matrix by `degrees`, leaving
+    /// greys alone, like `PixelExt::rotate_hue`.
+    /// Returns a list of the LED pixels.
+    pub fn rotate_hue(&mut self, degrees: f32, redraw: bool) -> SenseHatResult<[Pixel; 64]> {
+        let mut pixels = self.get_pixels();
+        for pixel in pixels.iter_mut() {
+            *pixel = pixel.rotate_hue(degrees);
+        }
+        if redraw {
+            self.set_pixels(&pixels)?;
+        }
+        Ok(pixels)
+    }
+
+    /// Updates the entire LED matrix based on a 64 length array of pixel values.
+    /// A pixel is a triplet of u8's (red, green, blue).
+    pub fn set_pixels(&mut self, pixels: &[Pixel; 64]) -> SenseHatResult<()> {
+        self.history.push(self.frame);
+        for (pos, pixel) in self.frame[..]
+            .chunks_mut(2)
+            .zip(pixels.iter()
+                       .map(|&p| convert_from_pixel(p)))
+        {
+            LittleEndian::write_u16(pos, pixel);
+        }
+        self.draw()
+    }
+
+    /// Get a vector of all `Pixel`s on the currently displayed image.
+    pub fn get_pixels(&self) -> [Pixel; 64] {
+        let mut pixels = [(0, 0, 0); 64];
+        for (index, value) in pixels
+            .iter_mut()
+            .zip(self.frame[..]
+                     .chunks(2)
+                     .map(LittleEndian::read_u16)
+                     .map(convert_to_pixel))
+        {
+            *index = value;
+        }
+        pixels
+    }
+
+    /// Sets a single LED matrix pixel at the given (x, y) coordinate
+    /// to the given color.
+    /// Returns an error if the coordinates are out of bounds.
+    pub fn set_pixel(&mut self, x: usize, y: usize, p: Pixel) -> SenseHatResult<()> {
+        if x > 7 || y > 7 {
+            return Err(SenseHatError::OutOfBounds);
+        }
+        self.history.push(self.frame);
+        let pos = 2 * (x + 8 * y);
+        let pixel = convert_from_pixel(p);
+        LittleEndian::write_u16(&mut self.frame[pos..], pixel);
+        self.draw()
+    }
+
+    /// Sets any number of pixels, each given with its (x, y) coordinate,
+    /// then draws them all at once. Coordinates off the LED matrix are
+    /// skipped, so shapes can be drawn partly off the edge, as graphics
+    /// libraries like embedded-graphics expect. It only counts as a change
+    /// for `undo` if a pixel changed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::Display;
+    ///
+    /// let mut display = Display::new().unwrap();
+    /// // A line from off the left edge to off the right edge.
+    /// display.draw_points((-4..12).map(|x| ((x, 3), (0, 255, 0)))).unwrap();
+    /// ```
+    ///
+    /// Off the hardware:
+    ///
+    /// ```
+    /// use sensehat::{Display, MemoryMatrix, SenseHatError};
+    ///
+    /// let matrix = MemoryMatrix::new();
+    /// let mut display = Display::with_matrix(matrix.clone());
+    /// display.set_history_depth(4);
+    /// display.draw_points((-4..12).map(|x| ((x, 3), (0, 255, 0)))).unwrap();
+    /// let row: Vec<_> = matrix.pixels()[8 * 3..8 * 4].to_vec();
+    /// assert_eq!(row, [(0, 255, 0); 8]);
+    /// assert_eq!(matrix.pixels()[8 * 4], (0, 0, 0));
+    ///
+    /// // Points which are all off the matrix, or the colour already there,
+    /// // leave nothing to undo.
+    /// display.draw_points(vec![((-1, 0), (255, 0, 0)), ((8, 8), (255, 0, 0))]).unwrap();
+    /// display.draw_points(vec![((0, 3), (0, 255, 0))]).unwrap();
+    /// display.undo().unwrap();
+    /// assert_eq!(matrix.pixels()[8 * 3], (0, 0, 0));
+    /// match display.undo() {
+    ///     Err(SenseHatError::NothingToUndo) => {}
+    ///     other => panic!("{:?}", other),
+    /// }
+    /// ```
+    pub fn draw_points<I>(&mut self, points: I) -> SenseHatResult<()>
+    where
+        I: IntoIterator<Item = ((i32, i32), Pixel)>,
+    {
+        let mut frame = self.frame;
+        for ((x, y), p) in points {
+            if !(0..8).contains(&x) || !(0..8).contains(&y) {
+                continue;
+            }
+            let pos = 2 * (x as usize + 8 * y as usize);
+            LittleEndian::write_u16(&mut frame[pos..], convert_from_pixel(p));
+        }
+        if frame != self.frame {
+            self.history.push(self.frame);
+            self.frame = frame;
+        }
+        self.draw()
+    }
+
+    /// Returns a single pixel value at the given coordinate.
+    /// Returns an error if the coordinates are out of bounds.
+    pub fn get_pixel(&self, x: usize, y: usize) -> SenseHatResult<Pixel> {
+        if x > 7 || y > 7 {
+            return Err(SenseHatError::OutOfBounds);
+        }
+        let pos = 2 * (x + 8 * y);
+        let value = LittleEndian::read_u16(&self.frame[pos..]);
+        let pixel = convert_to_pixel(value);
+        Ok(pixel)
+    }
+
+    /// Sets the entire LED matrix to a single color, defaults to blank/off.
+    pub fn clear(&mut self, color: Option<Pixel>) -> SenseHatResult<()> {
+        self.history.push(self.frame);
+        match color {
+            Some(c) => {
+                let pixel = convert_from_pixel(c);
+                for pos in self.frame[..].chunks_mut(2) {
+                    LittleEndian::write_u16(pos, pixel);
+                }
+            }
+            None => {
+                for p in self.frame.iter_mut() { *p = 0 }
+            }
+        }
+        self.draw()
+    }
+
+    /// Fills the LED matrix with a gradient from `from` to `to`, across it
+    /// in the given direction, interpolated like `gradient`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{Display, GradientDirection, GradientSpace};
+    ///
+    /// let mut display = Display::new().unwrap();
+    /// display
+    ///     .fill_gradient((255, 0, 0), (0, 0, 255), GradientDirection::Diagonal, GradientSpace::Hsv)
+    ///     .unwrap();
+    /// ```
+    pub fn fill_gradient(
+        &mut self,
+        from: Pixel,
+        to: Pixel,
+        direction: GradientDirection,
+        space: GradientSpace,
+    ) -> SenseHatResult<()> {
+        let steps = match direction {
+            GradientDirection::Horizontal | GradientDirection::Vertical => 8,
+            GradientDirection::Diagonal => 15,
+        };
+        let colors = gradient(from, to, steps, space);
+        let mut pixels = [(0, 0, 0); 64];
+        for (i, pixel) in pixels.iter_mut().enumerate() {
+            let (x, y) = (i % 8, i / 8);
+            *pixel = colors[match direction {
+                GradientDirection::Horizontal => x,
+                GradientDirection::Vertical => y,
+                GradientDirection::Diagonal => x + y,
+            }];
+        }
+        self.set_pixels(&pixels)
+    }
+
+    /// Keeps the image from before each of the last `depth` changes, so
+    /// `undo` can go back to it. Everything which changes the image
+    /// counts: `set_pixel`, `set_pixels`, `clear`, and everything built on
+    /// them, like the flips and `fill_gradient`. A depth of 0, the
+    /// default, turns it off and forgets the history.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::Display;
+    ///
+    /// let mut display = Display::new().unwrap();
+    /// display.set_history_depth(16);
+    /// display.set_pixel(0, 0, (255, 0, 0)).unwrap();
+    /// display.set_pixel(1, 0, (0, 255, 0)).unwrap();
+    /// display.undo().unwrap();
+    /// assert_eq!(display.get_pixel(1, 0).unwrap(), (0, 0, 0));
+    /// ```
+    pub fn set_history_depth(&mut self, depth: usize) {
+        self.history.set_depth(depth);
+    }
+
+    /// Puts back the image from before the last change, and draws it.
+    /// Returns `NothingToUndo` if there's no history left.
+    pub fn undo(&mut self) -> SenseHatResult<()> {
+        self.frame = self.history.pop().ok_or(SenseHatError::NothingToUndo)?;
+        self.draw()
+    }
+
+    /// Makes the pixel at (x, y) blink between `on` and `off` every
+    /// `period`, starting with `on`. Returns an error if the coordinates
+    /// are out of bounds.
+    ///
+    /// Blinking pixels are drawn on top of the image, so they keep blinking
+    /// through `set_pixels`, `set_pixel` and `flip_h`/`flip_v`, which only
+    /// change the image underneath, until `clear_blink` shows it again.
+    /// `get_pixels` and `get_pixel` return the image, not the blinking
+    /// pixels. Call `tick` regularly to make them blink.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::thread::sleep;
+    /// use std::time::{Duration, Instant};
+    /// use sensehat::Display;
+    ///
+    /// let mut display = Display::new().unwrap();
+    /// display.set_blink(0, 0, (255, 0, 0), (0, 0, 0), Duration::from_secs(1)).unwrap();
+    /// loop {
+    ///     display.tick(Instant::now()).unwrap();
+    ///     sleep(Duration::from_millis(50));
+    /// }
+    /// ```
+    pub fn set_blink(
+        &mut self,
+        x: usize,
+        y: usize,
+        on: Pixel,
+        off: Pixel,
+        period: Duration,
+    ) -> SenseHatResult<()> {
+        let now = Instant::now();
+        self.blinks.set(x, y, on, off, period, now)?;
+        self.blink_pixels = self.blinks.pixels_at(now);
+        self.draw()
+    }
+
+    /// Stops the pixel at (x, y) blinking, showing the image underneath
+    /// again. Does nothing if it wasn't blinking.
+    pub fn clear_blink(&mut self, x: usize, y: usize) -> SenseHatResult<()> {
+        if self.blinks.clear(x, y) {
+            self.blink_pixels.retain(|&(bx, by, _)| (bx, by) != (x, y));
+            self.draw()?;
+        }
+        Ok(())
+    }
+
+    /// Updates the blinking pixels to how they should be at `now`, only
+    /// redrawing if one of them changed.
+    pub fn tick(&mut self, now: Instant) -> SenseHatResult<()> {
+        let pixels = self.blinks.pixels_at(now);
+        if pixels != self.blink_pixels {
+            self.blink_pixels = pixels;
+            self.draw()?;
+        }
+        Ok(())
+    }
+
+    /// Retuns the current gamma settings.
+    pub fn gamma(&self) -> [u8; 32] {
+        self.matrix.gamma()
+    }
+
+    /// Changes the gamma settings.
+    pub fn set_gamma(&mut self, buffer: &[u8; 32]) -> SenseHatResult<()> {
+        if !buffer.iter().all(|&x| x <= 31) {
+            return Err(SenseHatError::InvalidGamma);
+        }
+        self.matrix.set_gamma(buffer);
+        Ok(())
+    }
+
+    /// Saves the current gamma settings as the profile `name` in `dir`, in
+    /// a file called `<name>.gamma`, replacing any profile with that name.
+    ///
+    /// The file is text, with a version line and the 32 entries:
+    ///
+    /// ```text
+    /// # Sense Hat gamma profile
+    /// version=1
+    /// gamma=0,0,0,0,0,0,1,1,2,2,3,3,4,5,6,7,8,9,10,11,12,14,15,17,18,20,21,23,25,27,29,31
+    /// ```
+    pub fn save_gamma_profile(&self, name: &str, dir: &Path) -> SenseHatResult<()> {
+        gamma::save(&gamma::profile_path(name, dir)?, &self.gamma())
+    }
+
+    /// Loads the profile `name` from `dir`, as saved by
+    /// `save_gamma_profile`, and applies it with `set_gamma`.
+    ///
+    /// Returns an `IoError` saying what's wrong if the file isn't a valid
+    /// profile, and leaves the gamma settings alone.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::path::Path;
+    /// use sensehat::Display;
+    ///
+    /// let mut display = Display::new().unwrap();
+    /// display.load_gamma_profile("diffuser", Path::new("/etc/sensehat")).unwrap();
+    /// ```
+    pub fn load_gamma_profile(&mut self, name: &str, dir: &Path) -> SenseHatResult<()> {
+        let table = gamma::load(&gamma::profile_path(name, dir)?)?;
+        self.set_gamma(&table)
+    }
+
+    /// Returns the names of the gamma profiles saved in `dir`, sorted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::fs;
+    /// use sensehat::Display;
+    ///
+    /// let dir = std::env::temp_dir().join("sensehat-gamma-profiles-doctest");
+    /// fs::create_dir_all(&dir).unwrap();
+    /// fs::write(dir.join("diffuser.gamma"), "version=1\n").unwrap();
+    /// fs::write(dir.join("notes.txt"), "not a profile\n").unwrap();
+    ///
+    /// assert_eq!(Display::list_gamma_profiles(&dir).unwrap(), vec!["diffuser"]);
+    /// fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    pub fn list_gamma_profiles(dir: &Path) -> SenseHatResult<Vec<String>> {
+        gamma::list(dir)
+    }
+
+    /// Resets the LED matrix gamma correction to default.
+    pub fn reset_gamma(&mut self) {
+        self.matrix.reset_gamma(false);
+    }
+
+    /// Checks if the display is set to low light mode.
+    pub fn is_low_light(&self) -> bool {
+        self.gamma() == LOW_LIGHT_GAMMA
+    }
+
+    /// Enables or disables low light mode.
+    pub fn low_light(&mut self, set_low: bool) {
+        self.matrix.reset_gamma(set_low);
+    }
+}
+
+/// What a `Display` draws on: the Sense Hat's framebuffer, or a stand-in
+/// for it like a `MemoryMatrix`, or a window showing the matrix drawn with
+/// `rasterize`.
+///
+/// Frames are the 64 pixels in rgb565, two bytes each, little endian, row
+/// by row from the top left, already rotated and mirrored as they should
+/// appear on the LEDs.
+pub trait LedMatrix: Send + fmt::Debug {
+    /// Shows `frame`. `changed` are the byte ranges of it which differ
+    /// from the frame last shown, as found by `changed_ranges`, or the
+    /// whole frame if that isn't known.
+    fn write(&mut self, frame: &[u8; 128], changed: &[Range<usize>]) -> SenseHatResult<()>;
+
+    /// Checks the matrix is still there, getting it back if it went away,
+    /// before `force_full_redraw` writes the whole frame. Does nothing by
+    /// default.
+    fn refresh(&mut self) -> SenseHatResult<()> {
+        Ok(())
+    }
+
+    /// Returns the gamma table, which sets how bright each of the 32
+    /// levels of a colour channel is.
+    fn gamma(&self) -> [u8; 32];
+
+    /// Replaces the gamma table. Every entry is at most 31.
+    fn set_gamma(&mut self, table: &[u8; 32]);
+
+    /// Puts back the default gamma table, or the low light one.
+    fn reset_gamma(&mut self, low_light: bool);
+}
+
+/// The Sense Hat's framebuffer, mapped into memory.
+#[derive(Debug)]
+struct SenseHatFramebuffer {
+    framebuffer: Framebuffer,
+    /// Watches the device the framebuffer was opened from.
+    watch: DeviceWatch,
+}
+
+impl SenseHatFramebuffer {
+    /// Helper function.
+    ///
+    /// Finds and opens the framebuffer.
+    fn open() -> SenseHatResult<Self> {
+        let (framebuffer, path) = open_framebuffer()?;
+        Ok(SenseHatFramebuffer {
+            framebuffer,
+            watch: DeviceWatch::new(path, STALE_CHECK_INTERVAL),
+        })
+    }
+
+    /// Helper function.
+    ///
+    /// Finds and reopens the framebuffer after it went away, trying a few
+    /// times in case it's on its way back. Returns the error from the last
+    /// attempt if it didn't come back.
+    fn reopen(&mut self) -> SenseHatResult<()> {
+        let mut attempt = 0;
+        loop {
+            match SenseHatFramebuffer::open() {
+                Ok(reopened) => {
+                    *self = reopened;
+                    return Ok(());
+                }
+                Err(err) => {
+                    attempt += 1;
+                    if attempt == REOPEN_ATTEMPTS {
+                        return Err(err);
+                    }
+                    thread::sleep(REOPEN_DELAY);
+                }
+            }
+        }
+    }
+}
+
+impl LedMatrix for SenseHatFramebuffer {
+    fn write(&mut self, frame: &[u8; 128], changed: &[Range<usize>]) -> SenseHatResult<()> {
+        if self.watch.is_stale(&self.framebuffer.device, Instant::now()) {
+            // The new framebuffer's contents aren't known, so they're all
+            // written.
+            self.reopen()?;
+            self.framebuffer.write_frame(frame);
+            return Ok(());
+        }
+        let mapped = unsafe { self.framebuffer.frame.as_mut_slice() };
+        for range in changed {
+            mapped[range.clone()].copy_from_slice(&frame[range.clone()]);
+        }
+        Ok(())
+    }
+
+    fn refresh(&mut self) -> SenseHatResult<()> {
+        if self.watch.check_now(&self.framebuffer.device, Instant::now()) {
+            self.reopen()?;
+        }
+        Ok(())
+    }
+
+    fn gamma(&self) -> [u8; 32] {
+        let mut buffer = [0u8; 32];
+        unsafe {
+            let fd = self.framebuffer.device.as_raw_fd();
+            ioctl(fd, SENSE_HAT_FBIOGET_GAMMA, &mut buffer);
+            // TODO: Maybe check ioctl return value for errors.
+        }
+        buffer
+    }
+
+    fn set_gamma(&mut self, table: &[u8; 32]) {
+        unsafe {
+            let fd = self.framebuffer.device.as_raw_fd();
+            ioctl(fd, SENSE_HAT_FBIOSET_GAMMA, table);
+            // TODO: Maybe check ioctl return value for errors.
+        }
+    }
+
+    fn reset_gamma(&mut self, low_light: bool) {
+        unsafe {
+            let fd = self.framebuffer.device.as_raw_fd();
+            let cmd = if low_light { SENSE_HAT_GAMMA_LOW } else { SENSE_HAT_GAMMA_DEFAULT };
+            ioctl(fd, SENSE_HAT_FBIORESET_GAMMA, cmd);
+            // TODO: Maybe check ioctl return value for errors.
+        }
+    }
+}
+
+/// An LED matrix in memory, for trying out display code off the hardware.
+///
+/// It keeps the frame last written and the gamma table, like the Sense
+/// Hat's framebuffer, and its driver's default and low light gamma tables.
+/// Clones share it, so a clone can be given to `Display::with_matrix` or a
+/// `SenseHatBuilder` and the original used to see what was drawn.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{Display, MemoryMatrix, Orientation};
+///
+/// let matrix = MemoryMatrix::new();
+/// let mut display = Display::with_matrix(matrix.clone());
+///
+/// display.set_pixel(1, 0, (255, 0, 0)).unwrap();
+/// assert_eq!(matrix.pixels()[1], (255, 0, 0));
+/// // The first draw writes everything, then only what changed.
+/// display.set_pixel(2, 0, (0, 0, 255)).unwrap();
+/// assert_eq!(matrix.writes(), [vec![0..128], vec![4..6]]);
+///
+/// // The LEDs show the image rotated.
+/// display.set_rotation(Orientation::Deg90, true).unwrap();
+/// assert_eq!(matrix.pixels()[7 + 8 * 1], (255, 0, 0));
+/// assert_eq!(display.get_pixel(1, 0).unwrap(), (255, 0, 0));
+///
+/// display.low_light(true);
+/// assert!(display.is_low_light());
+/// display.reset_gamma();
+/// assert_eq!(display.gamma()[31], 31);
+///
+/// // The matrix going away and not coming back.
+/// matrix.set_failing(true);
+/// assert!(display.clear(None).is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryMatrix {
+    state: Arc<Mutex<MemoryState>>,
+}
+
+/// The frame and gamma table of a `MemoryMatrix`, and what's been written.
+#[derive(Debug)]
+struct MemoryState {
+    frame: [u8; 128],
+    gamma: [u8; 32],
+    /// the byte ranges of every write, in order
+    writes: Vec<Vec<Range<usize>>>,
+    /// true if every write should fail
+    failing: bool,
+}
+
+impl Default for MemoryState {
+    fn default() -> Self {
+        MemoryState {
+            frame: [0; 128],
+            gamma: DEFAULT_GAMMA,
+            writes: Vec::new(),
+            failing: false,
+        }
+    }
+}
+
+impl MemoryMatrix {
+    /// Makes a matrix with every LED off and the default gamma table.
+    pub fn new() -> Self {
+        MemoryMatrix::default()
+    }
+
+    /// Returns the pixels as the LEDs show them, row by row from the top
+    /// left, after any rotation and mirroring.
+    pub fn pixels(&self) -> [Pixel; 64] {
+        let frame = self.frame();
+        let mut pixels = [(0, 0, 0); 64];
+        for (pixel, value) in pixels.iter_mut().zip(frame.chunks(2)) {
+            *pixel = convert_to_pixel(LittleEndian::read_u16(value));
+        }
+        pixels
+    }
+
+    /// Returns the frame last written, in rgb565.
+    pub fn frame(&self) -> [u8; 128] {
+        self.lock().frame
+    }
+
+    /// Returns the byte ranges of every write since the matrix was made,
+    /// or since `clear_writes`, in order.
+    pub fn writes(&self) -> Vec<Vec<Range<usize>>> {
+        self.lock().writes.clone()
+    }
+
+    /// Forgets the writes so far.
+    pub fn clear_writes(&self) {
+        self.lock().writes.clear();
+    }
+
+    /// Makes every write fail, as drawing does when the framebuffer went
+    /// away and didn't come back, or work again.
+    pub fn set_failing(&self, failing: bool) {
+        self.lock().failing = failing;
+    }
+
+    /// Helper function.
+    ///
+    /// Locks the state. A panic while it was locked can't leave it in a
+    /// state worth refusing to use.
+    fn lock(&self) -> MutexGuard<'_, MemoryState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl LedMatrix for MemoryMatrix {
+    fn write(&mut self, frame: &[u8; 128], changed: &[Range<usize>]) -> SenseHatResult<()> {
+        let mut state = self.lock();
+        if state.failing {
+            return Err(SenseHatError::MissingFramebuffer(FramebufferSearch::default()));
+        }
+        for range in changed {
+            state.frame[range.clone()].copy_from_slice(&frame[range.clone()]);
+        }
+        state.writes.push(changed.to_vec());
+        Ok(())
+    }
+
+    fn gamma(&self) -> [u8; 32] {
+        self.lock().gamma
+    }
+
+    fn set_gamma(&mut self, table: &[u8; 32]) {
+        self.lock().gamma = *table;
+    }
+
+    fn reset_gamma(&mut self, low_light: bool) {
+        self.lock().gamma = if low_light { LOW_LIGHT_GAMMA } else { DEFAULT_GAMMA };
+    }
+}
+
+/// What was found at one of the framebuffers checked while looking for
+/// the Sense Hat.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FramebufferCheck {
+    /// The framebuffer reported this id.
+    Id(String),
+    /// The framebuffer couldn't be checked, for this reason.
+    Failed(String),
+}
+
+/// The framebuffers checked while looking for the Sense Hat, as reported
+/// by `MissingFramebuffer`.
+///
+/// # Example
+///
+/// ```
+/// use std::path::PathBuf;
+/// use sensehat::{FramebufferCheck, FramebufferSearch};
+///
+/// let search = FramebufferSearch {
+///     checked: vec![
+///         (PathBuf::from("/dev/fb0"), FramebufferCheck::Id("BCM2708 FB".to_owned())),
+///         (PathBuf::from("/dev/fb1"), FramebufferCheck::Failed("permission denied".to_owned())),
+///     ],
+/// };
+/// assert_eq!(
+///     search.to_string(),
+///     "checked /dev/fb0 (\"BCM2708 FB\"), /dev/fb1 (permission denied); no RPi-Sense FB found"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FramebufferSearch {
+    /// Each framebuffer checked, in order, with what was found there.
+    pub checked: Vec<(PathBuf, FramebufferCheck)>,
+}
+
+impl fmt::Display for FramebufferSearch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.checked.is_empty() {
+            write!(f, "no framebuffers to check")?;
+        } else {
+            write!(f, "checked ")?;
+            for (i, (path, check)) in self.checked.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                match check {
+                    FramebufferCheck::Id(id) => write!(f, "{} ({:?})", path.display(), id)?,
+                    FramebufferCheck::Failed(reason) => write!(f, "{} ({})", path.display(), reason)?,
+                }
+            }
+        }
+        write!(f, "; no {} found", RPI_SENSE_FB)
+    }
+}
+
+/// Notices when a device file is replaced, as the Sense Hat framebuffer is
+/// when its kernel module is reloaded, by comparing the open file with
+/// what's at its path now. Once it's replaced, writing to the open file,
+/// or a mapping of it, does nothing.
+///
+/// Looking takes two system calls, so `is_stale` only looks once every
+/// `interval`, and `check_now` looks whenever it's asked.
+///
+/// # Example
+///
+/// ```
+/// extern crate sensehat;
+/// extern crate tempfile;
+///
+/// use std::fs::{self, File};
+/// use std::time::{Duration, Instant};
+/// use sensehat::DeviceWatch;
+///
+/// # fn main() {
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("fb1");
+/// fs::write(&path, "old").unwrap();
+/// let old = File::open(&path).unwrap();
+/// let start = Instant::now();
+/// let mut watch = DeviceWatch::new(&path, Duration::from_secs(1));
+/// assert!(!watch.is_stale(&old, start));
+///
+/// // The driver comes back with a new device at the same path.
+/// fs::write(dir.path().join("fb1.new"), "new").unwrap();
+/// fs::rename(dir.path().join("fb1.new"), &path).unwrap();
+///
+/// // It isn't looked for until a second after the last look...
+/// assert!(!watch.is_stale(&old, start + Duration::from_millis(500)));
+/// assert!(watch.is_stale(&old, start + Duration::from_secs(2)));
+/// // ...unless asked for straight away.
+/// assert!(watch.check_now(&old, start));
+///
+/// let new = File::open(&path).unwrap();
+/// assert!(!watch.check_now(&new, start));
+///
+/// // The device going away altogether counts, too.
+/// fs::remove_file(&path).unwrap();
+/// assert!(watch.check_now(&new, start));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DeviceWatch {
+    path: PathBuf,
+    interval: Duration,
+    /// When `is_stale` last looked.
+    checked: Instant,
+}
+
+impl DeviceWatch {
+    /// Watches the device at `path`, which has just been opened, looking at
+    /// most once every `interval`.
+    pub fn new<P: Into<PathBuf>>(path: P, interval: Duration) -> Self {
+        DeviceWatch {
+            path: path.into(),
+            interval,
+            checked: Instant::now(),
+        }
+    }
+
+    /// Returns the path being watched.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns whether `file` is no longer the device at the path, looking
+    /// if `interval` has passed since the last look, and otherwise
+    /// assuming it isn't.
+    pub fn is_stale(&mut self, file: &File, now: Instant) -> bool {
+        if now.saturating_duration_since(self.checked) < self.interval {
+            return false;
+        }
+        self.check_now(file, now)
+    }
+
+    /// Returns whether `file` is no longer the device at the path, which
+    /// counts as so if either can't be looked at.
+    pub fn check_now(&mut self, file: &File, now: Instant) -> bool {
+        self.checked = now;
+        let open = match file.metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => return true,
+        };
+        match fs::metadata(&self.path) {
+            Ok(current) => current.rdev() != open.rdev() || current.ino() != open.ino(),
+            Err(_) => true,
+        }
+    }
+}
+
+/// Finds the Sense Hat framebuffer device by reading `<base>/fb*/name`,
+/// where `base` is normally `/sys/class/graphics`, without opening any
+/// framebuffers. Returns `/dev/fbN` for the first match in number order,
+/// or `None` if none of them are the Sense Hat.
+///
+/// Returns an error if `base` can't be read.
+///
+/// # Example
+///
+/// ```
+/// use std::fs;
+/// use std::path::Path;
+/// use sensehat::find_framebuffer_at;
+///
+/// let base = std::env::temp_dir().join("sensehat-find-framebuffer-doctest");
+/// for &(fb, name) in &[("fb0", "vc4drmfb"), ("fb1", "RPi-Sense FB")] {
+///     fs::create_dir_all(base.join(fb)).unwrap();
+///     fs::write(base.join(fb).join("name"), format!("{}\n", name)).unwrap();
+/// }
+///
+/// let device = find_framebuffer_at(&base).unwrap();
+/// assert_eq!(device.as_deref(), Some(Path::new("/dev/fb1")));
+/// fs::remove_dir_all(&base).unwrap();
+/// ```
+pub fn find_framebuffer_at(base: &Path) -> SenseHatResult<Option<PathBuf>> {
+    Ok(search_sysfs(base)?.0)
+}
+
+/// Helper function.
+///
+/// Does the work of `find_framebuffer_at`, also returning what it found at
+/// every framebuffer, in number order.
+fn search_sysfs(base: &Path) -> SenseHatResult<(Option<PathBuf>, FramebufferSearch)> {
+    let mut found = Vec::new();
+    for entry in fs::read_dir(base)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let number = match file_name
+            .to_str()
+            .and_then(|name| name.strip_prefix("fb"))
+            .and_then(|number| number.parse::<u32>().ok())
+        {
+            Some(number) => number,
+            None => continue,
+        };
+        let check = match fs::read_to_string(entry.path().join("name")) {
+            Ok(name) => FramebufferCheck::Id(name.trim().to_owned()),
+            Err(err) => FramebufferCheck::Failed(err.kind().to_string()),
+        };
+        found.push((number, check));
+    }
+    found.sort_by_key(|&(number, _)| number);
+
+    let mut search = FramebufferSearch::default();
+    let mut device = None;
+    for (number, check) in found {
+        let path = PathBuf::from(format!("/dev/fb{}", number));
+        if device.is_none() && check == FramebufferCheck::Id(RPI_SENSE_FB.to_owned()) {
+            device = Some(path.clone());
+        }
+        search.checked.push((path, check));
+    }
+    Ok((device, search))
+}
+
+/// Helper function.
+///
+/// Finds and opens the Sense Hat framebuffer, through sysfs if it's there,
+/// returning it with the path it was opened from. Returns
+/// `MissingFramebuffer`, saying what was checked, if it isn't found.
+fn open_framebuffer() -> SenseHatResult<(Framebuffer, PathBuf)> {
+    match search_sysfs(Path::new(SYSFS_GRAPHICS)) {
+        Ok((Some(device), _)) => {
+            debug!("found the Sense Hat framebuffer at {} through sysfs", device.display());
+            Ok((Framebuffer::new(&device)?, device))
+        }
+        Ok((None, search)) => Err(SenseHatError::MissingFramebuffer(search)),
+        Err(_err) => {
+            debug!("can't search sysfs, probing /dev/fb* instead: {}", _err);
+            probe_framebuffers()
+        }
+    }
+}
+
+/// Helper function.
+///
+/// Finds the Sense Hat framebuffer by opening every `/dev/fb*` and checking
+/// its id, for when sysfs isn't available.
+fn probe_framebuffers() -> SenseHatResult<(Framebuffer, PathBuf)> {
+    let rpi_sense_fb = RPI_SENSE_FB.as_bytes();
+    let mut search = FramebufferSearch::default();
+    for path in glob("/dev/fb*")? {
+        let path = path?;
+        match Framebuffer::new(&path) {
+            Ok(fb) => {
+                let id = fb.fix_screen_info.id;
+                if rpi_sense_fb[..] == id[..rpi_sense_fb.len()] {
+                    debug!("found the Sense Hat framebuffer at {}", path.display());
+                    return Ok((fb, path));
+                }
+                let id = String::from_utf8_lossy(&id);
+                let id = id.trim_end_matches('\0').to_owned();
+                search.checked.push((path, FramebufferCheck::Id(id)));
+            }
+            Err(err) => search.checked.push((path, FramebufferCheck::Failed(err.to_string()))),
+        }
+    }
+    Err(SenseHatError::MissingFramebuffer(search))
+}
+
+/// Returns the byte ranges of the pixels which differ between two rgb565
+/// frames, with neighbouring changed pixels joined into one range. This is
+/// what the display writes when it's drawn, so copying these ranges from
+/// `new` into `old` always makes them equal.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::changed_ranges;
+///
+/// let old = [0u8; 128];
+/// let mut new = old;
+/// assert!(changed_ranges(&old, &new).is_empty());
+///
+/// // One pixel changing only writes its two bytes.
+/// new[20] = 0xff;
+/// assert_eq!(changed_ranges(&old, &new), vec![20..22]);
+///
+/// // Neighbouring pixels are joined together.
+/// new[23] = 0x1f;
+/// new[40] = 0x07;
+/// assert_eq!(changed_ranges(&old, &new), vec![20..24, 40..42]);
+///
+/// // Changing every pixel writes everything at once.
+/// let full = [0xaa; 128];
+/// assert_eq!(changed_ranges(&old, &full), vec![0..128]);
+///
+/// let mut shadow = old;
+/// for range in changed_ranges(&shadow, &new) {
+///     shadow[range.clone()].copy_from_slice(&new[range]);
+/// }
+/// assert_eq!(shadow, new);
+/// ```
+pub fn changed_ranges(old: &[u8; 128], new: &[u8; 128]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for pos in (0..128).step_by(2) {
+        if old[pos..pos + 2] == new[pos..pos + 2] {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some(last) if last.end == pos => last.end = pos + 2,
+            _ => ranges.push(pos..pos + 2),
+        }
+    }
+    ranges
+}
+
+/// Converts a rgb888 pixel into a rgb565 pixel.
+///
+/// Each channel is rounded to the nearest of its 32 or 64 levels, rather
+/// than having its low bits dropped, so colours don't come out darker.
+pub(crate) fn convert_from_pixel(p: Pixel) -> u16 {
+    let r = (p.0 as u16 * 31 + 127) / 255;
+    let g = (p.1 as u16 * 63 + 127) / 255;
+    let b = (p.2 as u16 * 31 + 127) / 255;
+    (r << 11) | (g << 5) | b
+}
+
+/// Converts a rgb565 pixel to a rgb888 pixel.
+///
+/// Each channel is scaled back up to the nearest 8 bit value, so full
+/// scale comes back as 255, and converting back with `convert_from_pixel`
+/// gives the same rgb565 value.
+pub(crate) fn convert_to_pixel(val: u16) -> Pixel {
+    let r = (val >> 11) & 0x1F;
+    let g = (val >> 5) & 0x3F;
+    let b = val & 0x1F;
+    (
+        ((r * 255 + 15) / 31) as u8,
+        ((g * 255 + 31) / 63) as u8,
+        ((b * 255 + 15) / 31) as u8,
+    )
+}
+
+impl fmt::Debug for Display {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Display {{ matrix: {:?} orientation: {:?} mirrored: {:?} blinks: {:?} }}",
+            self.matrix,
+            self.orientation,
+            self.mirrored,
+            self.blinks)
+    }
+}
@@ -0,0 +1,88 @@
+use display::Display;
+use {SenseHatError, SenseHatResult};
+
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{OriginDimensions, Size};
+use embedded_graphics::pixelcolor::{Rgb888, RgbColor};
+use embedded_graphics::Pixel;
+
+/// The LED matrix is 8x8, with the origin at the top left, as
+/// `set_pixel` has it.
+impl OriginDimensions for Display {
+    fn size(&self) -> Size {
+        Size::new(8, 8)
+    }
+}
+
+/// Lets embedded-graphics draw its primitives, fonts and images on the
+/// LED matrix. Each batch of pixels is drawn at once with `draw_points`,
+/// so it's a single write to the matrix, and pixels off the matrix are
+/// skipped.
+///
+/// `Display` has its own `clear`, which takes an `Option<Pixel>`, so the
+/// one from `DrawTarget` has to be called as `DrawTarget::clear`.
+///
+/// # Example
+///
+/// ```
+/// extern crate embedded_graphics;
+/// extern crate sensehat;
+///
+/// use embedded_graphics::pixelcolor::Rgb888;
+/// use embedded_graphics::prelude::*;
+/// use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+/// use sensehat::{Display, Frame, MemoryMatrix};
+///
+/// # fn main() {
+/// let matrix = MemoryMatrix::new();
+/// let mut display = Display::with_matrix(matrix.clone());
+/// DrawTarget::clear(&mut display, Rgb888::BLUE).unwrap();
+///
+/// // A red outline from (1, 1) to (6, 3).
+/// Rectangle::new(Point::new(1, 1), Size::new(6, 3))
+///     .into_styled(PrimitiveStyle::with_stroke(Rgb888::RED, 1))
+///     .draw(&mut display)
+///     .unwrap();
+/// // A green square hanging off the bottom right, drawn in one write.
+/// matrix.clear_writes();
+/// Rectangle::new(Point::new(6, 6), Size::new(4, 4))
+///     .into_styled(PrimitiveStyle::with_fill(Rgb888::GREEN))
+///     .draw(&mut display)
+///     .unwrap();
+/// assert_eq!(matrix.writes().len(), 1);
+///
+/// let expected = Frame::from_art(
+///     "bbbbbbbb\n\
+///      brrrrrrb\n\
+///      brbbbbrb\n\
+///      brrrrrrb\n\
+///      bbbbbbbb\n\
+///      bbbbbbbb\n\
+///      bbbbbbgg\n\
+///      bbbbbbgg",
+///     &[('r', (255, 0, 0)), ('g', (0, 255, 0)), ('b', (0, 0, 255))],
+/// )
+/// .unwrap();
+/// assert_eq!(&matrix.pixels(), expected.pixels());
+/// assert_eq!(display.bounding_box().size, Size::new(8, 8));
+/// # }
+/// ```
+impl DrawTarget for Display {
+    type Color = Rgb888;
+    type Error = SenseHatError;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> SenseHatResult<()>
+    where
+        I: IntoIterator<Item = Pixel<Rgb888>>,
+    {
+        self.draw_points(
+            pixels
+                .into_iter()
+                .map(|Pixel(point, color)| ((point.x, point.y), (color.r(), color.g(), color.b()))),
+        )
+    }
+
+    fn clear(&mut self, color: Rgb888) -> SenseHatResult<()> {
+        Display::clear(self, Some((color.r(), color.g(), color.b())))
+    }
+}
@@ -0,0 +1,901 @@
+#[cfg(feature = "sensors")]
+use i2cdev::linux::LinuxI2CError;
+#[cfg(feature = "sensors")]
+use outlier::OutlierReason;
+#[cfg(feature = "display")]
+use display::FramebufferSearch;
+#[cfg(feature = "display")]
+use frame::ArtProblem;
+#[cfg(feature = "display")]
+use framebuffer::FramebufferError;
+#[cfg(feature = "display")]
+use glob::{GlobError, PatternError};
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+/// A shortcut for Results that can return `T` or `SenseHatError`
+pub type SenseHatResult<T> = Result<T, SenseHatError>;
+
+/// Errors that this crate can return
+///
+/// Errors from the libraries the crate uses convert into it, so `?` works
+/// on them in functions returning `SenseHatResult`, and the original error
+/// is kept as the `source`.
+///
+/// # Example
+///
+/// ```
+/// extern crate sensehat;
+/// # #[cfg(feature = "display")]
+/// extern crate framebuffer;
+/// # #[cfg(feature = "display")]
+/// extern crate glob;
+/// # #[cfg(feature = "sensors")]
+/// extern crate i2cdev;
+///
+/// use std::error::Error;
+/// use std::io;
+/// use sensehat::{ErrorKind, SenseHatError, SenseHatResult};
+///
+/// fn open(path: &str) -> SenseHatResult<std::fs::File> {
+///     Ok(std::fs::File::open(path)?)
+/// }
+///
+/// # fn main() {
+/// let err = open("/no/such/file").unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::Io);
+/// match err {
+///     SenseHatError::IoError(ref io_err) => assert_eq!(io_err.kind(), io::ErrorKind::NotFound),
+///     ref other => panic!("{:?}", other),
+/// }
+/// let source = err.source().unwrap().downcast_ref::<io::Error>().unwrap();
+/// assert_eq!(source.kind(), io::ErrorKind::NotFound);
+///
+/// # #[cfg(feature = "sensors")]
+/// # {
+/// let i2c = i2cdev::linux::LinuxI2CError::from(io::Error::from(io::ErrorKind::TimedOut));
+/// let err = SenseHatError::from(i2c);
+/// assert_eq!(err.kind(), ErrorKind::I2c);
+/// assert!(err.source().unwrap().is::<i2cdev::linux::LinuxI2CError>());
+/// assert!(err.to_string().starts_with("I2C error: "));
+/// # }
+///
+/// # #[cfg(feature = "display")]
+/// # {
+/// let pattern = glob::Pattern::new("[").unwrap_err();
+/// let err = SenseHatError::from(pattern);
+/// assert_eq!(err.kind(), ErrorKind::Glob);
+/// assert!(err.source().unwrap().is::<glob::PatternError>());
+///
+/// // /dev/null isn't a framebuffer, so asking it for its screen info fails.
+/// let fb = framebuffer::Framebuffer::new("/dev/null").unwrap_err();
+/// let err = SenseHatError::from(fb);
+/// assert_eq!(err.kind(), ErrorKind::Framebuffer);
+/// assert!(err.source().unwrap().is::<framebuffer::FramebufferError>());
+/// # }
+/// # }
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SenseHatError {
+    // Sensor errors
+    /// The sensor has no new data available yet.
+    #[cfg(feature = "sensors")]
+    NotReady {
+        sensor: Sensor,
+        measurement: Measurement,
+    },
+
+    /// The sensor has been turned off, so it has no data.
+    #[cfg(feature = "sensors")]
+    ComponentDisabled {
+        sensor: Sensor,
+        measurement: Measurement,
+    },
+
+    /// The Sense Hat is in standby, so the sensor is powered down.
+    #[cfg(feature = "sensors")]
+    Standby {
+        sensor: Sensor,
+        measurement: Measurement,
+    },
+
+    /// A component of the Sense Hat failed to initialise, so it can't be
+    /// used.
+    ComponentNotAvailable {
+        component: Component,
+        cause: Arc<SenseHatError>,
+    },
+
+    /// The device at this I2C address reported a WHO_AM_I id we don't
+    /// recognise.
+    #[cfg(feature = "sensors")]
+    UnexpectedDevice {
+        address: u16,
+        id: u8,
+    },
+
+    /// The IMU reported this WHO_AM_I id, which isn't an LSM9DS1.
+    #[cfg(feature = "sensors")]
+    UnsupportedImu(u8),
+
+    /// The sensor had no new data within the timeout.
+    #[cfg(feature = "sensors")]
+    Timeout,
+
+    /// The FIFO watermark was more than 31 samples.
+    #[cfg(feature = "sensors")]
+    InvalidFifoWatermark(u8),
+
+    /// The pressure offset, in hPa, was too big for the pressure sensor's
+    /// offset register.
+    #[cfg(feature = "sensors")]
+    InvalidPressureOffset(f64),
+
+    /// The pressure threshold or reference, in hPa, was too big for the
+    /// pressure sensor's interrupt registers.
+    #[cfg(feature = "sensors")]
+    InvalidPressureThreshold(f64),
+
+    /// The smoothing factor wasn't more than 0 and no more than 1.
+    #[cfg(feature = "sensors")]
+    InvalidSmoothing(f64),
+
+    /// The reading broke a rule of the outlier policy, so it's probably a
+    /// glitch.
+    #[cfg(feature = "sensors")]
+    OutlierRejected {
+        value: f64,
+        reason: OutlierReason,
+    },
+
+    /// Calibration samples only covered this many of the eight octants
+    /// around their centre, so the board wasn't rotated enough.
+    #[cfg(feature = "sensors")]
+    InsufficientCoverage {
+        octants: u8,
+    },
+
+    /// The compass has never been calibrated, so its headings can be well
+    /// out.
+    #[cfg(feature = "sensors")]
+    UncalibratedCompass,
+
+    /// The magnetic field, in microtesla, was too strong or too weak to be
+    /// the Earth's, say from a magnet nearby, so the compass heading is
+    /// nonsense.
+    #[cfg(feature = "sensors")]
+    MagneticInterference {
+        magnitude: f64,
+    },
+
+    // Display errors
+    /// The given (x, y) coordinate is outside the 8x8 LED matrix.
+    #[cfg(feature = "display")]
+    OutOfBounds,
+    /// A gamma table contained a value greater than 31.
+    #[cfg(feature = "display")]
+    InvalidGamma,
+    /// A rotation wasn't 0, 90, 180 or 270 degrees.
+    #[cfg(feature = "display")]
+    InvalidRotation(u32),
+    /// A frame rate wasn't more than 0 frames a second and finite.
+    #[cfg(feature = "display")]
+    InvalidFrameRate(f64),
+    /// An animation would have had this many colours, more than the 256 a
+    /// palette can have.
+    #[cfg(feature = "display")]
+    TooManyColours(usize),
+    /// A frame used this palette index, which is past the end of its
+    /// palette.
+    #[cfg(feature = "display")]
+    InvalidPaletteIndex(u8),
+    /// Art for a frame couldn't be parsed, because of a problem at this
+    /// line and column, counting from 1.
+    #[cfg(feature = "display")]
+    InvalidArt {
+        line: usize,
+        column: usize,
+        problem: ArtProblem,
+    },
+    /// `undo` was called with no history to go back to.
+    #[cfg(feature = "display")]
+    NothingToUndo,
+    /// None of the framebuffers is the Sense HAT LED matrix. Says which
+    /// framebuffers were checked, and what was found at each.
+    #[cfg(feature = "display")]
+    MissingFramebuffer(FramebufferSearch),
+    /// The framebuffer could not be opened or mapped.
+    #[cfg(feature = "display")]
+    FramebufferError(FramebufferError),
+
+    // Joystick errors
+    /// None of the input devices is the Sense Hat joystick.
+    MissingJoystick,
+    /// A joystick or IMU callback panicked, with this message.
+    HandlerPanicked(String),
+
+    // Signal errors
+    /// `install_termination_handler` was called while a handler was
+    /// already installed.
+    CleanupHandlerInstalled,
+
+    // Sharing errors
+    /// A thread panicked while it had a `SharedSenseHat` locked.
+    Poisoned,
+
+    // Hat errors
+    /// The kernel did not find a HAT EEPROM.
+    NoHatDetected,
+
+    // IO errors
+    /// A file could not be read or written.
+    IoError(io::Error),
+    /// A path matched by a glob could not be read.
+    #[cfg(feature = "display")]
+    GlobError(GlobError),
+    /// A glob pattern was invalid.
+    #[cfg(feature = "display")]
+    PatternError(PatternError),
+    /// Talking to a device on the I2C bus failed.
+    #[cfg(feature = "sensors")]
+    I2CError(LinuxI2CError),
+}
+
+/// What sort of error a `SenseHatError` is, without anything it wraps, so
+/// it can be compared and matched on easily. There's one for each variant
+/// of `SenseHatError`, except `GlobError` and `PatternError`, which both
+/// have the kind `Glob`.
+/// Each kind needs the same feature as its variant, so a build without
+/// `display` has no display kinds, and one without `sensors` no sensor
+/// kinds.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    #[cfg(feature = "sensors")]
+    NotReady,
+    #[cfg(feature = "sensors")]
+    ComponentDisabled,
+    #[cfg(feature = "sensors")]
+    Standby,
+    ComponentNotAvailable,
+    #[cfg(feature = "sensors")]
+    UnexpectedDevice,
+    #[cfg(feature = "sensors")]
+    UnsupportedImu,
+    #[cfg(feature = "sensors")]
+    Timeout,
+    #[cfg(feature = "sensors")]
+    InvalidFifoWatermark,
+    #[cfg(feature = "sensors")]
+    InvalidPressureOffset,
+    #[cfg(feature = "sensors")]
+    InvalidPressureThreshold,
+    #[cfg(feature = "sensors")]
+    InvalidSmoothing,
+    #[cfg(feature = "sensors")]
+    OutlierRejected,
+    #[cfg(feature = "sensors")]
+    InsufficientCoverage,
+    #[cfg(feature = "sensors")]
+    UncalibratedCompass,
+    #[cfg(feature = "sensors")]
+    MagneticInterference,
+    #[cfg(feature = "display")]
+    OutOfBounds,
+    #[cfg(feature = "display")]
+    InvalidGamma,
+    #[cfg(feature = "display")]
+    InvalidRotation,
+    #[cfg(feature = "display")]
+    InvalidFrameRate,
+    #[cfg(feature = "display")]
+    TooManyColours,
+    #[cfg(feature = "display")]
+    InvalidPaletteIndex,
+    #[cfg(feature = "display")]
+    InvalidArt,
+    #[cfg(feature = "display")]
+    NothingToUndo,
+    #[cfg(feature = "display")]
+    MissingFramebuffer,
+    #[cfg(feature = "display")]
+    Framebuffer,
+    MissingJoystick,
+    HandlerPanicked,
+    CleanupHandlerInstalled,
+    Poisoned,
+    NoHatDetected,
+    Io,
+    #[cfg(feature = "display")]
+    Glob,
+    #[cfg(feature = "sensors")]
+    I2c,
+}
+
+impl SenseHatError {
+    /// Returns what sort of error this is.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io;
+    /// use std::sync::Arc;
+    /// use sensehat::{Component, ErrorKind, SenseHatError};
+    /// #[cfg(feature = "display")]
+    /// use sensehat::ArtProblem;
+    /// #[cfg(feature = "sensors")]
+    /// use sensehat::{Measurement, OutlierReason, Sensor};
+    ///
+    /// let mut errors = vec![
+    ///     (SenseHatError::MissingJoystick, ErrorKind::MissingJoystick),
+    ///     (SenseHatError::HandlerPanicked("oops".to_owned()), ErrorKind::HandlerPanicked),
+    ///     (SenseHatError::CleanupHandlerInstalled, ErrorKind::CleanupHandlerInstalled),
+    ///     (SenseHatError::Poisoned, ErrorKind::Poisoned),
+    ///     (SenseHatError::NoHatDetected, ErrorKind::NoHatDetected),
+    ///     (io::Error::new(io::ErrorKind::Other, "oops").into(), ErrorKind::Io),
+    ///     (
+    ///         SenseHatError::ComponentNotAvailable {
+    ///             component: Component::Imu,
+    ///             cause: Arc::new(SenseHatError::NoHatDetected),
+    ///         },
+    ///         ErrorKind::ComponentNotAvailable,
+    ///     ),
+    /// ];
+    ///
+    /// #[cfg(feature = "sensors")]
+    /// {
+    ///     let not_ready = SenseHatError::NotReady {
+    ///         sensor: Sensor::Humidity,
+    ///         measurement: Measurement::Humidity,
+    ///     };
+    ///     assert_eq!(not_ready.kind(), ErrorKind::NotReady);
+    ///     assert!(not_ready.is_not_ready());
+    ///     errors.extend(vec![
+    ///         (not_ready, ErrorKind::NotReady),
+    ///         (
+    ///             SenseHatError::ComponentDisabled {
+    ///                 sensor: Sensor::Imu,
+    ///                 measurement: Measurement::MagneticField,
+    ///             },
+    ///             ErrorKind::ComponentDisabled,
+    ///         ),
+    ///         (
+    ///             SenseHatError::Standby {
+    ///                 sensor: Sensor::Pressure,
+    ///                 measurement: Measurement::Pressure,
+    ///             },
+    ///             ErrorKind::Standby,
+    ///         ),
+    ///         (SenseHatError::Timeout, ErrorKind::Timeout),
+    ///         (SenseHatError::UnsupportedImu(0xd4), ErrorKind::UnsupportedImu),
+    ///         (SenseHatError::InvalidFifoWatermark(32), ErrorKind::InvalidFifoWatermark),
+    ///         (SenseHatError::InvalidPressureOffset(5000.0), ErrorKind::InvalidPressureOffset),
+    ///         (
+    ///             SenseHatError::InvalidPressureThreshold(-1.0),
+    ///             ErrorKind::InvalidPressureThreshold,
+    ///         ),
+    ///         (SenseHatError::InvalidSmoothing(0.0), ErrorKind::InvalidSmoothing),
+    ///         (
+    ///             SenseHatError::InsufficientCoverage { octants: 3 },
+    ///             ErrorKind::InsufficientCoverage,
+    ///         ),
+    ///         (SenseHatError::UncalibratedCompass, ErrorKind::UncalibratedCompass),
+    ///         (
+    ///             SenseHatError::MagneticInterference { magnitude: 400.0 },
+    ///             ErrorKind::MagneticInterference,
+    ///         ),
+    ///         (
+    ///             SenseHatError::UnexpectedDevice { address: 0x5f, id: 0 },
+    ///             ErrorKind::UnexpectedDevice,
+    ///         ),
+    ///         (
+    ///             SenseHatError::OutlierRejected {
+    ///                 value: -40.0,
+    ///                 reason: OutlierReason::OutOfRange { min: 0.0, max: 50.0 },
+    ///             },
+    ///             ErrorKind::OutlierRejected,
+    ///         ),
+    ///     ]);
+    /// }
+    ///
+    /// #[cfg(feature = "display")]
+    /// {
+    ///     assert!(SenseHatError::OutOfBounds.is_out_of_bounds());
+    ///     assert!(!SenseHatError::InvalidGamma.is_out_of_bounds());
+    ///     errors.extend(vec![
+    ///         (SenseHatError::OutOfBounds, ErrorKind::OutOfBounds),
+    ///         (SenseHatError::InvalidGamma, ErrorKind::InvalidGamma),
+    ///         (SenseHatError::InvalidRotation(45), ErrorKind::InvalidRotation),
+    ///         (SenseHatError::InvalidFrameRate(0.0), ErrorKind::InvalidFrameRate),
+    ///         (SenseHatError::TooManyColours(300), ErrorKind::TooManyColours),
+    ///         (SenseHatError::InvalidPaletteIndex(4), ErrorKind::InvalidPaletteIndex),
+    ///         (SenseHatError::NothingToUndo, ErrorKind::NothingToUndo),
+    ///         (
+    ///             SenseHatError::InvalidArt { line: 2, column: 9, problem: ArtProblem::LineTooLong },
+    ///             ErrorKind::InvalidArt,
+    ///         ),
+    ///     ]);
+    /// }
+    ///
+    /// for (error, kind) in errors {
+    ///     assert_eq!(error.kind(), kind, "{}", error);
+    /// }
+    /// ```
+    pub fn kind(&self) -> ErrorKind {
+        match *self {
+            #[cfg(feature = "sensors")]
+            SenseHatError::NotReady { .. } => ErrorKind::NotReady,
+            #[cfg(feature = "sensors")]
+            SenseHatError::ComponentDisabled { .. } => ErrorKind::ComponentDisabled,
+            #[cfg(feature = "sensors")]
+            SenseHatError::Standby { .. } => ErrorKind::Standby,
+            SenseHatError::ComponentNotAvailable { .. } => ErrorKind::ComponentNotAvailable,
+            #[cfg(feature = "sensors")]
+            SenseHatError::UnexpectedDevice { .. } => ErrorKind::UnexpectedDevice,
+            #[cfg(feature = "sensors")]
+            SenseHatError::UnsupportedImu(_) => ErrorKind::UnsupportedImu,
+            #[cfg(feature = "sensors")]
+            SenseHatError::Timeout => ErrorKind::Timeout,
+            #[cfg(feature = "sensors")]
+            SenseHatError::InvalidFifoWatermark(_) => ErrorKind::InvalidFifoWatermark,
+            #[cfg(feature = "sensors")]
+            SenseHatError::InvalidPressureOffset(_) => ErrorKind::InvalidPressureOffset,
+            #[cfg(feature = "sensors")]
+            SenseHatError::InvalidPressureThreshold(_) => ErrorKind::InvalidPressureThreshold,
+            #[cfg(feature = "sensors")]
+            SenseHatError::InvalidSmoothing(_) => ErrorKind::InvalidSmoothing,
+            #[cfg(feature = "sensors")]
+            SenseHatError::OutlierRejected { .. } => ErrorKind::OutlierRejected,
+            #[cfg(feature = "sensors")]
+            SenseHatError::InsufficientCoverage { .. } => ErrorKind::InsufficientCoverage,
+            #[cfg(feature = "sensors")]
+            SenseHatError::UncalibratedCompass => ErrorKind::UncalibratedCompass,
+            #[cfg(feature = "sensors")]
+            SenseHatError::MagneticInterference { .. } => ErrorKind::MagneticInterference,
+            #[cfg(feature = "display")]
+            SenseHatError::OutOfBounds => ErrorKind::OutOfBounds,
+            #[cfg(feature = "display")]
+            SenseHatError::InvalidGamma => ErrorKind::InvalidGamma,
+            #[cfg(feature = "display")]
+            SenseHatError::InvalidRotation(_) => ErrorKind::InvalidRotation,
+            #[cfg(feature = "display")]
+            SenseHatError::InvalidFrameRate(_) => ErrorKind::InvalidFrameRate,
+            #[cfg(feature = "display")]
+            SenseHatError::TooManyColours(_) => ErrorKind::TooManyColours,
+            #[cfg(feature = "display")]
+            SenseHatError::InvalidPaletteIndex(_) => ErrorKind::InvalidPaletteIndex,
+            #[cfg(feature = "display")]
+            SenseHatError::InvalidArt { .. } => ErrorKind::InvalidArt,
+            #[cfg(feature = "display")]
+            SenseHatError::NothingToUndo => ErrorKind::NothingToUndo,
+            #[cfg(feature = "display")]
+            SenseHatError::MissingFramebuffer(_) => ErrorKind::MissingFramebuffer,
+            #[cfg(feature = "display")]
+            SenseHatError::FramebufferError(_) => ErrorKind::Framebuffer,
+            SenseHatError::MissingJoystick => ErrorKind::MissingJoystick,
+            SenseHatError::HandlerPanicked(_) => ErrorKind::HandlerPanicked,
+            SenseHatError::CleanupHandlerInstalled => ErrorKind::CleanupHandlerInstalled,
+            SenseHatError::Poisoned => ErrorKind::Poisoned,
+            SenseHatError::NoHatDetected => ErrorKind::NoHatDetected,
+            SenseHatError::IoError(_) => ErrorKind::Io,
+            #[cfg(feature = "display")]
+            SenseHatError::GlobError(_) | SenseHatError::PatternError(_) => ErrorKind::Glob,
+            #[cfg(feature = "sensors")]
+            SenseHatError::I2CError(_) => ErrorKind::I2c,
+        }
+    }
+}
+
+/// The sensors on the Sense Hat.
+#[cfg(feature = "sensors")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Sensor {
+    /// The LPS25H pressure sensor.
+    Pressure,
+    /// The HTS221 humidity sensor.
+    Humidity,
+    /// The LSM9DS1 IMU.
+    Imu,
+}
+
+/// The components of the Sense Hat which are initialised separately.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Component {
+    /// The LPS25H pressure sensor.
+    Pressure,
+    /// The HTS221 humidity sensor.
+    Humidity,
+    /// The 8x8 LED matrix.
+    Display,
+    /// The LSM9DS1 IMU.
+    Imu,
+}
+
+/// The kinds of value the sensors measure.
+#[cfg(feature = "sensors")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Measurement {
+    Temperature,
+    Pressure,
+    Humidity,
+    Acceleration,
+    AngularRate,
+    MagneticField,
+    Orientation,
+}
+
+impl SenseHatError {
+    /// Returns true if this is a `NotReady` error, whichever sensor it came from.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(feature = "sensors")]
+    /// # {
+    /// use sensehat::{Measurement, MockI2CDevice, SenseHat, SenseHatError, SenseHatResult, Sensor};
+    ///
+    /// // Neither sensor has set any of its data ready bits.
+    /// let mut sense = SenseHat::builder()
+    ///     .pressure_device(MockI2CDevice::new())
+    ///     .humidity_device(MockI2CDevice::new())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let check = |result: SenseHatResult<()>, sensor, measurement, message: &str| {
+    ///     let err = result.unwrap_err();
+    ///     assert!(err.is_not_ready());
+    ///     assert_eq!(err.to_string(), message);
+    ///     match err {
+    ///         SenseHatError::NotReady { sensor: s, measurement: m } => {
+    ///             assert_eq!((s, m), (sensor, measurement))
+    ///         }
+    ///         other => panic!("{}", other),
+    ///     }
+    /// };
+    /// check(
+    ///     sense.get_pressure().map(|_| ()),
+    ///     Sensor::Pressure,
+    ///     Measurement::Pressure,
+    ///     "pressure sensor: pressure data not ready",
+    /// );
+    /// check(
+    ///     sense.get_temperature_from_pressure().map(|_| ()),
+    ///     Sensor::Pressure,
+    ///     Measurement::Temperature,
+    ///     "pressure sensor: temperature data not ready",
+    /// );
+    /// check(
+    ///     sense.get_humidity().map(|_| ()),
+    ///     Sensor::Humidity,
+    ///     Measurement::Humidity,
+    ///     "humidity sensor: humidity data not ready",
+    /// );
+    /// check(
+    ///     sense.get_temperature_from_humidity().map(|_| ()),
+    ///     Sensor::Humidity,
+    ///     Measurement::Temperature,
+    ///     "humidity sensor: temperature data not ready",
+    /// );
+    /// assert!(!SenseHatError::Timeout.is_not_ready());
+    /// # }
+    /// ```
+    #[cfg(feature = "sensors")]
+    pub fn is_not_ready(&self) -> bool {
+        matches!(*self, SenseHatError::NotReady { .. })
+    }
+
+    /// Returns true if this is an `OutOfBounds` error.
+    #[cfg(feature = "display")]
+    pub fn is_out_of_bounds(&self) -> bool {
+        matches!(*self, SenseHatError::OutOfBounds)
+    }
+}
+
+#[cfg(feature = "sensors")]
+impl fmt::Display for Sensor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Sensor::Pressure => write!(f, "pressure sensor"),
+            Sensor::Humidity => write!(f, "humidity sensor"),
+            Sensor::Imu => write!(f, "IMU"),
+        }
+    }
+}
+
+impl fmt::Display for Component {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Component::Pressure => write!(f, "pressure sensor"),
+            Component::Humidity => write!(f, "humidity sensor"),
+            Component::Display => write!(f, "LED matrix"),
+            Component::Imu => write!(f, "IMU"),
+        }
+    }
+}
+
+#[cfg(feature = "sensors")]
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Measurement::Temperature => write!(f, "temperature"),
+            Measurement::Pressure => write!(f, "pressure"),
+            Measurement::Humidity => write!(f, "humidity"),
+            Measurement::Acceleration => write!(f, "acceleration"),
+            Measurement::AngularRate => write!(f, "angular rate"),
+            Measurement::MagneticField => write!(f, "magnetic field"),
+            Measurement::Orientation => write!(f, "orientation"),
+        }
+    }
+}
+
+/// Describes the error in words, for showing to people.
+///
+/// # Example
+///
+/// ```
+/// extern crate sensehat;
+/// # #[cfg(feature = "sensors")]
+/// extern crate i2cdev;
+///
+/// use sensehat::{Component, SenseHatError};
+/// use std::io;
+/// use std::sync::Arc;
+///
+/// # fn main() {
+/// let mut errors = vec![
+///     (SenseHatError::MissingJoystick, "no Sense HAT joystick found"),
+///     (SenseHatError::HandlerPanicked("oops".to_owned()), "callback panicked: oops"),
+///     (SenseHatError::CleanupHandlerInstalled, "a cleanup handler is already installed"),
+///     (SenseHatError::Poisoned, "a thread panicked while using the shared Sense Hat"),
+///     (SenseHatError::NoHatDetected, "no HAT EEPROM detected"),
+///     (io::Error::new(io::ErrorKind::Other, "oops").into(), "I/O error: oops"),
+///     (SenseHatError::ComponentNotAvailable {
+///         component: Component::Display,
+///         cause: Arc::new(SenseHatError::NoHatDetected),
+///     }, "LED matrix not available: no HAT EEPROM detected"),
+/// ];
+/// # #[cfg(feature = "sensors")]
+/// errors.extend(vec![
+///     (SenseHatError::UnexpectedDevice { address: 0x5f, id: 0x12 },
+///      "unexpected device id 0x12 at I2C address 0x5f"),
+///     (SenseHatError::UnsupportedImu(0xd4), "unsupported IMU: found an LSM9DS0 (id 0xd4)"),
+///     (SenseHatError::UnsupportedImu(0x00), "unsupported IMU with id 0x00"),
+///     (SenseHatError::Timeout, "timed out waiting for sensor data"),
+///     (SenseHatError::InvalidFifoWatermark(32), "FIFO watermark 32 is more than 31 samples"),
+///     (SenseHatError::InvalidPressureOffset(3000.0),
+///      "pressure offset 3000 hPa is outside -2048 to 2047.9375 hPa"),
+///     (SenseHatError::InvalidPressureThreshold(-1.5),
+///      "pressure threshold -1.5 hPa doesn't fit in the pressure sensor's registers"),
+///     (SenseHatError::InvalidSmoothing(0.0),
+///      "smoothing factor 0 must be more than 0 and no more than 1"),
+///     (SenseHatError::InsufficientCoverage { octants: 3 },
+///      "calibration samples only covered 3 of 8 octants, keep rotating the board"),
+///     (SenseHatError::UncalibratedCompass, "the compass hasn't been calibrated"),
+///     (SenseHatError::MagneticInterference { magnitude: 123.45 },
+///      "magnetic field of 123.5uT is too far from the Earth's, is there a magnet nearby?"),
+///     (
+///         i2cdev::linux::LinuxI2CError::from(io::Error::new(io::ErrorKind::Other, "nak")).into(),
+///         "I2C error: nak",
+///     ),
+/// ]);
+/// # #[cfg(feature = "display")]
+/// errors.extend(vec![
+///     (SenseHatError::OutOfBounds, "coordinate outside the 8x8 LED matrix"),
+///     (SenseHatError::InvalidGamma, "gamma values must be between 0 and 31"),
+///     (SenseHatError::InvalidRotation(45), "rotation must be 0, 90, 180 or 270 degrees, not 45"),
+///     (SenseHatError::InvalidFrameRate(-1.0), "frame rate -1 must be more than 0 and finite"),
+///     (SenseHatError::TooManyColours(300), "300 colours don't fit in a palette of 256"),
+///     (SenseHatError::InvalidPaletteIndex(9), "palette index 9 is past the end of the palette"),
+///     (SenseHatError::NothingToUndo, "no display history to undo"),
+/// ]);
+/// for (error, message) in errors {
+///     assert_eq!(error.to_string(), message);
+/// }
+/// # }
+/// ```
+impl fmt::Display for SenseHatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            #[cfg(feature = "sensors")]
+            SenseHatError::NotReady { sensor, measurement } => {
+                write!(f, "{}: {} data not ready", sensor, measurement)
+            }
+            #[cfg(feature = "sensors")]
+            SenseHatError::Standby { sensor, measurement } => {
+                write!(f, "{}: can't read {} in standby", sensor, measurement)
+            }
+            #[cfg(feature = "sensors")]
+            SenseHatError::ComponentDisabled { sensor, measurement } => {
+                write!(f, "{}: {} readings are disabled", sensor, measurement)
+            }
+            SenseHatError::ComponentNotAvailable { component, ref cause } => {
+                write!(f, "{} not available: {}", component, cause)
+            }
+            #[cfg(feature = "sensors")]
+            SenseHatError::UnexpectedDevice { address, id } => {
+                write!(f, "unexpected device id {:#04x} at I2C address {:#04x}", id, address)
+            }
+            #[cfg(feature = "sensors")]
+            SenseHatError::UnsupportedImu(id) => match id {
+                0xd4 => write!(f, "unsupported IMU: found an LSM9DS0 (id {:#04x})", id),
+                _ => write!(f, "unsupported IMU with id {:#04x}", id),
+            },
+            #[cfg(feature = "sensors")]
+            SenseHatError::Timeout => write!(f, "timed out waiting for sensor data"),
+            #[cfg(feature = "sensors")]
+            SenseHatError::InvalidFifoWatermark(watermark) => {
+                write!(f, "FIFO watermark {} is more than 31 samples", watermark)
+            }
+            #[cfg(feature = "sensors")]
+            SenseHatError::InvalidPressureOffset(hectopascals) => write!(
+                f,
+                "pressure offset {} hPa is outside -2048 to 2047.9375 hPa",
+                hectopascals
+            ),
+            #[cfg(feature = "sensors")]
+            SenseHatError::InvalidPressureThreshold(hectopascals) => write!(
+                f,
+                "pressure threshold {} hPa doesn't fit in the pressure sensor's registers",
+                hectopascals
+            ),
+            #[cfg(feature = "sensors")]
+            SenseHatError::InvalidSmoothing(alpha) => {
+                write!(f, "smoothing factor {} must be more than 0 and no more than 1", alpha)
+            }
+            #[cfg(feature = "sensors")]
+            SenseHatError::OutlierRejected { value, reason } => {
+                write!(f, "rejected reading {} as an outlier: {}", value, reason)
+            }
+            #[cfg(feature = "sensors")]
+            SenseHatError::InsufficientCoverage { octants } => write!(
+                f,
+                "calibration samples only covered {} of 8 octants, keep rotating the board",
+                octants
+            ),
+            #[cfg(feature = "sensors")]
+            SenseHatError::UncalibratedCompass => {
+                write!(f, "the compass hasn't been calibrated")
+            }
+            #[cfg(feature = "sensors")]
+            SenseHatError::MagneticInterference { magnitude } => write!(
+                f,
+                "magnetic field of {:.1}uT is too far from the Earth's, is there a magnet nearby?",
+                magnitude
+            ),
+            #[cfg(feature = "display")]
+            SenseHatError::OutOfBounds => write!(f, "coordinate outside the 8x8 LED matrix"),
+            #[cfg(feature = "display")]
+            SenseHatError::InvalidGamma => write!(f, "gamma values must be between 0 and 31"),
+            #[cfg(feature = "display")]
+            SenseHatError::InvalidRotation(r) => {
+                write!(f, "rotation must be 0, 90, 180 or 270 degrees, not {}", r)
+            }
+            #[cfg(feature = "display")]
+            SenseHatError::InvalidFrameRate(fps) => {
+                write!(f, "frame rate {} must be more than 0 and finite", fps)
+            }
+            #[cfg(feature = "display")]
+            SenseHatError::TooManyColours(colours) => {
+                write!(f, "{} colours don't fit in a palette of 256", colours)
+            }
+            #[cfg(feature = "display")]
+            SenseHatError::InvalidPaletteIndex(index) => {
+                write!(f, "palette index {} is past the end of the palette", index)
+            }
+            #[cfg(feature = "display")]
+            SenseHatError::InvalidArt {
+                line,
+                column,
+                problem,
+            } => write!(f, "bad art at line {}, column {}: {}", line, column, problem),
+            #[cfg(feature = "display")]
+            SenseHatError::NothingToUndo => write!(f, "no display history to undo"),
+            #[cfg(feature = "display")]
+            SenseHatError::MissingFramebuffer(ref search) => write!(f, "{}", search),
+            #[cfg(feature = "display")]
+            SenseHatError::FramebufferError(ref err) => write!(f, "framebuffer error: {}", err),
+            SenseHatError::MissingJoystick => write!(f, "no Sense HAT joystick found"),
+            SenseHatError::HandlerPanicked(ref message) => {
+                write!(f, "callback panicked: {}", message)
+            }
+            SenseHatError::CleanupHandlerInstalled => {
+                write!(f, "a cleanup handler is already installed")
+            }
+            SenseHatError::Poisoned => {
+                write!(f, "a thread panicked while using the shared Sense Hat")
+            }
+            SenseHatError::NoHatDetected => write!(f, "no HAT EEPROM detected"),
+            SenseHatError::IoError(ref err) => write!(f, "I/O error: {}", err),
+            #[cfg(feature = "display")]
+            SenseHatError::GlobError(ref err) => write!(f, "glob error: {}", err),
+            #[cfg(feature = "display")]
+            SenseHatError::PatternError(ref err) => write!(f, "glob pattern error: {}", err),
+            #[cfg(feature = "sensors")]
+            SenseHatError::I2CError(ref err) => write!(f, "I2C error: {}", err),
+        }
+    }
+}
+
+/// `source` is the error this one wraps, if it wraps one.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{Component, SenseHatError};
+/// use std::error::Error;
+/// use std::io;
+/// use std::sync::Arc;
+///
+/// let io_error: SenseHatError = io::Error::new(io::ErrorKind::Other, "oops").into();
+/// let inner = io_error.source().unwrap().downcast_ref::<io::Error>().unwrap();
+/// assert_eq!(inner.to_string(), "oops");
+///
+/// // A component which isn't available has what stopped it as its source.
+/// let unavailable = SenseHatError::ComponentNotAvailable {
+///     component: Component::Imu,
+///     cause: Arc::new(io_error),
+/// };
+/// let cause = unavailable.source().unwrap().downcast_ref::<SenseHatError>().unwrap();
+/// assert_eq!(cause.to_string(), "I/O error: oops");
+/// assert!(cause.source().unwrap().is::<io::Error>());
+///
+/// // Errors of the crate's own don't have one.
+/// assert!(SenseHatError::NoHatDetected.source().is_none());
+/// assert!(SenseHatError::Poisoned.source().is_none());
+/// ```
+impl Error for SenseHatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            SenseHatError::ComponentNotAvailable { ref cause, .. } => Some(&**cause),
+            #[cfg(feature = "display")]
+            SenseHatError::FramebufferError(ref err) => Some(err),
+            SenseHatError::IoError(ref err) => Some(err),
+            #[cfg(feature = "display")]
+            SenseHatError::GlobError(ref err) => Some(err),
+            #[cfg(feature = "display")]
+            SenseHatError::PatternError(ref err) => Some(err),
+            #[cfg(feature = "sensors")]
+            SenseHatError::I2CError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "sensors")]
+impl From<LinuxI2CError> for SenseHatError {
+    fn from(err: LinuxI2CError) -> Self {
+        SenseHatError::I2CError(err)
+    }
+}
+
+impl From<io::Error> for SenseHatError {
+    fn from(err: io::Error) -> Self {
+        SenseHatError::IoError(err)
+    }
+}
+
+#[cfg(feature = "display")]
+impl From<GlobError> for SenseHatError {
+    fn from(err: GlobError) -> Self {
+        SenseHatError::GlobError(err)
+    }
+}
+
+#[cfg(feature = "display")]
+impl From<PatternError> for SenseHatError {
+    fn from(err: PatternError) -> Self {
+        SenseHatError::PatternError(err)
+    }
+}
+
+#[cfg(feature = "display")]
+impl From<FramebufferError> for SenseHatError {
+    fn from(err: FramebufferError) -> Self {
+        SenseHatError::FramebufferError(err)
+    }
+}
@@ -0,0 +1,292 @@
+use display::Pixel;
+use {SenseHatError, SenseHatResult};
+
+use std::fmt;
+
+/// A whole image for the LED matrix, 64 pixels row by row from the top
+/// left, as `Display::set_pixels` takes.
+///
+/// Frames are easiest to write as art, with a character for each pixel,
+/// eight lines of eight. See `from_art`.
+///
+/// # Example
+///
+/// ```no_run
+/// use sensehat::{Frame, SenseHat};
+///
+/// let heart = Frame::from_art(
+///     ".rr..rr.\n\
+///      rrrrrrrr\n\
+///      rrrrrrrr\n\
+///      rrrrrrrr\n\
+///      .rrrrrr.\n\
+///      ..rrrr..\n\
+///      ...rr...\n\
+///      ........",
+///     &[('r', (255, 0, 0))],
+/// )
+/// .unwrap();
+/// let mut sense = SenseHat::new().unwrap();
+/// sense.set_pixels(heart.pixels()).unwrap();
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Frame {
+    pixels: [Pixel; 64],
+}
+
+/// What was wrong with art given to `Frame::from_art`, as part of
+/// `SenseHatError::InvalidArt`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ArtProblem {
+    /// There were fewer than eight lines.
+    TooFewLines,
+    /// There were more than eight lines.
+    TooManyLines,
+    /// A line had fewer than eight characters.
+    LineTooShort,
+    /// A line had more than eight characters.
+    LineTooLong,
+    /// A character wasn't in the palette, or `.` or a space.
+    UnknownCharacter(char),
+}
+
+impl fmt::Display for ArtProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ArtProblem::TooFewLines => write!(f, "fewer than 8 lines"),
+            ArtProblem::TooManyLines => write!(f, "more than 8 lines"),
+            ArtProblem::LineTooShort => write!(f, "line has fewer than 8 characters"),
+            ArtProblem::LineTooLong => write!(f, "line has more than 8 characters"),
+            ArtProblem::UnknownCharacter(c) => write!(f, "{:?} isn't in the palette", c),
+        }
+    }
+}
+
+impl Frame {
+    /// Makes a frame of `pixels`, row by row from the top left.
+    pub const fn new(pixels: [Pixel; 64]) -> Self {
+        Frame { pixels }
+    }
+
+    /// Parses a frame from art: eight lines of eight characters, top to
+    /// bottom, each character the colour it's paired with in `palette`.
+    /// `.` and spaces are black, unless the palette says otherwise. A
+    /// newline at the very start or end of the art is ignored, and so are
+    /// carriage returns at the ends of lines.
+    ///
+    /// Returns `InvalidArt` saying where the art went wrong if there's a
+    /// character which isn't in the palette, or it isn't eight by eight.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::{ArtProblem, Frame, SenseHatError};
+    ///
+    /// let (w, b) = ((255, 255, 255), (0, 128, 255));
+    /// let palette = [('w', w), ('b', b)];
+    /// let frame = Frame::from_art(
+    ///     "
+    /// wwwwwbbb
+    /// bwbbwbbb
+    /// bbwbwbbb
+    /// bbbwwwww
+    /// bwwwwbbw
+    /// bbbwbwbw
+    /// bbbwbbww
+    /// bbbbbbbw
+    /// ",
+    ///     &palette,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(frame.pixels()[..8], [w, w, w, w, w, b, b, b]);
+    /// assert_eq!(frame.pixels()[63], w);
+    ///
+    /// // Dots and spaces are black.
+    /// let dot = Frame::from_art("........\n. ......\n..w.....\n........\n\
+    ///                            ........\n........\n........\n........", &palette);
+    /// let dot = dot.unwrap();
+    /// assert_eq!(dot.pixels()[8 * 2 + 2], w);
+    /// assert_eq!(dot.pixels().iter().filter(|&&p| p == (0, 0, 0)).count(), 63);
+    ///
+    /// let problem = |art: &str| match Frame::from_art(art, &palette) {
+    ///     Err(SenseHatError::InvalidArt { line, column, problem }) => (line, column, problem),
+    ///     other => panic!("{:?}", other),
+    /// };
+    /// // Ragged lines.
+    /// let short = "........\n.......\n........\n........\n\
+    ///              ........\n........\n........\n........";
+    /// assert_eq!(problem(short), (2, 8, ArtProblem::LineTooShort));
+    /// let long = "........\n........\n.........\n........\n\
+    ///             ........\n........\n........\n........";
+    /// assert_eq!(problem(long), (3, 9, ArtProblem::LineTooLong));
+    /// assert_eq!(problem("........\n........"), (3, 1, ArtProblem::TooFewLines));
+    /// assert_eq!(problem(&"........\n".repeat(9)), (9, 1, ArtProblem::TooManyLines));
+    /// // Characters which aren't in the palette.
+    /// let unknown = "........\n........\n........\n...x....\n\
+    ///                ........\n........\n........\n........";
+    /// assert_eq!(problem(unknown), (4, 4, ArtProblem::UnknownCharacter('x')));
+    /// let error = Frame::from_art(unknown, &palette).unwrap_err();
+    /// assert_eq!(error.to_string(), "bad art at line 4, column 4: 'x' isn't in the palette");
+    /// ```
+    pub fn from_art(art: &str, palette: &[(char, Pixel)]) -> SenseHatResult<Self> {
+        let art = art.strip_prefix('\n').unwrap_or(art);
+        let art = art.strip_suffix('\n').unwrap_or(art);
+        let mut pixels = [(0, 0, 0); 64];
+        let mut lines = 0;
+        for (y, line) in art.split('\n').enumerate() {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if y == 8 {
+                return Err(invalid_art(y, 0, ArtProblem::TooManyLines));
+            }
+            let mut columns = 0;
+            for (x, c) in line.chars().enumerate() {
+                if x == 8 {
+                    return Err(invalid_art(y, x, ArtProblem::LineTooLong));
+                }
+                pixels[x + 8 * y] = match palette.iter().find(|&&(key, _)| key == c) {
+                    Some(&(_, pixel)) => pixel,
+                    None if c == '.' || c == ' ' => (0, 0, 0),
+                    None => return Err(invalid_art(y, x, ArtProblem::UnknownCharacter(c))),
+                };
+                columns += 1;
+            }
+            if columns < 8 {
+                return Err(invalid_art(y, columns, ArtProblem::LineTooShort));
+            }
+            lines += 1;
+        }
+        if lines < 8 {
+            return Err(invalid_art(lines, 0, ArtProblem::TooFewLines));
+        }
+        Ok(Frame { pixels })
+    }
+
+    /// Parses a frame from art as `from_art` does, but at compile time, so
+    /// it can make a `const`. The art and palette can only use ASCII
+    /// characters, and art which `from_art` would return an error for
+    /// panics instead, which stops the build.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::Frame;
+    ///
+    /// const ARROW: Frame = Frame::from_art_const(
+    ///     "...##...\n\
+    ///      ..####..\n\
+    ///      .######.\n\
+    ///      ...##...\n\
+    ///      ...##...\n\
+    ///      ...##...\n\
+    ///      ...##...\n\
+    ///      ........",
+    ///     &[('#', (0, 255, 0))],
+    /// );
+    /// assert_eq!(ARROW.pixels()[3], (0, 255, 0));
+    /// assert_eq!(ARROW.pixels()[0], (0, 0, 0));
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use sensehat::Frame;
+    ///
+    /// // Only seven lines.
+    /// const SHORT: Frame = Frame::from_art_const(
+    ///     "........\n........\n........\n........\n........\n........\n........",
+    ///     &[],
+    /// );
+    /// ```
+    pub const fn from_art_const(art: &str, palette: &[(char, Pixel)]) -> Self {
+        let art = art.as_bytes();
+        let mut start = 0;
+        let mut end = art.len();
+        if end > 0 && art[0] == b'\n' {
+            start = 1;
+        }
+        if end > start && art[end - 1] == b'\n' {
+            end -= 1;
+        }
+        let mut pixels = [(0, 0, 0); 64];
+        let (mut x, mut y) = (0, 0);
+        let mut i = start;
+        while i <= end {
+            if i == end || art[i] == b'\n' {
+                if y == 8 {
+                    panic!("art has more than 8 lines");
+                }
+                if x < 8 {
+                    panic!("art has a line with fewer than 8 characters");
+                }
+                y += 1;
+                x = 0;
+                i += 1;
+                continue;
+            }
+            let c = art[i];
+            i += 1;
+            if c == b'\r' && (i == end || art[i] == b'\n') {
+                continue;
+            }
+            if !c.is_ascii() {
+                panic!("art has a character which isn't ASCII");
+            }
+            if y == 8 {
+                panic!("art has more than 8 lines");
+            }
+            if x == 8 {
+                panic!("art has a line with more than 8 characters");
+            }
+            let mut key = 0;
+            while key < palette.len() && palette[key].0 as u32 != c as u32 {
+                key += 1;
+            }
+            pixels[x + 8 * y] = if key < palette.len() {
+                palette[key].1
+            } else if c == b'.' || c == b' ' {
+                (0, 0, 0)
+            } else {
+                panic!("art has a character which isn't in the palette");
+            };
+            x += 1;
+        }
+        if y < 8 {
+            panic!("art has fewer than 8 lines");
+        }
+        Frame { pixels }
+    }
+
+    /// Returns the frame's pixels, row by row from the top left.
+    pub fn pixels(&self) -> &[Pixel; 64] {
+        &self.pixels
+    }
+}
+
+impl Default for Frame {
+    /// Returns a black frame.
+    fn default() -> Self {
+        Frame::new([(0, 0, 0); 64])
+    }
+}
+
+impl From<[Pixel; 64]> for Frame {
+    fn from(pixels: [Pixel; 64]) -> Self {
+        Frame::new(pixels)
+    }
+}
+
+impl From<Frame> for [Pixel; 64] {
+    fn from(frame: Frame) -> Self {
+        frame.pixels
+    }
+}
+
+/// Helper function.
+///
+/// Makes the error for a problem in art at the zero based line `y` and
+/// column `x`.
+fn invalid_art(y: usize, x: usize, problem: ArtProblem) -> SenseHatError {
+    SenseHatError::InvalidArt {
+        line: y + 1,
+        column: x + 1,
+        problem,
+    }
+}
@@ -0,0 +1,688 @@
+use imu::ImuSample;
+use units::Angle;
+
+use std::f64::consts::PI;
+use std::ops::Mul;
+
+/// The Madgwick filter gain the Madgwick paper recommends.
+const DEFAULT_MADGWICK_BETA: f64 = 0.1;
+
+/// How much the complementary filter trusts the integrated gyro over the
+/// accelerometer and magnetometer, unless configured otherwise.
+const DEFAULT_GYRO_WEIGHT: f64 = 0.98;
+
+/// The orientation of the Sense Hat.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Orientation3d {
+    /// Rotation around the Y axis.
+    pub pitch: Angle,
+    /// Rotation around the X axis.
+    pub roll: Angle,
+    /// Rotation around the Z axis, i.e. the compass heading.
+    pub yaw: Angle,
+    /// True if the yaw was worked out without the magnetometer, so it's
+    /// the integrated gyro, relative to wherever it started and drifting,
+    /// or it's relative to the orientation set by `Imu::tare`, rather than
+    /// a compass heading.
+    pub yaw_is_relative: bool,
+}
+
+impl Orientation3d {
+    /// Returns the pitch, roll and yaw in degrees.
+    pub fn degrees(&self) -> (f64, f64, f64) {
+        (self.pitch.as_degrees(), self.roll.as_degrees(), self.yaw.as_degrees())
+    }
+
+    /// Returns the pitch, roll and yaw in radians.
+    pub fn radians(&self) -> (f64, f64, f64) {
+        (self.pitch.as_radians(), self.roll.as_radians(), self.yaw.as_radians())
+    }
+
+    /// Turns the yaw by `by`, keeping it between -π and π.
+    pub(crate) fn turn_yaw(self, by: Angle) -> Self {
+        Orientation3d {
+            yaw: Angle::from_radians(wrap_angle(self.yaw.as_radians() + by.as_radians())),
+            ..self
+        }
+    }
+
+    /// Helper function.
+    ///
+    /// Builds an orientation from roll, pitch and yaw in radians.
+    fn from_radians(roll: f64, pitch: f64, yaw: f64, yaw_is_relative: bool) -> Self {
+        Orientation3d {
+            pitch: Angle::from_radians(pitch),
+            roll: Angle::from_radians(roll),
+            yaw: Angle::from_radians(yaw),
+            yaw_is_relative,
+        }
+    }
+}
+
+/// An orientation as a unit quaternion, which doesn't suffer from gimbal
+/// lock the way pitch, roll and yaw do.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Quaternion {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Quaternion {
+    /// No rotation at all.
+    pub const IDENTITY: Quaternion = Quaternion {
+        w: 1.0,
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+    };
+
+    /// Returns the opposite rotation, which for a unit quaternion is its
+    /// inverse.
+    pub fn conjugate(self) -> Self {
+        Quaternion {
+            w: self.w,
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+
+    /// Returns this orientation relative to `reference`, that is
+    /// `reference⁻¹ * self`, as `Imu::tare` uses. It's worked out on the
+    /// quaternions, so it doesn't suffer the gimbal lock that subtracting
+    /// pitch, roll and yaw would.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::{Angle, Orientation3d, Quaternion};
+    ///
+    /// let at = |pitch, roll, yaw| {
+    ///     Quaternion::from(Orientation3d {
+    ///         pitch: Angle::from_degrees(pitch),
+    ///         roll: Angle::from_degrees(roll),
+    ///         yaw: Angle::from_degrees(yaw),
+    ///         yaw_is_relative: false,
+    ///     })
+    /// };
+    /// let near = |a: f64, b: f64| (a - b).abs() < 1e-9;
+    ///
+    /// // Mounted at an awkward angle, which is taken as the reference.
+    /// let mounted = at(20.0, -35.0, 120.0);
+    /// let tared = mounted.relative_to(mounted);
+    /// assert!(near(tared.w, 1.0) && near(tared.x, 0.0) && near(tared.y, 0.0));
+    /// assert!(near(tared.z, 0.0));
+    ///
+    /// // Then tilted nose up 30 degrees from there.
+    /// let tilted = mounted * at(30.0, 0.0, 0.0);
+    /// let (pitch, roll, yaw) = tilted.relative_to(mounted).orientation().degrees();
+    /// assert!(near(pitch, 30.0) && near(roll, 0.0) && near(yaw, 0.0));
+    ///
+    /// // Near straight up, where pitch, roll and yaw break down.
+    /// let up = at(89.0, 10.0, 0.0);
+    /// let turned = up * at(0.0, 0.0, 45.0);
+    /// let (pitch, roll, yaw) = turned.relative_to(up).orientation().degrees();
+    /// assert!(near(pitch, 0.0) && near(roll, 0.0) && near(yaw, 45.0));
+    /// ```
+    pub fn relative_to(self, reference: Quaternion) -> Self {
+        let q = reference.conjugate() * self;
+        match normalised([q.w, q.x, q.y, q.z]) {
+            Some([w, x, y, z]) => Quaternion { w, x, y, z },
+            None => Quaternion::IDENTITY,
+        }
+    }
+
+    /// Returns the angle of the smallest rotation from this orientation to
+    /// `other`, from 0 to 180 degrees, whichever axis it's around.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::{Angle, Orientation3d, Quaternion};
+    ///
+    /// let at = |pitch, roll, yaw| {
+    ///     Quaternion::from(Orientation3d {
+    ///         pitch: Angle::from_degrees(pitch),
+    ///         roll: Angle::from_degrees(roll),
+    ///         yaw: Angle::from_degrees(yaw),
+    ///         yaw_is_relative: false,
+    ///     })
+    /// };
+    /// let degrees = |a: Quaternion, b| a.angle_to(b).as_degrees();
+    ///
+    /// assert!((degrees(at(0.0, 0.0, 0.0), at(0.0, 25.0, 0.0)) - 25.0).abs() < 1e-9);
+    /// assert!((degrees(at(0.0, 0.0, 170.0), at(0.0, 0.0, -170.0)) - 20.0).abs() < 1e-9);
+    /// // Pitch, roll and yaw all change by 180 degrees here, but it's the
+    /// // same orientation.
+    /// assert!(degrees(at(10.0, 0.0, 0.0), at(170.0, 180.0, 180.0)) < 1e-6);
+    /// ```
+    pub fn angle_to(self, other: Quaternion) -> Angle {
+        let q = self.conjugate() * other;
+        let sin = (q.x * q.x + q.y * q.y + q.z * q.z).sqrt();
+        Angle::from_radians(2.0 * sin.atan2(q.w.abs()))
+    }
+
+    /// Returns the pitch, roll and yaw this quaternion rotates by.
+    pub fn orientation(self) -> Orientation3d {
+        let q = self;
+        let roll = (2.0 * (q.w * q.x + q.y * q.z)).atan2(1.0 - 2.0 * (q.x * q.x + q.y * q.y));
+        let pitch = (2.0 * (q.w * q.y - q.z * q.x)).clamp(-1.0, 1.0).asin();
+        let yaw = (2.0 * (q.w * q.z + q.x * q.y)).atan2(1.0 - 2.0 * (q.y * q.y + q.z * q.z));
+        Orientation3d::from_radians(roll, pitch, yaw, false)
+    }
+}
+
+impl Mul for Quaternion {
+    type Output = Quaternion;
+
+    /// The Hamilton product, which composes the two rotations.
+    fn mul(self, rhs: Quaternion) -> Quaternion {
+        let (a, b) = (self, rhs);
+        Quaternion {
+            w: a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+            x: a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+            y: a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+            z: a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+        }
+    }
+}
+
+impl From<Orientation3d> for Quaternion {
+    fn from(orientation: Orientation3d) -> Self {
+        let (pitch, roll, yaw) = orientation.radians();
+        quaternion_from_angles(roll, pitch, yaw)
+    }
+}
+
+/// The sensor fusion algorithms `Imu` can use.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FusionAlgorithm {
+    /// A `ComplementaryFilter`.
+    Complementary,
+    /// A `MadgwickFilter` with this gain.
+    Madgwick { beta: f64 },
+}
+
+/// A complementary filter, fusing accelerometer, gyroscope and
+/// magnetometer readings into an orientation.
+///
+/// The gyro is integrated between samples, which is smooth but drifts, and
+/// the result is pulled towards the pitch and roll given by gravity and the
+/// yaw given by the tilt compensated magnetometer, which don't drift but
+/// are noisy.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::ComplementaryFilter;
+///
+/// // Lying flat and still, pointing north.
+/// let filter = ComplementaryFilter::default()
+///     .update([0.0, 0.0, 1.0], [0.0, 0.0, 0.0], Some([20.0, 0.0, -40.0]), 0.01)
+///     .update([0.0, 0.0, 1.0], [0.0, 0.0, 0.0], Some([20.0, 0.0, -40.0]), 0.01);
+/// let (pitch, roll, yaw) = filter.orientation().unwrap().degrees();
+/// assert!(pitch.abs() < 1e-9);
+/// assert!(roll.abs() < 1e-9);
+/// assert!(yaw.abs() < 1e-9);
+/// assert!(!filter.orientation().unwrap().yaw_is_relative);
+///
+/// // Without the magnetometer, tilted 30 degrees nose up and turning at
+/// // 90 degrees a second, the pitch is still from gravity, but the yaw is
+/// // only the turn since the filter started.
+/// let accel = [-0.5, 0.0, 0.866];
+/// let mut filter = ComplementaryFilter::default().update(accel, [0.0; 3], None, 0.01);
+/// for _ in 0..100 {
+///     filter = filter.update(accel, [0.0, 0.0, 90.0], None, 0.01);
+/// }
+/// let orientation = filter.orientation().unwrap();
+/// let (pitch, _, yaw) = orientation.degrees();
+/// assert!((pitch - 30.0).abs() < 0.1);
+/// assert!((yaw - 90.0).abs() < 1e-6);
+/// assert!(orientation.yaw_is_relative);
+///
+/// // Bringing the magnetometer back pulls the yaw round to north a little
+/// // at a time, rather than jumping.
+/// let next = filter.update(accel, [0.0; 3], Some([40.0, 0.0, 0.0]), 0.01);
+/// let (_, _, yaw) = next.orientation().unwrap().degrees();
+/// assert!(yaw > 85.0 && yaw < 90.0);
+/// assert!(!next.orientation().unwrap().yaw_is_relative);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ComplementaryFilter {
+    gyro_weight: f64,
+    // Roll, pitch and yaw in radians, once the first sample has been seen
+    angles: Option<[f64; 3]>,
+    // Whether the last sample had no magnetometer reading
+    relative_yaw: bool,
+}
+
+impl ComplementaryFilter {
+    /// Creates a filter which gives the integrated gyro `gyro_weight` (between
+    /// 0 and 1) of the say on each update, and the accelerometer and
+    /// magnetometer the rest.
+    pub fn new(gyro_weight: f64) -> Self {
+        ComplementaryFilter {
+            gyro_weight: gyro_weight.clamp(0.0, 1.0),
+            angles: None,
+            relative_yaw: false,
+        }
+    }
+
+    /// Returns the filter's current estimate, or `None` if it hasn't been
+    /// given a sample yet.
+    pub fn orientation(&self) -> Option<Orientation3d> {
+        self.angles.map(|[roll, pitch, yaw]| {
+            Orientation3d::from_radians(roll, pitch, yaw, self.relative_yaw)
+        })
+    }
+
+    /// Returns the filter's current estimate as a quaternion, or `None` if
+    /// it hasn't been given a sample yet.
+    pub fn quaternion(&self) -> Option<Quaternion> {
+        self.angles.map(|[roll, pitch, yaw]| quaternion_from_angles(roll, pitch, yaw))
+    }
+
+    /// Returns the filter state after one more sample, taken `dt` seconds
+    /// after the previous one.
+    ///
+    /// `accel` is in g, `gyro` in degrees per second and `mag` in any unit.
+    /// Without a magnetometer reading the yaw comes from the gyro alone,
+    /// and the orientation says it's relative.
+    /// Samples containing NaN or infinite values are ignored.
+    pub fn update(&self, accel: [f64; 3], gyro: [f64; 3], mag: Option<[f64; 3]>, dt: f64) -> Self {
+        if !is_valid_sample(accel, gyro, dt) {
+            return *self;
+        }
+        let mag = mag.filter(|m| is_finite(*m));
+        let (roll, pitch, yaw) = measured_angles(accel, mag);
+
+        let angles = match self.angles {
+            None => [roll, pitch, yaw.unwrap_or(0.0)],
+            Some([prev_roll, prev_pitch, prev_yaw]) => {
+                let dt = dt.max(0.0);
+                let predicted_roll = prev_roll + gyro[0].to_radians() * dt;
+                let predicted_pitch = prev_pitch + gyro[1].to_radians() * dt;
+                let predicted_yaw = prev_yaw + gyro[2].to_radians() * dt;
+                let roll = self.blend(predicted_roll, roll);
+                let pitch = self.blend(predicted_pitch, pitch);
+                let yaw = match yaw {
+                    Some(yaw) => self.blend(predicted_yaw, yaw),
+                    None => wrap_angle(predicted_yaw),
+                };
+                [roll, pitch, yaw]
+            }
+        };
+
+        ComplementaryFilter {
+            gyro_weight: self.gyro_weight,
+            angles: Some(angles),
+            relative_yaw: yaw.is_none(),
+        }
+    }
+
+    /// Helper function.
+    ///
+    /// Moves the predicted angle towards the measured one, the short way
+    /// round the circle.
+    fn blend(&self, predicted: f64, measured: f64) -> f64 {
+        let error = wrap_angle(measured - predicted);
+        wrap_angle(predicted + (1.0 - self.gyro_weight) * error)
+    }
+
+    /// Like `update`, but takes the readings from a sample.
+    pub fn update_sample(&self, sample: &ImuSample, dt: f64) -> Self {
+        self.update(sample.accel, sample.gyro, sample.mag, dt)
+    }
+}
+
+impl Default for ComplementaryFilter {
+    fn default() -> Self {
+        ComplementaryFilter::new(DEFAULT_GYRO_WEIGHT)
+    }
+}
+
+/// Sebastian Madgwick's gradient descent orientation filter, fusing
+/// accelerometer, gyroscope and optionally magnetometer readings into a
+/// quaternion.
+///
+/// It copes with vibration better than the `ComplementaryFilter`. Larger
+/// gains converge faster but are noisier.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::MadgwickFilter;
+///
+/// // Tilted 30 degrees nose up, and still.
+/// let accel = [-0.5, 0.0, 0.866];
+/// let mut filter = MadgwickFilter::new(0.1).update(accel, [0.0; 3], None, 0.01);
+/// for _ in 0..100 {
+///     filter = filter.update(accel, [0.0; 3], None, 0.01);
+/// }
+/// let orientation = filter.orientation().unwrap();
+/// let (pitch, roll, _) = orientation.degrees();
+/// assert!((pitch - 30.0).abs() < 0.5);
+/// assert!(roll.abs() < 0.5);
+/// // Without the magnetometer the yaw is only relative.
+/// assert!(orientation.yaw_is_relative);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MadgwickFilter {
+    beta: f64,
+    // w, x, y and z, once the first sample has been seen
+    q: Option<[f64; 4]>,
+    // Whether the last sample had no magnetometer reading
+    relative_yaw: bool,
+}
+
+impl MadgwickFilter {
+    /// Creates a filter with the gain `beta`.
+    pub fn new(beta: f64) -> Self {
+        MadgwickFilter {
+            beta: beta.max(0.0),
+            q: None,
+            relative_yaw: false,
+        }
+    }
+
+    /// Returns the filter's current estimate, or `None` if it hasn't been
+    /// given a sample yet.
+    pub fn orientation(&self) -> Option<Orientation3d> {
+        self.quaternion().map(|q| Orientation3d {
+            yaw_is_relative: self.relative_yaw,
+            ..q.orientation()
+        })
+    }
+
+    /// Returns the filter's current estimate as a quaternion, or `None` if
+    /// it hasn't been given a sample yet.
+    pub fn quaternion(&self) -> Option<Quaternion> {
+        self.q.map(|[w, x, y, z]| Quaternion { w, x, y, z })
+    }
+
+    /// Returns the filter state after one more sample, taken `dt` seconds
+    /// after the previous one.
+    ///
+    /// `accel` is in g, `gyro` in degrees per second and `mag` in any unit.
+    /// Without a magnetometer reading the yaw comes from the gyro alone,
+    /// and the orientation says it's relative.
+    /// Samples containing NaN or infinite values are ignored. The first
+    /// sample initialises the estimate from the accelerometer and
+    /// magnetometer directly, so it doesn't have to converge from level.
+    pub fn update(&self, accel: [f64; 3], gyro: [f64; 3], mag: Option<[f64; 3]>, dt: f64) -> Self {
+        if !is_valid_sample(accel, gyro, dt) {
+            return *self;
+        }
+        let mag = mag.filter(|m| is_finite(*m) && m.iter().any(|&v| v != 0.0));
+
+        let q = match self.q {
+            None => {
+                let (roll, pitch, yaw) = measured_angles(accel, mag);
+                let q = quaternion_from_angles(roll, pitch, yaw.unwrap_or(0.0));
+                [q.w, q.x, q.y, q.z]
+            }
+            Some(q) => {
+                let gyro = [gyro[0].to_radians(), gyro[1].to_radians(), gyro[2].to_radians()];
+                madgwick_step(q, accel, gyro, mag, self.beta, dt.max(0.0))
+            }
+        };
+
+        MadgwickFilter {
+            beta: self.beta,
+            q: Some(q),
+            relative_yaw: mag.is_none(),
+        }
+    }
+
+    /// Like `update`, but takes the readings from a sample.
+    pub fn update_sample(&self, sample: &ImuSample, dt: f64) -> Self {
+        self.update(sample.accel, sample.gyro, sample.mag, dt)
+    }
+}
+
+impl Default for MadgwickFilter {
+    fn default() -> Self {
+        MadgwickFilter::new(DEFAULT_MADGWICK_BETA)
+    }
+}
+
+/// Whichever fusion filter `Imu` is using.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum Fusion {
+    Complementary(ComplementaryFilter),
+    Madgwick(MadgwickFilter),
+}
+
+impl Fusion {
+    pub(crate) fn new(algorithm: FusionAlgorithm) -> Self {
+        match algorithm {
+            FusionAlgorithm::Complementary => Fusion::Complementary(ComplementaryFilter::default()),
+            FusionAlgorithm::Madgwick { beta } => Fusion::Madgwick(MadgwickFilter::new(beta)),
+        }
+    }
+
+    pub(crate) fn algorithm(&self) -> FusionAlgorithm {
+        match *self {
+            Fusion::Complementary(_) => FusionAlgorithm::Complementary,
+            Fusion::Madgwick(ref filter) => FusionAlgorithm::Madgwick { beta: filter.beta },
+        }
+    }
+
+    pub(crate) fn orientation(&self) -> Option<Orientation3d> {
+        match *self {
+            Fusion::Complementary(ref filter) => filter.orientation(),
+            Fusion::Madgwick(ref filter) => filter.orientation(),
+        }
+    }
+
+    pub(crate) fn quaternion(&self) -> Option<Quaternion> {
+        match *self {
+            Fusion::Complementary(ref filter) => filter.quaternion(),
+            Fusion::Madgwick(ref filter) => filter.quaternion(),
+        }
+    }
+
+    pub(crate) fn update_sample(&self, sample: &ImuSample, dt: f64) -> Self {
+        match *self {
+            Fusion::Complementary(ref filter) => {
+                Fusion::Complementary(filter.update_sample(sample, dt))
+            }
+            Fusion::Madgwick(ref filter) => Fusion::Madgwick(filter.update_sample(sample, dt)),
+        }
+    }
+}
+
+/// Helper function.
+///
+/// One step of Madgwick's filter, from his reference implementation. `gyro`
+/// is in radians per second.
+fn madgwick_step(
+    q: [f64; 4],
+    accel: [f64; 3],
+    gyro: [f64; 3],
+    mag: Option<[f64; 3]>,
+    beta: f64,
+    dt: f64,
+) -> [f64; 4] {
+    let [q0, q1, q2, q3] = q;
+    let [gx, gy, gz] = gyro;
+
+    // Rate of change of the quaternion from the gyro
+    let mut q_dot = [
+        0.5 * (-q1 * gx - q2 * gy - q3 * gz),
+        0.5 * (q0 * gx + q2 * gz - q3 * gy),
+        0.5 * (q0 * gy - q1 * gz + q3 * gx),
+        0.5 * (q0 * gz + q1 * gy - q2 * gx),
+    ];
+
+    if let Some([ax, ay, az]) = normalised(accel) {
+        // Objective function for gravity
+        let f_g = [
+            2.0 * (q1 * q3 - q0 * q2) - ax,
+            2.0 * (q0 * q1 + q2 * q3) - ay,
+            1.0 - 2.0 * (q1 * q1 + q2 * q2) - az,
+        ];
+        let mut step = [
+            -2.0 * q2 * f_g[0] + 2.0 * q1 * f_g[1],
+            2.0 * q3 * f_g[0] + 2.0 * q0 * f_g[1] - 4.0 * q1 * f_g[2],
+            -2.0 * q0 * f_g[0] + 2.0 * q3 * f_g[1] - 4.0 * q2 * f_g[2],
+            2.0 * q1 * f_g[0] + 2.0 * q2 * f_g[1],
+        ];
+
+        if let Some([mx, my, mz]) = mag.and_then(normalised) {
+            // The earth's magnetic field, in the earth frame
+            let hx = mx * (q0 * q0 + q1 * q1 - q2 * q2 - q3 * q3)
+                + 2.0 * my * (q1 * q2 - q0 * q3)
+                + 2.0 * mz * (q0 * q2 + q1 * q3);
+            let hy = 2.0 * mx * (q0 * q3 + q1 * q2)
+                + my * (q0 * q0 - q1 * q1 + q2 * q2 - q3 * q3)
+                + 2.0 * mz * (q2 * q3 - q0 * q1);
+            let bx = (hx * hx + hy * hy).sqrt();
+            let bz = 2.0 * mx * (q1 * q3 - q0 * q2)
+                + 2.0 * my * (q0 * q1 + q2 * q3)
+                + mz * (q0 * q0 - q1 * q1 - q2 * q2 + q3 * q3);
+
+            // Objective function for the magnetic field
+            let f_b = [
+                bx * (1.0 - 2.0 * (q2 * q2 + q3 * q3)) + 2.0 * bz * (q1 * q3 - q0 * q2) - mx,
+                2.0 * bx * (q1 * q2 - q0 * q3) + 2.0 * bz * (q0 * q1 + q2 * q3) - my,
+                2.0 * bx * (q0 * q2 + q1 * q3) + bz * (1.0 - 2.0 * (q1 * q1 + q2 * q2)) - mz,
+            ];
+            step[0] += -2.0 * bz * q2 * f_b[0]
+                + 2.0 * (-bx * q3 + bz * q1) * f_b[1]
+                + 2.0 * bx * q2 * f_b[2];
+            step[1] += 2.0 * bz * q3 * f_b[0]
+                + 2.0 * (bx * q2 + bz * q0) * f_b[1]
+                + 2.0 * (bx * q3 - 2.0 * bz * q1) * f_b[2];
+            step[2] += 2.0 * (-2.0 * bx * q2 - bz * q0) * f_b[0]
+                + 2.0 * (bx * q1 + bz * q3) * f_b[1]
+                + 2.0 * (bx * q0 - 2.0 * bz * q2) * f_b[2];
+            step[3] += 2.0 * (-2.0 * bx * q3 + bz * q1) * f_b[0]
+                + 2.0 * (-bx * q0 + bz * q2) * f_b[1]
+                + 2.0 * bx * q1 * f_b[2];
+        }
+
+        if let Some(step) = normalised(step) {
+            for (rate, correction) in q_dot.iter_mut().zip(step.iter()) {
+                *rate -= beta * correction;
+            }
+        }
+    }
+
+    let next = [
+        q0 + q_dot[0] * dt,
+        q1 + q_dot[1] * dt,
+        q2 + q_dot[2] * dt,
+        q3 + q_dot[3] * dt,
+    ];
+    normalised(next).unwrap_or(q)
+}
+
+/// Helper function.
+///
+/// Returns `v` scaled to unit length, or `None` if it's zero.
+fn normalised<T: AsMut<[f64]> + AsRef<[f64]>>(mut v: T) -> Option<T> {
+    let length = v.as_ref().iter().map(|x| x * x).sum::<f64>().sqrt();
+    if length > 0.0 && length.is_finite() {
+        for x in v.as_mut() {
+            *x /= length;
+        }
+        Some(v)
+    } else {
+        None
+    }
+}
+
+/// Helper function.
+///
+/// Returns true if every value is neither NaN nor infinite.
+fn is_finite(v: [f64; 3]) -> bool {
+    v.iter().all(|x| x.is_finite())
+}
+
+/// Helper function.
+///
+/// Returns true if a sample can be fed to a filter.
+fn is_valid_sample(accel: [f64; 3], gyro: [f64; 3], dt: f64) -> bool {
+    is_finite(accel) && is_finite(gyro) && dt.is_finite()
+}
+
+/// Helper function.
+///
+/// Returns the roll and pitch given by gravity, and the yaw given by the
+/// magnetometer if there is a reading, in radians.
+fn measured_angles(accel: [f64; 3], mag: Option<[f64; 3]>) -> (f64, f64, Option<f64>) {
+    let roll = accel[1].atan2(accel[2]);
+    let pitch = (-accel[0]).atan2((accel[1] * accel[1] + accel[2] * accel[2]).sqrt());
+    let yaw = mag.map(|m| tilt_compensated_yaw(m, roll, pitch));
+    (roll, pitch, yaw)
+}
+
+/// Turns a compass heading in degrees from magnetic north to true north,
+/// given the magnetic declination in degrees, east positive. The heading
+/// returned is from 0 up to 360, whatever the heading given.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::true_heading;
+///
+/// // East declination adds to the heading, and west takes away.
+/// assert_eq!(true_heading(90.0, 10.0), 100.0);
+/// assert_eq!(true_heading(90.0, -10.0), 80.0);
+/// // Either way, it wraps around north.
+/// assert_eq!(true_heading(359.0, 3.0), 2.0);
+/// assert_eq!(true_heading(1.0, -3.0), 358.0);
+/// assert_eq!(true_heading(-90.0, 0.0), 270.0);
+/// assert_eq!(true_heading(360.0, 0.0), 0.0);
+/// ```
+pub fn true_heading(magnetic: f64, declination: f64) -> f64 {
+    let heading = (magnetic + declination).rem_euclid(360.0);
+    // A tiny negative heading comes out as 360 after rounding.
+    if heading < 360.0 {
+        heading
+    } else {
+        0.0
+    }
+}
+
+/// Helper function.
+///
+/// Converts roll, pitch and yaw in radians into a quaternion.
+fn quaternion_from_angles(roll: f64, pitch: f64, yaw: f64) -> Quaternion {
+    let (sr, cr) = (roll / 2.0).sin_cos();
+    let (sp, cp) = (pitch / 2.0).sin_cos();
+    let (sy, cy) = (yaw / 2.0).sin_cos();
+    Quaternion {
+        w: cr * cp * cy + sr * sp * sy,
+        x: sr * cp * cy - cr * sp * sy,
+        y: cr * sp * cy + sr * cp * sy,
+        z: cr * cp * sy - sr * sp * cy,
+    }
+}
+
+/// Helper function.
+///
+/// Returns the heading given by the magnetometer once the tilt given by
+/// `roll` and `pitch` has been taken out.
+fn tilt_compensated_yaw(mag: [f64; 3], roll: f64, pitch: f64) -> f64 {
+    let (sin_roll, cos_roll) = roll.sin_cos();
+    let (sin_pitch, cos_pitch) = pitch.sin_cos();
+    let x = mag[0] * cos_pitch + mag[1] * sin_roll * sin_pitch + mag[2] * cos_roll * sin_pitch;
+    let y = mag[1] * cos_roll - mag[2] * sin_roll;
+    (-y).atan2(x)
+}
+
+/// Helper function.
+///
+/// Wraps an angle in radians into -π..π.
+fn wrap_angle(angle: f64) -> f64 {
+    let wrapped = (angle + PI).rem_euclid(2.0 * PI) - PI;
+    if wrapped.is_finite() {
+        wrapped
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,126 @@
+use {SenseHatError, SenseHatResult};
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// The extension gamma profile files have.
+const PROFILE_EXTENSION: &str = "gamma";
+
+/// The version of the gamma profile format written by `save`.
+const PROFILE_VERSION: u32 = 1;
+
+/// Helper function.
+///
+/// Returns the path of the named profile in `dir`, or an error if the name
+/// is empty or would point outside `dir`.
+pub(crate) fn profile_path(name: &str, dir: &Path) -> SenseHatResult<PathBuf> {
+    if name.is_empty() || name.contains(['/', '\\', '\0']) || name == ".." {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid gamma profile name: {:?}", name),
+        )
+        .into());
+    }
+    Ok(dir.join(format!("{}.{}", name, PROFILE_EXTENSION)))
+}
+
+/// Helper function.
+///
+/// Writes a gamma table to `path` in the format `load` reads.
+pub(crate) fn save(path: &Path, table: &[u8; 32]) -> SenseHatResult<()> {
+    let values: Vec<String> = table.iter().map(|value| value.to_string()).collect();
+    let mut file = File::create(path)?;
+    writeln!(file, "# Sense Hat gamma profile")?;
+    writeln!(file, "version={}", PROFILE_VERSION)?;
+    writeln!(file, "gamma={}", values.join(","))?;
+    Ok(())
+}
+
+/// Helper function.
+///
+/// Reads a gamma table written by `save`, checking it has 32 entries which
+/// are all between 0 and 31.
+pub(crate) fn load(path: &Path) -> SenseHatResult<[u8; 32]> {
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+
+    let mut version = None;
+    let mut table = None;
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.splitn(2, '=');
+        let key = parts.next().unwrap_or("").trim();
+        let value = parts.next().ok_or_else(|| corrupt(path, "expected key=value"))?.trim();
+        match key {
+            "version" => {
+                let parsed = value
+                    .parse::<u32>()
+                    .map_err(|_| corrupt(path, "version isn't a number"))?;
+                if parsed != PROFILE_VERSION {
+                    return Err(corrupt(path, &format!("unsupported version {}", parsed)));
+                }
+                version = Some(parsed);
+            }
+            "gamma" => table = Some(parse_table(path, value)?),
+            _ => return Err(corrupt(path, &format!("unknown key {:?}", key))),
+        }
+    }
+    if version.is_none() {
+        return Err(corrupt(path, "missing version"));
+    }
+    table.ok_or_else(|| corrupt(path, "missing gamma table"))
+}
+
+/// Helper function.
+///
+/// Returns the names of the gamma profiles in `dir`, sorted.
+pub(crate) fn list(dir: &Path) -> SenseHatResult<Vec<String>> {
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == PROFILE_EXTENSION) && path.is_file() {
+            if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                names.push(name.to_owned());
+            }
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Helper function.
+///
+/// Parses the comma separated entries of a gamma table.
+fn parse_table(path: &Path, value: &str) -> SenseHatResult<[u8; 32]> {
+    let mut table = [0u8; 32];
+    let mut count = 0;
+    for entry in value.split(',').map(str::trim) {
+        let parsed = entry
+            .parse::<u8>()
+            .ok()
+            .filter(|&parsed| parsed <= 31)
+            .ok_or_else(|| corrupt(path, &format!("gamma entry {:?} isn't between 0 and 31", entry)))?;
+        if count < table.len() {
+            table[count] = parsed;
+        }
+        count += 1;
+    }
+    if count != table.len() {
+        return Err(corrupt(path, &format!("expected 32 gamma entries, found {}", count)));
+    }
+    Ok(table)
+}
+
+/// Helper function.
+///
+/// Makes the error for a gamma profile we can't parse.
+fn corrupt(path: &Path, reason: &str) -> SenseHatError {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("bad gamma profile {}: {}", path.display(), reason),
+    )
+    .into()
+}
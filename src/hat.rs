@@ -0,0 +1,157 @@
+use {SenseHatError, SenseHatResult};
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Where the kernel exposes the HAT EEPROM contents.
+const DEVICE_TREE_HAT: &str = "/proc/device-tree/hat";
+
+/// The product string the Sense HAT EEPROM reports.
+const SENSE_HAT_PRODUCT: &str = "Sense HAT";
+
+/// The product id the Sense HAT EEPROM reports.
+const SENSE_HAT_PRODUCT_ID: u16 = 0x0001;
+
+/// The board revision of a Sense HAT.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum HatRevision {
+    /// The original Sense HAT.
+    V1,
+    /// The Sense HAT v2, with the TCS3400 colour sensor.
+    V2,
+}
+
+/// Information read from the HAT EEPROM.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HatInfo {
+    /// The product name, e.g. "Sense HAT".
+    pub product: String,
+    /// The vendor name, e.g. "Raspberry Pi".
+    pub vendor: String,
+    /// The product id.
+    pub product_id: u16,
+    /// The product version.
+    pub product_ver: u16,
+}
+
+impl HatInfo {
+    /// Returns true if the attached HAT is a Sense HAT.
+    pub fn is_sense_hat(&self) -> bool {
+        self.product == SENSE_HAT_PRODUCT && self.product_id == SENSE_HAT_PRODUCT_ID
+    }
+
+    /// Returns the Sense HAT board revision, or `None` if the attached HAT
+    /// isn't a Sense HAT or reports a version we don't know about.
+    pub fn revision(&self) -> Option<HatRevision> {
+        if !self.is_sense_hat() {
+            return None;
+        }
+        match self.product_ver {
+            0x0001 => Some(HatRevision::V1),
+            0x0002 => Some(HatRevision::V2),
+            _ => None,
+        }
+    }
+}
+
+/// Reads the HAT EEPROM to find out what is attached to the Raspberry Pi.
+///
+/// Returns `NoHatDetected` if the kernel didn't find a HAT EEPROM.
+///
+/// # Example
+///
+/// ```no_run
+/// let info = sensehat::identify().unwrap();
+/// if info.is_sense_hat() {
+///     println!("Found a Sense HAT ({:?})", info.revision());
+/// }
+/// ```
+pub fn identify() -> SenseHatResult<HatInfo> {
+    identify_at(Path::new(DEVICE_TREE_HAT))
+}
+
+/// Like `identify`, but reads the HAT EEPROM nodes from the given directory
+/// rather than `/proc/device-tree/hat`.
+///
+/// # Example
+///
+/// ```
+/// extern crate sensehat;
+/// extern crate tempfile;
+///
+/// use sensehat::{identify_at, HatRevision, SenseHatError};
+/// use std::fs;
+///
+/// # fn main() {
+/// let dir = tempfile::tempdir().unwrap();
+/// // The kernel gives NUL terminated strings.
+/// fs::write(dir.path().join("product"), "Sense HAT\0").unwrap();
+/// fs::write(dir.path().join("vendor"), "Raspberry Pi\0").unwrap();
+/// fs::write(dir.path().join("product_id"), "0x0001\0").unwrap();
+/// fs::write(dir.path().join("product_ver"), "0x0002\0").unwrap();
+///
+/// let info = identify_at(dir.path()).unwrap();
+/// assert_eq!(info.product, "Sense HAT");
+/// assert_eq!(info.vendor, "Raspberry Pi");
+/// assert_eq!((info.product_id, info.product_ver), (1, 2));
+/// assert!(info.is_sense_hat());
+/// assert_eq!(info.revision(), Some(HatRevision::V2));
+///
+/// // Some other HAT.
+/// fs::write(dir.path().join("product"), "Unicorn HAT\0").unwrap();
+/// let info = identify_at(dir.path()).unwrap();
+/// assert!(!info.is_sense_hat());
+/// assert_eq!(info.revision(), None);
+///
+/// // A version we don't know about.
+/// fs::write(dir.path().join("product"), "Sense HAT\0").unwrap();
+/// fs::write(dir.path().join("product_ver"), "0X0003\n").unwrap();
+/// assert_eq!(identify_at(dir.path()).unwrap().revision(), None);
+///
+/// // A number which isn't hex.
+/// fs::write(dir.path().join("product_id"), "one").unwrap();
+/// match identify_at(dir.path()) {
+///     Err(SenseHatError::NoHatDetected) => {}
+///     other => panic!("{:?}", other),
+/// }
+///
+/// // No HAT EEPROM at all.
+/// let empty = tempfile::tempdir().unwrap();
+/// match identify_at(empty.path()) {
+///     Err(SenseHatError::NoHatDetected) => {}
+///     other => panic!("{:?}", other),
+/// }
+/// # }
+/// ```
+pub fn identify_at(base: &Path) -> SenseHatResult<HatInfo> {
+    Ok(HatInfo {
+        product: read_node(base, "product")?,
+        vendor: read_node(base, "vendor")?,
+        product_id: read_hex_node(base, "product_id")?,
+        product_ver: read_hex_node(base, "product_ver")?,
+    })
+}
+
+/// Helper function.
+///
+/// Reads a device tree string node, which is NUL terminated.
+fn read_node(base: &Path, name: &str) -> SenseHatResult<String> {
+    let mut contents = String::new();
+    File::open(base.join(name))
+        .and_then(|mut file| file.read_to_string(&mut contents))
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => SenseHatError::NoHatDetected,
+            _ => SenseHatError::from(err),
+        })?;
+    Ok(contents.trim_matches(|c: char| c == '\0' || c.is_whitespace()).to_owned())
+}
+
+/// Helper function.
+///
+/// Reads a device tree string node holding a hex number, like "0x0001".
+fn read_hex_node(base: &Path, name: &str) -> SenseHatResult<u16> {
+    let value = read_node(base, name)?;
+    let digits = value.trim_start_matches("0x").trim_start_matches("0X");
+    u16::from_str_radix(digits, 16).map_err(|_| SenseHatError::NoHatDetected)
+}
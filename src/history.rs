@@ -0,0 +1,95 @@
+use std::collections::VecDeque;
+
+/// The frames the display had before each change, as used by
+/// `Display::set_history_depth` and `Display::undo`.
+///
+/// Only the last `depth` frames are kept; pushing another forgets the
+/// oldest. Each frame is the display's 128 byte rgb565 image, so even a
+/// deep history is small. With a depth of 0 nothing is kept, and nothing
+/// is allocated.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::FrameHistory;
+///
+/// let mut history = FrameHistory::new(2);
+/// history.push([1; 128]);
+/// history.push([2; 128]);
+/// history.push([3; 128]);
+/// assert_eq!(history.len(), 2);
+///
+/// assert_eq!(history.pop(), Some([3; 128]));
+/// assert_eq!(history.pop(), Some([2; 128]));
+/// assert_eq!(history.pop(), None);
+///
+/// // Making it shallower forgets the oldest frames.
+/// history.set_depth(8);
+/// for i in 0..5 {
+///     history.push([i; 128]);
+/// }
+/// history.set_depth(3);
+/// assert_eq!(history.pop(), Some([4; 128]));
+/// assert_eq!(history.pop(), Some([3; 128]));
+/// assert_eq!(history.pop(), Some([2; 128]));
+/// assert!(history.is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrameHistory {
+    depth: usize,
+    /// The oldest frame first.
+    frames: VecDeque<[u8; 128]>,
+}
+
+impl FrameHistory {
+    pub fn new(depth: usize) -> Self {
+        FrameHistory {
+            depth,
+            frames: VecDeque::new(),
+        }
+    }
+
+    /// Returns how many frames are kept.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Changes how many frames are kept, forgetting the oldest if there
+    /// are now too many. A depth of 0 forgets them all.
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+        while self.frames.len() > depth {
+            self.frames.pop_front();
+        }
+        if depth == 0 {
+            self.frames = VecDeque::new();
+        }
+    }
+
+    /// Remembers `frame`, forgetting the oldest if the history is full.
+    /// Does nothing if the depth is 0.
+    pub fn push(&mut self, frame: [u8; 128]) {
+        if self.depth == 0 {
+            return;
+        }
+        if self.frames.len() == self.depth {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    /// Takes the most recently pushed frame, if there is one.
+    pub fn pop(&mut self) -> Option<[u8; 128]> {
+        self.frames.pop_back()
+    }
+
+    /// Returns how many frames are remembered.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns true if no frames are remembered.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
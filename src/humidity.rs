@@ -0,0 +1,108 @@
+use byteorder::{ByteOrder, LittleEndian};
+
+/// The HTS221 humidity sensor's factory calibration, as read by
+/// `SenseHat::reload_humidity_calibration`: two known points each for
+/// temperature and humidity, and the straight lines through them which
+/// turn the sensor's raw readings into °C and %rH.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::HumidityCalibration;
+///
+/// // Registers 0x30 to 0x3f.
+/// let dump = [
+///     66, 150, 0xa7, 0x22, 0x00, 0x04, 0x00, 0x00,
+///     0x00, 0x00, 0x30, 0xdf, 0x18, 0xfc, 0x1e, 0x14,
+/// ];
+/// let cal = HumidityCalibration::from_registers(&dump);
+/// assert_eq!((cal.h0, cal.h1), (33.0, 75.0));
+/// assert_eq!((cal.h0_t0_out, cal.h1_t0_out), (0, -8400));
+/// assert_eq!((cal.t0, cal.t1), (20.875, 36.25));
+/// assert_eq!((cal.t0_out, cal.t1_out), (-1000, 5150));
+///
+/// let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+/// assert!(close(cal.temp_m, 0.0025));
+/// assert!(close(cal.temp_c, 23.375));
+/// assert!(close(cal.hum_m, -0.005));
+/// assert!(close(cal.hum_c, 33.0));
+///
+/// // Raw readings are turned into °C and %rH along those lines.
+/// assert!(close(cal.celsius(-1000), 20.875));
+/// assert!(close(cal.celsius(5150), 36.25));
+/// assert!(close(cal.celsius(400), 400.0 * cal.temp_m + cal.temp_c));
+/// assert!(close(cal.percent(-8400), 75.0));
+/// assert!(close(cal.percent(-2000), -2000.0 * cal.hum_m + cal.hum_c));
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct HumidityCalibration {
+    /// The temperature of the first point, in °C.
+    pub t0: f64,
+    /// The temperature of the second point, in °C.
+    pub t1: f64,
+    /// The raw temperature reading at `t0`.
+    pub t0_out: i16,
+    /// The raw temperature reading at `t1`.
+    pub t1_out: i16,
+    /// The humidity of the first point, in %rH.
+    pub h0: f64,
+    /// The humidity of the second point, in %rH.
+    pub h1: f64,
+    /// The raw humidity reading at `h0`.
+    pub h0_t0_out: i16,
+    /// The raw humidity reading at `h1`.
+    pub h1_t0_out: i16,
+    /// How many °C each step of the raw temperature reading is.
+    pub temp_m: f64,
+    /// The temperature, in °C, at a raw reading of 0.
+    pub temp_c: f64,
+    /// How many %rH each step of the raw humidity reading is.
+    pub hum_m: f64,
+    /// The humidity, in %rH, at a raw reading of 0.
+    pub hum_c: f64,
+}
+
+impl HumidityCalibration {
+    /// Works out the calibration from the sensor's calibration registers,
+    /// 0x30 to 0x3f.
+    pub fn from_registers(registers: &[u8; 16]) -> Self {
+        let h0 = registers[0x00] as f64 / 2.0;
+        let h1 = registers[0x01] as f64 / 2.0;
+        let msb = registers[0x05];
+        let t0 = LittleEndian::read_i16(&[registers[0x02], msb & 0x03]) as f64 / 8.0;
+        let t1 = LittleEndian::read_i16(&[registers[0x03], (msb & 0x0c) >> 2]) as f64 / 8.0;
+        let h0_t0_out = LittleEndian::read_i16(&registers[0x06..]);
+        let h1_t0_out = LittleEndian::read_i16(&registers[0x0a..]);
+        let t0_out = LittleEndian::read_i16(&registers[0x0c..]);
+        let t1_out = LittleEndian::read_i16(&registers[0x0e..]);
+
+        let temp_m = (t1 - t0) / (t1_out as f64 - t0_out as f64);
+        let temp_c = t0 - (temp_m * t0_out as f64);
+        let hum_m = (h1 - h0) / (h1_t0_out as f64 - h0_t0_out as f64);
+        let hum_c = h0 - (hum_m * h0_t0_out as f64);
+        HumidityCalibration {
+            t0,
+            t1,
+            t0_out,
+            t1_out,
+            h0,
+            h1,
+            h0_t0_out,
+            h1_t0_out,
+            temp_m,
+            temp_c,
+            hum_m,
+            hum_c,
+        }
+    }
+
+    /// Turns a raw temperature reading into °C.
+    pub fn celsius(&self, raw: i16) -> f64 {
+        (raw as f64 * self.temp_m) + self.temp_c
+    }
+
+    /// Turns a raw humidity reading into %rH.
+    pub fn percent(&self, raw: i16) -> f64 {
+        (raw as f64 * self.hum_m) + self.hum_c
+    }
+}
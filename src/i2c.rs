@@ -0,0 +1,445 @@
+use {SenseHatError, SenseHatResult};
+
+use i2cdev::core::I2CDevice;
+use i2cdev::linux::LinuxI2CDevice;
+
+#[cfg(feature = "log")]
+use std::fmt;
+
+/// The I2C bus the Sense Hat is on.
+const I2C_BUS: &str = "/dev/i2c-1";
+
+/// Logs a transfer to or from an I2C device, at trace level, with the
+/// `log` feature, or does nothing without it, not even working out its
+/// arguments.
+#[cfg(feature = "log")]
+macro_rules! trace_transfer {
+    ($address:expr, $($arg:tt)+) => {
+        trace!(target: "sensehat::i2c", "{:#04x} {}", $address, format_args!($($arg)+))
+    };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! trace_transfer {
+    ($($arg:tt)+) => {};
+}
+
+/// An I2C device which, with the `log` feature, logs every transfer at
+/// trace level, with the device's address, the register, the bytes and
+/// which way they went, or the error. Every sensor on the Sense Hat is
+/// talked to through one of these, so turning on trace logging for the
+/// `sensehat::i2c` target shows everything the crate does on the bus.
+///
+/// # Example
+///
+/// ```
+/// extern crate i2cdev;
+/// extern crate sensehat;
+///
+/// use i2cdev::core::I2CDevice;
+/// use sensehat::TracedI2CDevice;
+/// # use std::io;
+///
+/// // A device with 256 registers, standing in for a real sensor.
+/// struct Registers {
+///     values: [u8; 256],
+///     selected: usize,
+/// }
+///
+/// impl I2CDevice for Registers {
+///     type Error = io::Error;
+///
+///     fn read(&mut self, data: &mut [u8]) -> io::Result<()> {
+///         for byte in data {
+///             *byte = self.values[self.selected];
+///             self.selected = (self.selected + 1) % 256;
+///         }
+///         Ok(())
+///     }
+///
+///     fn write(&mut self, data: &[u8]) -> io::Result<()> {
+///         self.selected = data[0] as usize;
+///         for &byte in &data[1..] {
+///             self.values[self.selected] = byte;
+///             self.selected = (self.selected + 1) % 256;
+///         }
+///         Ok(())
+///     }
+/// #   fn smbus_write_quick(&mut self, _: bool) -> io::Result<()> { unimplemented!() }
+/// #   fn smbus_read_block_data(&mut self, _: u8) -> io::Result<Vec<u8>> { unimplemented!() }
+/// #   fn smbus_read_i2c_block_data(&mut self, _: u8, _: u8) -> io::Result<Vec<u8>> {
+/// #       unimplemented!()
+/// #   }
+/// #   fn smbus_write_block_data(&mut self, _: u8, _: &[u8]) -> io::Result<()> { unimplemented!() }
+/// #   fn smbus_process_block(&mut self, _: u8, _: &[u8]) -> io::Result<()> { unimplemented!() }
+/// }
+///
+/// # #[cfg(feature = "log")]
+/// # mod capture {
+/// #     extern crate log;
+/// #     use std::sync::Mutex;
+/// #     pub static LINES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+/// #     pub struct Capture;
+/// #     impl log::Log for Capture {
+/// #         fn enabled(&self, _: &log::Metadata) -> bool { true }
+/// #         fn log(&self, record: &log::Record) {
+/// #             LINES.lock().unwrap().push(format!("{} {}", record.target(), record.args()));
+/// #         }
+/// #         fn flush(&self) {}
+/// #     }
+/// #     pub fn install() {
+/// #         log::set_logger(&Capture).unwrap();
+/// #         log::set_max_level(log::LevelFilter::Trace);
+/// #     }
+/// # }
+/// # fn main() {
+/// # #[cfg(feature = "log")]
+/// # capture::install();
+/// let registers = Registers { values: [0; 256], selected: 0 };
+/// let mut device = TracedI2CDevice::new(registers, 0x5f);
+/// device.smbus_write_byte_data(0x20, 0x87).unwrap();
+/// assert_eq!(device.smbus_read_byte_data(0x20).unwrap(), 0x87);
+/// assert_eq!(device.address(), 0x5f);
+/// # #[cfg(feature = "log")]
+/// assert_eq!(
+///     *capture::LINES.lock().unwrap(),
+///     [
+///         "sensehat::i2c 0x5f write 0x20 [87]: ok",
+///         "sensehat::i2c 0x5f read 0x20: 87",
+///     ]
+/// );
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TracedI2CDevice<D> {
+    dev: D,
+    address: u16,
+}
+
+impl<D> TracedI2CDevice<D> {
+    /// Wraps the device at `address`, which is only used in the log.
+    pub fn new(dev: D, address: u16) -> Self {
+        TracedI2CDevice { dev, address }
+    }
+
+    /// Returns the address of the device.
+    pub fn address(&self) -> u16 {
+        self.address
+    }
+
+    /// Returns the device, without the logging.
+    pub fn into_inner(self) -> D {
+        self.dev
+    }
+}
+
+impl<D: I2CDevice> I2CDevice for TracedI2CDevice<D> {
+    type Error = D::Error;
+
+    fn read(&mut self, data: &mut [u8]) -> Result<(), D::Error> {
+        let result = self.dev.read(data);
+        trace_transfer!(self.address, "read: {}", Read(&result.as_ref().map(|_| &*data)));
+        result
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), D::Error> {
+        let result = self.dev.write(data);
+        trace_transfer!(self.address, "write {:02x?}: {}", data, Written(&result));
+        result
+    }
+
+    fn smbus_write_quick(&mut self, bit: bool) -> Result<(), D::Error> {
+        let result = self.dev.smbus_write_quick(bit);
+        trace_transfer!(self.address, "write quick {}: {}", bit, Written(&result));
+        result
+    }
+
+    fn smbus_read_byte(&mut self) -> Result<u8, D::Error> {
+        let result = self.dev.smbus_read_byte();
+        trace_transfer!(self.address, "read byte: {}", Read(&result));
+        result
+    }
+
+    fn smbus_write_byte(&mut self, value: u8) -> Result<(), D::Error> {
+        let result = self.dev.smbus_write_byte(value);
+        trace_transfer!(self.address, "write byte [{:02x}]: {}", value, Written(&result));
+        result
+    }
+
+    fn smbus_read_byte_data(&mut self, register: u8) -> Result<u8, D::Error> {
+        let result = self.dev.smbus_read_byte_data(register);
+        trace_transfer!(self.address, "read {:#04x}: {}", register, Read(&result));
+        result
+    }
+
+    fn smbus_write_byte_data(&mut self, register: u8, value: u8) -> Result<(), D::Error> {
+        let result = self.dev.smbus_write_byte_data(register, value);
+        trace_transfer!(
+            self.address,
+            "write {:#04x} [{:02x}]: {}",
+            register,
+            value,
+            Written(&result)
+        );
+        result
+    }
+
+    fn smbus_read_word_data(&mut self, register: u8) -> Result<u16, D::Error> {
+        let result = self.dev.smbus_read_word_data(register);
+        trace_transfer!(self.address, "read word {:#04x}: {}", register, Read(&result));
+        result
+    }
+
+    fn smbus_write_word_data(&mut self, register: u8, value: u16) -> Result<(), D::Error> {
+        let result = self.dev.smbus_write_word_data(register, value);
+        trace_transfer!(
+            self.address,
+            "write word {:#04x} [{:04x}]: {}",
+            register,
+            value,
+            Written(&result)
+        );
+        result
+    }
+
+    fn smbus_process_word(&mut self, register: u8, value: u16) -> Result<u16, D::Error> {
+        let result = self.dev.smbus_process_word(register, value);
+        trace_transfer!(
+            self.address,
+            "process word {:#04x} [{:04x}]: {}",
+            register,
+            value,
+            Read(&result)
+        );
+        result
+    }
+
+    fn smbus_read_block_data(&mut self, register: u8) -> Result<Vec<u8>, D::Error> {
+        let result = self.dev.smbus_read_block_data(register);
+        trace_transfer!(self.address, "read block {:#04x}: {}", register, Read(&result));
+        result
+    }
+
+    fn smbus_read_i2c_block_data(&mut self, register: u8, len: u8) -> Result<Vec<u8>, D::Error> {
+        let result = self.dev.smbus_read_i2c_block_data(register, len);
+        trace_transfer!(
+            self.address,
+            "read {:#04x} ({} bytes): {}",
+            register,
+            len,
+            Read(&result)
+        );
+        result
+    }
+
+    fn smbus_write_block_data(&mut self, register: u8, values: &[u8]) -> Result<(), D::Error> {
+        let result = self.dev.smbus_write_block_data(register, values);
+        trace_transfer!(
+            self.address,
+            "write block {:#04x} {:02x?}: {}",
+            register,
+            values,
+            Written(&result)
+        );
+        result
+    }
+
+    fn smbus_process_block(&mut self, register: u8, values: &[u8]) -> Result<(), D::Error> {
+        let result = self.dev.smbus_process_block(register, values);
+        trace_transfer!(
+            self.address,
+            "process block {:#04x} {:02x?}: {}",
+            register,
+            values,
+            Written(&result)
+        );
+        result
+    }
+}
+
+/// Any I2C device, real or not, with its errors turned into
+/// `SenseHatError`s, so the sensors can be talked to through whatever
+/// device they're given. `SenseHatBuilder` and `Imu::from_devices` take
+/// devices as these.
+pub struct BoxedI2CDevice {
+    dev: Box<dyn I2CDevice<Error = SenseHatError> + Send>,
+}
+
+impl BoxedI2CDevice {
+    /// Wraps `dev`, which can be any I2C device whose errors convert into
+    /// `SenseHatError`, such as a `LinuxI2CDevice` or a `MockI2CDevice`.
+    pub fn new<D>(dev: D) -> Self
+    where
+        D: I2CDevice + Send + 'static,
+        SenseHatError: From<D::Error>,
+    {
+        BoxedI2CDevice {
+            dev: Box::new(ConvertErrors(dev)),
+        }
+    }
+}
+
+impl I2CDevice for BoxedI2CDevice {
+    type Error = SenseHatError;
+
+    fn read(&mut self, data: &mut [u8]) -> SenseHatResult<()> {
+        self.dev.read(data)
+    }
+
+    fn write(&mut self, data: &[u8]) -> SenseHatResult<()> {
+        self.dev.write(data)
+    }
+
+    fn smbus_write_quick(&mut self, bit: bool) -> SenseHatResult<()> {
+        self.dev.smbus_write_quick(bit)
+    }
+
+    fn smbus_read_byte(&mut self) -> SenseHatResult<u8> {
+        self.dev.smbus_read_byte()
+    }
+
+    fn smbus_write_byte(&mut self, value: u8) -> SenseHatResult<()> {
+        self.dev.smbus_write_byte(value)
+    }
+
+    fn smbus_read_byte_data(&mut self, register: u8) -> SenseHatResult<u8> {
+        self.dev.smbus_read_byte_data(register)
+    }
+
+    fn smbus_write_byte_data(&mut self, register: u8, value: u8) -> SenseHatResult<()> {
+        self.dev.smbus_write_byte_data(register, value)
+    }
+
+    fn smbus_read_word_data(&mut self, register: u8) -> SenseHatResult<u16> {
+        self.dev.smbus_read_word_data(register)
+    }
+
+    fn smbus_write_word_data(&mut self, register: u8, value: u16) -> SenseHatResult<()> {
+        self.dev.smbus_write_word_data(register, value)
+    }
+
+    fn smbus_process_word(&mut self, register: u8, value: u16) -> SenseHatResult<u16> {
+        self.dev.smbus_process_word(register, value)
+    }
+
+    fn smbus_read_block_data(&mut self, register: u8) -> SenseHatResult<Vec<u8>> {
+        self.dev.smbus_read_block_data(register)
+    }
+
+    fn smbus_read_i2c_block_data(&mut self, register: u8, len: u8) -> SenseHatResult<Vec<u8>> {
+        self.dev.smbus_read_i2c_block_data(register, len)
+    }
+
+    fn smbus_write_block_data(&mut self, register: u8, values: &[u8]) -> SenseHatResult<()> {
+        self.dev.smbus_write_block_data(register, values)
+    }
+
+    fn smbus_process_block(&mut self, register: u8, values: &[u8]) -> SenseHatResult<()> {
+        self.dev.smbus_process_block(register, values)
+    }
+}
+
+/// An I2C device whose errors are turned into `SenseHatError`s, for
+/// `BoxedI2CDevice`.
+struct ConvertErrors<D>(D);
+
+impl<D> I2CDevice for ConvertErrors<D>
+where
+    D: I2CDevice,
+    SenseHatError: From<D::Error>,
+{
+    type Error = SenseHatError;
+
+    fn read(&mut self, data: &mut [u8]) -> SenseHatResult<()> {
+        Ok(self.0.read(data)?)
+    }
+
+    fn write(&mut self, data: &[u8]) -> SenseHatResult<()> {
+        Ok(self.0.write(data)?)
+    }
+
+    fn smbus_write_quick(&mut self, bit: bool) -> SenseHatResult<()> {
+        Ok(self.0.smbus_write_quick(bit)?)
+    }
+
+    fn smbus_read_byte(&mut self) -> SenseHatResult<u8> {
+        Ok(self.0.smbus_read_byte()?)
+    }
+
+    fn smbus_write_byte(&mut self, value: u8) -> SenseHatResult<()> {
+        Ok(self.0.smbus_write_byte(value)?)
+    }
+
+    fn smbus_read_byte_data(&mut self, register: u8) -> SenseHatResult<u8> {
+        Ok(self.0.smbus_read_byte_data(register)?)
+    }
+
+    fn smbus_write_byte_data(&mut self, register: u8, value: u8) -> SenseHatResult<()> {
+        Ok(self.0.smbus_write_byte_data(register, value)?)
+    }
+
+    fn smbus_read_word_data(&mut self, register: u8) -> SenseHatResult<u16> {
+        Ok(self.0.smbus_read_word_data(register)?)
+    }
+
+    fn smbus_write_word_data(&mut self, register: u8, value: u16) -> SenseHatResult<()> {
+        Ok(self.0.smbus_write_word_data(register, value)?)
+    }
+
+    fn smbus_process_word(&mut self, register: u8, value: u16) -> SenseHatResult<u16> {
+        Ok(self.0.smbus_process_word(register, value)?)
+    }
+
+    fn smbus_read_block_data(&mut self, register: u8) -> SenseHatResult<Vec<u8>> {
+        Ok(self.0.smbus_read_block_data(register)?)
+    }
+
+    fn smbus_read_i2c_block_data(&mut self, register: u8, len: u8) -> SenseHatResult<Vec<u8>> {
+        Ok(self.0.smbus_read_i2c_block_data(register, len)?)
+    }
+
+    fn smbus_write_block_data(&mut self, register: u8, values: &[u8]) -> SenseHatResult<()> {
+        Ok(self.0.smbus_write_block_data(register, values)?)
+    }
+
+    fn smbus_process_block(&mut self, register: u8, values: &[u8]) -> SenseHatResult<()> {
+        Ok(self.0.smbus_process_block(register, values)?)
+    }
+}
+
+/// How a read went, for the log: the bytes read, or the error.
+#[cfg(feature = "log")]
+struct Read<'a, T: 'a, E: 'a>(&'a Result<T, E>);
+
+#[cfg(feature = "log")]
+impl<'a, T: fmt::Debug, E: fmt::Display> fmt::Display for Read<'a, T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self.0 {
+            Ok(ref value) => write!(f, "{:02x?}", value),
+            Err(ref err) => write!(f, "error: {}", err),
+        }
+    }
+}
+
+/// How a write went, for the log: "ok", or the error.
+#[cfg(feature = "log")]
+struct Written<'a, E: 'a>(&'a Result<(), E>);
+
+#[cfg(feature = "log")]
+impl<'a, E: fmt::Display> fmt::Display for Written<'a, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self.0 {
+            Ok(()) => write!(f, "ok"),
+            Err(ref err) => write!(f, "error: {}", err),
+        }
+    }
+}
+
+/// Helper function.
+///
+/// Opens the device at `address` on the Sense Hat's I2C bus.
+pub(crate) fn open(address: u16) -> SenseHatResult<TracedI2CDevice<BoxedI2CDevice>> {
+    let dev = LinuxI2CDevice::new(I2C_BUS, address)?;
+    debug!("opened I2C device {:#04x} on {}", address, I2C_BUS);
+    Ok(TracedI2CDevice::new(BoxedI2CDevice::new(dev), address))
+}
@@ -0,0 +1,215 @@
+use display::{Display, Pixel};
+use frame::Frame;
+use scale::{downscale, pixels_from_rgb, pixels_from_rgba, pixels_to_rgb, ScaleFilter};
+use {SenseHatError, SenseHatResult};
+
+use image::{RgbImage, RgbaImage};
+
+use std::convert::TryFrom;
+use std::io;
+
+/// An 8x8 image is a frame. Returns an error for any other size, which
+/// `Frame::from_image_scaled` takes instead.
+///
+/// # Example
+///
+/// ```
+/// extern crate image;
+/// extern crate sensehat;
+///
+/// use image::{Rgb, RgbImage};
+/// use sensehat::Frame;
+/// use std::convert::TryFrom;
+///
+/// # fn main() {
+/// let image = RgbImage::from_fn(8, 8, |x, _| Rgb([32 * x as u8, 0, 255]));
+/// let frame = Frame::try_from(&image).unwrap();
+/// assert_eq!(frame.pixels()[8 * 5 + 3], (96, 0, 255));
+/// assert_eq!(RgbImage::from(&frame), image);
+///
+/// assert!(Frame::try_from(&RgbImage::new(8, 16)).is_err());
+/// # }
+/// ```
+impl<'a> TryFrom<&'a RgbImage> for Frame {
+    type Error = SenseHatError;
+
+    fn try_from(image: &'a RgbImage) -> SenseHatResult<Self> {
+        check_size(image.width(), image.height())?;
+        Ok(frame_of(&pixels_from_rgb(image)?))
+    }
+}
+
+/// The frame as an 8x8 image.
+impl<'a> From<&'a Frame> for RgbImage {
+    fn from(frame: &'a Frame) -> Self {
+        RgbImage::from_raw(8, 8, pixels_to_rgb(frame.pixels()))
+            .expect("a frame has 64 pixels")
+    }
+}
+
+impl Frame {
+    /// Makes a frame of an image of any size, scaled to fit with
+    /// `downscale`, with `background` round it if it isn't square.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate image;
+    /// extern crate sensehat;
+    ///
+    /// use image::{Rgb, RgbImage};
+    /// use sensehat::{Frame, ScaleFilter};
+    ///
+    /// # fn main() {
+    /// let wide = RgbImage::from_pixel(32, 16, Rgb([0, 255, 0]));
+    /// let frame = Frame::from_image_scaled(&wide, ScaleFilter::Box, (0, 0, 0)).unwrap();
+    /// assert_eq!(frame.pixels()[..16], [(0, 0, 0); 16]);
+    /// assert_eq!(frame.pixels()[16..48], [(0, 255, 0); 32]);
+    /// # }
+    /// ```
+    pub fn from_image_scaled(
+        image: &RgbImage,
+        filter: ScaleFilter,
+        background: Pixel,
+    ) -> SenseHatResult<Self> {
+        let pixels = pixels_from_rgb(image)?;
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        Ok(Frame::new(downscale(&pixels, width, height, filter, background)?))
+    }
+
+    /// Makes a frame of an 8x8 image with alpha, drawing each pixel over
+    /// `background`. Returns an error for any other size.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate image;
+    /// extern crate sensehat;
+    ///
+    /// use image::{Rgba, RgbaImage};
+    /// use sensehat::Frame;
+    ///
+    /// # fn main() {
+    /// let mut image = RgbaImage::from_pixel(8, 8, Rgba([255, 0, 0, 255]));
+    /// image.put_pixel(1, 0, Rgba([255, 0, 0, 0]));
+    /// image.put_pixel(2, 0, Rgba([255, 255, 255, 128]));
+    /// let frame = Frame::from_rgba_image(&image, (0, 0, 255)).unwrap();
+    /// assert_eq!(frame.pixels()[..3], [(255, 0, 0), (0, 0, 255), (128, 128, 255)]);
+    /// # }
+    /// ```
+    pub fn from_rgba_image(image: &RgbaImage, background: Pixel) -> SenseHatResult<Self> {
+        check_size(image.width(), image.height())?;
+        Ok(frame_of(&pixels_from_rgba(image, background)?))
+    }
+}
+
+impl Display {
+    /// Shows an 8x8 image on the LED matrix. Returns an error for any
+    /// other size, without changing what's shown.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate image;
+    /// extern crate sensehat;
+    ///
+    /// use image::{Rgb, RgbImage};
+    /// use sensehat::{Display, MemoryMatrix};
+    ///
+    /// # fn main() {
+    /// let matrix = MemoryMatrix::new();
+    /// let mut display = Display::with_matrix(matrix.clone());
+    /// let diagonal = |x, y| if x == y { Rgb([255, 0, 0]) } else { Rgb([0, 0, 255]) };
+    /// let image = RgbImage::from_fn(8, 8, diagonal);
+    /// display.show_image(&image).unwrap();
+    /// assert_eq!(matrix.pixels()[9], (255, 0, 0));
+    /// assert_eq!(matrix.pixels()[10], (0, 0, 255));
+    /// assert_eq!(display.to_image(), image);
+    ///
+    /// assert!(display.show_image(&RgbImage::new(16, 16)).is_err());
+    /// assert_eq!(display.to_image(), image);
+    /// # }
+    /// ```
+    pub fn show_image(&mut self, image: &RgbImage) -> SenseHatResult<()> {
+        let frame = Frame::try_from(image)?;
+        self.set_pixels(frame.pixels())
+    }
+
+    /// Shows an 8x8 image with alpha on the LED matrix, drawing each pixel
+    /// over `background`. Returns an error for any other size, without
+    /// changing what's shown.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate image;
+    /// extern crate sensehat;
+    ///
+    /// use image::{Rgb, Rgba, RgbaImage};
+    /// use sensehat::{Display, MemoryMatrix};
+    ///
+    /// # fn main() {
+    /// let matrix = MemoryMatrix::new();
+    /// let mut display = Display::with_matrix(matrix.clone());
+    /// let mut image = RgbaImage::from_pixel(8, 8, Rgba([0, 0, 0, 0]));
+    /// image.put_pixel(7, 7, Rgba([255, 255, 0, 255]));
+    /// display.show_rgba_image(&image, (0, 255, 0)).unwrap();
+    /// assert_eq!(matrix.pixels()[..63], [(0, 255, 0); 63]);
+    /// assert_eq!(matrix.pixels()[63], (255, 255, 0));
+    /// assert_eq!(display.to_image().get_pixel(7, 7), &Rgb([255, 255, 0]));
+    /// # }
+    /// ```
+    pub fn show_rgba_image(&mut self, image: &RgbaImage, background: Pixel) -> SenseHatResult<()> {
+        let frame = Frame::from_rgba_image(image, background)?;
+        self.set_pixels(frame.pixels())
+    }
+
+    /// Returns what's on the LED matrix as an 8x8 image, as `get_pixels`
+    /// reads it. The colours have been through the matrix's rgb565
+    /// format, so only those it stores exactly come back unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate image;
+    /// extern crate sensehat;
+    ///
+    /// use image::{Rgb, RgbImage};
+    /// use sensehat::{Display, MemoryMatrix, Pixel, PixelExt};
+    ///
+    /// # fn main() {
+    /// let mut display = Display::with_matrix(MemoryMatrix::new());
+    /// display.show_image(&RgbImage::from_pixel(8, 8, Rgb([0, 0, 64]))).unwrap();
+    /// // 64 isn't one of the 32 levels of blue the matrix has.
+    /// assert_eq!((0, 0, 64).to_rgb565(), (0, 0, 66).to_rgb565());
+    /// assert_eq!(Pixel::from_rgb565((0, 0, 64).to_rgb565()), (0, 0, 66));
+    /// assert_eq!(display.to_image(), RgbImage::from_pixel(8, 8, Rgb([0, 0, 66])));
+    /// # }
+    /// ```
+    pub fn to_image(&self) -> RgbImage {
+        RgbImage::from(&Frame::new(self.get_pixels()))
+    }
+}
+
+/// Helper function.
+///
+/// Returns an error unless an image is 8x8.
+fn check_size(width: u32, height: u32) -> SenseHatResult<()> {
+    if (width, height) != (8, 8) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("expected an 8x8 image, found {}x{}", width, height),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Helper function.
+///
+/// Makes a frame of the 64 pixels of an 8x8 image.
+fn frame_of(pixels: &[Pixel]) -> Frame {
+    let mut frame = [(0, 0, 0); 64];
+    frame.copy_from_slice(pixels);
+    Frame::new(frame)
+}
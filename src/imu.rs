@@ -1,70 +1,2684 @@
-use {SenseHatError, SenseHatResult};
-use settings::Settings;
-
-use i2cdev::core::I2CDevice;
-use i2cdev::linux::LinuxI2CDevice;
-
-/// I2C address to the accel and gyro sensor.
-const ACCEL_GYRO_ADDR: u8 = 0x6a;
-const MAG_ADDR: u8 = 0x00;
-
-pub struct Imu {
-    imu_dev: LinuxI2CDevice,
-    // Settings file
-    settings: Settings,
-    /// true if cal mode, so don't use cal data!
-    compass_calibration_mode: bool,
-    /// true if cal mode, so don't use cal data!
-    accel_calibration_mode: bool,
-    /// samples per second
-    sample_rate: i32,
-    /// interval betwwen samples in microseconds
-    sample_interval: u64,
-    /// gyro bias rapid learning rate
-    gyro_learning_alpha: f64,
-    /// gyro bias continous (slow) learning rate
-    gyro_continious_alpha: f64,
-    /// number of gyro samples used
-    gyro_sample_count: i32,
-    compass_cal_offset: [f64; 3],
-    compass_cal_scale: [f64; 3],
-    /// array of rotation matrices
-    axis_rotation: [[f64; 9]; 24],
-    gyro_scale: f64,
-    accel_scale: f64,
-    compass_scale: f64,
-}
-
-impl Imu {
-    pub fn new() -> SenseHatResult<Self> {
-        let mut imu = Self {
-            imu_dev: LinuxI2CDevice::new("/dev/i2c-1", 0x6a)?,
-            settings: Settings::default(),
-            compass_calibration_mode: false,
-            accel_calibration_mode: false,
-            sample_rate: 100,
-            sample_interval: 0,
-            gyro_learning_alpha: 0.0,
-            gyro_continious_alpha: 0.0,
-            gyro_sample_count: 0,
-            compass_cal_offset: [0.0; 3],
-            compass_cal_scale: [0.0; 3],
-            axis_rotation: [[0.0; 9]; 24],
-            gyro_scale: 0.0,
-            accel_scale: 0.0,
-            compass_scale: 0.0,
-        };
-
-        imu.imu_init()?;
-
-        Ok(imu)
-    }
-
-    fn imu_init(&mut self) -> SenseHatResult<()> {
-        Ok(())
-    }
-
-    pub fn imu_read(&mut self) -> bool {
-        false
-    }
-}
+use {Measurement, Sensor, SenseHatError, SenseHatResult};
+use calibration::{
+    self, check_compass, AccelCalibration, CompassCalibration, CompassEllipsoid, CompassStatus,
+    MagneticFieldLimits,
+};
+use fusion::{true_heading, Fusion, FusionAlgorithm, Orientation3d, Quaternion};
+use motion::{MotionDetector, MotionEvent, MotionThresholds};
+use rtimulib::RtimulibIni;
+use settings::{
+    AccelFullScaleRange, AccelLowPassFilter, AccelSampleRate, CompassFullScaleRange,
+    CompassSampleRate, GyroFullScaleRange, GyroSampleRate, Settings,
+};
+use smoothing::HeadingSmoother;
+use units::{Acceleration3d, Angle, AngularVelocity3d};
+
+use measurements::Temperature;
+
+use i2cdev::core::I2CDevice;
+use i2c::{self, BoxedI2CDevice, TracedI2CDevice};
+use byteorder::{ByteOrder, LittleEndian};
+
+use std::io;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The I2C addresses the accel and gyro sensor can be at, each with the
+/// address of the magnetometer that goes with it. The Sense Hat uses the
+/// first.
+const CANDIDATE_ADDRS: [(u8, u8); 2] = [(0x6a, 0x1c), (0x6b, 0x1e)];
+
+/// How far from 1g the acceleration can be for the Sense Hat to count as
+/// stationary while learning the gyro bias.
+const STATIONARY_ACCEL_TOLERANCE: f64 = 0.05;
+/// How fast the Sense Hat can be rotating, in degrees per second, to count
+/// as stationary while learning the gyro bias. This is RTIMULib's 0.2
+/// radians per second.
+const STATIONARY_GYRO_THRESHOLD: f64 = 11.459;
+/// How many seconds of stationary samples to learn the gyro bias rapidly
+/// from, before switching to continuous learning.
+const GYRO_RAPID_LEARNING_SECONDS: i32 = 5;
+/// How long to wait for a new magnetometer sample while calibrating.
+const CALIBRATION_POLL_INTERVAL: Duration = Duration::from_millis(10);
+/// The shortest time to sleep between polls while waiting for a sample.
+const MIN_SAMPLE_POLL_INTERVAL: Duration = Duration::from_millis(1);
+/// How old a heading can be and still be averaged by
+/// `get_compass_heading_smoothed`, so it doesn't lag after a gap.
+const HEADING_SMOOTHING_MAX_AGE: Duration = Duration::from_secs(1);
+
+/// How long `get_north` waits for the heading to settle the first time.
+const NORTH_PRIMING_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How many fresh magnetometer readings `get_north` fuses before it trusts
+/// the heading.
+const NORTH_PRIMING_SAMPLES: usize = 5;
+
+/// The rotation matrices for each `AxisRotation`, in the same order, from
+/// RTIMULib. Each is row-major and maps a sensor vector onto the board's
+/// axes.
+const AXIS_ROTATIONS: [[f64; 9]; 24] = [
+    [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0],
+    [0.0, -1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+    [-1.0, 0.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, 1.0],
+    [0.0, 1.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+    [1.0, 0.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, -1.0],
+    [0.0, 1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, -1.0],
+    [-1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, -1.0],
+    [0.0, -1.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.0, -1.0],
+    [0.0, 1.0, 0.0, 0.0, 0.0, -1.0, -1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0, 1.0, 0.0, -1.0, 0.0, 0.0],
+    [0.0, -1.0, 0.0, 0.0, 0.0, 1.0, -1.0, 0.0, 0.0],
+    [0.0, 0.0, -1.0, 0.0, -1.0, 0.0, -1.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, -1.0, 0.0, 1.0, 0.0, 1.0, 0.0, 0.0],
+    [0.0, -1.0, 0.0, 0.0, 0.0, -1.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, 0.0],
+    [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, -1.0, 0.0],
+    [0.0, 0.0, -1.0, 1.0, 0.0, 0.0, 0.0, -1.0, 0.0],
+    [-1.0, 0.0, 0.0, 0.0, 0.0, -1.0, 0.0, -1.0, 0.0],
+    [0.0, 0.0, 1.0, -1.0, 0.0, 0.0, 0.0, -1.0, 0.0],
+    [1.0, 0.0, 0.0, 0.0, 0.0, -1.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0],
+    [-1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, -1.0, -1.0, 0.0, 0.0, 0.0, 1.0, 0.0],
+];
+
+/// The WHO_AM_I register, which is at the same place on both chips.
+const WHO_AM_I: u8 = 0x0f;
+/// The WHO_AM_I id of the LSM9DS1 accel/gyro.
+const LSM9DS1_ID: u8 = 0x68;
+/// The WHO_AM_I id of the LSM9DS0 gyro, which is at the same addresses as
+/// the LSM9DS1 accel/gyro.
+const LSM9DS0_GYRO_ID: u8 = 0xd4;
+
+// LSM9DS1 accel/gyro registers
+const LSM9DS1_CTRL1_G: u8 = 0x10;
+const LSM9DS1_CTRL3_G: u8 = 0x12;
+const LSM9DS1_OUT_TEMP_L: u8 = 0x15;
+const LSM9DS1_STATUS_REG: u8 = 0x17;
+const LSM9DS1_OUT_X_L_G: u8 = 0x18;
+const LSM9DS1_CTRL5_XL: u8 = 0x1f;
+const LSM9DS1_CTRL6_XL: u8 = 0x20;
+const LSM9DS1_CTRL8: u8 = 0x22;
+const LSM9DS1_CTRL9: u8 = 0x23;
+const LSM9DS1_OUT_X_L_XL: u8 = 0x28;
+const LSM9DS1_FIFO_CTRL: u8 = 0x2e;
+const LSM9DS1_FIFO_SRC: u8 = 0x2f;
+
+/// How many LSBs of the temperature output make a degree Celsius.
+const LSM9DS1_TEMP_SCALE: f64 = 16.0;
+/// The temperature, in degrees Celsius, at which the output is zero.
+const LSM9DS1_TEMP_OFFSET: f64 = 25.0;
+
+/// The FIFO_EN bit of CTRL_REG9.
+const LSM9DS1_FIFO_EN: u8 = 0x02;
+/// The largest FIFO watermark, as the FIFO holds 32 samples.
+const LSM9DS1_FIFO_MAX_WATERMARK: u8 = 31;
+
+// LSM9DS1 magnetometer registers
+const LSM9DS1_MAG_ID: u8 = 0x3d;
+const LSM9DS1_MAG_CTRL1: u8 = 0x20;
+const LSM9DS1_MAG_CTRL2: u8 = 0x21;
+const LSM9DS1_MAG_CTRL3: u8 = 0x22;
+const LSM9DS1_MAG_STATUS: u8 = 0x27;
+const LSM9DS1_MAG_OUT_X_L: u8 = 0x28;
+/// Setting the MSB of a magnetometer register auto-increments it on
+/// multi-byte reads.
+const LSM9DS1_MAG_AUTO_INCREMENT: u8 = 0x80;
+
+/// How the IMU is mounted, named by which way its X and Y axes point when
+/// the board's X axis points north and its Y axis east, the way RTIMULib
+/// names them.
+///
+/// `XNorthYEast` is no rotation at all, which is right for the official
+/// Sense Hat.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum AxisRotation {
+    #[default]
+    XNorthYEast,
+    XEastYSouth,
+    XSouthYWest,
+    XWestYNorth,
+    XNorthYWest,
+    XEastYNorth,
+    XSouthYEast,
+    XWestYSouth,
+    XUpYNorth,
+    XUpYEast,
+    XUpYSouth,
+    XUpYWest,
+    XDownYNorth,
+    XDownYEast,
+    XDownYSouth,
+    XDownYWest,
+    XNorthYUp,
+    XEastYUp,
+    XSouthYUp,
+    XWestYUp,
+    XNorthYDown,
+    XEastYDown,
+    XSouthYDown,
+    XWestYDown,
+}
+
+/// The IMU chips which have been fitted to the Sense Hat.
+///
+/// Only the LSM9DS1 is supported; `Imu::new` returns `UnsupportedImu` if it
+/// finds anything else, so an `Imu` is always an LSM9DS1.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::ImuChip;
+///
+/// assert_eq!(ImuChip::from_id(0x68), Some(ImuChip::Lsm9ds1));
+/// assert_eq!(ImuChip::from_id(0xd4), Some(ImuChip::Lsm9ds0));
+/// assert_eq!(ImuChip::from_id(0x00), None);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ImuChip {
+    /// The LSM9DS1, on current Sense Hats.
+    Lsm9ds1,
+    /// The LSM9DS0, on some early Sense Hats.
+    Lsm9ds0,
+}
+
+impl ImuChip {
+    /// Returns the chip with this WHO_AM_I id at the accel/gyro address,
+    /// or `None` if it isn't one we know about.
+    pub fn from_id(id: u8) -> Option<ImuChip> {
+        match id {
+            LSM9DS1_ID => Some(ImuChip::Lsm9ds1),
+            LSM9DS0_GYRO_ID => Some(ImuChip::Lsm9ds0),
+            _ => None,
+        }
+    }
+
+    /// Returns the WHO_AM_I id the chip reports at the accel/gyro address.
+    pub fn id(&self) -> u8 {
+        match *self {
+            ImuChip::Lsm9ds1 => LSM9DS1_ID,
+            ImuChip::Lsm9ds0 => LSM9DS0_GYRO_ID,
+        }
+    }
+}
+
+/// One reading from each of the IMU's sensors.
+///
+/// With the `serde` feature this can be serialised, but the timestamp
+/// isn't, as an `Instant` only means something within the process that
+/// took it. Deserialised samples are stamped with the time they were
+/// deserialised.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ImuSample {
+    /// Acceleration in g.
+    pub accel: [f64; 3],
+    /// Angular rate in degrees per second.
+    pub gyro: [f64; 3],
+    /// Magnetic field in microtesla, if there has been a reading.
+    pub mag: Option<[f64; 3]>,
+    /// True if the magnetometer had no new reading, so `mag` is the one
+    /// from an earlier sample.
+    pub mag_stale: bool,
+    /// When the sample was taken.
+    #[cfg_attr(feature = "serde", serde(skip, default = "Instant::now"))]
+    pub timestamp: Instant,
+}
+
+/// How the LSM9DS1 FIFO fills up, see the LSM9DS1 manual for details.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FifoMode {
+    /// The FIFO is off.
+    Bypass,
+    /// Fill the FIFO, then stop until it's read.
+    Fifo,
+    /// Keep overwriting the oldest samples until an interrupt, then stop.
+    ContinuousToFifo,
+    /// Stay off until an interrupt, then keep overwriting the oldest
+    /// samples.
+    BypassToContinuous,
+    /// Keep overwriting the oldest samples.
+    Continuous,
+}
+
+impl FifoMode {
+    /// The FMODE bits of FIFO_CTRL.
+    fn bits(self) -> u8 {
+        match self {
+            FifoMode::Bypass => 0b000,
+            FifoMode::Fifo => 0b001,
+            FifoMode::ContinuousToFifo => 0b011,
+            FifoMode::BypassToContinuous => 0b100,
+            FifoMode::Continuous => 0b110,
+        }
+    }
+}
+
+/// The samples drained from the FIFO by `Imu::read_fifo`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FifoSamples {
+    /// The samples, oldest first. They have no magnetometer readings.
+    pub samples: Vec<ImuSample>,
+    /// True if the FIFO filled up and samples were lost or, depending on
+    /// the mode, not taken.
+    pub overrun: bool,
+}
+
+/// A compass heading from `Imu::get_compass_heading_checked`, with how far
+/// to trust it.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CheckedHeading {
+    /// The heading in degrees, from 0 up to 360, as `get_compass_heading`
+    /// gives.
+    pub heading: f64,
+    /// False if the compass has never been calibrated, so the heading can
+    /// be well out.
+    pub calibrated: bool,
+    /// The strength of the magnetic field the heading came from, in
+    /// microtesla.
+    pub magnitude: f64,
+}
+
+/// Which of the IMU's sensors are turned on, as set by `Imu::set_config`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ImuConfig {
+    pub compass: bool,
+    pub gyro: bool,
+    pub accel: bool,
+}
+
+impl Default for ImuConfig {
+    /// Everything turned on.
+    fn default() -> Self {
+        ImuConfig {
+            compass: true,
+            gyro: true,
+            accel: true,
+        }
+    }
+}
+
+pub struct Imu {
+    imu_dev: TracedI2CDevice<BoxedI2CDevice>,
+    mag_dev: TracedI2CDevice<BoxedI2CDevice>,
+    /// the I2C address of the magnetometer
+    mag_addr: u8,
+    // Settings file
+    settings: Settings,
+    /// true if cal mode, so don't use cal data!
+    compass_calibration_mode: bool,
+    /// true if cal mode, so don't use cal data!
+    accel_calibration_mode: bool,
+    /// samples per second
+    sample_rate: i32,
+    /// interval betwwen samples in microseconds
+    sample_interval: u64,
+    /// gyro bias rapid learning rate
+    gyro_learning_alpha: f64,
+    /// gyro bias continous (slow) learning rate
+    gyro_continious_alpha: f64,
+    /// number of gyro samples used
+    gyro_sample_count: i32,
+    /// the learned gyro bias, in degrees per second
+    gyro_bias: [f64; 3],
+    compass_cal_offset: [f64; 3],
+    compass_cal_scale: [f64; 3],
+    /// the soft iron correction applied after the compass calibration
+    compass_ellipsoid: Option<CompassEllipsoid>,
+    /// the correction applied to accelerometer readings
+    accel_calibration: Option<AccelCalibration>,
+    /// the orientation the output is relative to, if tared
+    tare: Option<Quaternion>,
+    /// true once `get_north` has let the heading settle
+    north_primed: bool,
+    /// the field strengths `get_compass_heading_checked` accepts
+    field_limits: MagneticFieldLimits,
+    /// array of rotation matrices
+    axis_rotation: [[f64; 9]; 24],
+    /// which of the rotation matrices to apply
+    rotation: AxisRotation,
+    gyro_scale: f64,
+    accel_scale: f64,
+    compass_scale: f64,
+    /// which sensors are turned on
+    config: ImuConfig,
+    /// the sensor fusion state
+    fusion: Fusion,
+    /// when the last sample was fused
+    last_fused: Option<Instant>,
+    /// the last magnetometer reading, as it updates slower than the others
+    last_mag: Option<[f64; 3]>,
+    /// the free fall and shake detector fed by `detect_motion`
+    motion: MotionDetector,
+    /// the magnetic declination added to the yaw, in degrees east
+    declination: f64,
+    /// averages the headings from `get_compass_heading_smoothed`, if set
+    heading_smoother: Option<HeadingSmoother>,
+    /// whether magnetometer readings are fused into the orientation
+    fuse_mag: bool,
+}
+
+impl Imu {
+    pub fn new() -> SenseHatResult<Self> {
+        Self::new_with_settings(Settings::default())
+    }
+
+    /// Like `new`, but configures the chip with the given settings rather
+    /// than the defaults.
+    ///
+    /// Returns `UnsupportedImu` if the chip isn't an LSM9DS1, such as the
+    /// LSM9DS0 on some early Sense Hats.
+    pub fn new_with_settings(settings: Settings) -> SenseHatResult<Self> {
+        Self::from_bus(|address| Ok(i2c::open(address)?.into_inner()), settings)
+    }
+
+    /// Like `new_with_settings`, but opens the device at each I2C address
+    /// with `open`, rather than on the Sense Hat's I2C bus. The
+    /// accel/gyro is looked for at 0x6a, then 0x6b, and the magnetometer
+    /// that goes with the one found, at 0x1c or 0x1e, is opened. If no
+    /// chip answers, returns the error from the last address tried, be it
+    /// from opening the device or reading from it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io;
+    /// use sensehat::{BoxedI2CDevice, ErrorKind, Imu, MockI2CDevice, SenseHatError, Settings};
+    ///
+    /// // An IMU at the second pair of addresses, with nothing at 0x6a.
+    /// let (accel_gyro, mag) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// accel_gyro.set_register(0x0f, 0x68);
+    /// mag.set_register(0x0f, 0x3d);
+    /// let mut opened = Vec::new();
+    /// let imu = Imu::from_bus(
+    ///     |address| {
+    ///         opened.push(address);
+    ///         match address {
+    ///             0x6b => Ok(BoxedI2CDevice::new(accel_gyro.clone())),
+    ///             0x1e => Ok(BoxedI2CDevice::new(mag.clone())),
+    ///             _ => Err(io::Error::from(io::ErrorKind::NotFound).into()),
+    ///         }
+    ///     },
+    ///     Settings::default(),
+    /// );
+    /// assert!(imu.is_ok());
+    /// assert_eq!(opened, [0x6a, 0x6b, 0x1e]);
+    ///
+    /// // No chip present: the devices open, but nothing answers.
+    /// let absent = MockI2CDevice::new();
+    /// absent.set_failing(true);
+    /// let mut opened = Vec::new();
+    /// let result = Imu::from_bus(
+    ///     |address| {
+    ///         opened.push(address);
+    ///         Ok(BoxedI2CDevice::new(absent.clone()))
+    ///     },
+    ///     Settings::default(),
+    /// );
+    /// match result {
+    ///     Err(SenseHatError::I2CError(_)) => {}
+    ///     Err(err) => panic!("{}", err),
+    ///     Ok(_) => panic!("found an IMU which isn't there"),
+    /// }
+    /// assert_eq!(opened, [0x6a, 0x6b]);
+    ///
+    /// // Neither address can be opened.
+    /// let result = Imu::from_bus(
+    ///     |_| Err(io::Error::from(io::ErrorKind::PermissionDenied).into()),
+    ///     Settings::default(),
+    /// );
+    /// assert_eq!(result.err().map(|err| err.kind()), Some(ErrorKind::Io));
+    ///
+    /// // An LSM9DS0 isn't supported.
+    /// accel_gyro.set_register(0x0f, 0xd4);
+    /// let result = Imu::from_bus(
+    ///     |address| match address {
+    ///         0x6a | 0x6b => Ok(BoxedI2CDevice::new(accel_gyro.clone())),
+    ///         _ => Ok(BoxedI2CDevice::new(mag.clone())),
+    ///     },
+    ///     Settings::default(),
+    /// );
+    /// assert_eq!(result.err().map(|err| err.kind()), Some(ErrorKind::UnsupportedImu));
+    /// ```
+    pub fn from_bus<F>(mut open: F, settings: Settings) -> SenseHatResult<Self>
+    where
+        F: FnMut(u16) -> SenseHatResult<BoxedI2CDevice>,
+    {
+        let mut open = |address: u16| open(address).map(|dev| TracedI2CDevice::new(dev, address));
+        let (imu_dev, id, mag_addr) = probe(&mut open)?;
+        debug!(
+            "found an IMU with id {:#04x} at {:#04x}, magnetometer at {:#04x}",
+            id,
+            imu_dev.address(),
+            mag_addr
+        );
+        if ImuChip::from_id(id) != Some(ImuChip::Lsm9ds1) {
+            return Err(SenseHatError::UnsupportedImu(id));
+        }
+        Self::init(imu_dev, open(mag_addr as u16)?, settings)
+    }
+
+    /// Like `new_with_settings`, but talks to the accelerometer and gyro,
+    /// and the magnetometer, through the given devices, rather than
+    /// looking for them on the Sense Hat's I2C bus. They're taken to be at
+    /// the Sense Hat's addresses, 0x6a and 0x1c.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::{BoxedI2CDevice, Imu, MockI2CDevice, SenseHatError, Settings};
+    ///
+    /// let (accel_gyro, mag) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// accel_gyro.set_register(0x0f, 0x68);
+    /// mag.set_register(0x0f, 0x3d);
+    /// let imu = Imu::from_devices(
+    ///     BoxedI2CDevice::new(accel_gyro.clone()),
+    ///     BoxedI2CDevice::new(mag.clone()),
+    ///     Settings::default(),
+    /// );
+    /// assert!(imu.is_ok());
+    ///
+    /// // An LSM9DS0 isn't supported.
+    /// accel_gyro.set_register(0x0f, 0xd4);
+    /// let result = Imu::from_devices(
+    ///     BoxedI2CDevice::new(accel_gyro.clone()),
+    ///     BoxedI2CDevice::new(mag.clone()),
+    ///     Settings::default(),
+    /// );
+    /// match result {
+    ///     Err(SenseHatError::UnsupportedImu(0xd4)) => {}
+    ///     Err(err) => panic!("{}", err),
+    ///     Ok(_) => panic!("initialised an LSM9DS0"),
+    /// }
+    /// ```
+    pub fn from_devices(
+        accel_gyro: BoxedI2CDevice,
+        magnetometer: BoxedI2CDevice,
+        settings: Settings,
+    ) -> SenseHatResult<Self> {
+        let (addr, mag_addr) = CANDIDATE_ADDRS[0];
+        let mut imu_dev = TracedI2CDevice::new(accel_gyro, addr as u16);
+        let id = imu_dev.smbus_read_byte_data(WHO_AM_I)?;
+        if ImuChip::from_id(id) != Some(ImuChip::Lsm9ds1) {
+            return Err(SenseHatError::UnsupportedImu(id));
+        }
+        Self::init(imu_dev, TracedI2CDevice::new(magnetometer, mag_addr as u16), settings)
+    }
+
+    /// Helper function.
+    ///
+    /// Initialises an LSM9DS1 found at the given devices.
+    fn init(
+        imu_dev: TracedI2CDevice<BoxedI2CDevice>,
+        mag_dev: TracedI2CDevice<BoxedI2CDevice>,
+        settings: Settings,
+    ) -> SenseHatResult<Self> {
+        let mut imu = Self {
+            mag_addr: mag_dev.address() as u8,
+            imu_dev,
+            mag_dev,
+            settings,
+            compass_calibration_mode: false,
+            accel_calibration_mode: false,
+            sample_rate: 0,
+            sample_interval: 0,
+            gyro_learning_alpha: 0.0,
+            gyro_continious_alpha: 0.0,
+            gyro_sample_count: 0,
+            gyro_bias: [0.0; 3],
+            compass_cal_offset: [0.0; 3],
+            compass_cal_scale: [1.0; 3],
+            compass_ellipsoid: None,
+            accel_calibration: None,
+            tare: None,
+            north_primed: false,
+            field_limits: MagneticFieldLimits::default(),
+            axis_rotation: AXIS_ROTATIONS,
+            rotation: AxisRotation::default(),
+            gyro_scale: 0.0,
+            accel_scale: 0.0,
+            compass_scale: 0.0,
+            config: ImuConfig::default(),
+            fusion: Fusion::new(FusionAlgorithm::Complementary),
+            last_fused: None,
+            last_mag: None,
+            motion: MotionDetector::default(),
+            declination: 0.0,
+            heading_smoother: None,
+            fuse_mag: true,
+        };
+
+        imu.imu_init()?;
+
+        Ok(imu)
+    }
+
+    /// Init sequence from https://github.com/RPi-Distro/RTIMULib
+    fn imu_init(&mut self) -> SenseHatResult<()> {
+        // Block data update, and auto-increment the register address on
+        // multi-byte reads.
+        self.imu_dev.smbus_write_byte_data(LSM9DS1_CTRL8, 0x44)?;
+        self.init_gyro()?;
+        self.init_accel()?;
+        self.init_compass()?;
+        self.update_sample_rate();
+        Ok(())
+    }
+
+    /// Helper function.
+    ///
+    /// Works out the sample rate from the settings, and the things which
+    /// depend on it.
+    fn update_sample_rate(&mut self) {
+        // When the gyro is on the accelerometer runs at its rate. With
+        // only the compass on, its rate is the only one there is.
+        let hz = if self.config.gyro {
+            self.settings.gyro_sample_rate().hz()
+        } else if self.config.accel || !self.config.compass {
+            self.settings.accel_sample_rate().hz()
+        } else {
+            self.settings.compass_sample_rate().hz()
+        };
+        self.sample_rate = hz.round() as i32;
+        self.sample_interval = (1_000_000.0 / hz).round() as u64;
+        // The gyro bias learning rates, from RTIMULib.
+        self.gyro_learning_alpha = 2.0 / hz;
+        self.gyro_continious_alpha = 0.01 / hz;
+    }
+
+    fn init_gyro(&mut self) -> SenseHatResult<()> {
+        if !self.config.gyro {
+            // Power down.
+            self.imu_dev.smbus_write_byte_data(LSM9DS1_CTRL1_G, 0x00)?;
+            return Ok(());
+        }
+        let ctrl1 = (self.settings.gyro_sample_rate().bits() << 5)
+            | (self.settings.gyro_fsr().bits() << 3)
+            | self.settings.gyro_bandwidth().bits();
+        self.imu_dev.smbus_write_byte_data(LSM9DS1_CTRL1_G, ctrl1)?;
+        self.imu_dev
+            .smbus_write_byte_data(LSM9DS1_CTRL3_G, self.settings.gyro_hpf().bits())?;
+        self.gyro_scale = self.settings.gyro_fsr().scale();
+        Ok(())
+    }
+
+    fn init_accel(&mut self) -> SenseHatResult<()> {
+        if !self.config.accel {
+            // Power down, and disable the axes in case the gyro is driving
+            // the accelerometer's data rate.
+            self.imu_dev.smbus_write_byte_data(LSM9DS1_CTRL6_XL, 0x00)?;
+            self.imu_dev.smbus_write_byte_data(LSM9DS1_CTRL5_XL, 0x00)?;
+            return Ok(());
+        }
+        let ctrl6 = (self.settings.accel_sample_rate().bits() << 5)
+            | (self.settings.accel_fsr().bits() << 3)
+            | 0x04
+            | self.settings.accel_lpf().bits();
+        self.imu_dev.smbus_write_byte_data(LSM9DS1_CTRL6_XL, ctrl6)?;
+        // Enable the X, Y and Z axes.
+        self.imu_dev.smbus_write_byte_data(LSM9DS1_CTRL5_XL, 0x38)?;
+        self.accel_scale = self.settings.accel_fsr().scale();
+        Ok(())
+    }
+
+    fn init_compass(&mut self) -> SenseHatResult<()> {
+        let id = self.mag_dev.smbus_read_byte_data(WHO_AM_I)?;
+        if id != LSM9DS1_MAG_ID {
+            return Err(SenseHatError::UnexpectedDevice {
+                address: self.mag_addr as u16,
+                id,
+            });
+        }
+        if !self.config.compass {
+            // Power down.
+            self.mag_dev.smbus_write_byte_data(LSM9DS1_MAG_CTRL3, 0x03)?;
+            return Ok(());
+        }
+        let ctrl1 = self.settings.compass_sample_rate().bits() << 2;
+        self.mag_dev.smbus_write_byte_data(LSM9DS1_MAG_CTRL1, ctrl1)?;
+        let ctrl2 = self.settings.compass_fsr().bits() << 5;
+        self.mag_dev.smbus_write_byte_data(LSM9DS1_MAG_CTRL2, ctrl2)?;
+        // Continuous conversion mode.
+        self.mag_dev.smbus_write_byte_data(LSM9DS1_MAG_CTRL3, 0x00)?;
+        self.compass_scale = self.settings.compass_fsr().scale();
+        Ok(())
+    }
+
+    /// Returns the settings the chip is configured with.
+    pub fn settings(&self) -> Settings {
+        self.settings
+    }
+
+    /// Reconfigures the chip with new sample rates, ranges and filters.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{GyroFullScaleRange, SenseHat, Settings};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let settings = Settings::builder().gyro_fsr(GyroFullScaleRange::Dps2000).build();
+    /// sense.imu().unwrap().apply_settings(&settings).unwrap();
+    /// ```
+    pub fn apply_settings(&mut self, settings: &Settings) -> SenseHatResult<()> {
+        self.settings = *settings;
+        self.init_gyro()?;
+        self.init_accel()?;
+        self.init_compass()?;
+        self.update_sample_rate();
+        Ok(())
+    }
+
+    /// Returns how many samples per second the IMU takes, rounded to the
+    /// nearest whole number.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate as u32
+    }
+
+    /// Returns how often to read the IMU to see every sample, like
+    /// RTIMULib's `IMUGetPollInterval()`.
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_micros(self.sample_interval)
+    }
+
+    /// Sets the gyro and accelerometer sample rates to the supported rate
+    /// closest to `hz`, and reconfigures the chip.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let imu = sense.imu().unwrap();
+    /// imu.set_sample_rate(200).unwrap();
+    /// assert_eq!(imu.sample_rate(), 238);
+    /// ```
+    pub fn set_sample_rate(&mut self, hz: u32) -> SenseHatResult<()> {
+        let rate = GyroSampleRate::nearest(hz as f64);
+        let settings = self
+            .settings
+            .to_builder()
+            .gyro_sample_rate(rate)
+            .accel_sample_rate(AccelSampleRate::matching(rate))
+            .build();
+        self.apply_settings(&settings)
+    }
+
+    /// Changes the gyro's full scale range, updating the settings, without
+    /// reconfiguring anything else. Readings are scaled for the new range
+    /// from the next one.
+    ///
+    /// The orientation estimate and the learned gyro bias are kept, as
+    /// they're in degrees per second whatever the range. If the gyro is
+    /// off, the range is used when it's turned on.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{GyroFullScaleRange, SenseHat};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let imu = sense.imu().unwrap();
+    /// imu.set_gyro_fsr(GyroFullScaleRange::Dps2000).unwrap();
+    /// assert_eq!(imu.read_gyro_fsr().unwrap(), GyroFullScaleRange::Dps2000);
+    /// ```
+    pub fn set_gyro_fsr(&mut self, fsr: GyroFullScaleRange) -> SenseHatResult<()> {
+        if self.config.gyro {
+            let ctrl1 = self.imu_dev.smbus_read_byte_data(LSM9DS1_CTRL1_G)?;
+            let ctrl1 = (ctrl1 & !0b0001_1000) | (fsr.bits() << 3);
+            self.imu_dev.smbus_write_byte_data(LSM9DS1_CTRL1_G, ctrl1)?;
+        }
+        // Only once the chip has it, so readings are never scaled for the
+        // wrong range.
+        self.gyro_scale = fsr.scale();
+        self.settings = self.settings.to_builder().gyro_fsr(fsr).build();
+        Ok(())
+    }
+
+    /// Reads the gyro's full scale range back from the chip. Returns an
+    /// `InvalidData` I/O error if the chip has bits set which the LSM9DS1
+    /// doesn't use.
+    pub fn read_gyro_fsr(&mut self) -> SenseHatResult<GyroFullScaleRange> {
+        let ctrl1 = self.imu_dev.smbus_read_byte_data(LSM9DS1_CTRL1_G)?;
+        GyroFullScaleRange::from_ctrl1_g(ctrl1).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unknown gyro full scale range in CTRL_REG1_G {:#04x}", ctrl1),
+            )
+            .into()
+        })
+    }
+
+    /// Changes the accelerometer's full scale range, updating the settings,
+    /// without reconfiguring anything else. Readings are scaled for the new
+    /// range from the next one, so they, and the free fall and shake
+    /// thresholds, stay in g.
+    ///
+    /// If the accelerometer is off, the range is used when it's turned on.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{AccelFullScaleRange, SenseHat};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let imu = sense.imu().unwrap();
+    /// imu.set_accel_fsr(AccelFullScaleRange::G16).unwrap();
+    /// assert_eq!(imu.read_accel_fsr().unwrap(), AccelFullScaleRange::G16);
+    /// ```
+    pub fn set_accel_fsr(&mut self, fsr: AccelFullScaleRange) -> SenseHatResult<()> {
+        if self.config.accel {
+            let ctrl6 = self.imu_dev.smbus_read_byte_data(LSM9DS1_CTRL6_XL)?;
+            let ctrl6 = (ctrl6 & !0b0001_1000) | (fsr.bits() << 3);
+            self.imu_dev.smbus_write_byte_data(LSM9DS1_CTRL6_XL, ctrl6)?;
+        }
+        self.accel_scale = fsr.scale();
+        self.settings = self.settings.to_builder().accel_fsr(fsr).build();
+        Ok(())
+    }
+
+    /// Reads the accelerometer's full scale range back from the chip.
+    pub fn read_accel_fsr(&mut self) -> SenseHatResult<AccelFullScaleRange> {
+        let ctrl6 = self.imu_dev.smbus_read_byte_data(LSM9DS1_CTRL6_XL)?;
+        Ok(AccelFullScaleRange::from_ctrl6_xl(ctrl6))
+    }
+
+    /// Changes the accelerometer's anti-aliasing filter, updating the
+    /// settings, without reconfiguring anything else. If the accelerometer
+    /// is off, the filter is used when it's turned on.
+    pub fn set_accel_lpf(&mut self, lpf: AccelLowPassFilter) -> SenseHatResult<()> {
+        if self.config.accel {
+            let ctrl6 = self.imu_dev.smbus_read_byte_data(LSM9DS1_CTRL6_XL)?;
+            // Setting BW_SCAL_ODR makes the chip use BW_XL rather than
+            // picking a filter for the sample rate.
+            let ctrl6 = (ctrl6 & !0b0000_0011) | 0x04 | lpf.bits();
+            self.imu_dev.smbus_write_byte_data(LSM9DS1_CTRL6_XL, ctrl6)?;
+        }
+        self.settings = self.settings.to_builder().accel_lpf(lpf).build();
+        Ok(())
+    }
+
+    /// Reads the accelerometer's anti-aliasing filter back from the chip.
+    pub fn read_accel_lpf(&mut self) -> SenseHatResult<AccelLowPassFilter> {
+        let ctrl6 = self.imu_dev.smbus_read_byte_data(LSM9DS1_CTRL6_XL)?;
+        Ok(AccelLowPassFilter::from_ctrl6_xl(ctrl6))
+    }
+
+    /// Changes the magnetometer's full scale range, updating the settings,
+    /// without reconfiguring anything else. Readings are scaled for the new
+    /// range from the next one.
+    ///
+    /// The compass calibration is kept, as its offsets are in microtesla
+    /// whatever the range. If the compass is off, the range is used when
+    /// it's turned on.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{CompassFullScaleRange, SenseHat};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let imu = sense.imu().unwrap();
+    /// imu.set_compass_fsr(CompassFullScaleRange::uT_8).unwrap();
+    /// assert_eq!(imu.read_compass_fsr().unwrap(), CompassFullScaleRange::uT_8);
+    /// ```
+    pub fn set_compass_fsr(&mut self, fsr: CompassFullScaleRange) -> SenseHatResult<()> {
+        if self.config.compass {
+            let ctrl2 = self.mag_dev.smbus_read_byte_data(LSM9DS1_MAG_CTRL2)?;
+            let ctrl2 = (ctrl2 & !0b0110_0000) | (fsr.bits() << 5);
+            self.mag_dev.smbus_write_byte_data(LSM9DS1_MAG_CTRL2, ctrl2)?;
+        }
+        self.compass_scale = fsr.scale();
+        self.settings = self.settings.to_builder().compass_fsr(fsr).build();
+        Ok(())
+    }
+
+    /// Reads the magnetometer's full scale range back from the chip.
+    pub fn read_compass_fsr(&mut self) -> SenseHatResult<CompassFullScaleRange> {
+        let ctrl2 = self.mag_dev.smbus_read_byte_data(LSM9DS1_MAG_CTRL2)?;
+        Ok(CompassFullScaleRange::from_ctrl2_m(ctrl2))
+    }
+
+    /// Changes the magnetometer's sample rate, updating the settings,
+    /// without reconfiguring anything else. With only the compass on, this
+    /// changes `sample_rate` and `poll_interval` too. If the compass is
+    /// off, the rate is used when it's turned on.
+    pub fn set_compass_sample_rate(&mut self, rate: CompassSampleRate) -> SenseHatResult<()> {
+        if self.config.compass {
+            let ctrl1 = self.mag_dev.smbus_read_byte_data(LSM9DS1_MAG_CTRL1)?;
+            let ctrl1 = (ctrl1 & !0b0001_1100) | (rate.bits() << 2);
+            self.mag_dev.smbus_write_byte_data(LSM9DS1_MAG_CTRL1, ctrl1)?;
+        }
+        self.settings = self.settings.to_builder().compass_sample_rate(rate).build();
+        self.update_sample_rate();
+        Ok(())
+    }
+
+    /// Reads the magnetometer's sample rate back from the chip.
+    pub fn read_compass_sample_rate(&mut self) -> SenseHatResult<CompassSampleRate> {
+        let ctrl1 = self.mag_dev.smbus_read_byte_data(LSM9DS1_MAG_CTRL1)?;
+        Ok(CompassSampleRate::from_ctrl1_m(ctrl1))
+    }
+
+    /// Turns on the FIFO, which buffers up to 32 accelerometer and gyro
+    /// samples so they can be read in bursts with `read_fifo`. Use
+    /// `FifoMode::Bypass` to turn it off again.
+    ///
+    /// `watermark` is the FIFO level, up to 31, which sets the threshold
+    /// flag.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::thread;
+    /// use sensehat::{FifoMode, SenseHat};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let imu = sense.imu().unwrap();
+    /// imu.enable_fifo(FifoMode::Continuous, 16).unwrap();
+    /// loop {
+    ///     thread::sleep(imu.poll_interval() * 16);
+    ///     let fifo = imu.read_fifo().unwrap();
+    ///     println!("{} samples, overrun: {}", fifo.samples.len(), fifo.overrun);
+    /// }
+    /// ```
+    ///
+    /// Off the hardware, with the chip mocked:
+    ///
+    /// ```
+    /// use sensehat::{BoxedI2CDevice, FifoMode, Imu, MockI2CDevice, SenseHatError, Settings};
+    ///
+    /// let (accel_gyro, mag) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// accel_gyro.set_register(0x0f, 0x68);
+    /// mag.set_register(0x0f, 0x3d);
+    /// let (ag, m) = (BoxedI2CDevice::new(accel_gyro.clone()), BoxedI2CDevice::new(mag));
+    /// let mut imu = Imu::from_devices(ag, m, Settings::default()).unwrap();
+    ///
+    /// accel_gyro.clear_writes();
+    /// imu.enable_fifo(FifoMode::Continuous, 16).unwrap();
+    /// // FIFO_EN in CTRL_REG9, then the mode and watermark in FIFO_CTRL.
+    /// assert_eq!(accel_gyro.writes(), [(0x23, 0x02), (0x2e, 0xd0)]);
+    /// match imu.enable_fifo(FifoMode::Fifo, 32) {
+    ///     Err(SenseHatError::InvalidFifoWatermark(32)) => {}
+    ///     other => panic!("{:?}", other),
+    /// }
+    ///
+    /// // Empty.
+    /// let fifo = imu.read_fifo().unwrap();
+    /// assert!(fifo.samples.is_empty() && !fifo.overrun);
+    ///
+    /// // Three samples, and it overran. 1g up Z at 8g, and 10dps
+    /// // around X at 500dps, in each.
+    /// accel_gyro.set_register(0x2f, 0x43);
+    /// accel_gyro.set_registers(0x18, &[0x3b, 0x02, 0x00, 0x00, 0x00, 0x00]);
+    /// accel_gyro.set_registers(0x28, &[0x00, 0x00, 0x00, 0x00, 0x00, 0x10]);
+    /// let fifo = imu.read_fifo().unwrap();
+    /// assert!(fifo.overrun);
+    /// assert_eq!(fifo.samples.len(), 3);
+    /// for sample in &fifo.samples {
+    ///     assert_eq!(sample.accel, [0.0, 0.0, 4096.0 * 0.000244]);
+    ///     assert_eq!(sample.gyro, [571.0 * 0.0175, 0.0, 0.0]);
+    ///     assert_eq!(sample.mag, None);
+    /// }
+    /// // Spaced out by the sample interval, oldest first.
+    /// let gap = fifo.samples[2].timestamp - fifo.samples[1].timestamp;
+    /// assert_eq!(gap, imu.poll_interval());
+    /// assert_eq!(fifo.samples[1].timestamp - fifo.samples[0].timestamp, gap);
+    ///
+    /// accel_gyro.clear_writes();
+    /// imu.enable_fifo(FifoMode::Bypass, 0).unwrap();
+    /// assert_eq!(accel_gyro.writes(), [(0x23, 0x00), (0x2e, 0x00)]);
+    ///
+    /// accel_gyro.set_failing(true);
+    /// assert!(imu.read_fifo().is_err());
+    /// ```
+    pub fn enable_fifo(&mut self, mode: FifoMode, watermark: u8) -> SenseHatResult<()> {
+        if watermark > LSM9DS1_FIFO_MAX_WATERMARK {
+            return Err(SenseHatError::InvalidFifoWatermark(watermark));
+        }
+        let ctrl9 = self.imu_dev.smbus_read_byte_data(LSM9DS1_CTRL9)?;
+        let ctrl9 = if mode == FifoMode::Bypass {
+            ctrl9 & !LSM9DS1_FIFO_EN
+        } else {
+            ctrl9 | LSM9DS1_FIFO_EN
+        };
+        self.imu_dev.smbus_write_byte_data(LSM9DS1_CTRL9, ctrl9)?;
+        self.imu_dev
+            .smbus_write_byte_data(LSM9DS1_FIFO_CTRL, (mode.bits() << 5) | watermark)?;
+        Ok(())
+    }
+
+    /// Drains the samples waiting in the FIFO, with the scaling, axis
+    /// rotation and gyro bias applied. The timestamps are worked out back
+    /// from now using the sample interval.
+    pub fn read_fifo(&mut self) -> SenseHatResult<FifoSamples> {
+        let src = self.imu_dev.smbus_read_byte_data(LSM9DS1_FIFO_SRC)?;
+        let overrun = (src & 0x40) != 0;
+        let level = (src & 0x3f) as usize;
+
+        let now = Instant::now();
+        let mut samples = Vec::with_capacity(level);
+        for i in 0..level {
+            let gyro = read_triplet(&mut self.imu_dev, LSM9DS1_OUT_X_L_G)?;
+            let accel = read_triplet(&mut self.imu_dev, LSM9DS1_OUT_X_L_XL)?;
+            let age = self.poll_interval() * (level - 1 - i) as u32;
+            samples.push(ImuSample {
+                accel: self.rotate(self.correct_accel(scaled(accel, self.accel_scale))),
+                gyro: self.rotate(self.correct_gyro(scaled(gyro, self.gyro_scale))),
+                mag: None,
+                mag_stale: false,
+                timestamp: now.checked_sub(age).unwrap_or(now),
+            });
+        }
+        Ok(FifoSamples { samples, overrun })
+    }
+
+    /// Returns which of the sensors are turned on.
+    pub fn config(&self) -> ImuConfig {
+        self.config
+    }
+
+    /// Turns the compass, gyroscope and accelerometer on or off, like the
+    /// Python API's `set_imu_config()`. Turning sensors off saves power,
+    /// and turning the compass off keeps motors from upsetting the
+    /// orientation.
+    ///
+    /// Reading a sensor which is off returns `ComponentDisabled`. Without
+    /// the compass the yaw comes from the gyro alone, without the gyro the
+    /// orientation follows the accelerometer and compass more slowly, and
+    /// without the accelerometer there is no orientation at all.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// // Only the accelerometer and gyro.
+    /// sense.imu().unwrap().set_config(false, true, true).unwrap();
+    /// ```
+    ///
+    /// Off the hardware, with the chip mocked:
+    ///
+    /// ```
+    /// use sensehat::{BoxedI2CDevice, Imu, MockI2CDevice, SenseHatError, Settings};
+    ///
+    /// let (accel_gyro, mag) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// accel_gyro.set_register(0x0f, 0x68);
+    /// mag.set_register(0x0f, 0x3d);
+    /// let (ag, m) = (BoxedI2CDevice::new(accel_gyro.clone()), BoxedI2CDevice::new(mag.clone()));
+    /// let mut imu = Imu::from_devices(ag, m, Settings::default()).unwrap();
+    /// let disabled = |result: Result<[f64; 3], SenseHatError>| match result {
+    ///     Err(SenseHatError::ComponentDisabled { .. }) => true,
+    ///     _ => false,
+    /// };
+    ///
+    /// // Everything but the compass, which is powered down.
+    /// accel_gyro.clear_writes();
+    /// mag.clear_writes();
+    /// imu.set_config(false, true, true).unwrap();
+    /// assert_eq!(mag.writes(), [(0x22, 0x03)]);
+    /// assert!(disabled(imu.read_magnetometer()));
+    /// assert_eq!(imu.sample_rate(), 119);
+    ///
+    /// // Only the compass. The gyro is powered down, and the
+    /// // accelerometer and its axes too.
+    /// accel_gyro.clear_writes();
+    /// imu.set_config(true, false, false).unwrap();
+    /// assert_eq!(accel_gyro.writes(), [(0x10, 0x00), (0x20, 0x00), (0x1f, 0x00)]);
+    /// assert!(disabled(imu.read_gyroscope()));
+    /// assert!(disabled(imu.read_accelerometer()));
+    /// assert!(imu.try_read_sample().is_err());
+    /// assert_eq!(imu.sample_rate(), 20);
+    ///
+    /// // Everything back on.
+    /// accel_gyro.clear_writes();
+    /// imu.set_config(true, true, true).unwrap();
+    /// assert_eq!(
+    ///     accel_gyro.writes(),
+    ///     [(0x10, 0x69), (0x12, 0x04), (0x20, 0x7f), (0x1f, 0x38)]
+    /// );
+    /// assert!(imu.read_accelerometer().unwrap_err().is_not_ready());
+    ///
+    /// // Errors turning a sensor on or off are returned.
+    /// mag.set_failing(true);
+    /// assert!(imu.set_config(false, true, true).is_err());
+    /// ```
+    pub fn set_config(&mut self, compass: bool, gyro: bool, accel: bool) -> SenseHatResult<()> {
+        self.config = ImuConfig {
+            compass,
+            gyro,
+            accel,
+        };
+        if !compass {
+            self.last_mag = None;
+        }
+        self.north_primed = false;
+        self.init_gyro()?;
+        self.init_accel()?;
+        self.init_compass()?;
+        self.update_sample_rate();
+        Ok(())
+    }
+
+    /// Returns the acceleration along the X, Y and Z axes, in g.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let [x, y, z] = sense.imu().unwrap().read_accelerometer().unwrap();
+    /// println!("x: {:.2}g, y: {:.2}g, z: {:.2}g", x, y, z);
+    /// ```
+    ///
+    /// Off the hardware, with the chip mocked: initialising it sets the
+    /// registers RTIMULib does, and readings are scaled for the range.
+    ///
+    /// ```
+    /// use sensehat::{AccelFullScaleRange, BoxedI2CDevice, Imu, MockI2CDevice, Settings};
+    ///
+    /// let (accel_gyro, mag) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// accel_gyro.set_register(0x0f, 0x68);
+    /// mag.set_register(0x0f, 0x3d);
+    /// let devices = || {
+    ///     (BoxedI2CDevice::new(accel_gyro.clone()), BoxedI2CDevice::new(mag.clone()))
+    /// };
+    /// let (ag, m) = devices();
+    /// let mut imu = Imu::from_devices(ag, m, Settings::default()).unwrap();
+    /// // CTRL_REG8, then the gyro at 119Hz and 500dps, then the
+    /// // accelerometer at 119Hz and 8g.
+    /// assert_eq!(
+    ///     accel_gyro.writes(),
+    ///     [(0x22, 0x44), (0x10, 0x69), (0x12, 0x04), (0x20, 0x7f), (0x1f, 0x38)]
+    /// );
+    ///
+    /// // No new data yet.
+    /// assert!(imu.read_accelerometer().unwrap_err().is_not_ready());
+    ///
+    /// // 1g down the Z axis, and -0.5g along X, at 8g.
+    /// accel_gyro.set_register(0x17, 0x01);
+    /// accel_gyro.set_registers(0x28, &[0x00, 0xf8, 0x00, 0x00, 0x00, 0x10]);
+    /// assert_eq!(imu.read_accelerometer().unwrap(), [-2048.0 * 0.000244, 0.0, 4096.0 * 0.000244]);
+    ///
+    /// // The extremes, in every range.
+    /// accel_gyro.set_registers(0x28, &[0x00, 0x80, 0xff, 0x7f, 0x00, 0x00]);
+    /// for &(fsr, scale) in &[
+    ///     (AccelFullScaleRange::G2, 0.000061),
+    ///     (AccelFullScaleRange::G4, 0.000122),
+    ///     (AccelFullScaleRange::G8, 0.000244),
+    ///     (AccelFullScaleRange::G16, 0.000732),
+    /// ] {
+    ///     imu.set_accel_fsr(fsr).unwrap();
+    ///     assert_eq!(imu.read_accel_fsr().unwrap(), fsr);
+    ///     assert_eq!(imu.read_accelerometer().unwrap(), [-32768.0 * scale, 32767.0 * scale, 0.0]);
+    /// }
+    ///
+    /// // The chip stopping answering.
+    /// accel_gyro.set_failing(true);
+    /// assert!(imu.read_accelerometer().is_err());
+    /// let (ag, m) = devices();
+    /// assert!(Imu::from_devices(ag, m, Settings::default()).is_err());
+    /// ```
+    pub fn read_accelerometer(&mut self) -> SenseHatResult<[f64; 3]> {
+        self.check_enabled(self.config.accel, Measurement::Acceleration)?;
+        let status = self.imu_dev.smbus_read_byte_data(LSM9DS1_STATUS_REG)?;
+        if (status & 1) != 0 {
+            let raw = read_triplet(&mut self.imu_dev, LSM9DS1_OUT_X_L_XL)?;
+            Ok(self.rotate(self.correct_accel(scaled(raw, self.accel_scale))))
+        } else {
+            Err(SenseHatError::NotReady {
+                sensor: Sensor::Imu,
+                measurement: Measurement::Acceleration,
+            })
+        }
+    }
+
+    /// Like `read_accelerometer`, but returns typed accelerations, scaled
+    /// for the configured `AccelFullScaleRange` like it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let accel = sense.imu().unwrap().get_accelerometer().unwrap();
+    /// println!("{}, total {}", accel, accel.magnitude());
+    /// ```
+    pub fn get_accelerometer(&mut self) -> SenseHatResult<Acceleration3d> {
+        Ok(Acceleration3d::from_g(self.read_accelerometer()?))
+    }
+
+    /// Returns the acceleration along the X, Y and Z axes in g, like the
+    /// Python library's `get_accelerometer_raw`. It's the same as
+    /// `read_accelerometer`.
+    pub fn get_accelerometer_raw(&mut self) -> SenseHatResult<[f64; 3]> {
+        self.read_accelerometer()
+    }
+
+    /// Returns the temperature from the IMU's own sensor. It's meant for
+    /// compensating the IMU's readings rather than measuring the room, and
+    /// reads warmer as the Raspberry Pi heats up the Sense Hat, but it's a
+    /// useful cross-check against the other temperature sensors.
+    ///
+    /// It only updates while the accelerometer or gyro is on.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let temp = sense.imu().unwrap().get_temperature().unwrap();
+    /// println!("IMU temperature: {:.1}C", temp.as_celsius());
+    /// ```
+    ///
+    /// Off the hardware, with the chip mocked. The reading is sixteenths of
+    /// a degree from 25C.
+    ///
+    /// ```
+    /// use sensehat::{BoxedI2CDevice, Imu, MockI2CDevice, SenseHatError, Settings};
+    ///
+    /// let (accel_gyro, mag) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// accel_gyro.set_register(0x0f, 0x68);
+    /// mag.set_register(0x0f, 0x3d);
+    /// let (ag, m) = (BoxedI2CDevice::new(accel_gyro.clone()), BoxedI2CDevice::new(mag));
+    /// let mut imu = Imu::from_devices(ag, m, Settings::default()).unwrap();
+    ///
+    /// assert!(imu.get_temperature().unwrap_err().is_not_ready());
+    /// accel_gyro.set_register(0x17, 0x04);
+    /// for &(raw, celsius) in &[
+    ///     (0i16, 25.0),
+    ///     (8, 25.5),
+    ///     (-1, 24.9375),
+    ///     (-400, 0.0),
+    ///     (-560, -10.0),
+    ///     (i16::MAX, 25.0 + 32767.0 / 16.0),
+    ///     (i16::MIN, 25.0 - 2048.0),
+    /// ] {
+    ///     accel_gyro.set_registers(0x15, &raw.to_le_bytes());
+    ///     assert_eq!(imu.get_temperature().unwrap().as_celsius(), celsius);
+    /// }
+    ///
+    /// // It needs the accelerometer or the gyro on.
+    /// imu.set_config(true, false, true).unwrap();
+    /// assert!(imu.get_temperature().is_ok());
+    /// imu.set_config(true, false, false).unwrap();
+    /// match imu.get_temperature() {
+    ///     Err(SenseHatError::ComponentDisabled { .. }) => {}
+    ///     other => panic!("{:?}", other.map(|t| t.as_celsius())),
+    /// }
+    /// ```
+    pub fn get_temperature(&mut self) -> SenseHatResult<Temperature> {
+        self.check_enabled(self.config.accel || self.config.gyro, Measurement::Temperature)?;
+        let status = self.imu_dev.smbus_read_byte_data(LSM9DS1_STATUS_REG)?;
+        if (status & 4) != 0 {
+            let buf = self.imu_dev.smbus_read_i2c_block_data(LSM9DS1_OUT_TEMP_L, 2)?;
+            if buf.len() < 2 {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "short I2C read").into());
+            }
+            let raw = LittleEndian::read_i16(&buf);
+            Ok(Temperature::from_celsius(
+                raw as f64 / LSM9DS1_TEMP_SCALE + LSM9DS1_TEMP_OFFSET,
+            ))
+        } else {
+            Err(SenseHatError::NotReady {
+                sensor: Sensor::Imu,
+                measurement: Measurement::Temperature,
+            })
+        }
+    }
+
+    /// Helper function.
+    ///
+    /// Reads the accelerometer, and the gyro without its bias corrected if
+    /// it's on, one straight after the other. Returns `None` unless both
+    /// have new data. The gyro reading is zero if it's off.
+    fn read_accel_gyro(&mut self) -> SenseHatResult<Option<([f64; 3], [f64; 3])>> {
+        self.check_enabled(self.config.accel, Measurement::Acceleration)?;
+        let status = self.imu_dev.smbus_read_byte_data(LSM9DS1_STATUS_REG)?;
+        let gyro_ready = !self.config.gyro || (status & 2) != 0;
+        if (status & 1) == 0 || !gyro_ready {
+            return Ok(None);
+        }
+        let gyro = if self.config.gyro {
+            let raw = read_triplet(&mut self.imu_dev, LSM9DS1_OUT_X_L_G)?;
+            scaled(raw, self.gyro_scale)
+        } else {
+            [0.0; 3]
+        };
+        let raw = read_triplet(&mut self.imu_dev, LSM9DS1_OUT_X_L_XL)?;
+        let accel = self.rotate(self.correct_accel(scaled(raw, self.accel_scale)));
+        Ok(Some((accel, gyro)))
+    }
+
+    /// Returns the angular rate around the X, Y and Z axes, in degrees per
+    /// second.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let [x, y, z] = sense.imu().unwrap().read_gyroscope().unwrap();
+    /// println!("x: {:.1}dps, y: {:.1}dps, z: {:.1}dps", x, y, z);
+    /// ```
+    ///
+    /// Off the hardware, with the chip mocked:
+    ///
+    /// ```
+    /// use sensehat::{BoxedI2CDevice, GyroFullScaleRange, Imu, MockI2CDevice, Settings};
+    ///
+    /// let (accel_gyro, mag) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// accel_gyro.set_register(0x0f, 0x68);
+    /// mag.set_register(0x0f, 0x3d);
+    /// let (ag, m) = (BoxedI2CDevice::new(accel_gyro.clone()), BoxedI2CDevice::new(mag));
+    /// let mut imu = Imu::from_devices(ag, m, Settings::default()).unwrap();
+    ///
+    /// // Only the accelerometer has new data.
+    /// accel_gyro.set_register(0x17, 0x01);
+    /// assert!(imu.read_gyroscope().unwrap_err().is_not_ready());
+    ///
+    /// accel_gyro.set_register(0x17, 0x02);
+    /// accel_gyro.set_registers(0x18, &[0x00, 0x80, 0xff, 0x7f, 0x18, 0xfc]);
+    /// for &(fsr, scale) in &[
+    ///     (GyroFullScaleRange::Dps250, 0.00875),
+    ///     (GyroFullScaleRange::Dps500, 0.0175),
+    ///     (GyroFullScaleRange::Dps2000, 0.07),
+    /// ] {
+    ///     imu.set_gyro_fsr(fsr).unwrap();
+    ///     assert_eq!(accel_gyro.register(0x10), 0x61 | fsr_bits(fsr));
+    ///     assert_eq!(
+    ///         imu.read_gyroscope().unwrap(),
+    ///         [-32768.0 * scale, 32767.0 * scale, -1000.0 * scale]
+    ///     );
+    /// }
+    ///
+    /// // Bits the LSM9DS1 doesn't use.
+    /// accel_gyro.set_register(0x10, 0x71);
+    /// assert!(imu.read_gyro_fsr().is_err());
+    ///
+    /// accel_gyro.set_failing(true);
+    /// assert!(imu.read_gyroscope().is_err());
+    ///
+    /// fn fsr_bits(fsr: GyroFullScaleRange) -> u8 {
+    ///     match fsr {
+    ///         GyroFullScaleRange::Dps250 => 0x00,
+    ///         GyroFullScaleRange::Dps500 => 0x08,
+    ///         GyroFullScaleRange::Dps2000 => 0x18,
+    ///     }
+    /// }
+    /// ```
+    pub fn read_gyroscope(&mut self) -> SenseHatResult<[f64; 3]> {
+        let raw = self.read_gyroscope_uncorrected()?;
+        Ok(self.rotate(self.correct_gyro(raw)))
+    }
+
+    /// Returns the gyro bias learned so far, in degrees per second, which
+    /// is subtracted from every gyro reading.
+    ///
+    /// The bias is learned from the samples read by `try_read_sample`,
+    /// `read_sample_blocking` and `get_orientation` while the Sense Hat is
+    /// stationary: quickly for the first few seconds, then slowly to follow
+    /// drift.
+    ///
+    /// # Example
+    ///
+    /// With the chip mocked, sitting still with a gyro which reads 2dps
+    /// around Z:
+    ///
+    /// ```
+    /// use sensehat::{BoxedI2CDevice, Imu, MockI2CDevice, Settings};
+    ///
+    /// let (accel_gyro, mag) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// accel_gyro.set_register(0x0f, 0x68);
+    /// mag.set_register(0x0f, 0x3d);
+    /// let (ag, m) = (BoxedI2CDevice::new(accel_gyro.clone()), BoxedI2CDevice::new(mag));
+    /// let mut imu = Imu::from_devices(ag, m, Settings::default()).unwrap();
+    /// let gyro = |raw_z: i16| {
+    ///     let [l, h] = raw_z.to_le_bytes();
+    ///     accel_gyro.set_registers(0x18, &[0x00, 0x00, 0x00, 0x00, l, h]);
+    /// };
+    /// accel_gyro.set_register(0x17, 0x03);
+    /// // 1g down Z at 8g.
+    /// accel_gyro.set_registers(0x28, &[0x00, 0x00, 0x00, 0x00, 0x00, 0x10]);
+    /// gyro(114);
+    /// let drift = 114.0 * 0.0175;
+    ///
+    /// // Rapid learning, 2 / 119 of the way there at 119Hz for each sample.
+    /// let rapid = 2.0 / 119.0;
+    /// imu.try_read_sample().unwrap();
+    /// assert!((imu.gyro_bias()[2] - drift * rapid).abs() < 1e-12);
+    /// for _ in 1..5 * 119 {
+    ///     imu.try_read_sample().unwrap();
+    /// }
+    /// let learned = imu.gyro_bias()[2];
+    /// assert!((learned - drift).abs() < 1e-3 * drift);
+    /// assert_eq!(imu.gyro_bias()[..2], [0.0, 0.0]);
+    /// assert!(imu.read_gyroscope().unwrap()[2].abs() < 1e-3);
+    ///
+    /// // Then slow learning, 0.01 / 119 of the way.
+    /// gyro(0);
+    /// imu.try_read_sample().unwrap();
+    /// let slow = 0.01 / 119.0;
+    /// assert!((imu.gyro_bias()[2] - learned * (1.0 - slow)).abs() < 1e-12);
+    ///
+    /// // Turning faster than a drift could be isn't learned.
+    /// let before = imu.gyro_bias();
+    /// gyro(1143);
+    /// imu.try_read_sample().unwrap();
+    /// assert_eq!(imu.gyro_bias(), before);
+    ///
+    /// imu.reset_gyro_bias();
+    /// assert_eq!(imu.gyro_bias(), [0.0; 3]);
+    /// gyro(114);
+    /// imu.try_read_sample().unwrap();
+    /// assert!((imu.gyro_bias()[2] - drift * rapid).abs() < 1e-12);
+    /// ```
+    pub fn gyro_bias(&self) -> [f64; 3] {
+        self.gyro_bias
+    }
+
+    /// Forgets the learned gyro bias, and starts learning it quickly again.
+    pub fn reset_gyro_bias(&mut self) {
+        self.gyro_bias = [0.0; 3];
+        self.gyro_sample_count = 0;
+    }
+
+    /// Helper function.
+    ///
+    /// Reads the gyro without subtracting the bias.
+    fn read_gyroscope_uncorrected(&mut self) -> SenseHatResult<[f64; 3]> {
+        self.check_enabled(self.config.gyro, Measurement::AngularRate)?;
+        let status = self.imu_dev.smbus_read_byte_data(LSM9DS1_STATUS_REG)?;
+        if (status & 2) != 0 {
+            let raw = read_triplet(&mut self.imu_dev, LSM9DS1_OUT_X_L_G)?;
+            Ok(scaled(raw, self.gyro_scale))
+        } else {
+            Err(SenseHatError::NotReady {
+                sensor: Sensor::Imu,
+                measurement: Measurement::AngularRate,
+            })
+        }
+    }
+
+    /// Like `read_gyroscope`, but returns typed angular velocities.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let rates = sense.imu().unwrap().get_gyroscope().unwrap();
+    /// println!("{}", rates);
+    /// ```
+    pub fn get_gyroscope(&mut self) -> SenseHatResult<AngularVelocity3d> {
+        Ok(AngularVelocity3d::from_degrees_per_second(self.read_gyroscope()?))
+    }
+
+    /// Returns the angular rate around the X, Y and Z axes in degrees per
+    /// second, like the Python library's `get_gyroscope_raw`. It's the same
+    /// as `read_gyroscope`.
+    pub fn get_gyroscope_raw(&mut self) -> SenseHatResult<[f64; 3]> {
+        self.read_gyroscope()
+    }
+
+    /// Returns the magnetic field along the X, Y and Z axes, in microtesla,
+    /// with the compass calibration applied.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let [x, y, z] = sense.imu().unwrap().read_magnetometer().unwrap();
+    /// println!("x: {:.1}uT, y: {:.1}uT, z: {:.1}uT", x, y, z);
+    /// ```
+    ///
+    /// Off the hardware, with both of the chip's I2C devices mocked:
+    ///
+    /// ```
+    /// use sensehat::{
+    ///     BoxedI2CDevice, CompassFullScaleRange, Imu, MockI2CDevice, SenseHatError, Settings,
+    /// };
+    ///
+    /// let (accel_gyro, mag) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// accel_gyro.set_register(0x0f, 0x68);
+    /// mag.set_register(0x0f, 0x3d);
+    /// let devices = || {
+    ///     (BoxedI2CDevice::new(accel_gyro.clone()), BoxedI2CDevice::new(mag.clone()))
+    /// };
+    /// let (ag, m) = devices();
+    /// let mut imu = Imu::from_devices(ag, m, Settings::default()).unwrap();
+    /// // 20Hz, 4 gauss and continuous conversion.
+    /// assert_eq!(mag.writes(), [(0x20, 0x14), (0x21, 0x00), (0x22, 0x00)]);
+    ///
+    /// assert!(imu.read_magnetometer().unwrap_err().is_not_ready());
+    ///
+    /// mag.set_register(0x27, 0x08);
+    /// mag.set_registers(0x28, &[0xe8, 0x03, 0x18, 0xfc, 0x00, 0x00]);
+    /// for &(fsr, scale) in &[
+    ///     (CompassFullScaleRange::uT_4, 0.014),
+    ///     (CompassFullScaleRange::uT_8, 0.029),
+    ///     (CompassFullScaleRange::uT_12, 0.043),
+    ///     (CompassFullScaleRange::uT_16, 0.058),
+    /// ] {
+    ///     imu.set_compass_fsr(fsr).unwrap();
+    ///     assert_eq!(imu.read_compass_fsr().unwrap(), fsr);
+    ///     assert_eq!(imu.read_magnetometer().unwrap(), [1000.0 * scale, -1000.0 * scale, 0.0]);
+    /// }
+    ///
+    /// // Something other than the LSM9DS1's magnetometer at its address.
+    /// mag.set_register(0x0f, 0x42);
+    /// let (ag, m) = devices();
+    /// match Imu::from_devices(ag, m, Settings::default()) {
+    ///     Err(SenseHatError::UnexpectedDevice { address: 0x1c, id: 0x42 }) => {}
+    ///     Err(err) => panic!("{}", err),
+    ///     Ok(_) => panic!("initialised the wrong magnetometer"),
+    /// }
+    ///
+    /// mag.set_failing(true);
+    /// assert!(imu.read_magnetometer().is_err());
+    /// ```
+    pub fn read_magnetometer(&mut self) -> SenseHatResult<[f64; 3]> {
+        let raw = self.read_magnetometer_uncorrected()?;
+        if self.compass_calibration_mode {
+            Ok(raw)
+        } else {
+            let corrected = self.compass_calibration().apply(raw);
+            match self.compass_ellipsoid {
+                Some(ellipsoid) => Ok(self.rotate(ellipsoid.apply(corrected))),
+                None => Ok(self.rotate(corrected)),
+            }
+        }
+    }
+
+    /// Returns how the IMU is mounted.
+    pub fn axis_rotation(&self) -> AxisRotation {
+        self.rotation
+    }
+
+    /// Sets how the IMU is mounted, for boards mounted upside down or clone
+    /// boards with the chip turned round. Every accelerometer, gyroscope
+    /// and magnetometer reading is rotated to match, before it's fused.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{AxisRotation, SenseHat};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// // The Sense Hat is hanging upside down.
+    /// sense.imu().unwrap().set_axis_rotation(AxisRotation::XNorthYWest);
+    /// ```
+    ///
+    /// Off the hardware, with the chip mocked, every rotation is a
+    /// different proper rotation of the axes, and it's applied to each
+    /// sensor before fusion:
+    ///
+    /// ```
+    /// use sensehat::AxisRotation::*;
+    /// use sensehat::{BoxedI2CDevice, Imu, MockI2CDevice, Settings};
+    ///
+    /// let (accel_gyro, mag) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// accel_gyro.set_register(0x0f, 0x68);
+    /// mag.set_register(0x0f, 0x3d);
+    /// let make = || {
+    ///     let ag = BoxedI2CDevice::new(accel_gyro.clone());
+    ///     Imu::from_devices(ag, BoxedI2CDevice::new(mag.clone()), Settings::default()).unwrap()
+    /// };
+    /// let raw = |v: [i16; 3]| -> Vec<u8> { v.iter().flat_map(|n| n.to_le_bytes()).collect() };
+    /// accel_gyro.set_register(0x17, 0x03);
+    /// mag.set_register(0x27, 0x08);
+    /// let mut imu = make();
+    ///
+    /// let rotations = [
+    ///     XNorthYEast, XEastYSouth, XSouthYWest, XWestYNorth, XNorthYWest, XEastYNorth,
+    ///     XSouthYEast, XWestYSouth, XUpYNorth, XUpYEast, XUpYSouth, XUpYWest, XDownYNorth,
+    ///     XDownYEast, XDownYSouth, XDownYWest, XNorthYUp, XEastYUp, XSouthYUp, XWestYUp,
+    ///     XNorthYDown, XEastYDown, XSouthYDown, XWestYDown,
+    /// ];
+    /// let mut matrices = Vec::new();
+    /// for &rotation in &rotations {
+    ///     imu.set_axis_rotation(rotation);
+    ///     // Where each axis goes, so the columns of the matrix.
+    ///     let mut m = [[0i32; 3]; 3];
+    ///     for (column, axis) in [[1, 0, 0], [0, 1, 0], [0, 0, 1]].iter().enumerate() {
+    ///         let [x, y, z] = *axis;
+    ///         accel_gyro.set_registers(0x28, &raw([x * 4096, y * 4096, z * 4096]));
+    ///         let v = imu.read_accelerometer().unwrap();
+    ///         for row in 0..3 {
+    ///             m[row][column] = (v[row] / (4096.0 * 0.000244)).round() as i32;
+    ///         }
+    ///     }
+    ///     let det = m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+    ///         - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+    ///         + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0]);
+    ///     assert_eq!(det, 1, "{:?}", rotation);
+    ///     for i in 0..3 {
+    ///         assert_eq!((0..3).map(|j| m[i][j].abs()).sum::<i32>(), 1, "{:?}", rotation);
+    ///         assert_eq!((0..3).map(|j| m[j][i].abs()).sum::<i32>(), 1, "{:?}", rotation);
+    ///     }
+    ///     assert!(!matrices.contains(&m), "{:?}", rotation);
+    ///     matrices.push(m);
+    /// }
+    /// assert_eq!(matrices[0], [[1, 0, 0], [0, 1, 0], [0, 0, 1]]);
+    ///
+    /// // Upside down, every sensor's Y and Z are turned round.
+    /// imu.set_axis_rotation(XNorthYWest);
+    /// accel_gyro.set_registers(0x18, &raw([1143, 2286, 3429]));
+    /// accel_gyro.set_registers(0x28, &raw([0, 0, -4096]));
+    /// mag.set_registers(0x28, &raw([1000, 2000, 3000]));
+    /// let sample = imu.try_read_sample().unwrap().unwrap();
+    /// assert_eq!(sample.accel, [0.0, 0.0, 4096.0 * 0.000244]);
+    /// assert_eq!(sample.gyro, [1143.0 * 0.0175, -2286.0 * 0.0175, -3429.0 * 0.0175]);
+    /// assert_eq!(sample.mag, Some([1000.0 * 0.014, -2000.0 * 0.014, -3000.0 * 0.014]));
+    ///
+    /// // So the fused orientation of a board hanging upside down is level.
+    /// let roll = |rotation| {
+    ///     let mut imu = make();
+    ///     imu.set_axis_rotation(rotation);
+    ///     imu.get_orientation().unwrap().degrees().1
+    /// };
+    /// assert!((roll(XNorthYEast).abs() - 180.0).abs() < 1e-6);
+    /// assert!(roll(XNorthYWest).abs() < 1e-6);
+    /// ```
+    pub fn set_axis_rotation(&mut self, rotation: AxisRotation) {
+        self.rotation = rotation;
+    }
+
+    /// Returns the compass calibration applied to magnetometer readings.
+    pub fn compass_calibration(&self) -> CompassCalibration {
+        CompassCalibration {
+            offset: self.compass_cal_offset,
+            scale: self.compass_cal_scale,
+        }
+    }
+
+    /// Applies a compass calibration to magnetometer readings from now on.
+    pub fn set_compass_calibration(&mut self, calibration: CompassCalibration) {
+        self.compass_cal_offset = calibration.offset;
+        self.compass_cal_scale = calibration.scale;
+    }
+
+    /// Collects magnetometer samples for `duration` while the board is
+    /// rotated in every direction, then works out and applies the compass
+    /// calibration from them.
+    ///
+    /// Returns `InsufficientCoverage` if the board wasn't rotated enough,
+    /// leaving the previous calibration in place.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let imu = sense.imu().unwrap();
+    /// println!("Rotate the Sense Hat in every direction...");
+    /// imu.calibrate_compass(Duration::from_secs(30)).unwrap();
+    /// imu.save_calibration("compass.cal").unwrap();
+    /// ```
+    pub fn calibrate_compass(&mut self, duration: Duration) -> SenseHatResult<CompassCalibration> {
+        self.compass_calibration_mode = true;
+        let samples = self.collect_compass_samples(duration);
+        self.compass_calibration_mode = false;
+
+        let calibration = CompassCalibration::from_samples(&samples?)?;
+        self.set_compass_calibration(calibration);
+        Ok(calibration)
+    }
+
+    /// Saves the compass calibration to a file, so it can be loaded with
+    /// `load_calibration` after a restart.
+    ///
+    /// The file is plain text, with `#` comments and one `key=value` line
+    /// for each of `compass_offset_x`, `compass_offset_y`,
+    /// `compass_offset_z`, `compass_scale_x`, `compass_scale_y` and
+    /// `compass_scale_z`.
+    pub fn save_calibration<P: AsRef<Path>>(&self, path: P) -> SenseHatResult<()> {
+        self.compass_calibration().save(path.as_ref())
+    }
+
+    /// Loads and applies a compass calibration saved by `save_calibration`.
+    /// Values missing from the file are left uncorrected.
+    pub fn load_calibration<P: AsRef<Path>>(&mut self, path: P) -> SenseHatResult<CompassCalibration> {
+        let calibration = CompassCalibration::load(path.as_ref())?;
+        self.set_compass_calibration(calibration);
+        Ok(calibration)
+    }
+
+    /// Returns the ellipsoid correction applied to magnetometer readings
+    /// after the compass calibration, if there is one.
+    pub fn compass_ellipsoid(&self) -> Option<CompassEllipsoid> {
+        self.compass_ellipsoid
+    }
+
+    /// Applies an ellipsoid correction to magnetometer readings from now
+    /// on, after the compass calibration, or stops applying one if None.
+    pub fn set_compass_ellipsoid(&mut self, ellipsoid: Option<CompassEllipsoid>) {
+        self.compass_ellipsoid = ellipsoid;
+    }
+
+    /// Returns the calibration applied to accelerometer readings, if there
+    /// is one.
+    pub fn accel_calibration(&self) -> Option<AccelCalibration> {
+        self.accel_calibration
+    }
+
+    /// Applies a calibration to accelerometer readings from now on, or
+    /// stops applying one if None.
+    pub fn set_accel_calibration(&mut self, calibration: Option<AccelCalibration>) {
+        self.accel_calibration = calibration;
+    }
+
+    /// Loads and applies the calibration from an `RTIMULib.ini`, as left in
+    /// the home directory by RTIMULib's calibration tool, so boards
+    /// calibrated for the Python library don't need calibrating again.
+    ///
+    /// The compass calibration comes from the smallest and largest
+    /// readings, with the ellipsoid correction on top if there is one, and
+    /// the accelerometer calibration from its smallest and largest
+    /// readings. Anything the file marks not valid is turned off, as it is
+    /// for RTIMULib. If the file can't be parsed, nothing is changed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let imu = sense.imu().unwrap();
+    /// imu.load_rtimulib_ini("/home/pi/.config/sense_hat/RTIMULib.ini").unwrap();
+    /// println!("{:?}", imu.compass_calibration());
+    /// ```
+    pub fn load_rtimulib_ini<P: AsRef<Path>>(&mut self, path: P) -> SenseHatResult<()> {
+        let ini = RtimulibIni::load(path)?;
+        let compass = ini.compass_calibration()?;
+        let ellipsoid = ini.compass_ellipsoid()?;
+        let accel = ini.accel_calibration()?;
+        // RTIMULib only uses the ellipsoid on top of the min/max calibration.
+        self.set_compass_ellipsoid(ellipsoid.filter(|_| compass.is_some()));
+        self.set_compass_calibration(compass.unwrap_or_default());
+        self.set_accel_calibration(accel);
+        Ok(())
+    }
+
+    /// Saves the calibration to an `RTIMULib.ini` which RTIMULib can load,
+    /// as `RtimulibIni::set_compass_calibration` describes. If the file is
+    /// already there, only the calibration settings are changed, and
+    /// everything else in it is kept.
+    pub fn save_rtimulib_ini<P: AsRef<Path>>(&self, path: P) -> SenseHatResult<()> {
+        let path = path.as_ref();
+        let mut ini = match RtimulibIni::load(path) {
+            Ok(ini) => ini,
+            Err(SenseHatError::IoError(ref err)) if err.kind() == io::ErrorKind::NotFound => {
+                RtimulibIni::new()
+            }
+            Err(err) => return Err(err),
+        };
+        let compass = self.compass_calibration();
+        let compass = Some(compass).filter(|compass| *compass != CompassCalibration::default());
+        ini.set_compass_calibration(compass.as_ref());
+        ini.set_compass_ellipsoid(self.compass_ellipsoid.as_ref());
+        ini.set_accel_calibration(self.accel_calibration.as_ref());
+        ini.save(path)
+    }
+
+    /// Helper function.
+    ///
+    /// Reads the magnetometer for `duration`.
+    fn collect_compass_samples(&mut self, duration: Duration) -> SenseHatResult<Vec<[f64; 3]>> {
+        let start = Instant::now();
+        let mut samples = Vec::new();
+        while start.elapsed() < duration {
+            match ready(self.read_magnetometer_uncorrected())? {
+                Some(sample) => samples.push(sample),
+                None => thread::sleep(CALIBRATION_POLL_INTERVAL),
+            }
+        }
+        Ok(samples)
+    }
+
+    /// Helper function.
+    ///
+    /// Reads the magnetometer without applying the calibration.
+    fn read_magnetometer_uncorrected(&mut self) -> SenseHatResult<[f64; 3]> {
+        self.check_enabled(self.config.compass, Measurement::MagneticField)?;
+        let status = self.mag_dev.smbus_read_byte_data(LSM9DS1_MAG_STATUS)?;
+        if (status & 0x08) != 0 {
+            let register = LSM9DS1_MAG_OUT_X_L | LSM9DS1_MAG_AUTO_INCREMENT;
+            let raw = read_triplet(&mut self.mag_dev, register)?;
+            Ok(scaled(raw, self.compass_scale))
+        } else {
+            Err(SenseHatError::NotReady {
+                sensor: Sensor::Imu,
+                measurement: Measurement::MagneticField,
+            })
+        }
+    }
+
+    /// Returns the orientation of the Sense Hat, like the Python API's
+    /// `get_orientation()`.
+    ///
+    /// Each call fuses the latest sample into the estimate, so call it
+    /// regularly for the gyro integration to track movement.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let orientation = sense.imu().unwrap().get_orientation().unwrap();
+    /// println!("pitch: {}, roll: {}, yaw: {}",
+    ///          orientation.pitch, orientation.roll, orientation.yaw);
+    /// ```
+    pub fn get_orientation(&mut self) -> SenseHatResult<Orientation3d> {
+        self.try_read_sample()?;
+        self.current_orientation()
+    }
+
+    /// Helper function.
+    ///
+    /// Returns the orientation as `get_orientation` does, without reading
+    /// another sample.
+    pub(crate) fn current_orientation(&self) -> SenseHatResult<Orientation3d> {
+        if let Some(reference) = self.tare {
+            // Relative to the tare there's no north for the declination to
+            // be from either.
+            let orientation = self.fused_quaternion()?.relative_to(reference).orientation();
+            return Ok(Orientation3d {
+                yaw_is_relative: true,
+                ..orientation
+            });
+        }
+        let orientation = self.fusion.orientation().ok_or(SenseHatError::NotReady {
+            sensor: Sensor::Imu,
+            measurement: Measurement::Orientation,
+        })?;
+        if orientation.yaw_is_relative {
+            // There's no north for the declination to be from.
+            return Ok(orientation);
+        }
+        Ok(orientation.turn_yaw(Angle::from_degrees(self.declination)))
+    }
+
+    /// Returns the compass heading in degrees, from 0 up to 360, clockwise
+    /// from north. That's true north if `set_magnetic_declination` has
+    /// been set, otherwise magnetic north.
+    ///
+    /// Like `get_orientation`, each call fuses the latest sample into the
+    /// estimate.
+    pub fn get_compass_heading(&mut self) -> SenseHatResult<f64> {
+        let yaw = self.get_orientation()?.yaw.as_degrees();
+        Ok(true_heading(yaw, 0.0))
+    }
+
+    /// Returns the compass heading like `get_compass_heading`, along with
+    /// whether the compass has been calibrated, so a caller can warn that
+    /// it might be out.
+    ///
+    /// Returns `MagneticInterference` if the magnetic field is outside the
+    /// `magnetic_field_limits`, say from a magnet nearby, as the heading
+    /// would be nonsense. The compass counts as calibrated if it has a
+    /// calibration other than the default or an ellipsoid correction.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let checked = sense.imu().unwrap().get_compass_heading_checked().unwrap();
+    /// if !checked.calibrated {
+    ///     println!("calibrate the compass for better headings");
+    /// }
+    /// println!("heading: {:.0}°", checked.heading);
+    /// ```
+    pub fn get_compass_heading_checked(&mut self) -> SenseHatResult<CheckedHeading> {
+        let heading = self.get_compass_heading()?;
+        self.check_enabled(self.config.compass, Measurement::MagneticField)?;
+        let field = self.last_mag.ok_or(SenseHatError::NotReady {
+            sensor: Sensor::Imu,
+            measurement: Measurement::MagneticField,
+        })?;
+        let calibrated =
+            !self.compass_calibration().is_identity() || self.compass_ellipsoid.is_some();
+        let magnitude = (field[0] * field[0] + field[1] * field[1] + field[2] * field[2]).sqrt();
+        match check_compass(field, calibrated, &self.field_limits) {
+            CompassStatus::Interference { magnitude } => {
+                Err(SenseHatError::MagneticInterference { magnitude })
+            }
+            status => Ok(CheckedHeading {
+                heading,
+                calibrated: status == CompassStatus::Calibrated,
+                magnitude,
+            }),
+        }
+    }
+
+    /// Like `get_compass_heading_checked`, but returns
+    /// `UncalibratedCompass` rather than a heading which might be out.
+    pub fn get_compass_heading_strict(&mut self) -> SenseHatResult<f64> {
+        let checked = self.get_compass_heading_checked()?;
+        if !checked.calibrated {
+            return Err(SenseHatError::UncalibratedCompass);
+        }
+        Ok(checked.heading)
+    }
+
+    /// Returns the field strengths `get_compass_heading_checked` takes to
+    /// be the Earth's.
+    pub fn magnetic_field_limits(&self) -> MagneticFieldLimits {
+        self.field_limits
+    }
+
+    /// Changes the field strengths `get_compass_heading_checked` takes to
+    /// be the Earth's, say for somewhere the field is unusually strong or
+    /// weak.
+    pub fn set_magnetic_field_limits(&mut self, limits: MagneticFieldLimits) {
+        self.field_limits = limits;
+    }
+
+    /// Returns the compass heading in degrees, from 0 up to 360, like the
+    /// Python API's `get_compass()`, for scripts which just want to know
+    /// which way is north.
+    ///
+    /// Unlike `get_compass_heading`, it makes sure there's a heading to
+    /// give: the compass and accelerometer are turned on if they're off,
+    /// and the orientation uses the magnetometer. The first call, and the
+    /// first after the configuration or fusion changes, starts the fusion
+    /// again and fuses samples until five magnetometer readings have gone
+    /// in, which takes about a quarter of a second at the default 20Hz
+    /// compass rate, and never more than a second, so it always comes
+    /// back. The calls after that are as quick as `get_compass_heading`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// println!("heading: {:.0}°", sense.imu().unwrap().get_north().unwrap());
+    /// ```
+    ///
+    /// Off the hardware, with the chip mocked:
+    ///
+    /// ```
+    /// use sensehat::{BoxedI2CDevice, Imu, ImuConfig, MockI2CDevice, Settings};
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let (accel_gyro, mag) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// accel_gyro.set_register(0x0f, 0x68);
+    /// mag.set_register(0x0f, 0x3d);
+    /// let (ag, m) = (BoxedI2CDevice::new(accel_gyro.clone()), BoxedI2CDevice::new(mag.clone()));
+    /// let mut imu = Imu::from_devices(ag, m, Settings::default()).unwrap();
+    /// // Level and still, with the field along X, or along Y.
+    /// accel_gyro.set_registers(0x28, &[0x00, 0x00, 0x00, 0x00, 0x00, 0x10]);
+    /// mag.set_register(0x27, 0x08);
+    /// let field_along = |x: u8, y: u8| mag.set_registers(0x28, &[0x00, x, 0x00, y, 0x00, 0x00]);
+    /// field_along(0x10, 0x00);
+    ///
+    /// // Nothing to read at all, so it gives up priming after a second.
+    /// let started = Instant::now();
+    /// assert!(imu.get_north().unwrap_err().is_not_ready());
+    /// assert!(started.elapsed() >= Duration::from_secs(1));
+    ///
+    /// // The compass off, and the magnetometer not fused.
+    /// imu.set_config(false, true, true).unwrap();
+    /// imu.set_fusion_inputs(false);
+    /// accel_gyro.set_register(0x17, 0x03);
+    /// let started = Instant::now();
+    /// let along_x = imu.get_north().unwrap();
+    /// assert!(started.elapsed() < Duration::from_secs(1));
+    /// assert_eq!(imu.config(), ImuConfig { compass: true, gyro: true, accel: true });
+    /// assert!(imu.fusion_uses_mag());
+    ///
+    /// // Once primed, the heading follows the field slowly.
+    /// field_along(0x00, 0x10);
+    /// let next = imu.get_north().unwrap();
+    /// assert!(next != along_x);
+    /// // Priming again after a configuration change makes it jump there.
+    /// imu.set_config(true, true, true).unwrap();
+    /// let along_y = imu.get_north().unwrap();
+    /// let turned = (along_y - along_x).rem_euclid(360.0);
+    /// assert!((turned - 90.0).abs() < 1e-6 || (turned - 270.0).abs() < 1e-6, "{}", turned);
+    /// assert!((along_y - next).abs() > 1.0);
+    /// ```
+    pub fn get_north(&mut self) -> SenseHatResult<f64> {
+        if !self.config.compass || !self.config.accel {
+            let gyro = self.config.gyro;
+            self.set_config(true, gyro, true)?;
+        }
+        if !self.fuse_mag {
+            self.set_fusion_inputs(true);
+        }
+        if !self.north_primed {
+            self.prime_heading()?;
+        }
+        self.get_compass_heading()
+    }
+
+    /// Helper function.
+    ///
+    /// Starts the fusion again, so the first magnetometer reading sets the
+    /// heading rather than pulling it round slowly, and fuses samples until
+    /// enough magnetometer readings have gone in, or the time's up.
+    fn prime_heading(&mut self) -> SenseHatResult<()> {
+        let algorithm = self.fusion_algorithm();
+        self.set_fusion_algorithm(algorithm);
+        let deadline = Instant::now() + NORTH_PRIMING_TIMEOUT;
+        let mut readings = 0;
+        while readings < NORTH_PRIMING_SAMPLES {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            match self.read_sample_blocking(deadline - now) {
+                Ok(sample) if sample.mag.is_some() && !sample.mag_stale => readings += 1,
+                Ok(_) => {}
+                Err(SenseHatError::Timeout) => break,
+                Err(err) => return Err(err),
+            }
+        }
+        self.north_primed = true;
+        Ok(())
+    }
+
+    /// Sets the magnetic declination where the Sense Hat is, in degrees,
+    /// east positive, so the yaw and `get_compass_heading` are from true
+    /// north rather than magnetic north. It's 0 unless set.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let imu = sense.imu().unwrap();
+    /// // Magnetic north is 1.5° west of true north here.
+    /// imu.set_magnetic_declination(-1.5);
+    /// println!("heading: {:.0}°", imu.get_compass_heading().unwrap());
+    /// ```
+    pub fn set_magnetic_declination(&mut self, declination: f64) {
+        self.declination = declination;
+    }
+
+    /// Returns the magnetic declination, in degrees, east positive.
+    pub fn magnetic_declination(&self) -> f64 {
+        self.declination
+    }
+
+    /// Makes `get_compass_heading_smoothed` average the last `window`
+    /// headings with a `HeadingSmoother`, dropping any more than a second
+    /// old. A window of 0 turns smoothing off.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let imu = sense.imu().unwrap();
+    /// imu.set_heading_smoothing(10);
+    /// loop {
+    ///     println!("heading: {:.0}°", imu.get_compass_heading_smoothed().unwrap());
+    /// }
+    /// ```
+    pub fn set_heading_smoothing(&mut self, window: usize) {
+        self.heading_smoother = if window == 0 {
+            None
+        } else {
+            Some(HeadingSmoother::new(window, Some(HEADING_SMOOTHING_MAX_AGE)))
+        };
+    }
+
+    /// Returns how many headings `get_compass_heading_smoothed` averages,
+    /// or 0 if it doesn't.
+    pub fn heading_smoothing(&self) -> usize {
+        self.heading_smoother.as_ref().map_or(0, HeadingSmoother::window)
+    }
+
+    /// Returns the compass heading like `get_compass_heading`, averaged
+    /// with the last few if `set_heading_smoothing` is set.
+    pub fn get_compass_heading_smoothed(&mut self) -> SenseHatResult<f64> {
+        let heading = self.get_compass_heading()?;
+        Ok(match self.heading_smoother {
+            Some(ref mut smoother) => smoother.update(heading, Instant::now()),
+            None => heading,
+        })
+    }
+
+    /// Returns the pitch, roll and yaw in degrees, like the Python API's
+    /// `get_orientation_degrees()`.
+    pub fn get_orientation_degrees(&mut self) -> SenseHatResult<(f64, f64, f64)> {
+        Ok(self.get_orientation()?.degrees())
+    }
+
+    /// Returns the pitch, roll and yaw in radians, like the Python API's
+    /// `get_orientation_radians()`.
+    pub fn get_orientation_radians(&mut self) -> SenseHatResult<(f64, f64, f64)> {
+        Ok(self.get_orientation()?.radians())
+    }
+
+    /// Returns the orientation of the Sense Hat as a quaternion.
+    ///
+    /// Like `get_orientation`, each call fuses the latest sample into the
+    /// estimate.
+    pub fn get_quaternion(&mut self) -> SenseHatResult<Quaternion> {
+        self.try_read_sample()?;
+        self.current_quaternion()
+    }
+
+    /// Helper function.
+    ///
+    /// Returns the orientation as `get_quaternion` does, without reading
+    /// another sample.
+    pub(crate) fn current_quaternion(&self) -> SenseHatResult<Quaternion> {
+        let q = self.fused_quaternion()?;
+        Ok(self.tare.map_or(q, |reference| q.relative_to(reference)))
+    }
+
+    /// Takes the current orientation as the reference, so from now on
+    /// `get_orientation`, `get_quaternion` and the rest report the
+    /// orientation relative to it, for a Sense Hat mounted at an angle.
+    /// The yaw, and so the compass heading, becomes relative to it too.
+    ///
+    /// Like `get_orientation`, it fuses the latest sample into the estimate
+    /// first.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let imu = sense.imu().unwrap();
+    /// imu.tare().unwrap();
+    /// // Level, relative to how it's mounted.
+    /// println!("{:?}", imu.get_orientation_degrees().unwrap());
+    /// imu.save_tare("tare.cal").unwrap();
+    /// ```
+    pub fn tare(&mut self) -> SenseHatResult<()> {
+        self.try_read_sample()?;
+        self.tare = Some(self.fused_quaternion()?);
+        Ok(())
+    }
+
+    /// Stops reporting the orientation relative to a tare.
+    pub fn clear_tare(&mut self) {
+        self.tare = None;
+    }
+
+    /// Returns the orientation set by `tare`, if there is one.
+    pub fn tare_offset(&self) -> Option<Quaternion> {
+        self.tare
+    }
+
+    /// Sets the orientation reported relative to, as `tare` does, or
+    /// clears it if None.
+    pub fn set_tare_offset(&mut self, reference: Option<Quaternion>) {
+        self.tare = reference;
+    }
+
+    /// Saves the tare to a file, so it can be loaded with `load_tare` after
+    /// a restart, in the same format as `save_calibration`, with `tare_w`,
+    /// `tare_x`, `tare_y` and `tare_z` keys. Without a tare it saves no
+    /// rotation, which loads as the same thing.
+    pub fn save_tare<P: AsRef<Path>>(&self, path: P) -> SenseHatResult<()> {
+        let q = self.tare.unwrap_or(Quaternion::IDENTITY);
+        let values = [("tare_w", q.w), ("tare_x", q.x), ("tare_y", q.y), ("tare_z", q.z)];
+        let values: Vec<_> = values.iter().map(|&(key, value)| (key.to_string(), value)).collect();
+        calibration::save_values(path.as_ref(), "Sense Hat orientation tare", &values)
+    }
+
+    /// Loads and applies a tare saved by `save_tare`. Returns an `IoError`
+    /// with `InvalidData` unless all four keys are there, and nothing
+    /// else is.
+    pub fn load_tare<P: AsRef<Path>>(&mut self, path: P) -> SenseHatResult<Quaternion> {
+        let mut q = [None; 4];
+        for (key, value) in calibration::load_values(path.as_ref())? {
+            let index = match key.as_str() {
+                "tare_w" => 0,
+                "tare_x" => 1,
+                "tare_y" => 2,
+                "tare_z" => 3,
+                _ => return Err(calibration::invalid_data(&key)),
+            };
+            q[index] = Some(value);
+        }
+        let reference = match q {
+            [Some(w), Some(x), Some(y), Some(z)] => Quaternion { w, x, y, z },
+            _ => return Err(calibration::invalid_data("missing tare values")),
+        };
+        self.tare = Some(reference);
+        Ok(reference)
+    }
+
+    /// Helper function.
+    ///
+    /// Returns the fusion's orientation estimate, without the tare.
+    fn fused_quaternion(&self) -> SenseHatResult<Quaternion> {
+        self.fusion.quaternion().ok_or(SenseHatError::NotReady {
+            sensor: Sensor::Imu,
+            measurement: Measurement::Orientation,
+        })
+    }
+
+    /// Returns the sensor fusion algorithm in use.
+    pub fn fusion_algorithm(&self) -> FusionAlgorithm {
+        self.fusion.algorithm()
+    }
+
+    /// Switches to another sensor fusion algorithm. The orientation
+    /// estimate starts again from the next sample.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{FusionAlgorithm, SenseHat};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let imu = sense.imu().unwrap();
+    /// imu.set_fusion_algorithm(FusionAlgorithm::Madgwick { beta: 0.1 });
+    /// ```
+    pub fn set_fusion_algorithm(&mut self, algorithm: FusionAlgorithm) {
+        self.fusion = Fusion::new(algorithm);
+        self.last_fused = None;
+        self.north_primed = false;
+    }
+
+    /// Chooses whether the orientation uses the magnetometer, which near
+    /// motors and speakers reads nonsense which spoils the yaw. Without
+    /// it the pitch and roll still come from gravity, but the yaw is the
+    /// integrated gyro, relative to where it was and drifting, and
+    /// `Orientation3d::yaw_is_relative` says so. It uses it unless this
+    /// is set.
+    ///
+    /// The filter carries on from its current estimate either way, so the
+    /// orientation doesn't jump. Turning the magnetometer back on pulls
+    /// the yaw round to the compass heading gradually.
+    ///
+    /// Samples still have magnetometer readings if the compass is on, see
+    /// `set_config`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let imu = sense.imu().unwrap();
+    /// imu.set_fusion_inputs(false);
+    /// let orientation = imu.get_orientation().unwrap();
+    /// assert!(orientation.yaw_is_relative);
+    /// ```
+    ///
+    /// Off the hardware, with the chip mocked, with either filter:
+    ///
+    /// ```
+    /// use sensehat::{BoxedI2CDevice, FusionAlgorithm, Imu, MockI2CDevice, Settings};
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let (accel_gyro, mag) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// accel_gyro.set_register(0x0f, 0x68);
+    /// mag.set_register(0x0f, 0x3d);
+    /// // Level and still, with the field along X and then along Y.
+    /// accel_gyro.set_register(0x17, 0x03);
+    /// accel_gyro.set_registers(0x28, &[0x00, 0x00, 0x00, 0x00, 0x00, 0x10]);
+    /// mag.set_register(0x27, 0x08);
+    /// let field_along_x = || mag.set_registers(0x28, &[0xe8, 0x03, 0x00, 0x00, 0x00, 0x00]);
+    /// let field_along_y = || mag.set_registers(0x28, &[0x00, 0x00, 0xe8, 0x03, 0x00, 0x00]);
+    ///
+    /// let algorithms = [FusionAlgorithm::Complementary, FusionAlgorithm::Madgwick { beta: 2.0 }];
+    /// for &algorithm in &algorithms {
+    ///     let ag = BoxedI2CDevice::new(accel_gyro.clone());
+    ///     let m = BoxedI2CDevice::new(mag.clone());
+    ///     let mut imu = Imu::from_devices(ag, m, Settings::default()).unwrap();
+    ///     imu.set_fusion_algorithm(algorithm);
+    ///     field_along_x();
+    ///
+    ///     // With the magnetometer, the yaw is a heading, and follows the field.
+    ///     assert!(imu.fusion_uses_mag());
+    ///     let start = imu.get_orientation().unwrap();
+    ///     assert!(!start.yaw_is_relative);
+    ///
+    ///     // Without it, the field makes no difference.
+    ///     imu.set_fusion_inputs(false);
+    ///     field_along_y();
+    ///     for _ in 0..50 {
+    ///         let sample = imu.try_read_sample().unwrap().unwrap();
+    ///         assert!(sample.mag.is_some());
+    ///     }
+    ///     let relative = imu.get_orientation().unwrap();
+    ///     assert!(relative.yaw_is_relative, "{:?}", algorithm);
+    ///     let turned = (relative.yaw.as_degrees() - start.yaw.as_degrees()).abs();
+    ///     assert!(turned < 1e-6, "{:?} turned {}", algorithm, turned);
+    ///
+    ///     // Back on, the yaw is pulled round to the new heading.
+    ///     imu.set_fusion_inputs(true);
+    ///     for _ in 0..50 {
+    ///         thread::sleep(Duration::from_millis(2));
+    ///         imu.try_read_sample().unwrap();
+    ///     }
+    ///     let absolute = imu.get_orientation().unwrap();
+    ///     assert!(!absolute.yaw_is_relative);
+    ///     let turned = (absolute.yaw.as_degrees() - start.yaw.as_degrees()).abs();
+    ///     assert!(turned > 1.0, "{:?} turned {}", algorithm, turned);
+    /// }
+    /// ```
+    pub fn set_fusion_inputs(&mut self, use_mag: bool) {
+        self.fuse_mag = use_mag;
+        self.north_primed = false;
+    }
+
+    /// Returns true if the orientation uses the magnetometer.
+    pub fn fusion_uses_mag(&self) -> bool {
+        self.fuse_mag
+    }
+
+    /// Reads a sample if the accelerometer and gyro have new data, without
+    /// waiting, and fuses it into the orientation estimate. Returns `None`
+    /// if there was no new data.
+    ///
+    /// The magnetometer updates less often, so if it has no new reading
+    /// the sample has the last one, with `mag_stale` set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::{BoxedI2CDevice, Imu, MockI2CDevice, Settings};
+    ///
+    /// let (accel_gyro, mag) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// accel_gyro.set_register(0x0f, 0x68);
+    /// mag.set_register(0x0f, 0x3d);
+    /// let (ag, m) = (BoxedI2CDevice::new(accel_gyro.clone()), BoxedI2CDevice::new(mag.clone()));
+    /// let mut imu = Imu::from_devices(ag, m, Settings::default()).unwrap();
+    /// accel_gyro.set_register(0x17, 0x03);
+    /// // Turning, so the gyro bias isn't learned.
+    /// accel_gyro.set_registers(0x18, &[0x00, 0x00, 0x77, 0x04, 0x00, 0x00]);
+    /// accel_gyro.set_registers(0x28, &[0x00, 0x00, 0x00, 0x00, 0x00, 0x10]);
+    /// mag.set_registers(0x28, &[0xe8, 0x03, 0x00, 0x00, 0x18, 0xfc]);
+    ///
+    /// // No magnetometer reading yet.
+    /// let sample = imu.try_read_sample().unwrap().unwrap();
+    /// assert_eq!(sample.accel, [0.0, 0.0, 4096.0 * 0.000244]);
+    /// assert_eq!(sample.gyro, [0.0, 1143.0 * 0.0175, 0.0]);
+    /// assert_eq!((sample.mag, sample.mag_stale), (None, false));
+    ///
+    /// // A new one.
+    /// mag.set_register(0x27, 0x08);
+    /// let sample = imu.try_read_sample().unwrap().unwrap();
+    /// let field = [1000.0 * 0.014, 0.0, -1000.0 * 0.014];
+    /// assert_eq!((sample.mag, sample.mag_stale), (Some(field), false));
+    ///
+    /// // Nothing new from the magnetometer, so the last reading again.
+    /// mag.set_register(0x27, 0x00);
+    /// let sample = imu.try_read_sample().unwrap().unwrap();
+    /// assert_eq!((sample.mag, sample.mag_stale), (Some(field), true));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `ComponentDisabled` if the accelerometer is turned off, and
+    /// `I2CError` if talking to the chip failed. A chip other than the
+    /// LSM9DS1 is rejected with `UnsupportedImu` by `Imu::new`, so can't get
+    /// this far.
+    ///
+    /// ```
+    /// use sensehat::{BoxedI2CDevice, ErrorKind, Imu, MockI2CDevice, SenseHatError, Settings};
+    /// use std::time::Duration;
+    ///
+    /// let (accel_gyro, mag) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// mag.set_register(0x0f, 0x3d);
+    /// let devices = || {
+    ///     (BoxedI2CDevice::new(accel_gyro.clone()), BoxedI2CDevice::new(mag.clone()))
+    /// };
+    ///
+    /// // An LSM9DS0.
+    /// accel_gyro.set_register(0x0f, 0xd4);
+    /// let (ag, m) = devices();
+    /// let err = Imu::from_devices(ag, m, Settings::default()).err().unwrap();
+    /// assert_eq!(err.kind(), ErrorKind::UnsupportedImu);
+    ///
+    /// accel_gyro.set_register(0x0f, 0x68);
+    /// let (ag, m) = devices();
+    /// let mut imu = Imu::from_devices(ag, m, Settings::default()).unwrap();
+    ///
+    /// // No new data.
+    /// assert!(imu.try_read_sample().unwrap().is_none());
+    /// let err = imu.read_sample_blocking(Duration::from_millis(10)).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::Timeout);
+    ///
+    /// // The chip stopped answering.
+    /// accel_gyro.set_failing(true);
+    /// match imu.try_read_sample() {
+    ///     Err(SenseHatError::I2CError(_)) => {}
+    ///     other => panic!("{:?}", other),
+    /// }
+    /// accel_gyro.set_failing(false);
+    ///
+    /// // The accelerometer turned off.
+    /// imu.set_config(true, true, false).unwrap();
+    /// let err = imu.try_read_sample().unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::ComponentDisabled);
+    /// ```
+    pub fn try_read_sample(&mut self) -> SenseHatResult<Option<ImuSample>> {
+        let (accel, gyro) = match self.read_accel_gyro()? {
+            Some(readings) => readings,
+            None => return Ok(None),
+        };
+        let gyro = if self.config.gyro {
+            self.learn_gyro_bias(accel, gyro);
+            self.rotate(self.correct_gyro(gyro))
+        } else {
+            gyro
+        };
+        let fresh_mag = if self.config.compass {
+            ready(self.read_magnetometer())?
+        } else {
+            None
+        };
+        if fresh_mag.is_some() {
+            self.last_mag = fresh_mag;
+        }
+
+        let now = Instant::now();
+        let sample = ImuSample {
+            accel,
+            gyro,
+            mag: self.last_mag,
+            mag_stale: fresh_mag.is_none() && self.last_mag.is_some(),
+            timestamp: now,
+        };
+        let dt = self
+            .last_fused
+            .map_or(0.0, |then| now.duration_since(then).as_secs_f64());
+        self.last_fused = Some(now);
+        self.fusion = if self.fuse_mag {
+            self.fusion.update_sample(&sample, dt)
+        } else {
+            self.fusion.update_sample(&ImuSample { mag: None, ..sample }, dt)
+        };
+        Ok(Some(sample))
+    }
+
+    /// Waits for the accelerometer and gyro to have new data, then reads
+    /// and fuses a sample like `try_read_sample`. Returns `Timeout` if
+    /// there was no new data within the timeout, or any error
+    /// `try_read_sample` returns.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let imu = sense.imu().unwrap();
+    /// loop {
+    ///     let sample = imu.read_sample_blocking(Duration::from_secs(1)).unwrap();
+    ///     println!("{:?}", sample.accel);
+    /// }
+    /// ```
+    ///
+    /// Off the hardware, with the chip mocked:
+    ///
+    /// ```
+    /// use sensehat::{BoxedI2CDevice, Imu, MockI2CDevice, SenseHatError, Settings};
+    /// use std::thread;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let (accel_gyro, mag) = (MockI2CDevice::new(), MockI2CDevice::new());
+    /// accel_gyro.set_register(0x0f, 0x68);
+    /// mag.set_register(0x0f, 0x3d);
+    /// let (ag, m) = (BoxedI2CDevice::new(accel_gyro.clone()), BoxedI2CDevice::new(mag));
+    /// let mut imu = Imu::from_devices(ag, m, Settings::default()).unwrap();
+    ///
+    /// // Nothing new, or only the accelerometer.
+    /// let started = Instant::now();
+    /// match imu.read_sample_blocking(Duration::from_millis(50)) {
+    ///     Err(SenseHatError::Timeout) => {}
+    ///     other => panic!("{:?}", other),
+    /// }
+    /// assert!(started.elapsed() >= Duration::from_millis(50));
+    /// accel_gyro.set_register(0x17, 0x01);
+    /// assert!(imu.try_read_sample().unwrap().is_none());
+    ///
+    /// // Both, once it's been waiting a while.
+    /// let chip = accel_gyro.clone();
+    /// let ready = thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(50));
+    ///     chip.set_registers(0x28, &[0x00, 0x00, 0x00, 0x00, 0x00, 0x10]);
+    ///     chip.set_register(0x17, 0x03);
+    /// });
+    /// let sample = imu.read_sample_blocking(Duration::from_secs(5)).unwrap();
+    /// assert_eq!(sample.accel, [0.0, 0.0, 4096.0 * 0.000244]);
+    /// ready.join().unwrap();
+    ///
+    /// accel_gyro.set_failing(true);
+    /// assert!(imu.read_sample_blocking(Duration::from_secs(5)).is_err());
+    /// ```
+    pub fn read_sample_blocking(&mut self, timeout: Duration) -> SenseHatResult<ImuSample> {
+        let deadline = Instant::now() + timeout;
+        let poll_interval = (self.poll_interval() / 4).max(MIN_SAMPLE_POLL_INTERVAL);
+        loop {
+            if let Some(sample) = self.try_read_sample()? {
+                return Ok(sample);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(SenseHatError::Timeout);
+            }
+            thread::sleep(poll_interval.min(deadline - now));
+        }
+    }
+
+    /// Returns the thresholds `detect_motion` uses.
+    pub fn motion_thresholds(&self) -> MotionThresholds {
+        self.motion.thresholds()
+    }
+
+    /// Changes the thresholds `detect_motion` uses.
+    pub fn set_motion_thresholds(&mut self, thresholds: MotionThresholds) {
+        self.motion.set_thresholds(thresholds);
+    }
+
+    /// Reads samples for `window`, like `read_sample_blocking`, and returns
+    /// the free falls and shakes in them.
+    ///
+    /// The detector carries on between calls, so a fall which started in
+    /// one window is reported in the next if that's where it ends.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use sensehat::{MotionEvent, SenseHat};
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let imu = sense.imu().unwrap();
+    /// loop {
+    ///     for event in imu.detect_motion(Duration::from_secs(1)).unwrap() {
+    ///         match event {
+    ///             MotionEvent::FreeFall { duration } => println!("Dropped for {:?}", duration),
+    ///             MotionEvent::Shake { peak_g } => println!("Shaken at {:.1}g", peak_g),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn detect_motion(&mut self, window: Duration) -> SenseHatResult<Vec<MotionEvent>> {
+        let deadline = Instant::now() + window;
+        let mut events = Vec::new();
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(events);
+            }
+            match self.read_sample_blocking(deadline - now) {
+                Ok(sample) => events.extend(self.motion.update(sample.accel, sample.timestamp)),
+                Err(SenseHatError::Timeout) => return Ok(events),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Helper function.
+    ///
+    /// Updates the gyro bias from an uncorrected sample, if the Sense Hat
+    /// is stationary.
+    fn learn_gyro_bias(&mut self, accel: [f64; 3], gyro: [f64; 3]) {
+        if !is_stationary(accel, gyro) {
+            return;
+        }
+        let alpha = if self.gyro_sample_count < GYRO_RAPID_LEARNING_SECONDS * self.sample_rate {
+            self.gyro_sample_count += 1;
+            self.gyro_learning_alpha
+        } else {
+            self.gyro_continious_alpha
+        };
+        self.gyro_bias = learn_bias(self.gyro_bias, gyro, alpha);
+    }
+
+    /// Helper function.
+    ///
+    /// Returns `ComponentDisabled` if a sensor has been turned off.
+    fn check_enabled(&self, enabled: bool, measurement: Measurement) -> SenseHatResult<()> {
+        if enabled {
+            Ok(())
+        } else {
+            Err(SenseHatError::ComponentDisabled {
+                sensor: Sensor::Imu,
+                measurement,
+            })
+        }
+    }
+
+    /// Helper function.
+    ///
+    /// Applies the axis rotation to a reading.
+    fn rotate(&self, v: [f64; 3]) -> [f64; 3] {
+        let m = &self.axis_rotation[self.rotation as usize];
+        [
+            m[0] * v[0] + m[1] * v[1] + m[2] * v[2],
+            m[3] * v[0] + m[4] * v[1] + m[5] * v[2],
+            m[6] * v[0] + m[7] * v[1] + m[8] * v[2],
+        ]
+    }
+
+    /// Helper function.
+    ///
+    /// Subtracts the learned bias from a gyro sample.
+    fn correct_gyro(&self, gyro: [f64; 3]) -> [f64; 3] {
+        [
+            gyro[0] - self.gyro_bias[0],
+            gyro[1] - self.gyro_bias[1],
+            gyro[2] - self.gyro_bias[2],
+        ]
+    }
+
+    /// Helper function.
+    ///
+    /// Applies the accelerometer calibration, if there is one, to a sample.
+    fn correct_accel(&self, accel: [f64; 3]) -> [f64; 3] {
+        match self.accel_calibration {
+            Some(calibration) if !self.accel_calibration_mode => calibration.apply(accel),
+            _ => accel,
+        }
+    }
+}
+
+/// Helper function.
+///
+/// Looks for the accel/gyro at each candidate address, opening each with
+/// `open`, returning the device, its WHO_AM_I id and the magnetometer
+/// address that goes with it. If nothing answers anywhere, returns the
+/// error from the last address tried.
+fn probe<F>(open: &mut F) -> SenseHatResult<(TracedI2CDevice<BoxedI2CDevice>, u8, u8)>
+where
+    F: FnMut(u16) -> SenseHatResult<TracedI2CDevice<BoxedI2CDevice>>,
+{
+    let mut last_err = None;
+    for &(addr, mag_addr) in &CANDIDATE_ADDRS {
+        let mut dev = match open(addr as u16) {
+            Ok(dev) => dev,
+            Err(err) => {
+                last_err = Some(err);
+                continue;
+            }
+        };
+        match dev.smbus_read_byte_data(WHO_AM_I) {
+            Ok(id) => return Ok((dev, id, mag_addr)),
+            Err(err) => last_err = Some(err),
+        }
+    }
+    Err(last_err.expect("no candidate addresses"))
+}
+
+/// Helper function.
+///
+/// Reads three little-endian i16 values starting at `register`.
+fn read_triplet(
+    dev: &mut TracedI2CDevice<BoxedI2CDevice>,
+    register: u8,
+) -> SenseHatResult<[f64; 3]> {
+    let buf = dev.smbus_read_i2c_block_data(register, 6)?;
+    if buf.len() < 6 {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "short I2C read").into());
+    }
+    Ok([
+        LittleEndian::read_i16(&buf[0..2]) as f64,
+        LittleEndian::read_i16(&buf[2..4]) as f64,
+        LittleEndian::read_i16(&buf[4..6]) as f64,
+    ])
+}
+
+/// Helper function.
+///
+/// Scales a raw reading by a sensitivity.
+fn scaled(raw: [f64; 3], scale: f64) -> [f64; 3] {
+    [raw[0] * scale, raw[1] * scale, raw[2] * scale]
+}
+
+/// Helper function.
+///
+/// Turns a `NotReady` error into `None`.
+fn ready<T>(result: SenseHatResult<T>) -> SenseHatResult<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(ref err) if err.is_not_ready() => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Helper function.
+///
+/// Returns true if a sample looks like the Sense Hat is sitting still, i.e.
+/// it's only feeling gravity and barely rotating.
+fn is_stationary(accel: [f64; 3], gyro: [f64; 3]) -> bool {
+    let length = |v: [f64; 3]| (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    (length(accel) - 1.0).abs() < STATIONARY_ACCEL_TOLERANCE
+        && length(gyro) < STATIONARY_GYRO_THRESHOLD
+}
+
+/// Helper function.
+///
+/// Moves the bias estimate `alpha` of the way towards a sample.
+fn learn_bias(bias: [f64; 3], sample: [f64; 3], alpha: f64) -> [f64; 3] {
+    [
+        bias[0] * (1.0 - alpha) + sample[0] * alpha,
+        bias[1] * (1.0 - alpha) + sample[1] * alpha,
+        bias[2] * (1.0 - alpha) + sample[2] * alpha,
+    ]
+}
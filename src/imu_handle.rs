@@ -0,0 +1,294 @@
+use fusion::{Orientation3d, Quaternion};
+use imu::Imu;
+use joystick::panic_message;
+use {SenseHatError, SenseHatResult};
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How long the IMU thread waits for a sample before checking whether it's
+/// been told to stop.
+const HANDLER_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long the orientation has to stay past a subscriber's threshold
+/// before `ImuHandle` notifies it.
+const ORIENTATION_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Decides when the orientation has changed enough to tell someone, as
+/// `ImuHandle::on_orientation_change` does for each subscriber.
+///
+/// The first orientation is the reference. After that, it fires when the
+/// angle from the orientation it last fired at, or the first one, is more
+/// than the threshold, which then becomes the reference. The angle is
+/// the one between the quaternions, so it's the same whichever way the
+/// Sense Hat turns, and doesn't jump about near straight up as pitch,
+/// roll and yaw do. To stop a wobble around the threshold firing it over
+/// and over, the orientation has to stay past the threshold for the
+/// debounce time first. Nothing here reads the clock, so the times are
+/// whatever the caller passes in.
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use sensehat::{Angle, Orientation3d, OrientationNotifier, Quaternion};
+///
+/// let start = Instant::now();
+/// let at = |ms| start + Duration::from_millis(ms);
+/// let tilted = |degrees| {
+///     Quaternion::from(Orientation3d {
+///         roll: Angle::from_degrees(degrees),
+///         ..Orientation3d::default()
+///     })
+/// };
+///
+/// let mut notifier = OrientationNotifier::new(20.0, Duration::from_millis(100));
+/// assert!(!notifier.update(tilted(0.0), at(0)));
+/// assert!(!notifier.update(tilted(15.0), at(10)));
+///
+/// // A wobble past the threshold and back doesn't count.
+/// assert!(!notifier.update(tilted(25.0), at(20)));
+/// assert!(!notifier.update(tilted(10.0), at(50)));
+/// assert!(!notifier.update(tilted(25.0), at(80)));
+/// assert!(!notifier.update(tilted(15.0), at(150)));
+///
+/// // Staying past it does, and the new orientation is the reference.
+/// assert!(!notifier.update(tilted(30.0), at(200)));
+/// assert!(!notifier.update(tilted(30.0), at(250)));
+/// assert!(notifier.update(tilted(30.0), at(300)));
+/// assert!(!notifier.update(tilted(45.0), at(400)));
+/// assert!(!notifier.update(tilted(45.0), at(600)));
+///
+/// // Back to level is 30 degrees from there.
+/// assert!(!notifier.update(tilted(0.0), at(700)));
+/// assert!(notifier.update(tilted(0.0), at(800)));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OrientationNotifier {
+    threshold_degrees: f64,
+    debounce: Duration,
+    /// The orientation it last fired at, or the first one.
+    reference: Option<Quaternion>,
+    /// When the orientation went past the threshold, if it still is.
+    exceeded_since: Option<Instant>,
+}
+
+impl OrientationNotifier {
+    /// Makes a notifier which fires when the orientation has turned more
+    /// than `threshold_degrees` and stayed that way for `debounce`.
+    pub fn new(threshold_degrees: f64, debounce: Duration) -> Self {
+        OrientationNotifier {
+            threshold_degrees,
+            debounce,
+            reference: None,
+            exceeded_since: None,
+        }
+    }
+
+    /// Returns how far the orientation has to turn, in degrees.
+    pub fn threshold_degrees(&self) -> f64 {
+        self.threshold_degrees
+    }
+
+    /// Returns how long the orientation has to stay past the threshold.
+    pub fn debounce(&self) -> Duration {
+        self.debounce
+    }
+
+    /// Returns the orientation the angle is measured from, if there's been
+    /// one yet.
+    pub fn reference(&self) -> Option<Quaternion> {
+        self.reference
+    }
+
+    /// Adds an orientation taken at `now`, and returns true if it's a
+    /// change worth telling someone about.
+    pub fn update(&mut self, orientation: Quaternion, now: Instant) -> bool {
+        let reference = match self.reference {
+            Some(reference) => reference,
+            None => {
+                self.reference = Some(orientation);
+                return false;
+            }
+        };
+        if reference.angle_to(orientation).as_degrees() <= self.threshold_degrees {
+            self.exceeded_since = None;
+            return false;
+        }
+        let since = *self.exceeded_since.get_or_insert(now);
+        if now.saturating_duration_since(since) < self.debounce {
+            return false;
+        }
+        self.reference = Some(orientation);
+        self.exceeded_since = None;
+        true
+    }
+
+    /// Forgets the reference, so the next orientation is the new one.
+    pub fn reset(&mut self) {
+        self.reference = None;
+        self.exceeded_since = None;
+    }
+}
+
+/// Says which subscription `ImuHandle::remove_subscription` removes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SubscriptionToken(u64);
+
+/// Someone to tell about orientation changes.
+struct Subscriber {
+    token: SubscriptionToken,
+    notifier: OrientationNotifier,
+    callback: Box<dyn FnMut(Orientation3d) + Send>,
+}
+
+/// The subscribers, and the token the next one gets.
+#[derive(Default)]
+struct Subscribers {
+    next_token: u64,
+    list: Vec<Subscriber>,
+}
+
+/// Should be kept while the IMU thread started by `Imu::spawn_handler` is
+/// wanted, and dropped or `stop`ped to stop it.
+///
+/// # Example
+///
+/// ```no_run
+/// use sensehat::Imu;
+///
+/// let handle = Imu::new().unwrap().spawn_handler();
+/// let token = handle.on_orientation_change(
+///     20.0,
+///     Box::new(|orientation| println!("tilted to {:?}", orientation.degrees())),
+/// );
+/// std::thread::sleep(std::time::Duration::from_secs(60));
+/// handle.remove_subscription(token);
+/// let imu = handle.stop().unwrap();
+/// ```
+pub struct ImuHandle {
+    stop: Arc<AtomicBool>,
+    subscribers: Arc<Mutex<Subscribers>>,
+    thread: Option<JoinHandle<SenseHatResult<Imu>>>,
+}
+
+impl Imu {
+    /// Starts a background thread which keeps reading and fusing samples,
+    /// and tells the subscribers added with
+    /// `ImuHandle::on_orientation_change` when the orientation changes.
+    ///
+    /// If a callback panics, the thread stops and the panic is returned by
+    /// `ImuHandle::stop`, as it is for any error reading the IMU.
+    pub fn spawn_handler(mut self) -> ImuHandle {
+        let stop = Arc::new(AtomicBool::new(false));
+        let subscribers = Arc::new(Mutex::new(Subscribers::default()));
+        let thread_stop = stop.clone();
+        let thread_subscribers = subscribers.clone();
+        let thread = thread::spawn(move || {
+            while !thread_stop.load(Ordering::SeqCst) {
+                match self.read_sample_blocking(HANDLER_POLL_INTERVAL) {
+                    Ok(_) => {}
+                    Err(SenseHatError::Timeout) => continue,
+                    Err(err) => return Err(err),
+                }
+                let q = self.current_quaternion()?;
+                let orientation = self.current_orientation()?;
+                let now = Instant::now();
+                for subscriber in &mut lock(&thread_subscribers).list {
+                    if subscriber.notifier.update(q, now) {
+                        let callback = &mut subscriber.callback;
+                        panic::catch_unwind(AssertUnwindSafe(|| callback(orientation)))
+                            .map_err(|payload| {
+                                SenseHatError::HandlerPanicked(panic_message(&*payload))
+                            })?;
+                    }
+                }
+            }
+            Ok(self)
+        });
+        ImuHandle {
+            stop,
+            subscribers,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl ImuHandle {
+    /// Calls `callback` with the orientation, as `Imu::get_orientation`
+    /// gives it, whenever it has turned more than `threshold_degrees` from
+    /// where it was last called, or from where it was when this was
+    /// called, as an `OrientationNotifier` with a 200ms debounce decides.
+    ///
+    /// Any number of callbacks can be subscribed, each with its own
+    /// threshold. They're called on the IMU thread, while it holds the
+    /// subscriptions, so they mustn't subscribe or unsubscribe themselves.
+    pub fn on_orientation_change(
+        &self,
+        threshold_degrees: f64,
+        callback: Box<dyn FnMut(Orientation3d) + Send>,
+    ) -> SubscriptionToken {
+        let mut subscribers = lock(&self.subscribers);
+        let token = SubscriptionToken(subscribers.next_token);
+        subscribers.next_token += 1;
+        subscribers.list.push(Subscriber {
+            token,
+            notifier: OrientationNotifier::new(threshold_degrees, ORIENTATION_DEBOUNCE),
+            callback,
+        });
+        token
+    }
+
+    /// Stops calling the callback subscribed with `token`. Returns false
+    /// if it had already been removed.
+    pub fn remove_subscription(&self, token: SubscriptionToken) -> bool {
+        let mut subscribers = lock(&self.subscribers);
+        let before = subscribers.list.len();
+        subscribers.list.retain(|subscriber| subscriber.token != token);
+        subscribers.list.len() != before
+    }
+
+    /// Returns true until the IMU thread has stopped, either because it
+    /// was told to or because of an error.
+    pub fn is_running(&self) -> bool {
+        self.thread.as_ref().is_some_and(|thread| !thread.is_finished())
+    }
+
+    /// Stops the IMU thread and waits for it to finish, giving back the
+    /// `Imu`. Returns the error that stopped the thread early instead, if
+    /// any, including panics in the callbacks.
+    pub fn stop(mut self) -> SenseHatResult<Imu> {
+        self.stop.store(true, Ordering::SeqCst);
+        let thread = self.thread.take().expect("only stop and drop take the thread");
+        join(thread)
+    }
+}
+
+impl Drop for ImuHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = join(thread);
+        }
+    }
+}
+
+/// Helper function.
+///
+/// Waits for the IMU thread to finish.
+fn join(thread: JoinHandle<SenseHatResult<Imu>>) -> SenseHatResult<Imu> {
+    thread
+        .join()
+        .unwrap_or_else(|payload| Err(SenseHatError::HandlerPanicked(panic_message(&*payload))))
+}
+
+/// Helper function.
+///
+/// Locks the subscribers, even if a callback panicked while they were
+/// locked.
+fn lock(subscribers: &Mutex<Subscribers>) -> MutexGuard<'_, Subscribers> {
+    subscribers.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,1104 @@
+use joystick_recording::{Recording, RecordingGuard};
+use {SenseHatError, SenseHatResult};
+
+use libc::{self, c_int, c_ulong, input_event, pollfd};
+#[cfg(feature = "mio")]
+use mio::{Interest, Registry, Token};
+#[cfg(feature = "mio")]
+use mio::unix::SourceFd;
+
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read};
+use std::mem;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// The name the joystick input device reports.
+const SENSE_HAT_JOYSTICK: &str = "Raspberry Pi Sense HAT Joystick";
+
+/// Where the kernel lists the input devices.
+const SYS_CLASS_INPUT: &str = "/sys/class/input";
+
+// Event types and key codes from linux/input-event-codes.h
+const EV_KEY: u16 = 0x01;
+const KEY_ENTER: u16 = 28;
+const KEY_UP: u16 = 103;
+const KEY_LEFT: u16 = 105;
+const KEY_RIGHT: u16 = 106;
+const KEY_DOWN: u16 = 108;
+const KEY_MAX: usize = 0x2ff;
+
+/// EVIOCGKEY(len), which reads the bitmask of keys which are down.
+const EVIOCGKEY: c_ulong = (2 << 30) | ((KEY_BITMASK_LEN as c_ulong) << 16) | (0x45 << 8) | 0x18;
+
+/// Enough bytes for a bit per key, up to and including KEY_MAX.
+const KEY_BITMASK_LEN: usize = KEY_MAX / 8 + 1;
+
+/// Every direction, in the order of `Direction::index`.
+pub(crate) const DIRECTIONS: [Direction; 5] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+    Direction::Enter,
+];
+
+/// The size of a `struct input_event` on this platform.
+const EVENT_SIZE: usize = mem::size_of::<input_event>();
+
+/// How long a direction has to be held to count as a long press, unless
+/// configured otherwise.
+const DEFAULT_LONG_PRESS: Duration = Duration::from_secs(1);
+
+/// How often the handler thread checks whether it has been stopped.
+const HANDLER_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A direction on the joystick. Pushing the stick in is `Enter`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+}
+
+/// What happened to a direction on the joystick.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Action {
+    Pressed,
+    Released,
+    /// The direction is still being held, and has been for this long. Sent
+    /// repeatedly while the direction is held.
+    Held(Duration),
+    /// The direction has been held for longer than the long press
+    /// threshold. Sent once per press, alongside the `Held` event which
+    /// crossed the threshold.
+    LongPress(Duration),
+}
+
+/// A single joystick event.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct JoystickEvent {
+    pub direction: Direction,
+    pub action: Action,
+    /// When the kernel saw the event.
+    pub timestamp: SystemTime,
+}
+
+/// Which directions of the joystick are currently pressed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct JoystickState {
+    pressed: [bool; 5],
+}
+
+/// A function called with joystick events by the handler thread.
+pub type JoystickCallback = Box<dyn FnMut(JoystickEvent) + Send>;
+
+/// The functions to call from the handler thread started by
+/// `Joystick::spawn_handler`.
+///
+/// Each direction's callback gets that direction's events, and `any` gets
+/// every event after the direction's callback has run.
+///
+/// # Example
+///
+/// ```no_run
+/// use sensehat::{Joystick, JoystickCallbacks};
+///
+/// let joystick = Joystick::new().unwrap();
+/// let handle = joystick.spawn_handler(JoystickCallbacks {
+///     up: Some(Box::new(|event| println!("up {:?}", event.action))),
+///     any: Some(Box::new(|event| println!("{:?}", event))),
+///     ..Default::default()
+/// });
+/// // ...
+/// handle.stop().unwrap();
+/// ```
+#[derive(Default)]
+pub struct JoystickCallbacks {
+    pub up: Option<JoystickCallback>,
+    pub down: Option<JoystickCallback>,
+    pub left: Option<JoystickCallback>,
+    pub right: Option<JoystickCallback>,
+    pub enter: Option<JoystickCallback>,
+    pub any: Option<JoystickCallback>,
+}
+
+/// Controls the handler thread started by `Joystick::spawn_handler`.
+///
+/// Dropping the handle stops the thread.
+pub struct JoystickHandle {
+    stop: Arc<AtomicBool>,
+    state: Arc<Mutex<JoystickState>>,
+    thread: Option<JoinHandle<SenseHatResult<()>>>,
+}
+
+/// Turns the key values the kernel reports (0 for released, 1 for pressed
+/// and 2 for repeat) into joystick actions, tracking how long each
+/// direction has been held for.
+///
+/// This is what `Joystick` uses internally, and it can be fed recorded
+/// events directly.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{Action, Direction, HoldTracker};
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let mut tracker = HoldTracker::new(Duration::from_secs(1));
+/// let start = UNIX_EPOCH + Duration::from_secs(100);
+/// tracker.feed(Direction::Up, 1, start).count();
+/// let actions: Vec<Action> = tracker
+///     .feed(Direction::Up, 2, start + Duration::from_millis(1200))
+///     .map(|event| event.action)
+///     .collect();
+/// assert_eq!(actions, [Action::Held(Duration::from_millis(1200)),
+///                      Action::LongPress(Duration::from_millis(1200))]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct HoldTracker {
+    long_press: Duration,
+    // When each direction was pressed, and whether its long press was sent
+    pressed: [Option<(SystemTime, bool)>; 5],
+}
+
+/// Represents the five-way joystick.
+#[derive(Debug)]
+pub struct Joystick {
+    device: File,
+    // Works out the actions from the raw key values
+    tracker: HoldTracker,
+    // Events which have been read but not handed out yet
+    queue: VecDeque<JoystickEvent>,
+    // The start of an event which hasn't been completely read yet
+    partial: Vec<u8>,
+    // Where events are logged as they're handed out, if anywhere
+    recording: Option<Arc<Mutex<Recording>>>,
+}
+
+impl Joystick {
+    /// Try to create a new Joystick object.
+    ///
+    /// Will look for the Sense Hat joystick among the input devices and open
+    /// it.
+    pub fn new() -> SenseHatResult<Self> {
+        let path = find_joystick(Path::new(SYS_CLASS_INPUT))?;
+        Joystick::open(path)
+    }
+
+    /// Opens the given event device as a joystick.
+    ///
+    /// # Example
+    ///
+    /// A file of raw `struct input_event`s reads as the events the kernel
+    /// sent. Anything but a key event for one of the joystick's
+    /// directions, at a real time, is skipped.
+    ///
+    /// ```
+    /// extern crate libc;
+    /// extern crate sensehat;
+    /// extern crate tempfile;
+    ///
+    /// use sensehat::{Action, Direction, Joystick};
+    /// use std::io::Write;
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use std::{mem, slice};
+    ///
+    /// fn event(sec: i64, usec: i64, type_: u16, code: u16, value: i32) -> Vec<u8> {
+    ///     let mut raw: libc::input_event = unsafe { mem::zeroed() };
+    ///     raw.time.tv_sec = sec as _;
+    ///     raw.time.tv_usec = usec as _;
+    ///     raw.type_ = type_;
+    ///     raw.code = code;
+    ///     raw.value = value;
+    ///     let size = mem::size_of::<libc::input_event>();
+    ///     unsafe { slice::from_raw_parts(&raw as *const _ as *const u8, size) }.to_vec()
+    /// }
+    /// const EV_SYN: u16 = 0;
+    /// const EV_KEY: u16 = 1;
+    /// const KEY_UP: u16 = 103;
+    /// const KEY_ENTER: u16 = 28;
+    /// const KEY_A: u16 = 30;
+    ///
+    /// # fn main() {
+    /// let mut file = tempfile::NamedTempFile::new().unwrap();
+    /// for raw in vec![
+    ///     event(1_000, 999_999, EV_KEY, KEY_UP, 1),
+    ///     event(1_000, 999_999, EV_SYN, 0, 0),
+    ///     event(1_001, 0, EV_KEY, KEY_A, 1),
+    ///     // Corrupt times.
+    ///     event(1_001, 5_000_000, EV_KEY, KEY_ENTER, 1),
+    ///     event(1_001, -1, EV_KEY, KEY_ENTER, 1),
+    ///     event(-1, 0, EV_KEY, KEY_ENTER, 1),
+    ///     event(1_002, 500_000, EV_KEY, KEY_UP, 0),
+    /// ] {
+    ///     file.write_all(&raw).unwrap();
+    /// }
+    ///
+    /// let mut joystick = Joystick::open(file.path()).unwrap();
+    /// let events = joystick.get_events().unwrap();
+    /// let at = |ms| UNIX_EPOCH + Duration::from_millis(ms);
+    /// let seen: Vec<_> = events.iter().map(|e| (e.direction, e.action, e.timestamp)).collect();
+    /// assert_eq!(
+    ///     seen,
+    ///     [
+    ///         (Direction::Up, Action::Pressed, at(1_000_999) + Duration::from_micros(999)),
+    ///         (Direction::Up, Action::Released, at(1_002_500)),
+    ///     ]
+    /// );
+    /// # }
+    /// ```
+    pub fn open<P: AsRef<Path>>(path: P) -> SenseHatResult<Self> {
+        let device = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(path)?;
+        Ok(Joystick::from_device(device))
+    }
+
+    /// Helper function.
+    ///
+    /// Makes a joystick reading events from a file opened non-blocking,
+    /// which is normally an event device but can be a pipe.
+    pub(crate) fn from_device(device: File) -> Self {
+        let mut tracker = HoldTracker::default();
+        // Start from whatever is held down right now, if the device can say
+        let now = SystemTime::now();
+        if let Some(keys) = read_key_bitmask(device.as_raw_fd()) {
+            for &direction in DIRECTIONS.iter() {
+                let code = direction.key_code() as usize;
+                if keys[code / 8] & (1 << (code % 8)) != 0 {
+                    tracker.pressed[direction.index()] = Some((now, false));
+                }
+            }
+        }
+        Joystick {
+            device,
+            tracker,
+            queue: VecDeque::new(),
+            partial: Vec::with_capacity(EVENT_SIZE),
+            recording: None,
+        }
+    }
+
+    /// Returns how long a direction has to be held before a `LongPress`
+    /// is sent.
+    pub fn long_press_threshold(&self) -> Duration {
+        self.tracker.long_press()
+    }
+
+    /// Sets how long a direction has to be held before a `LongPress` is
+    /// sent. The default is one second.
+    pub fn set_long_press_threshold(&mut self, threshold: Duration) {
+        self.tracker.set_long_press(threshold);
+    }
+
+    /// Returns which directions are pressed right now.
+    ///
+    /// This reads any new events from the device so it is up to date, and
+    /// queues them to be returned by the other methods as usual.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::{Direction, MockJoystick};
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// let (mut joystick, mut mock) = MockJoystick::new().unwrap();
+    /// let at = |ms: u64| UNIX_EPOCH + Duration::from_millis(1_000_000 + ms);
+    /// assert_eq!(joystick.state().unwrap().pressed().count(), 0);
+    ///
+    /// mock.press(Direction::Up, at(0)).unwrap();
+    /// mock.press(Direction::Enter, at(10)).unwrap();
+    /// let state = joystick.state().unwrap();
+    /// assert_eq!(state.pressed().collect::<Vec<_>>(), [Direction::Up, Direction::Enter]);
+    ///
+    /// mock.release(Direction::Up, at(20)).unwrap();
+    /// let state = joystick.state().unwrap();
+    /// assert!(!state.is_pressed(Direction::Up));
+    /// assert!(state.is_pressed(Direction::Enter));
+    ///
+    /// // The events are still there to be read.
+    /// assert_eq!(joystick.get_events().unwrap().len(), 3);
+    /// assert!(joystick.state().unwrap().is_pressed(Direction::Enter));
+    /// ```
+    pub fn state(&mut self) -> SenseHatResult<JoystickState> {
+        self.fill_queue()?;
+        Ok(self.tracker.state())
+    }
+
+    /// Returns all the events which happened since the last call.
+    /// Returns an empty list if nothing happened.
+    pub fn get_events(&mut self) -> SenseHatResult<Vec<JoystickEvent>> {
+        self.fill_queue()?;
+        let events: Vec<JoystickEvent> = self.queue.drain(..).collect();
+        for event in &events {
+            self.log(event);
+        }
+        Ok(events)
+    }
+
+    /// Returns all the events which are waiting to be read. Call this when
+    /// a `mio::Poll` says the joystick is readable; if the wakeup was
+    /// spurious the list is empty.
+    #[cfg(feature = "mio")]
+    pub fn drain_events(&mut self) -> SenseHatResult<Vec<JoystickEvent>> {
+        self.get_events()
+    }
+
+    /// Returns the next event if there is one, without waiting.
+    ///
+    /// # Example
+    ///
+    /// A pipe can hand over part of an event. The start is kept until the
+    /// rest arrives.
+    ///
+    /// ```
+    /// extern crate libc;
+    /// extern crate sensehat;
+    /// extern crate tempfile;
+    ///
+    /// use sensehat::{Action, Direction, Joystick};
+    /// use std::ffi::CString;
+    /// use std::fs::OpenOptions;
+    /// use std::io::Write;
+    /// use std::os::unix::ffi::OsStrExt;
+    /// use std::time::Duration;
+    /// use std::{mem, slice};
+    ///
+    /// fn event(sec: i64, usec: i64, code: u16, value: i32) -> Vec<u8> {
+    ///     let mut raw: libc::input_event = unsafe { mem::zeroed() };
+    ///     raw.time.tv_sec = sec as _;
+    ///     raw.time.tv_usec = usec as _;
+    ///     raw.type_ = 1; // EV_KEY
+    ///     raw.code = code;
+    ///     raw.value = value;
+    ///     let size = mem::size_of::<libc::input_event>();
+    ///     unsafe { slice::from_raw_parts(&raw as *const _ as *const u8, size) }.to_vec()
+    /// }
+    /// const KEY_UP: u16 = 103;
+    ///
+    /// # fn main() {
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let path = dir.path().join("event0");
+    /// let c_path = CString::new(path.as_os_str().as_bytes()).unwrap();
+    /// assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+    /// let mut joystick = Joystick::open(&path).unwrap();
+    /// let mut kernel = OpenOptions::new().write(true).open(&path).unwrap();
+    ///
+    /// // A byte at a time.
+    /// let press = event(1_000, 0, KEY_UP, 1);
+    /// let (last, start) = press.split_last().unwrap();
+    /// for &byte in start {
+    ///     kernel.write_all(&[byte]).unwrap();
+    ///     assert!(joystick.poll_event().unwrap().is_none());
+    /// }
+    /// kernel.write_all(&[*last]).unwrap();
+    /// let pressed = joystick.poll_event().unwrap().unwrap();
+    /// assert_eq!((pressed.direction, pressed.action), (Direction::Up, Action::Pressed));
+    ///
+    /// // The end of one event with the start of the next.
+    /// let mut bytes = event(1_000, 100_000, KEY_UP, 2);
+    /// bytes.extend(event(1_000, 250_000, KEY_UP, 0));
+    /// let (first, second) = bytes.split_at(press.len() / 2);
+    /// kernel.write_all(first).unwrap();
+    /// assert!(joystick.poll_event().unwrap().is_none());
+    /// let (second, third) = second.split_at(press.len());
+    /// kernel.write_all(second).unwrap();
+    /// let held = joystick.poll_event().unwrap().unwrap();
+    /// assert_eq!(held.action, Action::Held(Duration::from_millis(100)));
+    /// assert!(joystick.poll_event().unwrap().is_none());
+    /// kernel.write_all(third).unwrap();
+    /// assert_eq!(joystick.poll_event().unwrap().unwrap().action, Action::Released);
+    /// # }
+    /// ```
+    pub fn poll_event(&mut self) -> SenseHatResult<Option<JoystickEvent>> {
+        self.fill_queue()?;
+        let event = self.queue.pop_front();
+        if let Some(ref event) = event {
+            self.log(event);
+        }
+        Ok(event)
+    }
+
+    /// Waits until an event happens and returns it. Returns `None` if nothing
+    /// happened within the timeout. A timeout of `None` waits forever.
+    ///
+    /// Returns an error if the device goes away while waiting.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::{Action, Direction, MockJoystick};
+    /// use std::thread;
+    /// use std::time::{Duration, Instant, SystemTime};
+    ///
+    /// let (mut joystick, mut mock) = MockJoystick::new().unwrap();
+    /// // Nothing happens, so it gives up after the timeout.
+    /// let started = Instant::now();
+    /// assert!(joystick.wait_for_event(Some(Duration::from_millis(50))).unwrap().is_none());
+    /// assert!(started.elapsed() >= Duration::from_millis(50));
+    ///
+    /// // An event from another thread while waiting.
+    /// let sender = thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(50));
+    ///     mock.press(Direction::Left, SystemTime::now()).unwrap();
+    ///     mock
+    /// });
+    /// let event = joystick.wait_for_event(None).unwrap().unwrap();
+    /// assert_eq!((event.direction, event.action), (Direction::Left, Action::Pressed));
+    ///
+    /// // The device going away.
+    /// drop(sender.join().unwrap());
+    /// assert!(joystick.wait_for_event(Some(Duration::from_secs(5))).is_err());
+    /// ```
+    pub fn wait_for_event(&mut self, timeout: Option<Duration>) -> SenseHatResult<Option<JoystickEvent>> {
+        let deadline = timeout.map(|t| Instant::now() + t);
+        loop {
+            let eof = self.fill_queue()?;
+            if let Some(event) = self.queue.pop_front() {
+                self.log(&event);
+                return Ok(Some(event));
+            }
+            if eof {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "joystick device closed").into());
+            }
+            let remaining = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Ok(None);
+                    }
+                    Some(deadline - now)
+                }
+                None => None,
+            };
+            wait_readable(self.device.as_raw_fd(), remaining)?;
+        }
+    }
+
+    /// Waits until the given direction is pressed. Returns `None` if it
+    /// wasn't pressed within the timeout. Any other events which happen in
+    /// the meantime are discarded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::{Direction, MockJoystick};
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// let (mut joystick, mut mock) = MockJoystick::new().unwrap();
+    /// let at = |ms: u64| UNIX_EPOCH + Duration::from_millis(1_000_000 + ms);
+    /// mock.press(Direction::Up, at(0)).unwrap();
+    /// mock.release(Direction::Up, at(100)).unwrap();
+    /// mock.press(Direction::Enter, at(200)).unwrap();
+    /// mock.release(Direction::Enter, at(300)).unwrap();
+    ///
+    /// let timeout = Some(Duration::from_millis(50));
+    /// let event = joystick.wait_for_press(Direction::Enter, timeout).unwrap().unwrap();
+    /// assert_eq!((event.direction, event.timestamp), (Direction::Enter, at(200)));
+    /// // Enter's release doesn't count, and the press of Up was discarded.
+    /// assert!(joystick.wait_for_press(Direction::Enter, timeout).unwrap().is_none());
+    /// assert!(joystick.wait_for_press(Direction::Up, timeout).unwrap().is_none());
+    /// ```
+    pub fn wait_for_press(&mut self, direction: Direction, timeout: Option<Duration>) -> SenseHatResult<Option<JoystickEvent>> {
+        let deadline = timeout.map(|t| Instant::now() + t);
+        loop {
+            let remaining = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+            match self.wait_for_event(remaining)? {
+                Some(event) if event.direction == direction && event.action == Action::Pressed => {
+                    return Ok(Some(event));
+                }
+                Some(_) => continue,
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// Starts logging every event handed out from now on to a file, in the
+    /// format `ReplayJoystick` reads, until the guard is dropped or
+    /// finished. Any earlier recording stops, and an existing file is
+    /// overwritten.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::Joystick;
+    ///
+    /// let mut joystick = Joystick::new().unwrap();
+    /// let recording = joystick.record("ui-test.shjr").unwrap();
+    /// while let Some(event) = joystick.wait_for_event(None).unwrap() {
+    ///     println!("{:?}", event);
+    /// #   break;
+    /// }
+    /// recording.finish().unwrap();
+    /// ```
+    pub fn record<P: AsRef<Path>>(&mut self, path: P) -> SenseHatResult<RecordingGuard> {
+        let (recording, guard) = RecordingGuard::create(path.as_ref())?;
+        self.recording = Some(recording);
+        Ok(guard)
+    }
+
+    /// Starts a background thread which reads the joystick and calls the
+    /// given callbacks for each event.
+    ///
+    /// If a callback panics, the thread stops and the panic is returned by
+    /// `JoystickHandle::stop`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::{Direction, JoystickCallbacks, MockJoystick, SenseHatError};
+    /// use std::sync::mpsc;
+    /// use std::thread;
+    /// use std::time::{Duration, Instant, SystemTime};
+    ///
+    /// let timeout = Duration::from_secs(5);
+    /// let (joystick, mut mock) = MockJoystick::new().unwrap();
+    /// let (events, rx) = mpsc::channel();
+    /// let (up, any) = (events.clone(), events);
+    /// let handle = joystick.spawn_handler(JoystickCallbacks {
+    ///     up: Some(Box::new(move |event| up.send(("up", event.direction)).unwrap())),
+    ///     any: Some(Box::new(move |event| any.send(("any", event.direction)).unwrap())),
+    ///     ..Default::default()
+    /// });
+    ///
+    /// // Up's callback, then `any`, for Up, and only `any` for Left.
+    /// mock.press(Direction::Up, SystemTime::now()).unwrap();
+    /// mock.press(Direction::Left, SystemTime::now()).unwrap();
+    /// let seen: Vec<_> = (0..3).map(|_| rx.recv_timeout(timeout).unwrap()).collect();
+    /// assert_eq!(seen, [("up", Direction::Up), ("any", Direction::Up), ("any", Direction::Left)]);
+    /// assert!(handle.state().is_pressed(Direction::Left));
+    ///
+    /// // Dropping the handle stops the thread, dropping the callbacks and
+    /// // the joystick.
+    /// drop(handle);
+    /// assert!(rx.recv_timeout(timeout).is_err());
+    /// assert!(mock.release(Direction::Up, SystemTime::now()).is_err());
+    ///
+    /// // A panicking callback stops the thread, and `stop` returns it.
+    /// let (joystick, mut mock) = MockJoystick::new().unwrap();
+    /// let handle = joystick.spawn_handler(JoystickCallbacks {
+    ///     enter: Some(Box::new(|_| panic!("enter is broken"))),
+    ///     ..Default::default()
+    /// });
+    /// mock.press(Direction::Enter, SystemTime::now()).unwrap();
+    /// let deadline = Instant::now() + timeout;
+    /// while handle.is_running() && Instant::now() < deadline {
+    ///     thread::sleep(Duration::from_millis(10));
+    /// }
+    /// assert!(!handle.is_running());
+    /// match handle.stop() {
+    ///     Err(SenseHatError::HandlerPanicked(message)) => assert_eq!(message, "enter is broken"),
+    ///     other => panic!("{:?}", other),
+    /// }
+    /// ```
+    pub fn spawn_handler(mut self, mut callbacks: JoystickCallbacks) -> JoystickHandle {
+        let stop = Arc::new(AtomicBool::new(false));
+        let state = Arc::new(Mutex::new(self.tracker.state()));
+        let thread_stop = stop.clone();
+        let thread_state = state.clone();
+        let thread = thread::spawn(move || {
+            while !thread_stop.load(Ordering::SeqCst) {
+                let event = self.wait_for_event(Some(HANDLER_POLL_INTERVAL))?;
+                if let Ok(mut state) = thread_state.lock() {
+                    *state = self.tracker.state();
+                }
+                if let Some(event) = event {
+                    panic::catch_unwind(AssertUnwindSafe(|| callbacks.dispatch(event)))
+                        .map_err(|payload| SenseHatError::HandlerPanicked(panic_message(&*payload)))?;
+                }
+            }
+            Ok(())
+        });
+        JoystickHandle {
+            stop,
+            state,
+            thread: Some(thread),
+        }
+    }
+
+    /// Helper function.
+    ///
+    /// Logs an event being handed out to the recording, if there is one,
+    /// and lets go of the recording once its guard has finished with it.
+    fn log(&mut self, event: &JoystickEvent) {
+        let active = match self.recording {
+            Some(ref recording) => match recording.lock() {
+                Ok(mut recording) => recording.write(event),
+                Err(_) => false,
+            },
+            None => return,
+        };
+        if !active {
+            self.recording = None;
+        }
+    }
+
+    /// Helper function.
+    ///
+    /// Reads every event the device has available into the queue, without
+    /// blocking. Returns true if the device reached end-of-file, which an
+    /// event device never does unless it has gone away.
+    ///
+    /// An event device always hands out whole events, but a pipe may not, so
+    /// an incomplete event is kept until the rest of it has been read.
+    fn fill_queue(&mut self) -> SenseHatResult<bool> {
+        let mut buf = [0u8; EVENT_SIZE * 16];
+        loop {
+            match self.device.read(&mut buf) {
+                Ok(0) => return Ok(true),
+                Ok(len) => self.push_bytes(&buf[..len]),
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(false),
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    /// Helper function.
+    ///
+    /// Adds freshly read bytes to the incomplete event, and queues every
+    /// event which is now complete.
+    fn push_bytes(&mut self, mut bytes: &[u8]) {
+        if !self.partial.is_empty() {
+            let needed = (EVENT_SIZE - self.partial.len()).min(bytes.len());
+            self.partial.extend_from_slice(&bytes[..needed]);
+            bytes = &bytes[needed..];
+            if self.partial.len() < EVENT_SIZE {
+                return;
+            }
+            if let Some((direction, value, timestamp)) = parse_event(&self.partial) {
+                self.queue.extend(self.tracker.feed(direction, value, timestamp));
+            }
+            self.partial.clear();
+        }
+        let whole = bytes.len() - bytes.len() % EVENT_SIZE;
+        for (direction, value, timestamp) in bytes[..whole].chunks(EVENT_SIZE).filter_map(parse_event) {
+            self.queue.extend(self.tracker.feed(direction, value, timestamp));
+        }
+        self.partial.extend_from_slice(&bytes[whole..]);
+    }
+}
+
+impl HoldTracker {
+    /// Creates a tracker which sends a `LongPress` once a direction has been
+    /// held for the given time.
+    pub fn new(long_press: Duration) -> Self {
+        HoldTracker {
+            long_press,
+            pressed: [None; 5],
+        }
+    }
+
+    /// Returns how long a direction has to be held to count as a long press.
+    pub fn long_press(&self) -> Duration {
+        self.long_press
+    }
+
+    /// Sets how long a direction has to be held to count as a long press.
+    pub fn set_long_press(&mut self, long_press: Duration) {
+        self.long_press = long_press;
+    }
+
+    /// Returns which directions are pressed, according to the values fed in.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::{Direction, HoldTracker};
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// let mut tracker = HoldTracker::new(Duration::from_secs(1));
+    /// let at = |ms: u64| UNIX_EPOCH + Duration::from_millis(1_000_000 + ms);
+    /// tracker.feed(Direction::Left, 1, at(0)).count();
+    /// tracker.feed(Direction::Down, 1, at(10)).count();
+    /// tracker.feed(Direction::Left, 0, at(20)).count();
+    /// assert_eq!(tracker.state().pressed().collect::<Vec<_>>(), [Direction::Down]);
+    ///
+    /// // A repeat whose press was missed still means it's held.
+    /// tracker.feed(Direction::Right, 2, at(30)).count();
+    /// assert!(tracker.state().is_pressed(Direction::Right));
+    /// // Values the kernel doesn't send change nothing.
+    /// tracker.feed(Direction::Down, 7, at(40)).count();
+    /// assert!(tracker.state().is_pressed(Direction::Down));
+    /// ```
+    pub fn state(&self) -> JoystickState {
+        let mut state = JoystickState::default();
+        for (pressed, held) in state.pressed.iter_mut().zip(self.pressed.iter()) {
+            *pressed = held.is_some();
+        }
+        state
+    }
+
+    /// Feeds in a key value from the kernel for the given direction, and
+    /// returns the resulting events. Unknown values are ignored.
+    pub fn feed(&mut self, direction: Direction, value: i32, timestamp: SystemTime) -> impl Iterator<Item = JoystickEvent> {
+        let event = |action| JoystickEvent {
+            direction,
+            action,
+            timestamp,
+        };
+        let state = &mut self.pressed[direction.index()];
+        let (first, second) = match value {
+            0 => {
+                *state = None;
+                (Some(event(Action::Released)), None)
+            }
+            1 => {
+                *state = Some((timestamp, false));
+                (Some(event(Action::Pressed)), None)
+            }
+            2 => {
+                // If the press was missed, count the hold from now
+                let (pressed_at, long_press_sent) = state.unwrap_or((timestamp, false));
+                let held = timestamp.duration_since(pressed_at).unwrap_or_default();
+                let long_press = !long_press_sent && held >= self.long_press;
+                *state = Some((pressed_at, long_press_sent || long_press));
+                let second = if long_press {
+                    Some(event(Action::LongPress(held)))
+                } else {
+                    None
+                };
+                (Some(event(Action::Held(held))), second)
+            }
+            _ => (None, None),
+        };
+        first.into_iter().chain(second)
+    }
+}
+
+impl JoystickState {
+    /// Returns true if the direction is pressed.
+    pub fn is_pressed(&self, direction: Direction) -> bool {
+        self.pressed[direction.index()]
+    }
+
+    /// Returns the directions which are pressed.
+    pub fn pressed(&self) -> impl Iterator<Item = Direction> {
+        let pressed = self.pressed;
+        DIRECTIONS.iter().cloned().filter(move |d| pressed[d.index()])
+    }
+
+    /// Helper function.
+    ///
+    /// Keeps the state up to date with an event which has happened.
+    pub(crate) fn update(&mut self, event: &JoystickEvent) {
+        match event.action {
+            Action::Pressed => self.pressed[event.direction.index()] = true,
+            Action::Released => self.pressed[event.direction.index()] = false,
+            Action::Held(_) | Action::LongPress(_) => {}
+        }
+    }
+}
+
+impl Default for HoldTracker {
+    fn default() -> Self {
+        HoldTracker::new(DEFAULT_LONG_PRESS)
+    }
+}
+
+impl Direction {
+    /// Helper function.
+    ///
+    /// Gives each direction a place in a per-direction array.
+    pub(crate) fn index(self) -> usize {
+        match self {
+            Direction::Up => 0,
+            Direction::Down => 1,
+            Direction::Left => 2,
+            Direction::Right => 3,
+            Direction::Enter => 4,
+        }
+    }
+
+    /// Helper function.
+    ///
+    /// Returns the key code the kernel uses for the direction.
+    fn key_code(self) -> u16 {
+        match self {
+            Direction::Up => KEY_UP,
+            Direction::Down => KEY_DOWN,
+            Direction::Left => KEY_LEFT,
+            Direction::Right => KEY_RIGHT,
+            Direction::Enter => KEY_ENTER,
+        }
+    }
+}
+
+impl AsRawFd for Joystick {
+    fn as_raw_fd(&self) -> RawFd {
+        self.device.as_raw_fd()
+    }
+}
+
+/// Lets the joystick be registered with a `mio::Poll`. The joystick is
+/// always non-blocking, and reading events drains everything available, as
+/// mio's edge-triggered readiness requires.
+///
+/// # Example
+///
+/// ```
+/// extern crate mio;
+/// extern crate sensehat;
+///
+/// use mio::{Events, Interest, Poll, Token};
+/// use sensehat::{Action, Direction, MockJoystick};
+/// use std::time::{Duration, SystemTime};
+///
+/// # fn main() {
+/// let (mut joystick, mut mock) = MockJoystick::new().unwrap();
+/// let mut poll = Poll::new().unwrap();
+/// poll.registry().register(&mut joystick, Token(0), Interest::READABLE).unwrap();
+/// let mut events = Events::with_capacity(8);
+///
+/// // Nothing to read yet.
+/// poll.poll(&mut events, Some(Duration::from_millis(10))).unwrap();
+/// assert!(events.is_empty());
+///
+/// mock.press(Direction::Up, SystemTime::now()).unwrap();
+/// mock.release(Direction::Up, SystemTime::now()).unwrap();
+/// poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+/// assert!(events.iter().any(|event| event.token() == Token(0) && event.is_readable()));
+/// let actions: Vec<Action> = joystick.drain_events().unwrap().iter().map(|e| e.action).collect();
+/// assert_eq!(actions, [Action::Pressed, Action::Released]);
+///
+/// // That read until the pipe would block, so there's nothing left, and
+/// // the joystick isn't readable again until something else arrives.
+/// assert!(joystick.drain_events().unwrap().is_empty());
+/// poll.poll(&mut events, Some(Duration::from_millis(10))).unwrap();
+/// assert!(events.is_empty());
+///
+/// mock.press(Direction::Down, SystemTime::now()).unwrap();
+/// poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+/// assert!(!events.is_empty());
+/// let event = joystick.drain_events().unwrap()[0];
+/// assert_eq!((event.direction, event.action), (Direction::Down, Action::Pressed));
+/// poll.registry().deregister(&mut joystick).unwrap();
+/// # }
+/// ```
+#[cfg(feature = "mio")]
+impl mio::event::Source for Joystick {
+    fn register(&mut self, registry: &Registry, token: Token, interests: Interest) -> io::Result<()> {
+        SourceFd(&self.device.as_raw_fd()).register(registry, token, interests)
+    }
+
+    fn reregister(&mut self, registry: &Registry, token: Token, interests: Interest) -> io::Result<()> {
+        SourceFd(&self.device.as_raw_fd()).reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        SourceFd(&self.device.as_raw_fd()).deregister(registry)
+    }
+}
+
+impl JoystickCallbacks {
+    /// Helper function.
+    ///
+    /// Calls the callbacks interested in the event.
+    fn dispatch(&mut self, event: JoystickEvent) {
+        let callback = match event.direction {
+            Direction::Up => &mut self.up,
+            Direction::Down => &mut self.down,
+            Direction::Left => &mut self.left,
+            Direction::Right => &mut self.right,
+            Direction::Enter => &mut self.enter,
+        };
+        if let Some(ref mut callback) = *callback {
+            callback(event);
+        }
+        if let Some(ref mut any) = self.any {
+            any(event);
+        }
+    }
+}
+
+impl JoystickHandle {
+    /// Returns true until the handler thread has stopped, either because it
+    /// was told to or because of an error.
+    pub fn is_running(&self) -> bool {
+        self.thread.as_ref().is_some_and(|thread| !thread.is_finished())
+    }
+
+    /// Returns which directions were pressed, as of the last events the
+    /// handler thread read.
+    pub fn state(&self) -> JoystickState {
+        match self.state.lock() {
+            Ok(state) => *state,
+            Err(poisoned) => *poisoned.into_inner(),
+        }
+    }
+
+    /// Stops the handler thread and waits for it to finish. Returns the
+    /// error that stopped the thread early, if any, including panics in the
+    /// callbacks.
+    pub fn stop(mut self) -> SenseHatResult<()> {
+        self.shutdown()
+    }
+
+    /// Helper function.
+    ///
+    /// Tells the thread to stop and waits for it.
+    fn shutdown(&mut self) -> SenseHatResult<()> {
+        self.stop.store(true, Ordering::SeqCst);
+        match self.thread.take() {
+            Some(thread) => thread
+                .join()
+                .unwrap_or_else(|payload| Err(SenseHatError::HandlerPanicked(panic_message(&*payload)))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for JoystickHandle {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
+    }
+}
+
+/// Helper function.
+///
+/// Gets the message out of a panic, if it has one.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_owned()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "callback panicked".to_owned()
+    }
+}
+
+/// Helper function.
+///
+/// Blocks until the file descriptor is readable or the timeout runs out.
+/// Returns early if interrupted by a signal; callers are expected to check
+/// for events and work out the remaining timeout again.
+fn wait_readable(fd: RawFd, timeout: Option<Duration>) -> SenseHatResult<()> {
+    let timeout_ms = match timeout {
+        // Round up, so we don't spin when less than a millisecond is left
+        Some(t) => {
+            let ms = t.as_secs() * 1000 + u64::from(t.subsec_nanos()).div_ceil(1_000_000);
+            ms.min(c_int::MAX as u64) as c_int
+        }
+        None => -1,
+    };
+    let mut fds = [pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    }];
+    let ret = unsafe { libc::poll(fds.as_mut_ptr(), 1, timeout_ms) };
+    if ret < 0 {
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err.into());
+        }
+    }
+    Ok(())
+}
+
+/// Helper function.
+///
+/// Asks the event device which keys are down. Returns `None` if the file
+/// isn't an event device.
+fn read_key_bitmask(fd: RawFd) -> Option<[u8; KEY_BITMASK_LEN]> {
+    let mut keys = [0u8; KEY_BITMASK_LEN];
+    let ret = unsafe { libc::ioctl(fd, EVIOCGKEY as _, keys.as_mut_ptr()) };
+    if ret < 0 {
+        None
+    } else {
+        Some(keys)
+    }
+}
+
+/// Helper function.
+///
+/// Finds the event device of the Sense Hat joystick by checking the name of
+/// every input device listed under the given directory, which is normally
+/// `/sys/class/input`.
+fn find_joystick(sys_class_input: &Path) -> SenseHatResult<PathBuf> {
+    let entries = match fs::read_dir(sys_class_input) {
+        Ok(entries) => entries,
+        Err(_) => return Err(SenseHatError::MissingJoystick),
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let file_name = entry.file_name();
+        let event = file_name.to_string_lossy();
+        if !event.starts_with("event") {
+            continue;
+        }
+        let name = fs::read_to_string(entry.path().join("device/name")).unwrap_or_default();
+        if name.trim() == SENSE_HAT_JOYSTICK {
+            return Ok(Path::new("/dev/input").join(&*event));
+        }
+    }
+    Err(SenseHatError::MissingJoystick)
+}
+
+/// Helper function.
+///
+/// Builds the `struct input_event` the kernel would send for an event, the
+/// other way round from `parse_event`. Returns `None` for a `LongPress`,
+/// which the kernel never sends, as the `HoldTracker` works it out.
+pub(crate) fn encode_event(event: &JoystickEvent) -> Option<[u8; EVENT_SIZE]> {
+    let value = match event.action {
+        Action::Released => 0,
+        Action::Pressed => 1,
+        Action::Held(_) => 2,
+        Action::LongPress(_) => return None,
+    };
+    let since_epoch = event.timestamp.duration_since(UNIX_EPOCH).unwrap_or_default();
+    // An input_event is plain old data, so all zeroes is a valid one.
+    let mut raw: input_event = unsafe { mem::zeroed() };
+    raw.time.tv_sec = since_epoch.as_secs() as _;
+    raw.time.tv_usec = since_epoch.subsec_micros() as _;
+    raw.type_ = EV_KEY;
+    raw.code = event.direction.key_code();
+    raw.value = value;
+    let mut buf = [0u8; EVENT_SIZE];
+    unsafe { ptr::write_unaligned(buf.as_mut_ptr() as *mut input_event, raw) };
+    Some(buf)
+}
+
+/// Helper function.
+///
+/// Parses one `struct input_event` and returns its direction, key value and
+/// timestamp, if it's a key event for one of the joystick's directions.
+fn parse_event(buf: &[u8]) -> Option<(Direction, i32, SystemTime)> {
+    if buf.len() < EVENT_SIZE {
+        return None;
+    }
+    // The buffer holds a complete input_event, which is plain old data.
+    let raw: input_event = unsafe { ptr::read_unaligned(buf.as_ptr() as *const input_event) };
+    if raw.type_ != EV_KEY {
+        return None;
+    }
+    let direction = match raw.code {
+        KEY_UP => Direction::Up,
+        KEY_DOWN => Direction::Down,
+        KEY_LEFT => Direction::Left,
+        KEY_RIGHT => Direction::Right,
+        KEY_ENTER => Direction::Enter,
+        _ => return None,
+    };
+    // A corrupt event could have any time at all, so only take ones which
+    // are a real time after the epoch.
+    if raw.time.tv_sec < 0 || raw.time.tv_usec < 0 || raw.time.tv_usec >= 1_000_000 {
+        return None;
+    }
+    let micros = Duration::from_micros(raw.time.tv_usec as u64);
+    let timestamp = UNIX_EPOCH.checked_add(Duration::from_secs(raw.time.tv_sec as u64) + micros)?;
+    Some((direction, raw.value, timestamp))
+}
@@ -0,0 +1,501 @@
+//! Recording joystick events to a file and playing them back, for testing
+//! joystick code without the hardware.
+//!
+//! # File format
+//!
+//! A recording is a 16 byte header followed by an 18 byte record for each
+//! event, with every number little-endian.
+//!
+//! The header is
+//!
+//! | Bytes  | Contents                                              |
+//! |--------|-------------------------------------------------------|
+//! | 0..4   | `SHJR`                                                |
+//! | 4..6   | The format version, 1 (u16)                           |
+//! | 6..8   | Zero                                                  |
+//! | 8..16  | When recording started, in µs since 1970 (i64)        |
+//!
+//! and each record is
+//!
+//! | Bytes  | Contents                                              |
+//! |--------|-------------------------------------------------------|
+//! | 0..8   | When the event happened, in µs since the start (i64)  |
+//! | 8      | The direction: 0 up, 1 down, 2 left, 3 right, 4 enter |
+//! | 9      | The action: 0 pressed, 1 released, 2 held, 3 long press |
+//! | 10..18 | How long held for in µs, or zero if not held (u64)    |
+//!
+//! Times are relative to the start of the recording, so it plays back at
+//! the same pace whenever it's played. An event can come before the start
+//! if the kernel saw it before recording started, so times are signed.
+
+use joystick::{Action, Direction, JoystickEvent, JoystickState};
+use {SenseHatError, SenseHatResult};
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// What a recording starts with.
+const MAGIC: &[u8; 4] = b"SHJR";
+
+/// The version of the format written.
+const VERSION: u16 = 1;
+
+/// The size of the header, in bytes.
+const HEADER_SIZE: usize = 16;
+
+/// The size of each event's record, in bytes.
+const RECORD_SIZE: usize = 18;
+
+/// Stops the recording started by `Joystick::record` when dropped.
+///
+/// Dropping the guard ignores any error writing the file. `finish` stops
+/// the recording and returns the first error instead.
+#[derive(Debug)]
+pub struct RecordingGuard {
+    recording: Arc<Mutex<Recording>>,
+}
+
+/// Helper struct.
+///
+/// The file a joystick is logging events to, shared with the guard.
+#[derive(Debug)]
+pub(crate) struct Recording {
+    // None once the guard has finished, or writing failed
+    writer: Option<BufWriter<File>>,
+    start: SystemTime,
+    error: Option<io::Error>,
+}
+
+/// How fast `ReplayJoystick` plays events back.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReplaySpeed {
+    /// At the pace they were recorded at, as if someone were using the
+    /// joystick.
+    Recorded,
+    /// Every event straight away, for tests.
+    AsFastAsPossible,
+}
+
+/// Plays back a recording made by `Joystick::record`, with the same methods
+/// as `Joystick`, so code using the joystick can be tested without hands
+/// on the hardware.
+///
+/// Events come back exactly as they were recorded, timestamps included.
+/// At `ReplaySpeed::Recorded`, which is the default, the recording starts
+/// playing the first time events are asked for, and each event is handed
+/// out once it's as far into playing as it was into the recording. The
+/// `_at` methods take the time rather than reading the clock, for tests.
+///
+/// Once every event has been handed out, `wait_for_event` returns an
+/// `UnexpectedEof` error, as `Joystick` does if the device goes away.
+///
+/// # Example
+///
+/// A recording plays back what was recorded:
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use sensehat::{Direction, MockJoystick, ReplayJoystick, ReplaySpeed};
+///
+/// let path = std::env::temp_dir().join("sensehat-replay-round-trip-doctest.shjr");
+/// let (mut joystick, mut mock) = MockJoystick::new().unwrap();
+/// let recording = joystick.record(&path).unwrap();
+///
+/// let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+/// let at = |ms| start + Duration::from_millis(ms);
+/// mock.press(Direction::Left, at(0)).unwrap();
+/// mock.hold(Direction::Left, at(1100)).unwrap();
+/// mock.release(Direction::Left, at(1250)).unwrap();
+/// mock.press(Direction::Enter, at(2000)).unwrap();
+/// mock.release(Direction::Enter, at(2040)).unwrap();
+/// let recorded = joystick.get_events().unwrap();
+/// recording.finish().unwrap();
+///
+/// let mut replay = ReplayJoystick::from_file(&path).unwrap();
+/// replay.set_speed(ReplaySpeed::AsFastAsPossible);
+/// assert_eq!(replay.len(), 6);
+/// assert_eq!(replay.get_events().unwrap(), recorded);
+/// assert!(replay.is_empty());
+/// assert!(replay.wait_for_event(None).is_err());
+/// ```
+///
+/// And at the pace it was recorded at:
+///
+/// ```
+/// use std::time::{Duration, Instant, SystemTime};
+/// use sensehat::{Action, Direction, MockJoystick, ReplayJoystick};
+///
+/// let path = std::env::temp_dir().join("sensehat-replay-pacing-doctest.shjr");
+/// let (mut joystick, mut mock) = MockJoystick::new().unwrap();
+/// let started = SystemTime::now();
+/// let recording = joystick.record(&path).unwrap();
+/// mock.press(Direction::Up, started + Duration::from_secs(2)).unwrap();
+/// mock.release(Direction::Up, started + Duration::from_secs(3)).unwrap();
+/// joystick.get_events().unwrap();
+/// drop(recording);
+///
+/// let mut replay = ReplayJoystick::from_file(&path).unwrap();
+/// let start = Instant::now();
+/// let at = |ms| start + Duration::from_millis(ms);
+/// assert_eq!(replay.poll_event_at(at(0)), None);
+/// // The recording started a moment after `started`.
+/// let due = replay.next_due().unwrap();
+/// assert!(due > at(1990) && due <= at(2000));
+/// assert_eq!(replay.poll_event_at(at(1990)), None);
+/// assert_eq!(replay.poll_event_at(at(2000)).unwrap().action, Action::Pressed);
+/// assert!(replay.state_at(at(2500)).is_pressed(Direction::Up));
+/// assert_eq!(replay.get_events_at(at(2500)), []);
+/// assert_eq!(replay.get_events_at(at(5000)).len(), 1);
+/// assert_eq!(replay.next_due(), None);
+/// ```
+#[derive(Debug)]
+pub struct ReplayJoystick {
+    // Events not handed out yet, with how long after the start they're due
+    pending: VecDeque<(Duration, JoystickEvent)>,
+    // Events which are due but haven't been handed out yet
+    queue: VecDeque<JoystickEvent>,
+    speed: ReplaySpeed,
+    // When playing started, once it has
+    started: Option<Instant>,
+    state: JoystickState,
+}
+
+impl RecordingGuard {
+    /// Helper function.
+    ///
+    /// Creates the file and writes the header, returning the recording for
+    /// the joystick to log to along with its guard.
+    pub(crate) fn create(path: &Path) -> SenseHatResult<(Arc<Mutex<Recording>>, RecordingGuard)> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        // Whole microseconds, so the times in the file give back the
+        // timestamps exactly.
+        let start = from_micros(to_micros(SystemTime::now()));
+        let mut header = [0u8; HEADER_SIZE];
+        header[..4].copy_from_slice(MAGIC);
+        header[4..6].copy_from_slice(&VERSION.to_le_bytes());
+        header[8..].copy_from_slice(&to_micros(start).to_le_bytes());
+        writer.write_all(&header)?;
+        let recording = Arc::new(Mutex::new(Recording {
+            writer: Some(writer),
+            start,
+            error: None,
+        }));
+        let guard = RecordingGuard {
+            recording: recording.clone(),
+        };
+        Ok((recording, guard))
+    }
+
+    /// Stops the recording, writing out anything still buffered. Returns
+    /// the first error writing the file, if there was one.
+    pub fn finish(self) -> SenseHatResult<()> {
+        match self.close() {
+            Some(err) => Err(err.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Helper function.
+    ///
+    /// Stops the recording, returning the first error writing the file.
+    fn close(&self) -> Option<io::Error> {
+        let mut recording = self
+            .recording
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(mut writer) = recording.writer.take() {
+            if let Err(err) = writer.flush() {
+                recording.error.get_or_insert(err);
+            }
+        }
+        recording.error.take()
+    }
+}
+
+impl Drop for RecordingGuard {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+impl Recording {
+    /// Helper function.
+    ///
+    /// Writes an event's record, keeping the error if that fails. Returns
+    /// false if the recording has stopped.
+    pub(crate) fn write(&mut self, event: &JoystickEvent) -> bool {
+        let record = encode_record(event, self.start);
+        let result = match self.writer {
+            Some(ref mut writer) => writer.write_all(&record),
+            None => return false,
+        };
+        if let Err(err) = result {
+            self.error = Some(err);
+            self.writer = None;
+            return false;
+        }
+        true
+    }
+}
+
+impl ReplayJoystick {
+    /// Loads a recording made by `Joystick::record`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> SenseHatResult<Self> {
+        ReplayJoystick::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Reads a recording made by `Joystick::record` from anything. Returns
+    /// an `InvalidData` error if it isn't a recording this version
+    /// understands.
+    pub fn from_reader<R: Read>(mut reader: R) -> SenseHatResult<Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        if data.len() < HEADER_SIZE || &data[..4] != MAGIC {
+            return Err(invalid_recording("not a joystick recording"));
+        }
+        let version = u16::from_le_bytes([data[4], data[5]]);
+        if version != VERSION {
+            return Err(invalid_recording(&format!("unsupported version {}", version)));
+        }
+        let start = from_micros(read_i64(&data[8..16]));
+        let records = &data[HEADER_SIZE..];
+        if records.len() % RECORD_SIZE != 0 {
+            return Err(invalid_recording("truncated event"));
+        }
+        let pending = records
+            .chunks(RECORD_SIZE)
+            .map(|record| decode_record(record, start))
+            .collect::<SenseHatResult<_>>()?;
+        Ok(ReplayJoystick {
+            pending,
+            queue: VecDeque::new(),
+            speed: ReplaySpeed::Recorded,
+            started: None,
+            state: JoystickState::default(),
+        })
+    }
+
+    /// Returns how fast events are played back.
+    pub fn speed(&self) -> ReplaySpeed {
+        self.speed
+    }
+
+    /// Changes how fast events are played back.
+    pub fn set_speed(&mut self, speed: ReplaySpeed) {
+        self.speed = speed;
+    }
+
+    /// Returns how many events haven't been handed out yet.
+    pub fn len(&self) -> usize {
+        self.pending.len() + self.queue.len()
+    }
+
+    /// Returns true once every event has been handed out.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns when the next event which isn't due yet will be, or None if
+    /// playing hasn't started, or every event is due.
+    pub fn next_due(&self) -> Option<Instant> {
+        let started = self.started?;
+        self.pending.front().map(|&(offset, _)| match self.speed {
+            ReplaySpeed::Recorded => started + offset,
+            ReplaySpeed::AsFastAsPossible => started,
+        })
+    }
+
+    /// See `Joystick::state`.
+    pub fn state(&mut self) -> SenseHatResult<JoystickState> {
+        Ok(self.state_at(Instant::now()))
+    }
+
+    /// See `Joystick::get_events`.
+    pub fn get_events(&mut self) -> SenseHatResult<Vec<JoystickEvent>> {
+        Ok(self.get_events_at(Instant::now()))
+    }
+
+    /// See `Joystick::poll_event`.
+    pub fn poll_event(&mut self) -> SenseHatResult<Option<JoystickEvent>> {
+        Ok(self.poll_event_at(Instant::now()))
+    }
+
+    /// Returns which directions are pressed at `now`.
+    pub fn state_at(&mut self, now: Instant) -> JoystickState {
+        self.fill_queue(now);
+        self.state
+    }
+
+    /// Returns all the events due by `now` which haven't been handed out.
+    pub fn get_events_at(&mut self, now: Instant) -> Vec<JoystickEvent> {
+        self.fill_queue(now);
+        self.queue.drain(..).collect()
+    }
+
+    /// Returns the next event if it's due by `now`.
+    pub fn poll_event_at(&mut self, now: Instant) -> Option<JoystickEvent> {
+        self.fill_queue(now);
+        self.queue.pop_front()
+    }
+
+    /// See `Joystick::wait_for_event`.
+    pub fn wait_for_event(&mut self, timeout: Option<Duration>) -> SenseHatResult<Option<JoystickEvent>> {
+        let deadline = timeout.map(|t| Instant::now() + t);
+        loop {
+            let now = Instant::now();
+            if let Some(event) = self.poll_event_at(now) {
+                return Ok(Some(event));
+            }
+            let due = match self.next_due() {
+                Some(due) => due,
+                None => {
+                    return Err(
+                        io::Error::new(io::ErrorKind::UnexpectedEof, "joystick recording finished").into(),
+                    )
+                }
+            };
+            let until = match deadline {
+                Some(deadline) if deadline <= now => return Ok(None),
+                Some(deadline) => due.min(deadline),
+                None => due,
+            };
+            thread::sleep(until.saturating_duration_since(now));
+        }
+    }
+
+    /// See `Joystick::wait_for_press`.
+    pub fn wait_for_press(&mut self, direction: Direction, timeout: Option<Duration>) -> SenseHatResult<Option<JoystickEvent>> {
+        let deadline = timeout.map(|t| Instant::now() + t);
+        loop {
+            let remaining = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+            match self.wait_for_event(remaining)? {
+                Some(event) if event.direction == direction && event.action == Action::Pressed => {
+                    return Ok(Some(event));
+                }
+                Some(_) => continue,
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// Helper function.
+    ///
+    /// Starts playing if it hasn't started, and queues every event due by
+    /// `now`.
+    fn fill_queue(&mut self, now: Instant) {
+        let started = *self.started.get_or_insert(now);
+        let elapsed = now.saturating_duration_since(started);
+        while let Some(&(offset, event)) = self.pending.front() {
+            if self.speed == ReplaySpeed::Recorded && offset > elapsed {
+                break;
+            }
+            self.pending.pop_front();
+            self.state.update(&event);
+            self.queue.push_back(event);
+        }
+    }
+}
+
+/// Helper function.
+///
+/// Encodes an event as a record in the file.
+fn encode_record(event: &JoystickEvent, start: SystemTime) -> [u8; RECORD_SIZE] {
+    let mut record = [0u8; RECORD_SIZE];
+    let offset = to_micros(event.timestamp) - to_micros(start);
+    record[..8].copy_from_slice(&offset.to_le_bytes());
+    record[8] = match event.direction {
+        Direction::Up => 0,
+        Direction::Down => 1,
+        Direction::Left => 2,
+        Direction::Right => 3,
+        Direction::Enter => 4,
+    };
+    let (action, held) = match event.action {
+        Action::Pressed => (0, Duration::default()),
+        Action::Released => (1, Duration::default()),
+        Action::Held(held) => (2, held),
+        Action::LongPress(held) => (3, held),
+    };
+    record[9] = action;
+    record[10..].copy_from_slice(&(held.as_micros() as u64).to_le_bytes());
+    record
+}
+
+/// Helper function.
+///
+/// Decodes a record in the file, returning the event with how long after
+/// the start of the recording it happened.
+fn decode_record(record: &[u8], start: SystemTime) -> SenseHatResult<(Duration, JoystickEvent)> {
+    let offset = read_i64(&record[..8]);
+    let direction = match record[8] {
+        0 => Direction::Up,
+        1 => Direction::Down,
+        2 => Direction::Left,
+        3 => Direction::Right,
+        4 => Direction::Enter,
+        other => return Err(invalid_recording(&format!("unknown direction {}", other))),
+    };
+    let mut held = [0u8; 8];
+    held.copy_from_slice(&record[10..]);
+    let held = Duration::from_micros(u64::from_le_bytes(held));
+    let action = match record[9] {
+        0 => Action::Pressed,
+        1 => Action::Released,
+        2 => Action::Held(held),
+        3 => Action::LongPress(held),
+        other => return Err(invalid_recording(&format!("unknown action {}", other))),
+    };
+    let event = JoystickEvent {
+        direction,
+        action,
+        timestamp: from_micros(to_micros(start) + offset),
+    };
+    Ok((Duration::from_micros(offset.max(0) as u64), event))
+}
+
+/// Helper function.
+///
+/// Reads an i64 from eight little-endian bytes.
+fn read_i64(bytes: &[u8]) -> i64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(bytes);
+    i64::from_le_bytes(buf)
+}
+
+/// Helper function.
+///
+/// Returns a time as microseconds since 1970, negative if it's before.
+fn to_micros(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_micros() as i64,
+        Err(err) => -(err.duration().as_micros() as i64),
+    }
+}
+
+/// Helper function.
+///
+/// Returns the time some microseconds after 1970, or before if negative.
+fn from_micros(micros: i64) -> SystemTime {
+    if micros >= 0 {
+        UNIX_EPOCH + Duration::from_micros(micros as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_micros(micros.unsigned_abs())
+    }
+}
+
+/// Helper function.
+///
+/// Makes the error for a file which isn't a recording this version can
+/// read.
+fn invalid_recording(problem: &str) -> SenseHatError {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("bad joystick recording: {}", problem),
+    )
+    .into()
+}
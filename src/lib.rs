@@ -1,60 +1,273 @@
+//! Rust support for the Raspberry Pi Sense Hat.
+//!
+//! # Features
+//!
+//! Both halves of the board are enabled by default. Turn off default
+//! features and pick one if you only need part of it:
+//!
+//! * `display` - the 8x8 LED matrix (needs the framebuffer).
+//! * `sensors` - the environmental sensors and IMU (needs I2C).
+//!
+//! A `SenseHat` only has the methods for the half which is built, and
+//! only reports the health of its components. The joystick is always
+//! there.
+//!
+//! With only `display`, the LED matrix is all there is:
+//!
+//! ```
+//! # #[cfg(all(feature = "display", not(feature = "sensors")))]
+//! # {
+//! use sensehat::{Component, MemoryMatrix, SenseHat};
+//!
+//! let matrix = MemoryMatrix::new();
+//! let mut sense = SenseHat::builder().led_matrix(matrix.clone()).strict(true).build().unwrap();
+//! sense.set_pixel(3, 4, (255, 0, 255)).unwrap();
+//! assert_eq!(matrix.pixels()[8 * 4 + 3], (255, 0, 255));
+//! let health = sense.health();
+//! assert!(health.status(Component::Display).unwrap().is_available());
+//! assert!(health.status(Component::Humidity).is_none());
+//! # }
+//! ```
+//!
+//! With only `sensors`, the sensors are:
+//!
+//! ```
+//! # #[cfg(all(feature = "sensors", not(feature = "display")))]
+//! # {
+//! use sensehat::{Component, MockI2CDevice, SenseHat};
+//!
+//! let pressure = MockI2CDevice::new();
+//! // 1013.25 hPa, and ready.
+//! pressure.set_registers(0x27, &[0x03, 0x00, 0x54, 0x3f]);
+//! let (accel_gyro, mag) = (MockI2CDevice::new(), MockI2CDevice::new());
+//! accel_gyro.set_register(0x0f, 0x68);
+//! mag.set_register(0x0f, 0x3d);
+//! let mut sense = SenseHat::builder()
+//!     .pressure_device(pressure)
+//!     .humidity_device(MockI2CDevice::new())
+//!     .imu_devices(accel_gyro, mag)
+//!     .strict(true)
+//!     .build()
+//!     .unwrap();
+//! assert_eq!(sense.get_pressure().unwrap().as_hectopascals(), 1013.25);
+//! let health = sense.health();
+//! assert!(health.status(Component::Imu).unwrap().is_available());
+//! assert!(health.status(Component::Display).is_none());
+//! # }
+//! ```
+//!
+//! With neither, there's the joystick, and a `SenseHat` with no
+//! components:
+//!
+//! ```
+//! # #[cfg(not(any(feature = "display", feature = "sensors")))]
+//! # {
+//! use std::time::{Duration, SystemTime};
+//! use sensehat::{Action, Component, Direction, MockJoystick, SenseHat};
+//!
+//! let sense = SenseHat::builder().strict(true).build().unwrap();
+//! let health = sense.health();
+//! assert!(health.is_healthy());
+//! let components = [Component::Pressure, Component::Humidity, Component::Display, Component::Imu];
+//! assert!(components.iter().all(|&component| health.status(component).is_none()));
+//!
+//! let (mut joystick, mut mock) = MockJoystick::new().unwrap();
+//! mock.press(Direction::Enter, SystemTime::now()).unwrap();
+//! let event = joystick.wait_for_event(Some(Duration::from_secs(5))).unwrap().unwrap();
+//! assert_eq!((event.direction, event.action), (Direction::Enter, Action::Pressed));
+//! # }
+//! ```
+//!
+//! There are also some optional extras:
+//!
+//! * `embedded-graphics` - lets embedded-graphics draw on the LED matrix,
+//!   through `DrawTarget` for `Display`. Implies `display`.
+//! * `image` - converts between frames and the image crate's `RgbImage`
+//!   and `RgbaImage`, and shows them on the LED matrix. Implies `display`.
+//! * `log` - logs I2C transfers and finding and initialising the hardware.
+//! * `mio` - lets the joystick be registered with a mio `Poll`.
+//! * `serde` - `Serialize` and `Deserialize` for the IMU samples and
+//!   timestamped readings.
+//! * `simulator` - shows the LED matrix in a window on the desktop, with
+//!   `SimulatorDisplay` and `SimulatorWindow`, and works the joystick with
+//!   the arrow keys and Enter. Implies `display`.
+
 extern crate byteorder;
+#[cfg(feature = "embedded-graphics")]
+extern crate embedded_graphics;
+#[cfg(feature = "sensors")]
 extern crate i2cdev;
+#[cfg(feature = "sensors")]
+#[macro_use]
 extern crate measurements;
+#[cfg(feature = "display")]
 extern crate framebuffer;
+#[cfg(feature = "display")]
 extern crate glob;
+#[cfg(feature = "image")]
+extern crate image;
 extern crate libc;
+#[cfg(feature = "simulator")]
+extern crate minifb;
+#[cfg(feature = "mio")]
+extern crate mio;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(feature = "serde")]
+extern crate serde_json;
+#[cfg(feature = "log")]
+#[macro_use]
+extern crate log;
+
+/// Without the `log` feature, `debug!` does nothing, not even working out
+/// its arguments.
+#[cfg(not(feature = "log"))]
+#[allow(unused_macros)]
+macro_rules! debug {
+    ($($arg:tt)+) => {};
+}
 
+#[cfg(feature = "display")]
+mod animation;
+#[cfg(feature = "display")]
+mod blink;
+#[cfg(feature = "sensors")]
+mod calibration;
+#[cfg(feature = "display")]
+mod canvas;
+mod cleanup;
+#[cfg(feature = "display")]
+mod color;
+#[cfg(all(feature = "display", feature = "sensors"))]
+pub mod compat;
 mod device;
+#[cfg(feature = "display")]
 mod display;
+#[cfg(feature = "embedded-graphics")]
+mod draw_target;
+mod error;
+#[cfg(feature = "display")]
+mod frame;
+#[cfg(feature = "sensors")]
+mod fusion;
+#[cfg(feature = "display")]
+mod gamma;
+mod hat;
+#[cfg(feature = "display")]
+mod history;
+#[cfg(feature = "sensors")]
+mod humidity;
+#[cfg(feature = "sensors")]
+mod i2c;
+#[cfg(feature = "image")]
+mod image_buffer;
+#[cfg(feature = "sensors")]
 mod imu;
+#[cfg(feature = "sensors")]
+mod imu_handle;
+mod joystick;
+mod joystick_recording;
+#[cfg(feature = "sensors")]
+mod mock_i2c;
+mod mock_joystick;
+#[cfg(feature = "sensors")]
+mod motion;
+#[cfg(feature = "sensors")]
+mod outlier;
+#[cfg(feature = "display")]
+mod pacing;
+#[cfg(feature = "sensors")]
+mod pressure;
+#[cfg(feature = "display")]
+mod raster;
+#[cfg(feature = "sensors")]
+mod reading;
+#[cfg(all(feature = "sensors", feature = "serde"))]
+pub mod recorder;
+#[cfg(feature = "display")]
+pub mod rle;
+#[cfg(feature = "sensors")]
+mod rtimulib;
+#[cfg(feature = "display")]
+mod scale;
+#[cfg(feature = "sensors")]
+mod selftest;
+#[cfg(feature = "sensors")]
 mod settings;
+mod shared;
+#[cfg(feature = "simulator")]
+mod simulator;
+#[cfg(feature = "sensors")]
+mod smoothing;
+#[cfg(feature = "display")]
+mod text;
+#[cfg(feature = "display")]
+pub mod transform;
+#[cfg(feature = "sensors")]
+mod units;
 
+#[cfg(feature = "display")]
+pub use animation::*;
+#[cfg(feature = "display")]
+pub use blink::*;
+#[cfg(feature = "sensors")]
+pub use calibration::*;
+#[cfg(feature = "display")]
+pub use canvas::*;
+pub use cleanup::*;
+#[cfg(feature = "display")]
+pub use color::*;
 pub use device::*;
+#[cfg(feature = "display")]
 pub use display::*;
+pub use error::*;
+#[cfg(feature = "display")]
+pub use frame::*;
+#[cfg(feature = "sensors")]
+pub use fusion::*;
+pub use hat::*;
+#[cfg(feature = "display")]
+pub use history::*;
+#[cfg(feature = "sensors")]
+pub use humidity::*;
+#[cfg(feature = "sensors")]
+pub use i2c::*;
+#[cfg(feature = "sensors")]
 pub use imu::*;
-
-use i2cdev::linux::LinuxI2CError;
-use framebuffer::FramebufferError;
-use glob::{GlobError, PatternError};
-
-/// A shortcut for Results that can return `T` or `SenseHatError`
-pub type SenseHatResult<T> = Result<T, SenseHatError>;
-
-/// Errors that this crate can return
-#[derive(Debug)]
-pub enum SenseHatError {
-    NotReady,
-    GenericError,
-    OutOfBounds,
-    InvalidGamma,
-    MissingFramebuffer,
-    GlobError(GlobError),
-    PatternError(PatternError),
-    FramebufferError(FramebufferError),
-    I2CError(LinuxI2CError),
-}
-
-impl From<LinuxI2CError> for SenseHatError {
-    fn from(err: LinuxI2CError) -> Self {
-        SenseHatError::I2CError(err)
-    }
-}
-
-impl From<GlobError> for SenseHatError {
-    fn from(err: GlobError) -> Self {
-        SenseHatError::GlobError(err)
-    }
-}
-
-impl From<PatternError> for SenseHatError {
-    fn from(err: PatternError) -> Self {
-        SenseHatError::PatternError(err)
-    }
-}
-
-impl From<FramebufferError> for SenseHatError {
-    fn from(err: FramebufferError) -> Self {
-        SenseHatError::FramebufferError(err)
-    }
-}
\ No newline at end of file
+#[cfg(feature = "sensors")]
+pub use imu_handle::*;
+pub use joystick::*;
+pub use joystick_recording::*;
+#[cfg(feature = "sensors")]
+pub use mock_i2c::*;
+pub use mock_joystick::*;
+#[cfg(feature = "sensors")]
+pub use motion::*;
+#[cfg(feature = "sensors")]
+pub use outlier::*;
+#[cfg(feature = "display")]
+pub use pacing::*;
+#[cfg(feature = "sensors")]
+pub use pressure::*;
+#[cfg(feature = "display")]
+pub use raster::*;
+#[cfg(feature = "sensors")]
+pub use reading::*;
+#[cfg(feature = "sensors")]
+pub use rtimulib::*;
+#[cfg(feature = "display")]
+pub use scale::*;
+#[cfg(feature = "sensors")]
+pub use selftest::*;
+#[cfg(feature = "sensors")]
+pub use settings::*;
+pub use shared::*;
+#[cfg(feature = "simulator")]
+pub use simulator::*;
+#[cfg(feature = "sensors")]
+pub use smoothing::*;
+#[cfg(feature = "display")]
+pub use text::*;
+#[cfg(feature = "sensors")]
+pub use units::*;
@@ -0,0 +1,224 @@
+use i2cdev::core::I2CDevice;
+use i2cdev::linux::LinuxI2CError;
+
+use std::io;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// How many registers a `MockI2CDevice` has.
+const REGISTERS: usize = 0x80;
+
+/// Stands in for one of the sensors on the Sense Hat's I2C bus, for trying
+/// out sensor code off the hardware.
+///
+/// It's a bank of 128 registers. Like the Sense Hat's sensors, reads and
+/// writes of more than one byte move on to the next register, and the top
+/// bit of a register address, which asks some of them to do that, is
+/// ignored. Clones share the registers, so a clone can be given to a
+/// `SenseHatBuilder` and the original used to set what the sensor reads
+/// and see what was written to it.
+///
+/// # Example
+///
+/// ```
+/// extern crate i2cdev;
+/// extern crate sensehat;
+///
+/// use i2cdev::core::I2CDevice;
+/// use sensehat::MockI2CDevice;
+///
+/// # fn main() {
+/// let mock = MockI2CDevice::new();
+/// mock.set_registers(0x28, &[0x34, 0x12]);
+///
+/// let mut dev = mock.clone();
+/// assert_eq!(dev.smbus_read_word_data(0x28).unwrap(), 0x1234);
+/// assert_eq!(dev.smbus_read_i2c_block_data(0x28 | 0x80, 2).unwrap(), [0x34, 0x12]);
+/// dev.smbus_write_byte_data(0x20, 0x87).unwrap();
+/// dev.write(&[0x10, 1, 2]).unwrap();
+///
+/// assert_eq!(mock.register(0x20), 0x87);
+/// assert_eq!(mock.writes(), [(0x20, 0x87), (0x10, 1), (0x11, 2)]);
+///
+/// mock.set_failing(true);
+/// assert!(dev.smbus_read_byte_data(0x0f).is_err());
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockI2CDevice {
+    state: Arc<Mutex<MockState>>,
+}
+
+/// The registers of a `MockI2CDevice`, and what's been done to them.
+#[derive(Debug)]
+struct MockState {
+    registers: [u8; REGISTERS],
+    /// the register the next read or write is of
+    selected: usize,
+    /// every register written, with the value, in order
+    writes: Vec<(u8, u8)>,
+    /// true if every transfer should fail
+    failing: bool,
+}
+
+impl Default for MockState {
+    fn default() -> Self {
+        MockState {
+            registers: [0; REGISTERS],
+            selected: 0,
+            writes: Vec::new(),
+            failing: false,
+        }
+    }
+}
+
+impl MockI2CDevice {
+    /// Makes a device with every register zero.
+    pub fn new() -> Self {
+        MockI2CDevice::default()
+    }
+
+    /// Sets a register, as the sensor would, without it counting as a
+    /// write.
+    pub fn set_register(&self, register: u8, value: u8) {
+        self.set_registers(register, &[value]);
+    }
+
+    /// Sets registers from `register` on, as the sensor would, without it
+    /// counting as a write.
+    pub fn set_registers(&self, register: u8, values: &[u8]) {
+        let mut state = self.lock();
+        for (i, &value) in values.iter().enumerate() {
+            state.registers[(register as usize + i) % REGISTERS] = value;
+        }
+    }
+
+    /// Returns the value of a register.
+    pub fn register(&self, register: u8) -> u8 {
+        self.lock().registers[register as usize % REGISTERS]
+    }
+
+    /// Returns every register written since the device was made, or since
+    /// `clear_writes`, with the value written, in order.
+    pub fn writes(&self) -> Vec<(u8, u8)> {
+        self.lock().writes.clone()
+    }
+
+    /// Forgets the writes so far.
+    pub fn clear_writes(&self) {
+        self.lock().writes.clear();
+    }
+
+    /// Makes every transfer fail, with the error the I2C bus gives when a
+    /// sensor isn't answering, or work again.
+    pub fn set_failing(&self, failing: bool) {
+        self.lock().failing = failing;
+    }
+
+    /// Helper function.
+    ///
+    /// Locks the state, for a transfer or a test to look at. A panic while
+    /// it was locked can't leave it in a state worth refusing to use.
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Helper function.
+    ///
+    /// Locks the state for a transfer, or returns the error a failing
+    /// device gives.
+    fn transfer(&self) -> Result<MutexGuard<'_, MockState>, LinuxI2CError> {
+        let state = self.lock();
+        if state.failing {
+            return Err(io::Error::other("mock I2C device is failing").into());
+        }
+        Ok(state)
+    }
+}
+
+impl MockState {
+    /// Helper function.
+    ///
+    /// Reads from the selected register on.
+    fn read(&mut self, data: &mut [u8]) {
+        for byte in data {
+            *byte = self.registers[self.selected];
+            self.selected = (self.selected + 1) % REGISTERS;
+        }
+    }
+
+    /// Helper function.
+    ///
+    /// Selects a register, and writes to it and the ones after it.
+    fn write(&mut self, register: u8, values: &[u8]) {
+        self.selected = register as usize % REGISTERS;
+        for &value in values {
+            self.registers[self.selected] = value;
+            self.writes.push((self.selected as u8, value));
+            self.selected = (self.selected + 1) % REGISTERS;
+        }
+    }
+}
+
+impl I2CDevice for MockI2CDevice {
+    type Error = LinuxI2CError;
+
+    fn read(&mut self, data: &mut [u8]) -> Result<(), LinuxI2CError> {
+        self.transfer()?.read(data);
+        Ok(())
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), LinuxI2CError> {
+        let mut state = self.transfer()?;
+        if let Some((&register, values)) = data.split_first() {
+            state.write(register, values);
+        }
+        Ok(())
+    }
+
+    fn smbus_write_quick(&mut self, _bit: bool) -> Result<(), LinuxI2CError> {
+        self.transfer().map(|_| ())
+    }
+
+    fn smbus_read_block_data(&mut self, register: u8) -> Result<Vec<u8>, LinuxI2CError> {
+        // SMBus block reads start with how many bytes follow.
+        let mut state = self.transfer()?;
+        state.selected = register as usize % REGISTERS;
+        let mut len = [0];
+        state.read(&mut len);
+        let mut data = vec![0; len[0].min(32) as usize];
+        state.read(&mut data);
+        Ok(data)
+    }
+
+    fn smbus_read_i2c_block_data(
+        &mut self,
+        register: u8,
+        len: u8,
+    ) -> Result<Vec<u8>, LinuxI2CError> {
+        let mut state = self.transfer()?;
+        state.selected = register as usize % REGISTERS;
+        let mut data = vec![0; len as usize];
+        state.read(&mut data);
+        Ok(data)
+    }
+
+    fn smbus_write_block_data(
+        &mut self,
+        register: u8,
+        values: &[u8],
+    ) -> Result<(), LinuxI2CError> {
+        let mut state = self.transfer()?;
+        let mut data = vec![values.len() as u8];
+        data.extend_from_slice(values);
+        state.write(register, &data);
+        Ok(())
+    }
+
+    fn smbus_process_block(
+        &mut self,
+        register: u8,
+        values: &[u8],
+    ) -> Result<(), LinuxI2CError> {
+        self.smbus_write_block_data(register, values)
+    }
+}
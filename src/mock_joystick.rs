@@ -0,0 +1,154 @@
+use joystick::{encode_event, Action, Direction, Joystick, JoystickEvent};
+use {SenseHatError, SenseHatResult};
+
+use libc;
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::os::unix::io::FromRawFd;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::SystemTime;
+
+/// Stands in for the Sense Hat joystick, for trying out joystick code off
+/// the hardware.
+///
+/// `MockJoystick::new` makes a real `Joystick`, with all the same methods,
+/// which reads from a pipe rather than the event device, and a
+/// `MockJoystick` which writes to the pipe what the kernel would. So the
+/// `Joystick` works out holds and long presses from the timestamps
+/// exactly as it does on the Sense Hat. Like on the Sense Hat, timestamps
+/// are to the microsecond, and `LongPress` events, and the durations in
+/// `Held` ones, come from the `Joystick` rather than being sent.
+///
+/// Dropping the `MockJoystick` closes the pipe, which the `Joystick` sees
+/// as the device going away.
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use sensehat::{Action, Direction, MockJoystick};
+///
+/// let (mut joystick, mut mock) = MockJoystick::new().unwrap();
+/// let start = UNIX_EPOCH + Duration::from_secs(1_000);
+/// let at = |ms| start + Duration::from_millis(ms);
+///
+/// mock.press(Direction::Up, at(0)).unwrap();
+/// mock.hold(Direction::Up, at(500)).unwrap();
+/// mock.hold(Direction::Up, at(1200)).unwrap();
+/// mock.hold(Direction::Up, at(1300)).unwrap();
+/// mock.release(Direction::Up, at(1400)).unwrap();
+///
+/// let actions: Vec<Action> = joystick.get_events().unwrap().iter().map(|e| e.action).collect();
+/// assert_eq!(
+///     actions,
+///     [
+///         Action::Pressed,
+///         Action::Held(Duration::from_millis(500)),
+///         Action::Held(Duration::from_millis(1200)),
+///         Action::LongPress(Duration::from_millis(1200)),
+///         Action::Held(Duration::from_millis(1300)),
+///         Action::Released,
+///     ]
+/// );
+/// assert!(!joystick.state().unwrap().is_pressed(Direction::Up));
+/// ```
+#[derive(Debug)]
+pub struct MockJoystick {
+    pipe: File,
+}
+
+impl MockJoystick {
+    /// Makes a `Joystick` reading what the returned `MockJoystick` sends.
+    pub fn new() -> SenseHatResult<(Joystick, MockJoystick)> {
+        let mut fds = [0; 2];
+        if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        // Owning the ends straight away means they're closed on errors.
+        let (reader, writer) = unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) };
+        // A Joystick expects never to block, as an event device opened by
+        // Joystick::open doesn't.
+        if unsafe { libc::fcntl(fds[0], libc::F_SETFL, libc::O_NONBLOCK) } != 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        Ok((Joystick::from_device(reader), MockJoystick { pipe: writer }))
+    }
+
+    /// Makes a `Joystick` reading the events sent down the returned
+    /// channel, for when they come from another thread. A thread passes
+    /// them on as `send` does, until every sender has been dropped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::{Duration, SystemTime};
+    /// use sensehat::{Action, Direction, JoystickEvent, MockJoystick};
+    ///
+    /// let (mut joystick, sender) = MockJoystick::channel().unwrap();
+    /// thread::spawn(move || {
+    ///     let timestamp = SystemTime::now();
+    ///     let event = JoystickEvent { direction: Direction::Enter, action: Action::Pressed, timestamp };
+    ///     sender.send(event).unwrap();
+    /// });
+    /// let event = joystick.wait_for_event(Some(Duration::from_secs(5))).unwrap().unwrap();
+    /// assert_eq!((event.direction, event.action), (Direction::Enter, Action::Pressed));
+    /// ```
+    pub fn channel() -> SenseHatResult<(Joystick, Sender<JoystickEvent>)> {
+        let (joystick, mut mock) = MockJoystick::new()?;
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for event in receiver {
+                if mock.send(event).is_err() {
+                    // The joystick has been dropped.
+                    break;
+                }
+            }
+        });
+        Ok((joystick, sender))
+    }
+
+    /// Sends an event as the kernel would. `LongPress` events are ignored,
+    /// as the kernel never sends them, and the duration in a `Held` is
+    /// ignored, as it's worked out from the timestamps.
+    pub fn send(&mut self, event: JoystickEvent) -> SenseHatResult<()> {
+        if let Some(raw) = encode_event(&event) {
+            self.pipe.write_all(&raw).map_err(SenseHatError::from)?;
+        }
+        Ok(())
+    }
+
+    /// Presses a direction at `timestamp`.
+    pub fn press(&mut self, direction: Direction, timestamp: SystemTime) -> SenseHatResult<()> {
+        self.send_action(direction, Action::Pressed, timestamp)
+    }
+
+    /// Sends the repeat the kernel sends while a direction is held, at
+    /// `timestamp`.
+    pub fn hold(&mut self, direction: Direction, timestamp: SystemTime) -> SenseHatResult<()> {
+        self.send_action(direction, Action::Held(Default::default()), timestamp)
+    }
+
+    /// Releases a direction at `timestamp`.
+    pub fn release(&mut self, direction: Direction, timestamp: SystemTime) -> SenseHatResult<()> {
+        self.send_action(direction, Action::Released, timestamp)
+    }
+
+    /// Helper function.
+    ///
+    /// Sends an event made from its parts.
+    fn send_action(
+        &mut self,
+        direction: Direction,
+        action: Action,
+        timestamp: SystemTime,
+    ) -> SenseHatResult<()> {
+        self.send(JoystickEvent {
+            direction,
+            action,
+            timestamp,
+        })
+    }
+}
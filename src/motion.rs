@@ -0,0 +1,203 @@
+use units::Acceleration3d;
+
+use std::time::{Duration, Instant};
+
+/// Something the Sense Hat's movement did, found by `MotionDetector`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MotionEvent {
+    /// The acceleration stayed near zero for this long, as it does while
+    /// the Sense Hat is falling. Reported when the fall ends.
+    FreeFall { duration: Duration },
+    /// The Sense Hat was shaken back and forth, with this peak
+    /// acceleration in g.
+    Shake { peak_g: f64 },
+}
+
+/// The thresholds `MotionDetector` uses.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MotionThresholds {
+    /// The acceleration, in g, below which the Sense Hat counts as falling.
+    pub free_fall_g: f64,
+    /// How long the acceleration has to stay below `free_fall_g` to be a
+    /// fall rather than a bump.
+    pub free_fall_time: Duration,
+    /// The acceleration, in g, above which a movement counts towards a
+    /// shake.
+    pub shake_g: f64,
+    /// How close together two movements in opposite directions have to be
+    /// to make a shake.
+    pub shake_window: Duration,
+}
+
+impl Default for MotionThresholds {
+    /// A 0.3g fall lasting 100ms, or two 2g movements within 500ms.
+    fn default() -> Self {
+        MotionThresholds {
+            free_fall_g: 0.3,
+            free_fall_time: Duration::from_millis(100),
+            shake_g: 2.0,
+            shake_window: Duration::from_millis(500),
+        }
+    }
+}
+
+/// One movement above the shake threshold.
+#[derive(Debug, Copy, Clone)]
+struct Peak {
+    /// When the acceleration went above the threshold.
+    start: Instant,
+    /// The axis with the biggest acceleration when it did.
+    axis: usize,
+    /// Whether that acceleration was positive.
+    positive: bool,
+    /// The largest acceleration, in g, during the movement.
+    peak_g: f64,
+}
+
+/// Finds free falls and shakes in a stream of accelerometer readings.
+///
+/// It only looks at the readings it's fed, so works the same on readings
+/// from `Imu::try_read_sample`, a FIFO, or a recording.
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use sensehat::{MotionDetector, MotionEvent};
+///
+/// let mut detector = MotionDetector::default();
+/// let start = Instant::now();
+/// let at = |ms| start + Duration::from_millis(ms);
+///
+/// // Resting, then dropped for 200ms.
+/// let mut events = Vec::new();
+/// for ms in (0..400).step_by(10) {
+///     let z = if (100..300).contains(&ms) { 0.05 } else { 1.0 };
+///     events.extend(detector.update([0.0, 0.0, z], at(ms)));
+/// }
+/// assert_eq!(
+///     events,
+///     vec![MotionEvent::FreeFall { duration: Duration::from_millis(200) }]
+/// );
+///
+/// // Shaken left and right.
+/// let mut events = Vec::new();
+/// for (i, &x) in [0.0, 3.0, 0.0, -2.5, 0.0].iter().enumerate() {
+///     events.extend(detector.update([x, 0.0, 1.0], at(1000 + i as u64 * 50)));
+/// }
+/// match events[..] {
+///     [MotionEvent::Shake { peak_g }] => assert!(peak_g > 3.0),
+///     _ => panic!("expected one shake, got {:?}", events),
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MotionDetector {
+    thresholds: MotionThresholds,
+    /// When the current fall started.
+    fall_start: Option<Instant>,
+    /// The movement above the shake threshold happening now.
+    peak: Option<Peak>,
+    /// The last movement above the shake threshold, waiting for one in the
+    /// opposite direction.
+    last_peak: Option<Peak>,
+}
+
+impl MotionDetector {
+    pub fn new(thresholds: MotionThresholds) -> Self {
+        MotionDetector {
+            thresholds,
+            fall_start: None,
+            peak: None,
+            last_peak: None,
+        }
+    }
+
+    /// Returns the thresholds in use.
+    pub fn thresholds(&self) -> MotionThresholds {
+        self.thresholds
+    }
+
+    /// Changes the thresholds, forgetting any motion in progress.
+    pub fn set_thresholds(&mut self, thresholds: MotionThresholds) {
+        *self = MotionDetector::new(thresholds);
+    }
+
+    /// Feeds an accelerometer reading, in g, taken at `timestamp`. Returns
+    /// the event it finished, if any.
+    pub fn update(&mut self, accel: [f64; 3], timestamp: Instant) -> Option<MotionEvent> {
+        let magnitude = Acceleration3d::from_g(accel).magnitude().as_g();
+        self.update_free_fall(magnitude, timestamp)
+            .or_else(|| self.update_shake(accel, magnitude, timestamp))
+    }
+
+    /// Helper function.
+    ///
+    /// Tracks how long the acceleration has been below the free fall
+    /// threshold, and reports the fall once it's over.
+    fn update_free_fall(&mut self, magnitude: f64, timestamp: Instant) -> Option<MotionEvent> {
+        if magnitude < self.thresholds.free_fall_g {
+            self.fall_start.get_or_insert(timestamp);
+            return None;
+        }
+        let duration = timestamp.duration_since(self.fall_start.take()?);
+        if duration >= self.thresholds.free_fall_time {
+            Some(MotionEvent::FreeFall { duration })
+        } else {
+            None
+        }
+    }
+
+    /// Helper function.
+    ///
+    /// Tracks movements above the shake threshold, and reports a shake
+    /// when one ends close enough after another in the opposite direction.
+    fn update_shake(
+        &mut self,
+        accel: [f64; 3],
+        magnitude: f64,
+        timestamp: Instant,
+    ) -> Option<MotionEvent> {
+        if magnitude > self.thresholds.shake_g {
+            match self.peak {
+                Some(ref mut peak) => peak.peak_g = peak.peak_g.max(magnitude),
+                None => {
+                    let axis = (0..3)
+                        .max_by(|&a, &b| accel[a].abs().total_cmp(&accel[b].abs()))
+                        .unwrap_or(0);
+                    self.peak = Some(Peak {
+                        start: timestamp,
+                        axis,
+                        positive: accel[axis] > 0.0,
+                        peak_g: magnitude,
+                    });
+                }
+            }
+            return None;
+        }
+
+        let peak = self.peak.take()?;
+        let window = self.thresholds.shake_window;
+        match self.last_peak.take() {
+            Some(last)
+                if last.axis == peak.axis
+                    && last.positive != peak.positive
+                    && peak.start.duration_since(last.start) <= window =>
+            {
+                Some(MotionEvent::Shake {
+                    peak_g: last.peak_g.max(peak.peak_g),
+                })
+            }
+            _ => {
+                self.last_peak = Some(peak);
+                None
+            }
+        }
+    }
+}
+
+impl Default for MotionDetector {
+    /// A detector using the default thresholds.
+    fn default() -> Self {
+        MotionDetector::new(MotionThresholds::default())
+    }
+}
@@ -0,0 +1,165 @@
+use {SenseHatError, SenseHatResult};
+
+use std::fmt;
+use std::time::Instant;
+
+/// A check a reading has to pass to be believed, for
+/// `SenseHat::set_outlier_policy`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum OutlierRule {
+    /// The reading has to be between `min` and `max`, inclusive.
+    Range { min: f64, max: f64 },
+    /// The reading can't have changed faster than this, in units per
+    /// second, since the last one which was believed.
+    JumpLimit { max_delta_per_second: f64 },
+}
+
+/// Which readings `SenseHat::set_outlier_policy` rejects, in °C, %rH and
+/// hPa. The default has no rules, so nothing is rejected.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OutlierPolicy {
+    /// The rules for both temperature readings.
+    pub temperature: Vec<OutlierRule>,
+    /// The rules for humidity readings.
+    pub humidity: Vec<OutlierRule>,
+    /// The rules for pressure readings.
+    pub pressure: Vec<OutlierRule>,
+    /// Whether to read once more when a reading is rejected, rather than
+    /// returning `OutlierRejected` straight away.
+    pub retry: bool,
+}
+
+/// Why a reading was rejected.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum OutlierReason {
+    /// It was outside the `Range` rule.
+    OutOfRange { min: f64, max: f64 },
+    /// It changed faster than the `JumpLimit` rule, by this much per
+    /// second.
+    TooFast {
+        delta_per_second: f64,
+        max_delta_per_second: f64,
+    },
+}
+
+impl fmt::Display for OutlierReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            OutlierReason::OutOfRange { min, max } => write!(f, "outside {} to {}", min, max),
+            OutlierReason::TooFast {
+                delta_per_second,
+                max_delta_per_second,
+            } => write!(
+                f,
+                "changed by {} per second, more than {}",
+                delta_per_second, max_delta_per_second
+            ),
+        }
+    }
+}
+
+/// Checks a stream of readings against a list of `OutlierRule`s,
+/// remembering the last one it believed for `JumpLimit`. Nothing here
+/// reads the clock, so the times are whatever the caller passes in.
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use sensehat::{OutlierFilter, OutlierReason, OutlierRule};
+///
+/// let start = Instant::now();
+/// let at = |s| start + Duration::from_secs(s);
+///
+/// let mut filter = OutlierFilter::new(vec![
+///     OutlierRule::Range { min: -40.0, max: 85.0 },
+///     OutlierRule::JumpLimit { max_delta_per_second: 1.0 },
+/// ]);
+/// assert_eq!(filter.check(21.0, at(0)), Ok(()));
+/// filter.accept(21.0, at(0));
+///
+/// // A glitch between two good readings is rejected, and doesn't count
+/// // as the last good one.
+/// assert_eq!(
+///     filter.check(-40.0, at(1)),
+///     Err(OutlierReason::TooFast { delta_per_second: 61.0, max_delta_per_second: 1.0 })
+/// );
+/// assert!(filter.filter(-40.0, at(1)).is_err());
+/// assert_eq!(filter.filter(21.5, at(2)).unwrap(), 21.5);
+///
+/// // A big change is fine given long enough.
+/// assert!(filter.filter(30.0, at(4)).is_err());
+/// assert_eq!(filter.filter(30.0, at(20)).unwrap(), 30.0);
+///
+/// assert_eq!(
+///     filter.check(90.0, at(100)),
+///     Err(OutlierReason::OutOfRange { min: -40.0, max: 85.0 })
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlierFilter {
+    rules: Vec<OutlierRule>,
+    /// The last reading believed, and when it was taken.
+    last: Option<(f64, Instant)>,
+}
+
+impl OutlierFilter {
+    /// Makes a filter with nothing believed yet.
+    pub fn new(rules: Vec<OutlierRule>) -> Self {
+        OutlierFilter { rules, last: None }
+    }
+
+    /// Returns the rules readings are checked against.
+    pub fn rules(&self) -> &[OutlierRule] {
+        &self.rules
+    }
+
+    /// Checks a reading taken at `now` against every rule, without
+    /// remembering it, returning why it was rejected if it was.
+    pub fn check(&self, value: f64, now: Instant) -> Result<(), OutlierReason> {
+        for rule in &self.rules {
+            match *rule {
+                OutlierRule::Range { min, max } => {
+                    if !(value >= min && value <= max) {
+                        return Err(OutlierReason::OutOfRange { min, max });
+                    }
+                }
+                OutlierRule::JumpLimit {
+                    max_delta_per_second,
+                } => {
+                    if let Some((last, then)) = self.last {
+                        let elapsed = now.saturating_duration_since(then).as_secs_f64();
+                        let delta = (value - last).abs();
+                        if delta.is_nan() || delta > max_delta_per_second * elapsed {
+                            return Err(OutlierReason::TooFast {
+                                delta_per_second: delta / elapsed,
+                                max_delta_per_second,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Remembers a reading taken at `now` as the last one believed.
+    pub fn accept(&mut self, value: f64, now: Instant) {
+        self.last = Some((value, now));
+    }
+
+    /// Checks a reading, and remembers it if it passes. Returns the
+    /// reading, or `OutlierRejected` if it didn't pass.
+    pub fn filter(&mut self, value: f64, now: Instant) -> SenseHatResult<f64> {
+        self.check(value, now)
+            .map_err(|reason| SenseHatError::OutlierRejected { value, reason })?;
+        self.accept(value, now);
+        Ok(value)
+    }
+
+    /// Forgets the last reading believed, so `JumpLimit` passes anything
+    /// next.
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
@@ -0,0 +1,211 @@
+use display::Display;
+use {SenseHatError, SenseHatResult};
+
+use std::collections::VecDeque;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How many frames `FramePacer::actual_fps` is worked out over.
+const FPS_WINDOW: usize = 30;
+
+/// Keeps an animation at a steady frame rate, as made by
+/// `Display::frame_pacer`.
+///
+/// Frames are due at fixed intervals from when the pacer starts, so the
+/// time spent drawing each one doesn't add up into drift the way sleeping
+/// a fixed time between frames does. If a frame is drawn too late for its
+/// time, the pacer doesn't sleep, and skips any frame times which have
+/// already gone by rather than rushing through them to catch up, so the
+/// animation carries on in step.
+///
+/// `tick` sleeps until the next frame is due. `schedule` does the same
+/// sums without reading the clock or sleeping, for tests.
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use sensehat::FramePacer;
+///
+/// let start = Instant::now();
+/// let at = |ms| start + Duration::from_millis(ms);
+///
+/// let mut pacer = FramePacer::new(20.0).unwrap();
+/// pacer.start(at(0));
+/// // Drawing took 10ms, so sleep the other 40.
+/// let tick = pacer.schedule(at(10));
+/// assert_eq!((tick.sleep, tick.skipped), (Duration::from_millis(40), 0));
+/// assert_eq!(tick.due, at(50));
+///
+/// // Drawing took until 170ms, so this frame is late for 100, and the one
+/// // due at 150 is skipped.
+/// let tick = pacer.schedule(at(170));
+/// assert_eq!((tick.sleep, tick.skipped), (Duration::ZERO, 1));
+/// // The next one is due at 200, still in step.
+/// assert_eq!(pacer.schedule(at(180)).sleep, Duration::from_millis(20));
+/// assert_eq!(pacer.skipped_frames(), 1);
+///
+/// assert!(FramePacer::new(0.0).is_err());
+/// assert!(FramePacer::new(f64::INFINITY).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct FramePacer {
+    interval: Duration,
+    // When the next frame is due
+    next: Instant,
+    // When the last few frames were shown, oldest first
+    frames: VecDeque<Instant>,
+    skipped: u64,
+}
+
+/// What `FramePacer::schedule` worked out for a frame.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FrameTick {
+    /// How long to sleep before showing the frame.
+    pub sleep: Duration,
+    /// When the frame is shown, once the sleep is over.
+    pub due: Instant,
+    /// How many frame times had gone by before this one, and were skipped.
+    pub skipped: u32,
+}
+
+impl FramePacer {
+    /// Makes a pacer for `fps` frames a second, starting now. Returns
+    /// `InvalidFrameRate` unless `fps` is more than 0 and finite.
+    pub fn new(fps: f64) -> SenseHatResult<Self> {
+        if !(fps > 0.0 && fps.is_finite()) {
+            return Err(SenseHatError::InvalidFrameRate(fps));
+        }
+        let interval = Duration::try_from_secs_f64(1.0 / fps)
+            .map_err(|_| SenseHatError::InvalidFrameRate(fps))?;
+        Ok(FramePacer::with_interval(interval))
+    }
+
+    /// Makes a pacer showing a frame every `interval`, starting now. An
+    /// interval of zero never sleeps.
+    pub fn with_interval(interval: Duration) -> Self {
+        let now = Instant::now();
+        FramePacer {
+            interval,
+            next: now + interval,
+            frames: VecDeque::with_capacity(FPS_WINDOW),
+            skipped: 0,
+        }
+    }
+
+    /// Returns how long there is between frames.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Returns the frame rate asked for, in frames a second.
+    pub fn fps(&self) -> f64 {
+        1.0 / self.interval.as_secs_f64()
+    }
+
+    /// Starts again at `now`, so the next frame is due an interval after
+    /// it, and forgets the frames so far.
+    pub fn start(&mut self, now: Instant) {
+        self.next = now + self.interval;
+        self.frames.clear();
+        self.skipped = 0;
+    }
+
+    /// Sleeps until the next frame is due, then returns how many frame
+    /// times were skipped because the last one took too long. Call it
+    /// between frames; an animation can skip as many frames to stay in
+    /// step.
+    pub fn tick(&mut self) -> u32 {
+        let tick = self.schedule(Instant::now());
+        thread::sleep(tick.sleep);
+        tick.skipped
+    }
+
+    /// Works out when the frame being drawn at `now` is due, as `tick`
+    /// does, without sleeping.
+    pub fn schedule(&mut self, now: Instant) -> FrameTick {
+        let (sleep, due, skipped) = if now <= self.next {
+            (self.next - now, self.next, 0)
+        } else if self.interval == Duration::ZERO {
+            (Duration::ZERO, now, 0)
+        } else {
+            let late = now - self.next;
+            let skipped = (late.as_nanos() / self.interval.as_nanos()) as u32;
+            (Duration::ZERO, now, skipped)
+        };
+        self.skipped += u64::from(skipped);
+        self.next += self.interval * (skipped + 1);
+        if self.next < due {
+            // Zero intervals and huge gaps.
+            self.next = due;
+        }
+        if self.frames.len() == FPS_WINDOW {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(due);
+        FrameTick {
+            sleep,
+            due,
+            skipped,
+        }
+    }
+
+    /// Returns how many frames a second have really been shown, over the
+    /// last few frames, or None until there have been two.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use sensehat::FramePacer;
+    ///
+    /// let mut now = Instant::now();
+    /// let mut pacer = FramePacer::new(10.0).unwrap();
+    /// pacer.start(now);
+    /// assert_eq!(pacer.actual_fps(), None);
+    /// // Every frame takes 130ms to draw, longer than the 100ms there is.
+    /// for _ in 0..40 {
+    ///     now += Duration::from_millis(130);
+    ///     pacer.schedule(now);
+    /// }
+    /// assert!((pacer.actual_fps().unwrap() - 1000.0 / 130.0).abs() < 1e-6);
+    /// assert!(pacer.skipped_frames() > 0);
+    /// ```
+    pub fn actual_fps(&self) -> Option<f64> {
+        let first = self.frames.front()?;
+        let last = self.frames.back()?;
+        let elapsed = last.saturating_duration_since(*first).as_secs_f64();
+        if self.frames.len() < 2 || elapsed == 0.0 {
+            return None;
+        }
+        Some((self.frames.len() - 1) as f64 / elapsed)
+    }
+
+    /// Returns how many frame times have been skipped since the pacer
+    /// started.
+    pub fn skipped_frames(&self) -> u64 {
+        self.skipped
+    }
+}
+
+impl Display {
+    /// Makes a pacer for animating the LED matrix at `fps` frames a second.
+    /// Returns `InvalidFrameRate` unless `fps` is more than 0 and finite.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::Display;
+    ///
+    /// let mut display = Display::new().unwrap();
+    /// let mut pacer = display.frame_pacer(30.0).unwrap();
+    /// for frame in 0..300 {
+    ///     let pixels = [((frame % 256) as u8, 0, 0); 64];
+    ///     display.set_pixels(&pixels).unwrap();
+    ///     pacer.tick();
+    /// }
+    /// ```
+    pub fn frame_pacer(&self, fps: f64) -> SenseHatResult<FramePacer> {
+        FramePacer::new(fps)
+    }
+}
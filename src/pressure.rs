@@ -0,0 +1,220 @@
+use measurements::Pressure;
+use {SenseHatError, SenseHatResult};
+
+/// How many steps of the LPS25H RPDS offset register there are in a
+/// hectopascal.
+const OFFSET_STEPS_PER_HPA: f64 = 16.0;
+
+/// How many steps of the LPS25H pressure output, and of the REF_P
+/// reference pressure registers, there are in a hectopascal.
+const PRESSURE_STEPS_PER_HPA: f64 = 4096.0;
+
+/// Turns the LPS25H's raw pressure reading, from `SenseHat::get_pressure_raw`,
+/// into hPa.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::pressure_from_raw;
+///
+/// assert_eq!(pressure_from_raw(4096 * 1000).as_hectopascals(), 1000.0);
+/// assert_eq!(pressure_from_raw(4_150_272).as_hectopascals(), 4_150_272.0 / 4096.0);
+/// ```
+pub fn pressure_from_raw(raw: i32) -> Pressure {
+    Pressure::from_hectopascals(raw as f64 / PRESSURE_STEPS_PER_HPA)
+}
+
+/// Which pressure threshold crossings the LPS25H latches as an interrupt,
+/// for `SenseHat::set_pressure_interrupt`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PressureIntMode {
+    /// No pressure interrupts.
+    Disabled,
+    /// The pressure going above the reference plus the threshold.
+    High,
+    /// The pressure going below the reference minus the threshold.
+    Low,
+    /// Either.
+    HighOrLow,
+}
+
+impl PressureIntMode {
+    /// Returns the value of the INTERRUPT_CFG register for this mode, with
+    /// the interrupt latched until INT_SOURCE is read.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::PressureIntMode;
+    ///
+    /// assert_eq!(PressureIntMode::Disabled.interrupt_cfg(), 0x00);
+    /// assert_eq!(PressureIntMode::High.interrupt_cfg(), 0x05);
+    /// assert_eq!(PressureIntMode::Low.interrupt_cfg(), 0x06);
+    /// assert_eq!(PressureIntMode::HighOrLow.interrupt_cfg(), 0x07);
+    /// ```
+    pub fn interrupt_cfg(self) -> u8 {
+        match self {
+            PressureIntMode::Disabled => 0x00,
+            PressureIntMode::High => 0x04 | 0x01,
+            PressureIntMode::Low => 0x04 | 0x02,
+            PressureIntMode::HighOrLow => 0x04 | 0x03,
+        }
+    }
+
+    /// Returns the value of the CTRL_REG3 register for this mode, which
+    /// puts the interrupt on the INT1 pin, active high and push-pull. With
+    /// interrupts disabled, the pin shows when data is ready, as it does
+    /// by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::PressureIntMode;
+    ///
+    /// assert_eq!(PressureIntMode::Disabled.ctrl_reg3(), 0x00);
+    /// assert_eq!(PressureIntMode::High.ctrl_reg3(), 0x01);
+    /// assert_eq!(PressureIntMode::Low.ctrl_reg3(), 0x02);
+    /// assert_eq!(PressureIntMode::HighOrLow.ctrl_reg3(), 0x03);
+    /// ```
+    pub fn ctrl_reg3(self) -> u8 {
+        match self {
+            PressureIntMode::Disabled => 0x00,
+            PressureIntMode::High => 0x01,
+            PressureIntMode::Low => 0x02,
+            PressureIntMode::HighOrLow => 0x03,
+        }
+    }
+}
+
+/// The LPS25H INT_SOURCE register, saying which pressure threshold
+/// crossings have happened since it was last read.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::PressureIntSource;
+///
+/// let source = PressureIntSource::from_register(0x06);
+/// assert!(source.active);
+/// assert!(source.low);
+/// assert!(!source.high);
+/// assert_eq!(source.raw, 0x06);
+///
+/// assert!(!PressureIntSource::from_register(0x00).active);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PressureIntSource {
+    /// An interrupt has happened.
+    pub active: bool,
+    /// The pressure went above the reference plus the threshold.
+    pub high: bool,
+    /// The pressure went below the reference minus the threshold.
+    pub low: bool,
+    /// The INT_SOURCE register as read.
+    pub raw: u8,
+}
+
+impl PressureIntSource {
+    /// Decodes the value of the INT_SOURCE register.
+    pub fn from_register(raw: u8) -> Self {
+        PressureIntSource {
+            active: raw & 0x04 != 0,
+            low: raw & 0x02 != 0,
+            high: raw & 0x01 != 0,
+            raw,
+        }
+    }
+}
+
+/// Converts a pressure interrupt threshold to the value of the LPS25H
+/// THS_P_L and THS_P_H registers, in that order, rounding to the nearest
+/// 1/16 hPa.
+///
+/// Returns `InvalidPressureThreshold` if it doesn't fit in the registers,
+/// which hold from 0 to 4095.9375 hPa.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{pressure_threshold_to_registers, Pressure};
+///
+/// let threshold = Pressure::from_hectopascals(2.5);
+/// assert_eq!(pressure_threshold_to_registers(threshold).unwrap(), [40, 0]);
+/// let largest = Pressure::from_hectopascals(4095.9375);
+/// assert_eq!(pressure_threshold_to_registers(largest).unwrap(), [0xff, 0xff]);
+/// assert!(pressure_threshold_to_registers(Pressure::from_hectopascals(-1.0)).is_err());
+/// ```
+pub fn pressure_threshold_to_registers(threshold: Pressure) -> SenseHatResult<[u8; 2]> {
+    let hectopascals = threshold.as_hectopascals();
+    let steps = (hectopascals * OFFSET_STEPS_PER_HPA).round();
+    if !(0.0..=u16::MAX as f64).contains(&steps) {
+        return Err(SenseHatError::InvalidPressureThreshold(hectopascals));
+    }
+    Ok((steps as u16).to_le_bytes())
+}
+
+/// Converts a reference pressure to the value of the LPS25H REF_P_XL,
+/// REF_P_L and REF_P_H registers, in that order, rounding to the nearest
+/// 1/4096 hPa.
+///
+/// Returns `InvalidPressureThreshold` if it doesn't fit in the registers,
+/// which hold from -2048 to just under 2048 hPa.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{pressure_reference_to_registers, Pressure};
+///
+/// let reference = Pressure::from_hectopascals(1000.0);
+/// assert_eq!(pressure_reference_to_registers(reference).unwrap(), [0x00, 0x80, 0x3e]);
+/// assert!(pressure_reference_to_registers(Pressure::from_hectopascals(2048.0)).is_err());
+/// ```
+pub fn pressure_reference_to_registers(reference: Pressure) -> SenseHatResult<[u8; 3]> {
+    let hectopascals = reference.as_hectopascals();
+    let steps = (hectopascals * PRESSURE_STEPS_PER_HPA).round();
+    if !(-8_388_608.0..=8_388_607.0).contains(&steps) {
+        return Err(SenseHatError::InvalidPressureThreshold(hectopascals));
+    }
+    let bytes = (steps as i32).to_le_bytes();
+    Ok([bytes[0], bytes[1], bytes[2]])
+}
+
+/// Converts a pressure offset to the value of the LPS25H RPDS_L and RPDS_H
+/// registers, in that order, rounding to the nearest 1/16 hPa.
+///
+/// Returns `InvalidPressureOffset` if it doesn't fit in the register, which
+/// holds from -2048 to 2047.9375 hPa.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{pressure_offset_from_registers, pressure_offset_to_registers, Pressure};
+///
+/// let offset = Pressure::from_hectopascals(1.5);
+/// assert_eq!(pressure_offset_to_registers(offset).unwrap(), [24, 0]);
+/// let negative = pressure_offset_to_registers(Pressure::from_hectopascals(-0.0625)).unwrap();
+/// assert_eq!(negative, [0xff, 0xff]);
+///
+/// let largest = pressure_offset_to_registers(Pressure::from_hectopascals(2047.9375)).unwrap();
+/// assert_eq!(largest, [0xff, 0x7f]);
+/// assert!(pressure_offset_to_registers(Pressure::from_hectopascals(2048.0)).is_err());
+/// assert!(pressure_offset_to_registers(Pressure::from_hectopascals(-2048.5)).is_err());
+///
+/// let back = pressure_offset_from_registers([24, 0]);
+/// assert_eq!(back.as_hectopascals(), 1.5);
+/// assert_eq!(pressure_offset_from_registers([0, 0x80]).as_hectopascals(), -2048.0);
+/// ```
+pub fn pressure_offset_to_registers(offset: Pressure) -> SenseHatResult<[u8; 2]> {
+    let hectopascals = offset.as_hectopascals();
+    let steps = (hectopascals * OFFSET_STEPS_PER_HPA).round();
+    if !(i16::MIN as f64..=i16::MAX as f64).contains(&steps) {
+        return Err(SenseHatError::InvalidPressureOffset(hectopascals));
+    }
+    Ok((steps as i16).to_le_bytes())
+}
+
+/// Converts the value of the LPS25H RPDS_L and RPDS_H registers, in that
+/// order, to the pressure offset they hold.
+pub fn pressure_offset_from_registers(registers: [u8; 2]) -> Pressure {
+    Pressure::from_hectopascals(i16::from_le_bytes(registers) as f64 / OFFSET_STEPS_PER_HPA)
+}
@@ -0,0 +1,83 @@
+use display::Pixel;
+
+/// How `rasterize` draws the LED matrix, as a grid of squares with gaps
+/// between them, like the LEDs on the board.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RasterLayout {
+    /// How many window pixels across each LED's square is.
+    pub cell: usize,
+    /// How many window pixels go between the squares, and around the edge.
+    pub gap: usize,
+    /// The colour of the gaps.
+    pub background: Pixel,
+}
+
+impl Default for RasterLayout {
+    fn default() -> Self {
+        RasterLayout {
+            cell: 40,
+            gap: 4,
+            background: (32, 32, 32),
+        }
+    }
+}
+
+impl RasterLayout {
+    /// Returns how many window pixels across, and down, the drawn matrix
+    /// is.
+    pub fn size(&self) -> usize {
+        8 * self.cell + 9 * self.gap
+    }
+}
+
+/// Draws an 8x8 frame as a square image of `layout.size()` pixels each
+/// way, row by row from the top left, each pixel as `0x00RRGGBB`, which
+/// is what window libraries like minifb take.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{rasterize, RasterLayout};
+///
+/// let layout = RasterLayout { cell: 2, gap: 1, background: (0, 0, 0) };
+/// assert_eq!(layout.size(), 25);
+///
+/// let mut frame = [(0, 0, 0); 64];
+/// frame[0] = (255, 0, 0);
+/// frame[63] = (0, 0, 255);
+/// let image = rasterize(&frame, &layout);
+/// assert_eq!(image.len(), 25 * 25);
+///
+/// // The edge is a gap, then the top left LED's square.
+/// assert_eq!(image[0], 0);
+/// assert_eq!(&image[25 + 1..25 + 3], &[0xff0000, 0xff0000]);
+/// assert_eq!(&image[2 * 25 + 1..2 * 25 + 3], &[0xff0000, 0xff0000]);
+/// assert_eq!(image[25 + 3], 0);
+///
+/// // The bottom right square ends a gap away from the corner.
+/// assert_eq!(image[23 * 25 + 23], 0x0000ff);
+/// assert_eq!(image[24 * 25 + 24], 0);
+/// ```
+pub fn rasterize(frame: &[Pixel; 64], layout: &RasterLayout) -> Vec<u32> {
+    let size = layout.size();
+    let mut image = vec![to_0rgb(layout.background); size * size];
+    let step = layout.cell + layout.gap;
+    for (i, &pixel) in frame.iter().enumerate() {
+        let left = layout.gap + step * (i % 8);
+        let top = layout.gap + step * (i / 8);
+        let colour = to_0rgb(pixel);
+        for y in top..top + layout.cell {
+            for p in &mut image[left + size * y..left + layout.cell + size * y] {
+                *p = colour;
+            }
+        }
+    }
+    image
+}
+
+/// Helper function.
+///
+/// Packs a pixel as `0x00RRGGBB`.
+fn to_0rgb((r, g, b): Pixel) -> u32 {
+    (r as u32) << 16 | (g as u32) << 8 | b as u32
+}
@@ -0,0 +1,299 @@
+use {SenseHatResult, Sensor};
+
+use std::io;
+use std::ops::Deref;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A sensor value, with when it was read and which sensor it came from.
+///
+/// It derefs to the value, so it can mostly be used as one. With the
+/// `serde` feature it can be serialised, with `taken_at` as an RFC 3339
+/// timestamp.
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use sensehat::{Reading, Sensor};
+///
+/// let taken_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+/// let reading = Reading::new(21.5, taken_at, Sensor::Humidity);
+/// assert_eq!(*reading + 1.0, 22.5);
+/// assert_eq!(reading.taken_at_rfc3339(), "2023-11-14T22:13:20Z");
+///
+/// let fahrenheit = reading.map(|celsius| celsius * 1.8 + 32.0);
+/// assert_eq!(fahrenheit.value, 70.7);
+/// assert_eq!(fahrenheit.sensor, Sensor::Humidity);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Reading<T> {
+    /// The value read.
+    pub value: T,
+    /// When it was read.
+    #[cfg_attr(feature = "serde", serde(with = "rfc3339"))]
+    pub taken_at: SystemTime,
+    /// The sensor it was read from.
+    pub sensor: Sensor,
+}
+
+impl<T> Reading<T> {
+    /// Makes a reading of `value`, taken at `taken_at` from `sensor`.
+    pub fn new(value: T, taken_at: SystemTime, sensor: Sensor) -> Self {
+        Reading {
+            value,
+            taken_at,
+            sensor,
+        }
+    }
+
+    /// Converts the value, keeping when and where it was read.
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Reading<U> {
+        Reading {
+            value: f(self.value),
+            taken_at: self.taken_at,
+            sensor: self.sensor,
+        }
+    }
+
+    /// Returns when the reading was taken, as an RFC 3339 timestamp in UTC.
+    pub fn taken_at_rfc3339(&self) -> String {
+        format_rfc3339(self.taken_at)
+    }
+}
+
+impl<T> Deref for Reading<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+/// The temperature, humidity and pressure at one moment, as returned by
+/// `SenseHat::get_environment`, in °C, %rH and hPa, with the IMU's
+/// temperature if it has one. With the `serde`
+/// feature it can be serialised, with `taken_at` as an RFC 3339 timestamp.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Environment {
+    /// When the readings were finished.
+    #[cfg_attr(feature = "serde", serde(with = "rfc3339"))]
+    pub taken_at: SystemTime,
+    /// The temperature from the humidity sensor, the more accurate one.
+    pub temperature: f64,
+    /// The relative humidity.
+    pub humidity: f64,
+    /// The air pressure.
+    pub pressure: f64,
+    /// The temperature from the IMU, the least accurate one, or None if it
+    /// couldn't be read, say because the IMU isn't working.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub imu_temperature: Option<f64>,
+}
+
+/// Formats a time as an RFC 3339 timestamp in UTC, with as many digits of
+/// fractional seconds as it needs, up to nanoseconds.
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use sensehat::format_rfc3339;
+///
+/// assert_eq!(format_rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+/// assert_eq!(
+///     format_rfc3339(UNIX_EPOCH + Duration::new(951_825_600, 250_000_000)),
+///     "2000-02-29T12:00:00.25Z"
+/// );
+/// assert_eq!(format_rfc3339(UNIX_EPOCH - Duration::from_millis(1)), "1969-12-31T23:59:59.999Z");
+/// ```
+pub fn format_rfc3339(time: SystemTime) -> String {
+    let (secs, nanos) = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => (since.as_secs() as i64, since.subsec_nanos()),
+        Err(err) => {
+            let before = err.duration();
+            match before.subsec_nanos() {
+                0 => (-(before.as_secs() as i64), 0),
+                nanos => (-(before.as_secs() as i64) - 1, 1_000_000_000 - nanos),
+            }
+        }
+    };
+    let (days, secs_of_day) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+    let (year, month, day) = civil_from_days(days);
+    let mut out = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    );
+    if nanos != 0 {
+        out.push_str(format!(".{:09}", nanos).trim_end_matches('0'));
+    }
+    out.push('Z');
+    out
+}
+
+/// Parses an RFC 3339 timestamp, with any UTC offset, like the ones
+/// `format_rfc3339` writes. Returns an io `InvalidData` error if it isn't
+/// one.
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use sensehat::parse_rfc3339;
+///
+/// let time = UNIX_EPOCH + Duration::new(1_700_000_000, 5_000_000);
+/// assert_eq!(parse_rfc3339("2023-11-14T22:13:20.005Z").unwrap(), time);
+/// assert_eq!(parse_rfc3339("2023-11-15t00:13:20.005+02:00").unwrap(), time);
+/// assert_eq!(parse_rfc3339("1969-12-31T23:59:59Z").unwrap(), UNIX_EPOCH - Duration::from_secs(1));
+/// assert!(parse_rfc3339("2023-11-14 22:13:20").is_err());
+/// assert!(parse_rfc3339("2023-13-14T22:13:20Z").is_err());
+/// ```
+pub fn parse_rfc3339(text: &str) -> SenseHatResult<SystemTime> {
+    parse(text).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{:?} isn't an RFC 3339 timestamp", text),
+        )
+        .into()
+    })
+}
+
+/// Helper function.
+///
+/// Parses an RFC 3339 timestamp, or returns None if it isn't one.
+fn parse(text: &str) -> Option<SystemTime> {
+    let bytes = text.as_bytes();
+    if bytes.len() < 20 || !bytes[..19].is_ascii() {
+        return None;
+    }
+    let separators = [(4, b'-'), (7, b'-'), (13, b':'), (16, b':')];
+    if separators.iter().any(|&(i, c)| bytes[i] != c) || !b"Tt".contains(&bytes[10]) {
+        return None;
+    }
+    let (year, month, day) = (digits(&text[0..4])?, digits(&text[5..7])?, digits(&text[8..10])?);
+    let hour = digits(&text[11..13])?;
+    let minute = digits(&text[14..16])?;
+    let second = digits(&text[17..19])?;
+    if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+        return None;
+    }
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let mut rest = &text[19..];
+    let mut nanos = 0;
+    if rest.starts_with('.') {
+        let digits = rest[1..].bytes().take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 {
+            return None;
+        }
+        for (i, c) in rest[1..=digits].bytes().enumerate().take(9) {
+            nanos += u32::from(c - b'0') * 10u32.pow(8 - i as u32);
+        }
+        rest = &rest[1 + digits..];
+    }
+    let offset = match rest {
+        "Z" | "z" => 0,
+        _ if rest.len() == 6 && rest.is_ascii() && rest.as_bytes()[3] == b':' => {
+            let sign = match rest.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let (hours, minutes) = (digits(&rest[1..3])?, digits(&rest[4..6])?);
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            sign * (hours * 3600 + minutes * 60)
+        }
+        _ => return None,
+    };
+
+    let secs = days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second
+        - offset;
+    let since = Duration::new(secs.unsigned_abs(), 0);
+    let whole = if secs >= 0 {
+        UNIX_EPOCH.checked_add(since)?
+    } else {
+        UNIX_EPOCH.checked_sub(since)?
+    };
+    whole.checked_add(Duration::new(0, nanos))
+}
+
+/// Helper function.
+///
+/// Parses a run of decimal digits, with no sign.
+fn digits(text: &str) -> Option<i64> {
+    if text.bytes().all(|c| c.is_ascii_digit()) {
+        text.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Helper function.
+///
+/// Returns the number of days in a month of the Gregorian calendar.
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Helper function.
+///
+/// Returns the year, month and day `days` after 1970-01-01, from Howard
+/// Hinnant's `civil_from_days`.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Helper function.
+///
+/// Returns the number of days from 1970-01-01 to the given date, from
+/// Howard Hinnant's `days_from_civil`.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// (De)serialises a `SystemTime` as an RFC 3339 timestamp, for
+/// `#[serde(with)]`.
+#[cfg(feature = "serde")]
+pub(crate) mod rfc3339 {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::SystemTime;
+
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::format_rfc3339(*time))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        super::parse_rfc3339(&text).map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,162 @@
+//! Writing sensor readings out as newline-delimited JSON, for piping into
+//! other tools.
+
+use device::SenseHat;
+use reading::{format_rfc3339, Environment};
+use {SenseHatError, SenseHatResult};
+
+use serde_json;
+
+use std::cmp;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How long `run` sleeps at a time, so it notices being stopped quickly.
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A record for a reading which failed.
+#[derive(Serialize)]
+struct ErrorRecord {
+    taken_at: String,
+    error: String,
+    kind: String,
+}
+
+/// Writes `Environment` readings as JSON, one object per line, flushing
+/// after every line so `tail -f` shows them straight away.
+///
+/// Readings look like
+/// `{"taken_at":"2023-11-14T22:13:20Z","temperature":21.5,"humidity":40.0,"pressure":1013.25}`,
+/// with an `imu_temperature` after the pressure when there is one, and
+/// readings which failed like
+/// `{"taken_at":"2023-11-14T22:13:21Z","error":"humidity sensor: humidity data not ready","kind":"NotReady"}`.
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use sensehat::recorder::JsonLinesRecorder;
+/// use sensehat::{Environment, Measurement, Sensor, SenseHatError};
+///
+/// let taken_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+/// let mut recorder = JsonLinesRecorder::new(Vec::new());
+/// recorder
+///     .record(&Environment {
+///         taken_at,
+///         temperature: 21.5,
+///         humidity: 40.0,
+///         pressure: 1013.25,
+///         imu_temperature: None,
+///     })
+///     .unwrap();
+/// let not_ready = SenseHatError::NotReady {
+///     sensor: Sensor::Humidity,
+///     measurement: Measurement::Humidity,
+/// };
+/// recorder.record_error(&not_ready, taken_at + Duration::from_secs(1)).unwrap();
+///
+/// let output = String::from_utf8(recorder.into_inner()).unwrap();
+/// let lines: Vec<_> = output.lines().collect();
+/// assert_eq!(
+///     lines,
+///     [
+///         r#"{"taken_at":"2023-11-14T22:13:20Z","temperature":21.5,"humidity":40.0,"pressure":1013.25}"#,
+///         r#"{"taken_at":"2023-11-14T22:13:21Z","error":"humidity sensor: humidity data not ready","kind":"NotReady"}"#,
+///     ]
+/// );
+/// assert!(output.ends_with('\n'));
+/// ```
+#[derive(Debug)]
+pub struct JsonLinesRecorder<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> JsonLinesRecorder<W> {
+    /// Makes a recorder which writes to `writer`.
+    pub fn new(writer: W) -> Self {
+        JsonLinesRecorder { writer }
+    }
+
+    /// Writes a line for a reading.
+    pub fn record(&mut self, environment: &Environment) -> SenseHatResult<()> {
+        let line = serde_json::to_string(environment).map_err(io::Error::from)?;
+        self.write_line(&line)
+    }
+
+    /// Writes a line for a reading taken at `taken_at` which failed.
+    pub fn record_error(
+        &mut self,
+        error: &SenseHatError,
+        taken_at: SystemTime,
+    ) -> SenseHatResult<()> {
+        let record = ErrorRecord {
+            taken_at: format_rfc3339(taken_at),
+            error: error.to_string(),
+            kind: format!("{:?}", error.kind()),
+        };
+        let line = serde_json::to_string(&record).map_err(io::Error::from)?;
+        self.write_line(&line)
+    }
+
+    /// Takes a reading with `SenseHat::get_environment` every `interval`
+    /// and records it, until `stop` is set. Readings which fail are
+    /// recorded as errors and don't stop it, so it only returns early if
+    /// writing fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::io;
+    /// use std::sync::atomic::AtomicBool;
+    /// use std::time::Duration;
+    /// use sensehat::recorder::JsonLinesRecorder;
+    /// use sensehat::SenseHat;
+    ///
+    /// let mut sense = SenseHat::new().unwrap();
+    /// let stop = AtomicBool::new(false);
+    /// let mut recorder = JsonLinesRecorder::new(io::stdout());
+    /// recorder.run(&mut sense, Duration::from_secs(10), &stop).unwrap();
+    /// ```
+    pub fn run(
+        &mut self,
+        hat: &mut SenseHat,
+        interval: Duration,
+        stop: &AtomicBool,
+    ) -> SenseHatResult<()> {
+        let mut next = Instant::now();
+        while !stop.load(Ordering::Relaxed) {
+            let now = Instant::now();
+            if now < next {
+                thread::sleep(cmp::min(next - now, STOP_POLL_INTERVAL));
+                continue;
+            }
+            match hat.get_environment() {
+                Ok(environment) => self.record(&environment)?,
+                Err(err) => self.record_error(&err, SystemTime::now())?,
+            }
+            next += interval;
+            // If a reading took longer than the interval, don't try to
+            // catch up.
+            if next < now {
+                next = now + interval;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Helper function.
+    ///
+    /// Writes a line and flushes it.
+    fn write_line(&mut self, line: &str) -> SenseHatResult<()> {
+        writeln!(self.writer, "{}", line)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
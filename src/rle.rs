@@ -0,0 +1,82 @@
+//! Run-length encoding of bytes, as used by the animation file format.
+//!
+//! The encoding is a list of pairs of bytes: how many times a byte is
+//! repeated, from 1 to 255, then the byte. A run longer than 255 takes
+//! more than one pair.
+//!
+//! # Example
+//!
+//! ```
+//! use sensehat::rle;
+//!
+//! assert_eq!(rle::encode(&[7, 7, 7, 0, 1, 1]), [3, 7, 1, 0, 2, 1]);
+//! assert_eq!(rle::decode(&[3, 7, 1, 0, 2, 1]).unwrap(), [7, 7, 7, 0, 1, 1]);
+//!
+//! // Long runs are split up.
+//! let zeros = [0; 600];
+//! assert_eq!(rle::encode(&zeros), [255, 0, 255, 0, 90, 0]);
+//! assert_eq!(rle::decode(&rle::encode(&zeros)).unwrap(), &zeros[..]);
+//! assert!(rle::encode(&[]).is_empty());
+//!
+//! // Anything round trips.
+//! let mut seed = 12345u32;
+//! for len in 0..300 {
+//!     let data: Vec<u8> = (0..len)
+//!         .map(|_| {
+//!             seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+//!             // Mostly a few values, so there are runs.
+//!             (seed >> 28) as u8 % 3
+//!         })
+//!         .collect();
+//!     assert_eq!(rle::decode(&rle::encode(&data)).unwrap(), data);
+//! }
+//!
+//! // A run with no length, or a length with no byte, isn't valid.
+//! assert!(rle::decode(&[0, 7]).is_err());
+//! assert!(rle::decode(&[3, 7, 2]).is_err());
+//! ```
+
+use {SenseHatError, SenseHatResult};
+
+use std::io;
+use std::iter;
+
+/// Encodes `data` as runs.
+pub fn encode(data: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    let mut rest = data;
+    while let Some(&byte) = rest.first() {
+        let run = rest.iter().take(255).take_while(|&&b| b == byte).count();
+        encoded.push(run as u8);
+        encoded.push(byte);
+        rest = &rest[run..];
+    }
+    encoded
+}
+
+/// Decodes runs made by `encode`. Returns an io `InvalidData` error if
+/// there's a run of no bytes, or the last run is missing its byte.
+pub fn decode(encoded: &[u8]) -> SenseHatResult<Vec<u8>> {
+    if !encoded.len().is_multiple_of(2) {
+        return Err(invalid_runs("last run has no byte"));
+    }
+    let mut data = Vec::new();
+    for pair in encoded.chunks(2) {
+        if pair[0] == 0 {
+            return Err(invalid_runs("run of no bytes"));
+        }
+        data.extend(iter::repeat_n(pair[1], pair[0] as usize));
+    }
+    Ok(data)
+}
+
+/// Helper function.
+///
+/// Makes the error for runs which can't be decoded.
+fn invalid_runs(problem: &str) -> SenseHatError {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("bad run-length encoding: {}", problem),
+    )
+    .into()
+}
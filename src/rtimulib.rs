@@ -0,0 +1,347 @@
+use calibration::{invalid_data, AccelCalibration, CompassCalibration, CompassEllipsoid};
+use {SenseHatError, SenseHatResult};
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+/// How far the widest axis's range reaches either side of the offset, in
+/// microtesla, when a compass calibration is written as RTIMULib's smallest
+/// and largest readings. Only the ratios between the axes matter.
+const NOMINAL_FIELD: f64 = 50.0;
+
+/// The suffixes RTIMULib gives the keys for each axis.
+const AXES: [&str; 3] = ["X", "Y", "Z"];
+
+/// The contents of an `RTIMULib.ini` settings file, as written by RTIMULib
+/// and its calibration tool, which plenty of Sense Hats have been
+/// calibrated with.
+///
+/// Every line is kept, so changing the calibration and saving leaves the
+/// comments and the settings this crate doesn't use as they were.
+/// `Imu::load_rtimulib_ini` and `Imu::save_rtimulib_ini` use this to read
+/// the calibration into the IMU and write it back.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::RtimulibIni;
+///
+/// let text = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/RTIMULib.ini"));
+/// let mut ini = RtimulibIni::parse(text);
+/// assert_eq!(ini.get("I2CBus"), Some("1"));
+///
+/// let compass = ini.compass_calibration().unwrap().unwrap();
+/// let ellipsoid = ini.compass_ellipsoid().unwrap().unwrap();
+/// let accel = ini.accel_calibration().unwrap().unwrap();
+/// assert_eq!(accel.min[0], -0.988288);
+///
+/// // Writing back what was read changes nothing.
+/// ini.set_compass_calibration(Some(&compass));
+/// ini.set_compass_ellipsoid(Some(&ellipsoid));
+/// ini.set_accel_calibration(Some(&accel));
+/// assert_eq!(ini.to_string(), text);
+///
+/// // Changes only touch their own keys.
+/// ini.set_accel_calibration(None);
+/// let changed = RtimulibIni::parse(&ini.to_string());
+/// assert_eq!(changed.accel_calibration().unwrap(), None);
+/// assert_eq!(changed.get("AccelCalMinX"), Some("-0.988288"));
+/// assert_eq!(changed.get("LSM9DS1GyroFsr"), Some("2"));
+/// assert_eq!(changed.compass_calibration().unwrap(), Some(compass));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RtimulibIni {
+    lines: Vec<String>,
+}
+
+impl RtimulibIni {
+    /// Makes an empty file, with no settings in it.
+    pub fn new() -> Self {
+        RtimulibIni::default()
+    }
+
+    /// Reads the contents of a file. Nothing is checked until a setting is
+    /// asked for, so this can't fail.
+    pub fn parse(text: &str) -> Self {
+        RtimulibIni {
+            lines: text.lines().map(String::from).collect(),
+        }
+    }
+
+    /// Reads a file from disk.
+    pub fn load<P: AsRef<Path>>(path: P) -> SenseHatResult<Self> {
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+        Ok(RtimulibIni::parse(&text))
+    }
+
+    /// Writes the file to disk.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> SenseHatResult<()> {
+        let mut file = File::create(path)?;
+        write!(file, "{}", self)?;
+        Ok(())
+    }
+
+    /// Returns a setting's value, if it's set. If it's set more than once
+    /// the last one counts, as it does for RTIMULib.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.find(key).map(|index| value_of(&self.lines[index]))
+    }
+
+    /// Changes a setting, adding it to the end if it isn't set yet.
+    pub fn set(&mut self, key: &str, value: &str) {
+        let line = format!("{}={}", key, value);
+        match self.find(key) {
+            Some(index) => self.lines[index] = line,
+            None => self.lines.push(line),
+        }
+    }
+
+    /// Returns the compass calibration from the smallest and largest
+    /// readings, worked out as `CompassCalibration::from_min_max` does, or
+    /// None if `CompassCalValid` isn't `true`.
+    ///
+    /// Returns an `IoError` with `InvalidData` if a value is missing or
+    /// isn't a number, or a range is empty.
+    pub fn compass_calibration(&self) -> SenseHatResult<Option<CompassCalibration>> {
+        if !self.flag("CompassCalValid")? {
+            return Ok(None);
+        }
+        let min = self.triple("CompassCalMin")?;
+        let max = self.triple("CompassCalMax")?;
+        for (axis, name) in AXES.iter().enumerate() {
+            if max[axis] <= min[axis] {
+                return Err(invalid_data(&format!("CompassCalMax{}", name)));
+            }
+        }
+        Ok(Some(CompassCalibration::from_min_max(min, max)))
+    }
+
+    /// Changes the compass calibration, or marks it not valid if None.
+    ///
+    /// RTIMULib only keeps the smallest and largest readings, which the
+    /// calibration is worked out from when it's loaded, and scales to the
+    /// widest axis. If the file already has readings which give the same
+    /// calibration, they're left alone. Otherwise ones are made up which
+    /// give the same offsets and the same ratios between the scales, so
+    /// headings come out the same.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::{CompassCalibration, RtimulibIni};
+    ///
+    /// let mut ini = RtimulibIni::new();
+    /// let cal = CompassCalibration { offset: [10.0, -5.0, 0.0], scale: [0.8, 1.6, 1.6] };
+    /// ini.set_compass_calibration(Some(&cal));
+    /// assert_eq!(ini.get("CompassCalMinX"), Some("-40.000000"));
+    /// assert_eq!(ini.get("CompassCalMaxY"), Some("20.000000"));
+    ///
+    /// let loaded = ini.compass_calibration().unwrap().unwrap();
+    /// assert_eq!(loaded.offset, cal.offset);
+    /// assert_eq!(loaded.scale, [1.0, 2.0, 2.0]);
+    /// ```
+    pub fn set_compass_calibration(&mut self, calibration: Option<&CompassCalibration>) {
+        let calibration = match calibration {
+            Some(calibration) => calibration,
+            None => return self.set_flag("CompassCalValid", false),
+        };
+        let unchanged = match self.compass_calibration() {
+            Ok(Some(existing)) => same_calibration(&existing, calibration),
+            _ => false,
+        };
+        if !unchanged {
+            let smallest = calibration.scale.iter().cloned().fold(f64::INFINITY, f64::min);
+            for (axis, name) in AXES.iter().enumerate() {
+                let radius = NOMINAL_FIELD * smallest / calibration.scale[axis];
+                let offset = calibration.offset[axis];
+                self.set_number(&format!("CompassCalMin{}", name), offset - radius);
+                self.set_number(&format!("CompassCalMax{}", name), offset + radius);
+            }
+        }
+        self.set_flag("CompassCalValid", true);
+    }
+
+    /// Returns the ellipsoid correction for the compass, or None if
+    /// `compassCalEllipsoidValid` isn't `true`.
+    ///
+    /// Returns an `IoError` with `InvalidData` if a value is missing or
+    /// isn't a number.
+    pub fn compass_ellipsoid(&self) -> SenseHatResult<Option<CompassEllipsoid>> {
+        if !self.flag("compassCalEllipsoidValid")? {
+            return Ok(None);
+        }
+        let mut matrix = [[0.0; 3]; 3];
+        for (row, values) in matrix.iter_mut().enumerate() {
+            for (column, value) in values.iter_mut().enumerate() {
+                *value = self.number(&format!("compassCalCorr{}{}", row + 1, column + 1))?;
+            }
+        }
+        Ok(Some(CompassEllipsoid {
+            offset: self.triple("compassCalOffset")?,
+            matrix,
+        }))
+    }
+
+    /// Changes the ellipsoid correction for the compass, or marks it not
+    /// valid if None.
+    pub fn set_compass_ellipsoid(&mut self, ellipsoid: Option<&CompassEllipsoid>) {
+        if let Some(ellipsoid) = ellipsoid {
+            self.set_triple("compassCalOffset", ellipsoid.offset);
+            for (row, values) in ellipsoid.matrix.iter().enumerate() {
+                for (column, &value) in values.iter().enumerate() {
+                    self.set_number(&format!("compassCalCorr{}{}", row + 1, column + 1), value);
+                }
+            }
+        }
+        self.set_flag("compassCalEllipsoidValid", ellipsoid.is_some());
+    }
+
+    /// Returns the accelerometer calibration, or None if `AccelCalValid`
+    /// isn't `true`.
+    ///
+    /// Returns an `IoError` with `InvalidData` if a value is missing or
+    /// isn't a number, or a smallest reading isn't negative or a largest
+    /// one positive.
+    pub fn accel_calibration(&self) -> SenseHatResult<Option<AccelCalibration>> {
+        if !self.flag("AccelCalValid")? {
+            return Ok(None);
+        }
+        let min = self.triple("AccelCalMin")?;
+        let max = self.triple("AccelCalMax")?;
+        for (axis, name) in AXES.iter().enumerate() {
+            if min[axis].partial_cmp(&0.0) != Some(Ordering::Less) {
+                return Err(invalid_data(&format!("AccelCalMin{}", name)));
+            }
+            if max[axis].partial_cmp(&0.0) != Some(Ordering::Greater) {
+                return Err(invalid_data(&format!("AccelCalMax{}", name)));
+            }
+        }
+        Ok(Some(AccelCalibration { min, max }))
+    }
+
+    /// Changes the accelerometer calibration, or marks it not valid if
+    /// None.
+    pub fn set_accel_calibration(&mut self, calibration: Option<&AccelCalibration>) {
+        if let Some(calibration) = calibration {
+            self.set_triple("AccelCalMin", calibration.min);
+            self.set_triple("AccelCalMax", calibration.max);
+        }
+        self.set_flag("AccelCalValid", calibration.is_some());
+    }
+
+    /// Helper function.
+    ///
+    /// Returns the index of the last line setting `key`.
+    fn find(&self, key: &str) -> Option<usize> {
+        self.lines.iter().rposition(|line| key_of(line) == Some(key))
+    }
+
+    /// Helper function.
+    ///
+    /// Reads a `true` or `false` setting, which is false if it isn't set.
+    fn flag(&self, key: &str) -> SenseHatResult<bool> {
+        match self.get(key) {
+            None | Some("false") => Ok(false),
+            Some("true") => Ok(true),
+            Some(value) => Err(invalid_data(&format!("{}={}", key, value))),
+        }
+    }
+
+    /// Helper function.
+    ///
+    /// Reads a number which has to be set.
+    fn number(&self, key: &str) -> SenseHatResult<f64> {
+        let value = self.get(key).ok_or_else(|| missing(key))?;
+        value
+            .parse()
+            .map_err(|_| invalid_data(&format!("{}={}", key, value)))
+    }
+
+    /// Helper function.
+    ///
+    /// Reads the numbers for the X, Y and Z axes.
+    fn triple(&self, prefix: &str) -> SenseHatResult<[f64; 3]> {
+        let mut values = [0.0; 3];
+        for (value, axis) in values.iter_mut().zip(AXES.iter()) {
+            *value = self.number(&format!("{}{}", prefix, axis))?;
+        }
+        Ok(values)
+    }
+
+    /// Helper function.
+    ///
+    /// Writes a `true` or `false` setting.
+    fn set_flag(&mut self, key: &str, value: bool) {
+        self.set(key, if value { "true" } else { "false" });
+    }
+
+    /// Helper function.
+    ///
+    /// Writes a number to six decimal places, as RTIMULib does, leaving the
+    /// line alone if it already has that value.
+    fn set_number(&mut self, key: &str, value: f64) {
+        let text = format!("{:.6}", value);
+        let existing = self.get(key).and_then(|existing| existing.parse::<f64>().ok());
+        if existing != text.parse().ok() {
+            self.set(key, &text);
+        }
+    }
+
+    /// Helper function.
+    ///
+    /// Writes the numbers for the X, Y and Z axes.
+    fn set_triple(&mut self, prefix: &str, values: [f64; 3]) {
+        for (&value, axis) in values.iter().zip(AXES.iter()) {
+            self.set_number(&format!("{}{}", prefix, axis), value);
+        }
+    }
+}
+
+impl fmt::Display for RtimulibIni {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Helper function.
+///
+/// Returns the key a line sets, or None for comments and blank lines.
+fn key_of(line: &str) -> Option<&str> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    line.split('=').next().map(str::trim)
+}
+
+/// Helper function.
+///
+/// Returns the value a line sets it to.
+fn value_of(line: &str) -> &str {
+    line.split_once('=').map_or("", |(_, value)| value.trim())
+}
+
+/// Helper function.
+///
+/// Returns true if two compass calibrations are the same, give or take
+/// rounding.
+fn same_calibration(a: &CompassCalibration, b: &CompassCalibration) -> bool {
+    (0..3).all(|axis| {
+        (a.offset[axis] - b.offset[axis]).abs() < 1e-6
+            && (a.scale[axis] - b.scale[axis]).abs() < 1e-6
+    })
+}
+
+/// Helper function.
+///
+/// Makes the error for a setting which has to be in the file but isn't.
+fn missing(key: &str) -> SenseHatError {
+    io::Error::new(io::ErrorKind::InvalidData, format!("missing setting: {}", key)).into()
+}
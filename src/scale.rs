@@ -0,0 +1,287 @@
+use color::PixelExt;
+use display::Pixel;
+use SenseHatResult;
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// How `downscale` works out each pixel of the 8x8 image.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ScaleFilter {
+    /// The average of every source pixel it covers. Smooth, but slower.
+    Box,
+    /// The source pixel nearest its middle. Fast, but drops detail.
+    Nearest,
+}
+
+/// Scales a `width` by `height` image, given row by row from the top left,
+/// to fit the 8x8 LED matrix.
+///
+/// The aspect ratio is kept, so an image which isn't square fills the
+/// matrix one way and is centred the other, with `background` either side.
+/// Images smaller than 8x8 are scaled up. Returns an error if the image is
+/// empty, or `pixels` isn't `width * height` long.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{downscale, ScaleFilter};
+///
+/// // A 16x16 checkerboard of single pixels averages out to grey.
+/// let checkerboard: Vec<_> = (0..256)
+///     .map(|i| if (i % 16 + i / 16) % 2 == 0 { (255, 255, 255) } else { (0, 0, 0) })
+///     .collect();
+/// let grey = downscale(&checkerboard, 16, 16, ScaleFilter::Box, (0, 0, 0)).unwrap();
+/// assert!(grey.iter().all(|&p| p == (128, 128, 128)));
+///
+/// // A checkerboard of 2x2 squares becomes one of single pixels.
+/// let squares: Vec<_> = (0..256)
+///     .map(|i| if (i % 16 / 2 + i / 32) % 2 == 0 { (255, 0, 0) } else { (0, 0, 255) })
+///     .collect();
+/// let small = downscale(&squares, 16, 16, ScaleFilter::Box, (0, 0, 0)).unwrap();
+/// assert_eq!(small[0], (255, 0, 0));
+/// assert_eq!(small[1], (0, 0, 255));
+/// assert_eq!(small[8], (0, 0, 255));
+/// assert_eq!(small, downscale(&squares, 16, 16, ScaleFilter::Nearest, (0, 0, 0)).unwrap());
+///
+/// // A wide image is letterboxed.
+/// let wide = vec![(0, 255, 0); 16 * 8];
+/// let boxed = downscale(&wide, 16, 8, ScaleFilter::Box, (10, 10, 10)).unwrap();
+/// assert_eq!(boxed[..16], [(10, 10, 10); 16]);
+/// assert_eq!(boxed[16..48], [(0, 255, 0); 32]);
+/// assert_eq!(boxed[48..], [(10, 10, 10); 16]);
+/// ```
+pub fn downscale(
+    pixels: &[Pixel],
+    width: usize,
+    height: usize,
+    filter: ScaleFilter,
+    background: Pixel,
+) -> SenseHatResult<[Pixel; 64]> {
+    if width == 0 || height == 0 || Some(pixels.len()) != width.checked_mul(height) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "expected a {}x{} image to have {} pixels, found {}",
+                width,
+                height,
+                width.saturating_mul(height),
+                pixels.len()
+            ),
+        )
+        .into());
+    }
+
+    // The size of the scaled image, and where it goes on the matrix.
+    let (cols, rows) = if width >= height {
+        (8, ((8 * height + width / 2) / width).max(1))
+    } else {
+        (((8 * width + height / 2) / height).max(1), 8)
+    };
+    let (left, top) = ((8 - cols) / 2, (8 - rows) / 2);
+
+    let mut out = [background; 64];
+    for row in 0..rows {
+        let ys = span(row, rows, height);
+        for col in 0..cols {
+            let xs = span(col, cols, width);
+            out[(left + col) + 8 * (top + row)] = match filter {
+                ScaleFilter::Box => average(pixels, width, xs, ys),
+                ScaleFilter::Nearest => {
+                    let x = (2 * col + 1) * width / (2 * cols);
+                    let y = (2 * row + 1) * height / (2 * rows);
+                    pixels[x + width * y]
+                }
+            };
+        }
+    }
+    Ok(out)
+}
+
+/// Reads a binary PPM (P6) image, of any size, and scales it to fit the
+/// 8x8 LED matrix with `downscale`.
+///
+/// # Example
+///
+/// ```
+/// use std::fs;
+/// use sensehat::{load_image_scaled, ScaleFilter};
+///
+/// let path = std::env::temp_dir().join("sensehat-load-image-doctest.ppm");
+/// let mut ppm = b"P6\n# a red and blue icon\n2 2\n255\n".to_vec();
+/// ppm.extend_from_slice(&[255, 0, 0, 0, 0, 255, 0, 0, 255, 255, 0, 0]);
+/// fs::write(&path, ppm).unwrap();
+///
+/// let icon = load_image_scaled(&path, ScaleFilter::Nearest, (0, 0, 0)).unwrap();
+/// assert_eq!(icon[0], (255, 0, 0));
+/// assert_eq!(icon[7], (0, 0, 255));
+/// assert_eq!(icon[63], (255, 0, 0));
+/// fs::remove_file(&path).unwrap();
+/// ```
+pub fn load_image_scaled(
+    path: &Path,
+    filter: ScaleFilter,
+    background: Pixel,
+) -> SenseHatResult<[Pixel; 64]> {
+    let mut contents = Vec::new();
+    File::open(path)?.read_to_end(&mut contents)?;
+    let (width, height, pixels) = parse_ppm(&contents).map_err(|reason| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("bad PPM image {}: {}", path.display(), reason),
+        )
+    })?;
+    downscale(&pixels, width, height, filter, background)
+}
+
+/// Converts tightly packed 8-bit RGB data, as `image::RgbImage::as_raw`
+/// returns it, to pixels, for `downscale` or `Display::set_pixels`.
+/// Returns an error if the data isn't a whole number of pixels.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{pixels_from_rgb, pixels_to_rgb};
+///
+/// let data = [255, 0, 0, 0, 128, 255];
+/// let pixels = pixels_from_rgb(&data).unwrap();
+/// assert_eq!(pixels, vec![(255, 0, 0), (0, 128, 255)]);
+/// assert_eq!(pixels_to_rgb(&pixels), data);
+/// assert!(pixels_from_rgb(&data[..4]).is_err());
+/// ```
+pub fn pixels_from_rgb(data: &[u8]) -> SenseHatResult<Vec<Pixel>> {
+    check_channels(data, 3)?;
+    Ok(data.chunks(3).map(|rgb| (rgb[0], rgb[1], rgb[2])).collect())
+}
+
+/// Converts tightly packed 8-bit RGBA data, as `image::RgbaImage::as_raw`
+/// returns it, to pixels, drawing each over `background` with its alpha.
+/// Returns an error if the data isn't a whole number of pixels.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::pixels_from_rgba;
+///
+/// let data = [255, 0, 0, 255, 255, 0, 0, 0, 255, 255, 255, 128];
+/// let pixels = pixels_from_rgba(&data, (0, 0, 255)).unwrap();
+/// assert_eq!(pixels, vec![(255, 0, 0), (0, 0, 255), (128, 128, 255)]);
+/// assert!(pixels_from_rgba(&data[..6], (0, 0, 0)).is_err());
+/// ```
+pub fn pixels_from_rgba(data: &[u8], background: Pixel) -> SenseHatResult<Vec<Pixel>> {
+    check_channels(data, 4)?;
+    Ok(data
+        .chunks(4)
+        .map(|rgba| (rgba[0], rgba[1], rgba[2]).blend_over(background, rgba[3] as f32 / 255.0))
+        .collect())
+}
+
+/// Converts pixels to tightly packed 8-bit RGB data, which
+/// `image::RgbImage::from_raw` takes. Pixels read back from the LED matrix
+/// have been through its rgb565 format, which drops their low bits.
+pub fn pixels_to_rgb(pixels: &[Pixel]) -> Vec<u8> {
+    pixels.iter().flat_map(|&(r, g, b)| vec![r, g, b]).collect()
+}
+
+/// Helper function.
+///
+/// Returns an error unless `data` is a whole number of pixels of
+/// `channels` bytes each.
+fn check_channels(data: &[u8], channels: usize) -> SenseHatResult<()> {
+    if !data.len().is_multiple_of(channels) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "expected {} bytes per pixel, found {} bytes",
+                channels,
+                data.len()
+            ),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Helper function.
+///
+/// Returns the range of source pixels which scale to pixel `index` of
+/// `count`, from `size` source pixels. Never empty, even when scaling up.
+fn span(index: usize, count: usize, size: usize) -> (usize, usize) {
+    let start = index * size / count;
+    let end = ((index + 1) * size / count).max(start + 1);
+    (start, end)
+}
+
+/// Helper function.
+///
+/// Returns the average, rounded, of the source pixels in the given ranges.
+fn average(pixels: &[Pixel], width: usize, xs: (usize, usize), ys: (usize, usize)) -> Pixel {
+    let (mut r, mut g, mut b) = (0, 0, 0);
+    for y in ys.0..ys.1 {
+        for &(pr, pg, pb) in &pixels[xs.0 + width * y..xs.1 + width * y] {
+            r += pr as usize;
+            g += pg as usize;
+            b += pb as usize;
+        }
+    }
+    let n = (xs.1 - xs.0) * (ys.1 - ys.0);
+    let avg = |total: usize| ((total + n / 2) / n) as u8;
+    (avg(r), avg(g), avg(b))
+}
+
+/// Helper function.
+///
+/// Parses a binary PPM, returning its size and pixels, or why it isn't
+/// valid.
+fn parse_ppm(data: &[u8]) -> Result<(usize, usize, Vec<Pixel>), String> {
+    let mut pos = 0;
+    let mut fields = Vec::new();
+    while fields.len() < 4 {
+        match data.get(pos) {
+            None => return Err("header ends early".to_owned()),
+            Some(b'#') => {
+                while data.get(pos).is_some_and(|&c| c != b'\n') {
+                    pos += 1;
+                }
+            }
+            Some(c) if c.is_ascii_whitespace() => pos += 1,
+            Some(_) => {
+                let start = pos;
+                while data.get(pos).is_some_and(|c| !c.is_ascii_whitespace()) {
+                    pos += 1;
+                }
+                fields.push(String::from_utf8_lossy(&data[start..pos]).into_owned());
+            }
+        }
+    }
+    // One whitespace character separates the header from the pixels.
+    pos += 1;
+
+    if fields[0] != "P6" {
+        return Err(format!("expected a binary PPM (P6), found {:?}", fields[0]));
+    }
+    let number = |field: &str| {
+        field
+            .parse::<usize>()
+            .map_err(|_| format!("{:?} isn't a number", field))
+    };
+    let (width, height, max) = (number(&fields[1])?, number(&fields[2])?, number(&fields[3])?);
+    if max == 0 || max > 255 {
+        return Err(format!("unsupported maximum value {}", max));
+    }
+    let bytes = width
+        .checked_mul(height)
+        .and_then(|count| count.checked_mul(3))
+        .ok_or_else(|| format!("{}x{} is too big", width, height))?;
+    let body = data.get(pos..).unwrap_or(&[]);
+    if body.len() < bytes {
+        return Err(format!("expected {} bytes of pixels, found {}", bytes, body.len()));
+    }
+    let level = |value: u8| ((value as usize * 255 + max / 2) / max) as u8;
+    let pixels = body[..bytes]
+        .chunks(3)
+        .map(|rgb| (level(rgb[0]), level(rgb[1]), level(rgb[2])))
+        .collect();
+    Ok((width, height, pixels))
+}
@@ -0,0 +1,122 @@
+use {Component, Measurement};
+
+use std::fmt;
+
+/// One thing `SenseHat::self_test` checked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelfTestCheck {
+    /// The component checked.
+    pub component: Component,
+    /// What was checked, like "WHO_AM_I" or "humidity".
+    pub check: String,
+    /// What was found, or the error which stopped it being checked.
+    pub observed: String,
+    /// Whether it was what it should be.
+    pub passed: bool,
+}
+
+/// The results of `SenseHat::self_test`, with a `Display` impl for
+/// pasting into bug reports.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{Component, SelfTestCheck, SelfTestReport};
+///
+/// let report = SelfTestReport {
+///     checks: vec![
+///         SelfTestCheck {
+///             component: Component::Humidity,
+///             check: "WHO_AM_I".to_owned(),
+///             observed: "0xbc".to_owned(),
+///             passed: true,
+///         },
+///         SelfTestCheck {
+///             component: Component::Humidity,
+///             check: "humidity".to_owned(),
+///             observed: "103.2%".to_owned(),
+///             passed: false,
+///         },
+///     ],
+/// };
+/// assert!(!report.passed());
+/// assert_eq!(
+///     report.to_string(),
+///     "self test: 1 of 2 checks failed\n\
+///      ok   humidity sensor WHO_AM_I: 0xbc\n\
+///      FAIL humidity sensor humidity: 103.2%\n"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelfTestReport {
+    /// Every check, in the order they were made.
+    pub checks: Vec<SelfTestCheck>,
+}
+
+impl SelfTestReport {
+    /// Returns true if every check passed.
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.passed)
+    }
+}
+
+impl fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let failed = self.checks.iter().filter(|check| !check.passed).count();
+        if failed == 0 {
+            writeln!(f, "self test: all {} checks passed", self.checks.len())?;
+        } else {
+            writeln!(f, "self test: {} of {} checks failed", failed, self.checks.len())?;
+        }
+        for check in &self.checks {
+            writeln!(
+                f,
+                "{:<4} {} {}: {}",
+                if check.passed { "ok" } else { "FAIL" },
+                check.component,
+                check.check,
+                check.observed
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the range a reading has to be in to be believable, in °C, %
+/// or hPa, or None if there's no range to check for that measurement.
+///
+/// These are the ranges the sensors are made to work over, so a reading
+/// outside them means the sensor, or how it's read, is broken.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{is_plausible, plausible_range, Measurement};
+///
+/// assert_eq!(plausible_range(Measurement::Temperature), Some((-40.0, 85.0)));
+/// assert!(is_plausible(Measurement::Temperature, 21.5));
+/// assert!(!is_plausible(Measurement::Temperature, -273.0));
+/// assert!(is_plausible(Measurement::Humidity, 0.0));
+/// assert!(!is_plausible(Measurement::Humidity, 100.1));
+/// assert!(is_plausible(Measurement::Pressure, 1013.25));
+/// assert!(!is_plausible(Measurement::Pressure, 0.0));
+/// assert!(!is_plausible(Measurement::Pressure, f64::NAN));
+/// assert!(is_plausible(Measurement::Acceleration, 1.0));
+/// ```
+pub fn plausible_range(measurement: Measurement) -> Option<(f64, f64)> {
+    match measurement {
+        Measurement::Temperature => Some((-40.0, 85.0)),
+        Measurement::Humidity => Some((0.0, 100.0)),
+        Measurement::Pressure => Some((260.0, 1260.0)),
+        _ => None,
+    }
+}
+
+/// Returns true if `value` is within `plausible_range` for the
+/// measurement, or there's no range to check.
+pub fn is_plausible(measurement: Measurement, value: f64) -> bool {
+    match plausible_range(measurement) {
+        Some((min, max)) => value >= min && value <= max,
+        None => !value.is_nan(),
+    }
+}
@@ -1,159 +1,654 @@
-/// Settings for the LSM9DS1 sensor
-#[derive(Debug, Default)]
-pub struct Settings {
-    gyro_sample_rate: GyroSampleRate,
-    gyro_bandwidth: GyroBandwidth,
-    gyro_fsr: GyroFullScaleRange,
-    gyro_hpf: GyroHighPassFilter,
-    accel_sample_rate: AccelSampleRate,
-    accel_fsr: AccelFullScaleRange,
-    accel_lpf: AccelLowPassFilter,
-    compass_sample_rate: CompassSampleRate,
-    compass_fsr: CompassFullScaleRange,
-}
-
-/// Samplingrate of the gyroscope.
-///
-/// Represents sample rate in Hz.
-#[derive(Debug)]
-pub enum GyroSampleRate {
-    Hz_14_9,
-    Hz_59_5,
-    Hz_119,
-    Hz_238,
-    Hz_476,
-    Hz_952,
-}
-
-#[derive(Debug)]
-/// Gyro bandwidth.
-///
-/// 0 - 3, see the LSM9DS1 manual for details.
-pub enum GyroBandwidth {
-    Bw0,
-    Bw1,
-    Bw2,
-    Bw3,
-}
-
-/// Gyro full scale range.
-///
-/// Represents degrees per second.
-#[derive(Debug)]
-pub enum GyroFullScaleRange {
-    Dps250,
-    Dps500,
-    Dps2000,
-}
-
-/// Gyro high pass filter.
-///
-/// 0 - 9, see the LSM9DS1 manual for details.
-#[derive(Debug)]
-pub enum GyroHighPassFilter {
-    Hpf0,
-    Hpf1,
-    Hpf2,
-    Hpf3,
-    Hpf4,
-    Hpf5,
-    Hpf6,
-    Hpf7,
-    Hpf8,
-    Hpf9,
-}
-
-/// Accelerometer sample rate.
-///
-/// Represents sample rate in Hz.
-#[derive(Debug)]
-pub enum AccelSampleRate {
-    Hz_14_9,
-    Hz_59_5,
-    Hz_119,
-    Hz_238,
-    Hz_476,
-    Hz_952,
-}
-
-/// Accelerometer full scale range.
-///
-/// ± x gauss, where x is either 4, 8, 12, 16
-#[derive(Debug)]
-pub enum AccelFullScaleRange {
-    G4,
-    G8,
-    G12,
-    G16,
-}
-
-/// Accelerometer low pass filter.
-#[derive(Debug)]
-pub enum AccelLowPassFilter {
-    Hz_408,
-    Hz_211,
-    Hz_105,
-    Hz_50,
-}
-
-/// Compass sample rate.
-///
-/// Represents sample rate in Hz.
-#[derive(Debug)]
-pub enum CompassSampleRate {
-    Hz_0_625,
-    Hz_1_25,
-    Hz_2_5,
-    Hz_5,
-    Hz_10,
-    Hz_20,
-    Hz_40,
-    Hz_80,
-}
-
-/// Compass full scale range.
-///
-/// ± x uT, where x is either 400, 800, 1200 or 1600
-#[derive(Debug)]
-pub enum CompassFullScaleRange {
-    uT_4,
-    uT_8,
-    uT_12,
-    uT_16,
-}
-
-impl Default for GyroSampleRate {
-    fn default() -> Self { GyroSampleRate::Hz_119 }
-}
-
-impl Default for GyroBandwidth {
-    fn default() -> Self { GyroBandwidth::Bw1 }
-}
-
-impl Default for GyroFullScaleRange {
-    fn default() -> Self { GyroFullScaleRange::Dps500 }
-}
-
-impl Default for GyroHighPassFilter{
-    fn default() -> Self { GyroHighPassFilter::Hpf4 }
-}
-
-impl Default for AccelSampleRate {
-    fn default() -> Self { AccelSampleRate::Hz_119 }
-}
-
-impl Default for AccelFullScaleRange {
-    fn default() -> Self { AccelFullScaleRange::G8 }
-}
-
-impl Default for AccelLowPassFilter {
-    fn default() -> Self { AccelLowPassFilter::Hz_50 }
-}
-
-impl Default for CompassSampleRate {
-    fn default() -> Self { CompassSampleRate::Hz_20 }
-}
-
-impl Default for CompassFullScaleRange {
-    fn default() -> Self { CompassFullScaleRange::uT_4 }
-}
\ No newline at end of file
+/// Settings for the LSM9DS1 sensor
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{AccelFullScaleRange, GyroSampleRate, Settings};
+///
+/// let settings = Settings::builder()
+///     .gyro_sample_rate(GyroSampleRate::Hz_476)
+///     .accel_fsr(AccelFullScaleRange::G4)
+///     .build();
+/// assert_eq!(settings.accel_fsr(), AccelFullScaleRange::G4);
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Settings {
+    gyro_sample_rate: GyroSampleRate,
+    gyro_bandwidth: GyroBandwidth,
+    gyro_fsr: GyroFullScaleRange,
+    gyro_hpf: GyroHighPassFilter,
+    accel_sample_rate: AccelSampleRate,
+    accel_fsr: AccelFullScaleRange,
+    accel_lpf: AccelLowPassFilter,
+    compass_sample_rate: CompassSampleRate,
+    compass_fsr: CompassFullScaleRange,
+}
+
+/// Samplingrate of the gyroscope.
+///
+/// Represents sample rate in Hz.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[allow(non_camel_case_types)]
+pub enum GyroSampleRate {
+    Hz_14_9,
+    Hz_59_5,
+    #[default]
+    Hz_119,
+    Hz_238,
+    Hz_476,
+    Hz_952,
+}
+
+/// Gyro bandwidth.
+///
+/// 0 - 3, see the LSM9DS1 manual for details.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum GyroBandwidth {
+    Bw0,
+    #[default]
+    Bw1,
+    Bw2,
+    Bw3,
+}
+
+/// Gyro full scale range.
+///
+/// Represents degrees per second.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum GyroFullScaleRange {
+    Dps250,
+    #[default]
+    Dps500,
+    Dps2000,
+}
+
+/// Gyro high pass filter.
+///
+/// 0 - 9, see the LSM9DS1 manual for details.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum GyroHighPassFilter {
+    Hpf0,
+    Hpf1,
+    Hpf2,
+    Hpf3,
+    #[default]
+    Hpf4,
+    Hpf5,
+    Hpf6,
+    Hpf7,
+    Hpf8,
+    Hpf9,
+}
+
+/// Accelerometer sample rate.
+///
+/// Represents sample rate in Hz.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[allow(non_camel_case_types)]
+pub enum AccelSampleRate {
+    Hz_14_9,
+    Hz_59_5,
+    #[default]
+    Hz_119,
+    Hz_238,
+    Hz_476,
+    Hz_952,
+}
+
+/// Accelerometer full scale range.
+///
+/// ± x g, where x is either 2, 4, 8 or 16
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum AccelFullScaleRange {
+    G2,
+    G4,
+    #[default]
+    G8,
+    G16,
+}
+
+/// Accelerometer low pass filter.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[allow(non_camel_case_types)]
+pub enum AccelLowPassFilter {
+    Hz_408,
+    Hz_211,
+    Hz_105,
+    #[default]
+    Hz_50,
+}
+
+/// Compass sample rate.
+///
+/// Represents sample rate in Hz.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[allow(non_camel_case_types)]
+pub enum CompassSampleRate {
+    Hz_0_625,
+    Hz_1_25,
+    Hz_2_5,
+    Hz_5,
+    Hz_10,
+    #[default]
+    Hz_20,
+    Hz_40,
+    Hz_80,
+}
+
+/// Compass full scale range.
+///
+/// ± x uT, where x is either 400, 800, 1200 or 1600
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[allow(non_camel_case_types)]
+pub enum CompassFullScaleRange {
+    #[default]
+    uT_4,
+    uT_8,
+    uT_12,
+    uT_16,
+}
+
+/// Builds `Settings`, starting from the defaults.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct SettingsBuilder {
+    settings: Settings,
+}
+
+impl SettingsBuilder {
+    pub fn gyro_sample_rate(mut self, rate: GyroSampleRate) -> Self {
+        self.settings.gyro_sample_rate = rate;
+        self
+    }
+
+    pub fn gyro_bandwidth(mut self, bandwidth: GyroBandwidth) -> Self {
+        self.settings.gyro_bandwidth = bandwidth;
+        self
+    }
+
+    pub fn gyro_fsr(mut self, fsr: GyroFullScaleRange) -> Self {
+        self.settings.gyro_fsr = fsr;
+        self
+    }
+
+    pub fn gyro_hpf(mut self, hpf: GyroHighPassFilter) -> Self {
+        self.settings.gyro_hpf = hpf;
+        self
+    }
+
+    pub fn accel_sample_rate(mut self, rate: AccelSampleRate) -> Self {
+        self.settings.accel_sample_rate = rate;
+        self
+    }
+
+    pub fn accel_fsr(mut self, fsr: AccelFullScaleRange) -> Self {
+        self.settings.accel_fsr = fsr;
+        self
+    }
+
+    pub fn accel_lpf(mut self, lpf: AccelLowPassFilter) -> Self {
+        self.settings.accel_lpf = lpf;
+        self
+    }
+
+    pub fn compass_sample_rate(mut self, rate: CompassSampleRate) -> Self {
+        self.settings.compass_sample_rate = rate;
+        self
+    }
+
+    pub fn compass_fsr(mut self, fsr: CompassFullScaleRange) -> Self {
+        self.settings.compass_fsr = fsr;
+        self
+    }
+
+    pub fn build(self) -> Settings {
+        self.settings
+    }
+}
+
+impl Settings {
+    /// Returns a builder for customising the default settings.
+    pub fn builder() -> SettingsBuilder {
+        SettingsBuilder::default()
+    }
+
+    /// Returns a builder for customising these settings.
+    pub fn to_builder(&self) -> SettingsBuilder {
+        SettingsBuilder { settings: *self }
+    }
+
+    /// Returns the gyro sample rate.
+    pub fn gyro_sample_rate(&self) -> GyroSampleRate {
+        self.gyro_sample_rate
+    }
+
+    /// Returns the gyro bandwidth.
+    pub fn gyro_bandwidth(&self) -> GyroBandwidth {
+        self.gyro_bandwidth
+    }
+
+    /// Returns the gyro full scale range.
+    pub fn gyro_fsr(&self) -> GyroFullScaleRange {
+        self.gyro_fsr
+    }
+
+    /// Returns the gyro high pass filter.
+    pub fn gyro_hpf(&self) -> GyroHighPassFilter {
+        self.gyro_hpf
+    }
+
+    /// Returns the accelerometer sample rate.
+    pub fn accel_sample_rate(&self) -> AccelSampleRate {
+        self.accel_sample_rate
+    }
+
+    /// Returns the accelerometer full scale range.
+    pub fn accel_fsr(&self) -> AccelFullScaleRange {
+        self.accel_fsr
+    }
+
+    /// Returns the accelerometer low pass filter.
+    pub fn accel_lpf(&self) -> AccelLowPassFilter {
+        self.accel_lpf
+    }
+
+    /// Returns the compass sample rate.
+    pub fn compass_sample_rate(&self) -> CompassSampleRate {
+        self.compass_sample_rate
+    }
+
+    /// Returns the compass full scale range.
+    pub fn compass_fsr(&self) -> CompassFullScaleRange {
+        self.compass_fsr
+    }
+}
+
+impl GyroSampleRate {
+    /// The ODR_G bits of CTRL_REG1_G.
+    pub(crate) fn bits(self) -> u8 {
+        use self::GyroSampleRate::*;
+        match self {
+            Hz_14_9 => 0b001,
+            Hz_59_5 => 0b010,
+            Hz_119 => 0b011,
+            Hz_238 => 0b100,
+            Hz_476 => 0b101,
+            Hz_952 => 0b110,
+        }
+    }
+}
+
+impl GyroSampleRate {
+    /// Every gyro sample rate, slowest first.
+    pub(crate) const ALL: [GyroSampleRate; 6] = [
+        GyroSampleRate::Hz_14_9,
+        GyroSampleRate::Hz_59_5,
+        GyroSampleRate::Hz_119,
+        GyroSampleRate::Hz_238,
+        GyroSampleRate::Hz_476,
+        GyroSampleRate::Hz_952,
+    ];
+
+    /// The sample rate in Hz.
+    pub fn hz(self) -> f64 {
+        use self::GyroSampleRate::*;
+        match self {
+            Hz_14_9 => 14.9,
+            Hz_59_5 => 59.5,
+            Hz_119 => 119.0,
+            Hz_238 => 238.0,
+            Hz_476 => 476.0,
+            Hz_952 => 952.0,
+        }
+    }
+
+    /// Returns the supported sample rate closest to `hz`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::GyroSampleRate;
+    ///
+    /// assert_eq!(GyroSampleRate::nearest(200.0), GyroSampleRate::Hz_238);
+    /// assert_eq!(GyroSampleRate::nearest(0.0), GyroSampleRate::Hz_14_9);
+    /// ```
+    pub fn nearest(hz: f64) -> Self {
+        let distance = |rate: GyroSampleRate| (rate.hz() - hz).abs();
+        let mut nearest = GyroSampleRate::Hz_14_9;
+        for &rate in GyroSampleRate::ALL.iter() {
+            if distance(rate) < distance(nearest) {
+                nearest = rate;
+            }
+        }
+        nearest
+    }
+}
+
+impl GyroBandwidth {
+    /// The BW_G bits of CTRL_REG1_G.
+    pub(crate) fn bits(self) -> u8 {
+        use self::GyroBandwidth::*;
+        match self {
+            Bw0 => 0b00,
+            Bw1 => 0b01,
+            Bw2 => 0b10,
+            Bw3 => 0b11,
+        }
+    }
+}
+
+impl GyroFullScaleRange {
+    /// The FS_G bits of CTRL_REG1_G.
+    pub(crate) fn bits(self) -> u8 {
+        use self::GyroFullScaleRange::*;
+        match self {
+            Dps250 => 0b00,
+            Dps500 => 0b01,
+            Dps2000 => 0b11,
+        }
+    }
+
+    /// Decodes the FS_G bits of a CTRL_REG1_G value, ignoring the rest.
+    /// Returns None for the bits the LSM9DS1 doesn't use.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::GyroFullScaleRange::{self, Dps2000, Dps250, Dps500};
+    ///
+    /// // The sample rate and bandwidth bits either side don't matter.
+    /// assert_eq!(GyroFullScaleRange::from_ctrl1_g(0b011_00_011), Some(Dps250));
+    /// assert_eq!(GyroFullScaleRange::from_ctrl1_g(0b011_01_011), Some(Dps500));
+    /// assert_eq!(GyroFullScaleRange::from_ctrl1_g(0b011_11_011), Some(Dps2000));
+    /// assert_eq!(GyroFullScaleRange::from_ctrl1_g(0b011_10_011), None);
+    ///
+    /// assert_eq!(Dps250.scale(), 0.00875);
+    /// assert_eq!(Dps500.scale(), 0.0175);
+    /// assert_eq!(Dps2000.scale(), 0.07);
+    /// ```
+    pub fn from_ctrl1_g(value: u8) -> Option<Self> {
+        use self::GyroFullScaleRange::*;
+        [Dps250, Dps500, Dps2000]
+            .iter()
+            .cloned()
+            .find(|fsr| fsr.bits() == (value >> 3) & 0b11)
+    }
+
+    /// The sensitivity, in degrees per second per LSB.
+    pub fn scale(self) -> f64 {
+        use self::GyroFullScaleRange::*;
+        match self {
+            Dps250 => 0.00875,
+            Dps500 => 0.0175,
+            Dps2000 => 0.07,
+        }
+    }
+}
+
+impl GyroHighPassFilter {
+    /// The HPCF_G bits of CTRL_REG3_G.
+    pub(crate) fn bits(self) -> u8 {
+        use self::GyroHighPassFilter::*;
+        match self {
+            Hpf0 => 0,
+            Hpf1 => 1,
+            Hpf2 => 2,
+            Hpf3 => 3,
+            Hpf4 => 4,
+            Hpf5 => 5,
+            Hpf6 => 6,
+            Hpf7 => 7,
+            Hpf8 => 8,
+            Hpf9 => 9,
+        }
+    }
+}
+
+impl AccelSampleRate {
+    /// The sample rate in Hz.
+    pub fn hz(self) -> f64 {
+        use self::AccelSampleRate::*;
+        match self {
+            Hz_14_9 => 14.9,
+            Hz_59_5 => 59.5,
+            Hz_119 => 119.0,
+            Hz_238 => 238.0,
+            Hz_476 => 476.0,
+            Hz_952 => 952.0,
+        }
+    }
+
+    /// Returns the accelerometer sample rate matching a gyro sample rate,
+    /// as they run together.
+    pub(crate) fn matching(rate: GyroSampleRate) -> Self {
+        use self::AccelSampleRate::*;
+        match rate {
+            GyroSampleRate::Hz_14_9 => Hz_14_9,
+            GyroSampleRate::Hz_59_5 => Hz_59_5,
+            GyroSampleRate::Hz_119 => Hz_119,
+            GyroSampleRate::Hz_238 => Hz_238,
+            GyroSampleRate::Hz_476 => Hz_476,
+            GyroSampleRate::Hz_952 => Hz_952,
+        }
+    }
+
+    /// The ODR_XL bits of CTRL_REG6_XL.
+    pub(crate) fn bits(self) -> u8 {
+        use self::AccelSampleRate::*;
+        match self {
+            Hz_14_9 => 0b001,
+            Hz_59_5 => 0b010,
+            Hz_119 => 0b011,
+            Hz_238 => 0b100,
+            Hz_476 => 0b101,
+            Hz_952 => 0b110,
+        }
+    }
+}
+
+impl AccelFullScaleRange {
+    /// The FS_XL bits of CTRL_REG6_XL.
+    pub(crate) fn bits(self) -> u8 {
+        use self::AccelFullScaleRange::*;
+        match self {
+            G2 => 0b00,
+            G16 => 0b01,
+            G4 => 0b10,
+            G8 => 0b11,
+        }
+    }
+
+    /// Decodes the FS_XL bits of a CTRL_REG6_XL value, ignoring the rest.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::AccelFullScaleRange::{self, G16, G2, G4, G8};
+    ///
+    /// // The sample rate and filter bits either side don't matter.
+    /// assert_eq!(AccelFullScaleRange::from_ctrl6_xl(0b010_00_111), G2);
+    /// assert_eq!(AccelFullScaleRange::from_ctrl6_xl(0b010_01_111), G16);
+    /// assert_eq!(AccelFullScaleRange::from_ctrl6_xl(0b010_10_111), G4);
+    /// assert_eq!(AccelFullScaleRange::from_ctrl6_xl(0b010_11_111), G8);
+    ///
+    /// assert_eq!(G2.scale(), 0.000061);
+    /// assert_eq!(G4.scale(), 0.000122);
+    /// assert_eq!(G8.scale(), 0.000244);
+    /// assert_eq!(G16.scale(), 0.000732);
+    /// ```
+    pub fn from_ctrl6_xl(value: u8) -> Self {
+        use self::AccelFullScaleRange::*;
+        match (value >> 3) & 0b11 {
+            0b00 => G2,
+            0b01 => G16,
+            0b10 => G4,
+            _ => G8,
+        }
+    }
+
+    /// The sensitivity, in g per LSB.
+    pub fn scale(self) -> f64 {
+        use self::AccelFullScaleRange::*;
+        match self {
+            G2 => 0.000061,
+            G4 => 0.000122,
+            G8 => 0.000244,
+            G16 => 0.000732,
+        }
+    }
+}
+
+impl AccelLowPassFilter {
+    /// The BW_XL bits of CTRL_REG6_XL.
+    pub(crate) fn bits(self) -> u8 {
+        use self::AccelLowPassFilter::*;
+        match self {
+            Hz_408 => 0b00,
+            Hz_211 => 0b01,
+            Hz_105 => 0b10,
+            Hz_50 => 0b11,
+        }
+    }
+
+    /// Decodes the BW_XL bits of a CTRL_REG6_XL value, ignoring the rest.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::AccelLowPassFilter::{self, Hz_105, Hz_211, Hz_408, Hz_50};
+    ///
+    /// assert_eq!(AccelLowPassFilter::from_ctrl6_xl(0b010_11_100), Hz_408);
+    /// assert_eq!(AccelLowPassFilter::from_ctrl6_xl(0b010_11_101), Hz_211);
+    /// assert_eq!(AccelLowPassFilter::from_ctrl6_xl(0b010_11_110), Hz_105);
+    /// assert_eq!(AccelLowPassFilter::from_ctrl6_xl(0b010_11_111), Hz_50);
+    /// ```
+    pub fn from_ctrl6_xl(value: u8) -> Self {
+        use self::AccelLowPassFilter::*;
+        match value & 0b11 {
+            0b00 => Hz_408,
+            0b01 => Hz_211,
+            0b10 => Hz_105,
+            _ => Hz_50,
+        }
+    }
+}
+
+impl CompassSampleRate {
+    /// The DO bits of CTRL_REG1_M.
+    pub(crate) fn bits(self) -> u8 {
+        use self::CompassSampleRate::*;
+        match self {
+            Hz_0_625 => 0b000,
+            Hz_1_25 => 0b001,
+            Hz_2_5 => 0b010,
+            Hz_5 => 0b011,
+            Hz_10 => 0b100,
+            Hz_20 => 0b101,
+            Hz_40 => 0b110,
+            Hz_80 => 0b111,
+        }
+    }
+
+    /// Decodes the DO bits of a CTRL_REG1_M value, ignoring the rest.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::CompassSampleRate::{self, Hz_0_625, Hz_10, Hz_80};
+    ///
+    /// assert_eq!(CompassSampleRate::from_ctrl1_m(0b1_11_000_00), Hz_0_625);
+    /// assert_eq!(CompassSampleRate::from_ctrl1_m(0b1_11_100_00), Hz_10);
+    /// assert_eq!(CompassSampleRate::from_ctrl1_m(0b0_00_111_11), Hz_80);
+    /// assert_eq!(Hz_0_625.hz(), 0.625);
+    /// assert_eq!(Hz_80.hz(), 80.0);
+    /// ```
+    pub fn from_ctrl1_m(value: u8) -> Self {
+        use self::CompassSampleRate::*;
+        match (value >> 2) & 0b111 {
+            0b000 => Hz_0_625,
+            0b001 => Hz_1_25,
+            0b010 => Hz_2_5,
+            0b011 => Hz_5,
+            0b100 => Hz_10,
+            0b101 => Hz_20,
+            0b110 => Hz_40,
+            _ => Hz_80,
+        }
+    }
+
+    /// The sample rate in Hz.
+    pub fn hz(self) -> f64 {
+        use self::CompassSampleRate::*;
+        match self {
+            Hz_0_625 => 0.625,
+            Hz_1_25 => 1.25,
+            Hz_2_5 => 2.5,
+            Hz_5 => 5.0,
+            Hz_10 => 10.0,
+            Hz_20 => 20.0,
+            Hz_40 => 40.0,
+            Hz_80 => 80.0,
+        }
+    }
+}
+
+impl CompassFullScaleRange {
+    /// The FS bits of CTRL_REG2_M.
+    pub(crate) fn bits(self) -> u8 {
+        use self::CompassFullScaleRange::*;
+        match self {
+            uT_4 => 0b00,
+            uT_8 => 0b01,
+            uT_12 => 0b10,
+            uT_16 => 0b11,
+        }
+    }
+
+    /// Decodes the FS bits of a CTRL_REG2_M value, ignoring the rest.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sensehat::CompassCalibration;
+    /// use sensehat::CompassFullScaleRange::{self, uT_12, uT_16, uT_4, uT_8};
+    ///
+    /// assert_eq!(CompassFullScaleRange::from_ctrl2_m(0b0_00_0_1100), uT_4);
+    /// assert_eq!(CompassFullScaleRange::from_ctrl2_m(0b0_01_0_1100), uT_8);
+    /// assert_eq!(CompassFullScaleRange::from_ctrl2_m(0b0_10_0_1100), uT_12);
+    /// assert_eq!(CompassFullScaleRange::from_ctrl2_m(0b0_11_0_1100), uT_16);
+    ///
+    /// assert_eq!(uT_4.scale(), 0.014);
+    /// assert_eq!(uT_16.scale(), 0.058);
+    ///
+    /// // Readings are scaled to microtesla before they're calibrated, so a
+    /// // calibration keeps working when the range changes.
+    /// let calibration = CompassCalibration { offset: [10.0, 0.0, 0.0], scale: [1.0; 3] };
+    /// let field = 29.0;
+    /// let counts_4 = (field / uT_4.scale()).round();
+    /// let counts_16 = (field / uT_16.scale()).round();
+    /// assert_ne!(counts_4, counts_16);
+    /// let at_4 = calibration.apply([counts_4 * uT_4.scale(), 0.0, 0.0]);
+    /// let at_16 = calibration.apply([counts_16 * uT_16.scale(), 0.0, 0.0]);
+    /// assert!((at_4[0] - 19.0).abs() < 0.01);
+    /// assert!((at_16[0] - 19.0).abs() < 0.01);
+    /// ```
+    pub fn from_ctrl2_m(value: u8) -> Self {
+        use self::CompassFullScaleRange::*;
+        match (value >> 5) & 0b11 {
+            0b00 => uT_4,
+            0b01 => uT_8,
+            0b10 => uT_12,
+            _ => uT_16,
+        }
+    }
+
+    /// The sensitivity, in microtesla per LSB.
+    pub fn scale(self) -> f64 {
+        use self::CompassFullScaleRange::*;
+        match self {
+            uT_4 => 0.014,
+            uT_8 => 0.029,
+            uT_12 => 0.043,
+            uT_16 => 0.058,
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use device::SenseHat;
+#[cfg(feature = "sensors")]
+use device::{Pressure, RelativeHumidity, Temperature};
+#[cfg(feature = "display")]
+use display::Pixel;
+#[cfg(feature = "display")]
+use text::{MessageMetrics, TextStyle};
+use {SenseHatError, SenseHatResult};
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A `SenseHat` which can be cloned and used from several threads at once.
+///
+/// Every clone uses the same Sense Hat. Each method locks it only while it
+/// runs, so one thread can read the sensors while another draws on the LED
+/// matrix. `lock` gets at anything which isn't forwarded here.
+///
+/// If a thread panics while it has the Sense Hat locked, every method
+/// returns `Poisoned` from then on, rather than panicking too.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(all(feature = "display", feature = "sensors"))]
+/// # {
+/// use sensehat::{SenseHat, SharedSenseHat};
+/// use std::thread;
+///
+/// let sense = SharedSenseHat::new(SenseHat::new().unwrap());
+/// let reader = sense.clone();
+/// let thread = thread::spawn(move || {
+///     for _ in 0..10 {
+///         println!("{}", reader.get_temperature_from_humidity().unwrap());
+///     }
+/// });
+/// for x in 0..8 {
+///     sense.set_pixel(x, 0, (0, 0, 255)).unwrap();
+/// }
+/// thread.join().unwrap();
+/// # }
+/// ```
+///
+/// Off the hardware, with the barometer mocked, two threads reading it at
+/// once, and a third panicking with it locked:
+///
+/// ```
+/// # #[cfg(feature = "sensors")]
+/// # {
+/// use sensehat::{MockI2CDevice, SenseHat, SenseHatError, SharedSenseHat};
+/// use std::thread;
+///
+/// let pressure = MockI2CDevice::new();
+/// pressure.set_registers(0x27, &[0x03, 0x00, 0x00, 0x40]);
+/// let hat = SenseHat::builder().pressure_device(pressure).build().unwrap();
+/// let sense = SharedSenseHat::new(hat);
+///
+/// let readers: Vec<_> = (0..2)
+///     .map(|_| {
+///         let sense = sense.clone();
+///         thread::spawn(move || {
+///             for _ in 0..100 {
+///                 assert_eq!(sense.get_pressure().unwrap().as_hectopascals(), 1024.0);
+///             }
+///         })
+///     })
+///     .collect();
+/// for reader in readers {
+///     reader.join().unwrap();
+/// }
+///
+/// let panicker = sense.clone();
+/// let guard = thread::spawn(move || {
+///     let _hat = panicker.lock().unwrap();
+///     panic!("with the Sense Hat locked");
+/// });
+/// assert!(guard.join().is_err());
+/// match sense.get_pressure() {
+///     Err(SenseHatError::Poisoned) => {}
+///     other => panic!("{:?}", other.map(|p| p.as_hectopascals())),
+/// }
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct SharedSenseHat {
+    hat: Arc<Mutex<SenseHat>>,
+}
+
+impl SharedSenseHat {
+    /// Shares a `SenseHat`.
+    pub fn new(hat: SenseHat) -> Self {
+        SharedSenseHat {
+            hat: Arc::new(Mutex::new(hat)),
+        }
+    }
+
+    /// Locks the Sense Hat, for as long as the guard is kept. Returns
+    /// `Poisoned` if a thread panicked with it locked.
+    pub fn lock(&self) -> SenseHatResult<MutexGuard<'_, SenseHat>> {
+        self.hat.lock().map_err(|_| SenseHatError::Poisoned)
+    }
+
+    /// Returns the mutex the Sense Hat is kept in, say for
+    /// `install_cleanup_handler`.
+    pub fn as_arc(&self) -> &Arc<Mutex<SenseHat>> {
+        &self.hat
+    }
+
+    /// See `SenseHat::get_temperature_from_humidity`.
+    #[cfg(feature = "sensors")]
+    pub fn get_temperature_from_humidity(&self) -> SenseHatResult<Temperature> {
+        self.lock()?.get_temperature_from_humidity()
+    }
+
+    /// See `SenseHat::get_pressure`.
+    #[cfg(feature = "sensors")]
+    pub fn get_pressure(&self) -> SenseHatResult<Pressure> {
+        self.lock()?.get_pressure()
+    }
+
+    /// See `SenseHat::get_humidity`.
+    #[cfg(feature = "sensors")]
+    pub fn get_humidity(&self) -> SenseHatResult<RelativeHumidity> {
+        self.lock()?.get_humidity()
+    }
+
+    /// See `SenseHat::set_pixel`.
+    #[cfg(feature = "display")]
+    pub fn set_pixel(&self, x: usize, y: usize, p: Pixel) -> SenseHatResult<()> {
+        self.lock()?.set_pixel(x, y, p)
+    }
+
+    /// See `SenseHat::set_pixels`.
+    #[cfg(feature = "display")]
+    pub fn set_pixels(&self, pixels: &[Pixel; 64]) -> SenseHatResult<()> {
+        self.lock()?.set_pixels(pixels)
+    }
+
+    /// See `SenseHat::clear`.
+    #[cfg(feature = "display")]
+    pub fn clear(&self, color: Option<Pixel>) -> SenseHatResult<()> {
+        self.lock()?.clear(color)
+    }
+
+    /// See `SenseHat::show_message`. The Sense Hat stays locked until the
+    /// message has finished scrolling, so other threads wait for it.
+    #[cfg(feature = "display")]
+    pub fn show_message(
+        &self,
+        text: &str,
+        color: Pixel,
+        style: &TextStyle,
+    ) -> SenseHatResult<MessageMetrics> {
+        self.lock()?.show_message(text, color, style)
+    }
+}
+
+impl From<SenseHat> for SharedSenseHat {
+    fn from(hat: SenseHat) -> Self {
+        SharedSenseHat::new(hat)
+    }
+}
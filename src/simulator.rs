@@ -0,0 +1,370 @@
+use display::{convert_to_pixel, LedMatrix, Pixel, DEFAULT_GAMMA, LOW_LIGHT_GAMMA};
+use joystick::{Action, Direction, Joystick, JoystickEvent, DIRECTIONS};
+use mock_joystick::MockJoystick;
+use raster::{rasterize, RasterLayout};
+use SenseHatResult;
+
+use byteorder::{ByteOrder, LittleEndian};
+use minifb::{Key, Window, WindowOptions};
+
+use std::io;
+use std::ops::Range;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+/// How often the window is redrawn, at most.
+const WINDOW_FPS: usize = 60;
+
+/// How long a key is held before it starts repeating, as the kernel has
+/// it for the joystick.
+const REPEAT_DELAY: Duration = Duration::from_millis(250);
+
+/// How often a held key repeats, as the kernel has it for the joystick.
+const REPEAT_PERIOD: Duration = Duration::from_millis(33);
+
+/// An LED matrix shown in a window on the desktop, for running programs
+/// off the Pi. Give it to `SenseHatBuilder::led_matrix` or
+/// `Display::with_matrix`, and show it in a `SimulatorWindow`.
+///
+/// Clones share the same matrix, so one can be drawn on while another is
+/// watched.
+///
+/// # Example
+///
+/// The window shows what `rasterize` draws from the matrix, which can be
+/// checked without opening it.
+///
+/// ```
+/// use sensehat::{Display, RasterLayout, SimulatorDisplay};
+///
+/// let simulator = SimulatorDisplay::new();
+/// let mut display = Display::with_matrix(simulator.clone());
+/// display.set_pixel(1, 0, (255, 0, 0)).unwrap();
+/// assert_eq!(simulator.pixels()[1], (255, 0, 0));
+///
+/// let layout = RasterLayout { cell: 2, gap: 1, background: (0, 0, 0) };
+/// let image = simulator.rasterize(&layout);
+/// assert_eq!(image.len(), 25 * 25);
+/// // The second square along the top row, a gap in from the top.
+/// assert_eq!(&image[25 + 4..25 + 6], &[0xff0000, 0xff0000]);
+/// assert_eq!(&image[25 + 1..25 + 3], &[0, 0]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SimulatorDisplay {
+    state: Arc<Mutex<SimulatorState>>,
+}
+
+/// The frame and gamma table of a `SimulatorDisplay`, and whether its
+/// window is closed.
+#[derive(Debug)]
+struct SimulatorState {
+    frame: [u8; 128],
+    gamma: [u8; 32],
+    /// true once the window showing it has been closed
+    closed: bool,
+}
+
+impl Default for SimulatorState {
+    fn default() -> Self {
+        SimulatorState {
+            frame: [0; 128],
+            gamma: DEFAULT_GAMMA,
+            closed: false,
+        }
+    }
+}
+
+/// A window showing a `SimulatorDisplay`, redrawn as it changes.
+///
+/// Windows have to stay on the thread which opened them, and on macOS
+/// that has to be the main thread, so open the window there, draw on
+/// the display from another thread, and `run` the window.
+///
+/// Once the window is closed, writing to the display returns an error,
+/// so a program drawing on it stops.
+///
+/// The arrow keys and Enter work the joystick from `joystick`, while the
+/// window has the keyboard.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::thread;
+/// use std::time::Duration;
+/// use sensehat::{RasterLayout, SenseHat, SimulatorDisplay, SimulatorWindow};
+///
+/// let simulator = SimulatorDisplay::new();
+/// let layout = RasterLayout::default();
+/// let mut window = SimulatorWindow::open("Sense HAT", &simulator, layout).unwrap();
+/// let mut joystick = window.joystick().unwrap();
+/// thread::spawn(move || {
+///     let mut sense = SenseHat::builder().led_matrix(simulator).build().unwrap();
+///     for i in 0.. {
+///         sense.clear(Some((0, 0, (i % 8) as u8 * 32))).unwrap();
+///         let timeout = Some(Duration::from_millis(100));
+///         if let Some(event) = joystick.wait_for_event(timeout).unwrap() {
+///             println!("{:?}", event);
+///         }
+///     }
+/// });
+/// window.run().unwrap();
+/// ```
+pub struct SimulatorWindow {
+    window: Window,
+    display: SimulatorDisplay,
+    layout: RasterLayout,
+    keys: KeyboardJoystick,
+    // Where the keys' joystick events go, once `joystick` has been called
+    joystick: Option<Sender<JoystickEvent>>,
+}
+
+/// Turns the arrow keys and Enter into joystick events, as the kernel
+/// sends them for the real joystick: a press when a key goes down, a
+/// repeat every 33ms once it has been held for 250ms, and a release when
+/// it comes up. `SimulatorWindow` feeds it the window's keys.
+///
+/// # Example
+///
+/// ```
+/// extern crate minifb;
+/// extern crate sensehat;
+///
+/// use minifb::Key;
+/// use sensehat::{Action, Direction, KeyboardJoystick, MockJoystick};
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// # fn main() {
+/// let at = |ms: u64| UNIX_EPOCH + Duration::from_millis(1_000_000_000 + ms);
+/// let mut keys = KeyboardJoystick::new();
+/// let mut update = |down: &[Key], ms| -> Vec<(Direction, Action)> {
+///     keys.update(down, at(ms)).iter().map(|e| (e.direction, e.action)).collect()
+/// };
+/// // Keys which aren't the joystick's are ignored.
+/// assert_eq!(update(&[Key::Up, Key::A], 0), [(Direction::Up, Action::Pressed)]);
+/// assert!(update(&[Key::Up], 200).is_empty());
+/// let held = |ms| (Direction::Up, Action::Held(Duration::from_millis(ms)));
+/// assert_eq!(update(&[Key::Up], 250), [held(250)]);
+/// assert!(update(&[Key::Up], 270).is_empty());
+/// let enter = (Direction::Enter, Action::Pressed);
+/// assert_eq!(update(&[Key::Up, Key::Enter], 290), [held(290), enter]);
+/// assert_eq!(
+///     update(&[], 300),
+///     [(Direction::Up, Action::Released), (Direction::Enter, Action::Released)]
+/// );
+///
+/// // Through a joystick, a key held for a second is a long press, as the
+/// // joystick's is.
+/// let (mut joystick, mut mock) = MockJoystick::new().unwrap();
+/// let mut keys = KeyboardJoystick::new();
+/// for ms in (0..=1100).step_by(10) {
+///     let down: &[Key] = if ms < 1100 { &[Key::Enter] } else { &[] };
+///     for event in keys.update(down, at(ms)) {
+///         mock.send(event).unwrap();
+///     }
+/// }
+/// let actions: Vec<Action> = joystick
+///     .get_events()
+///     .unwrap()
+///     .iter()
+///     .map(|event| event.action)
+///     .filter(|action| match *action {
+///         Action::Held(_) => false,
+///         _ => true,
+///     })
+///     .collect();
+/// assert_eq!(
+///     actions,
+///     [Action::Pressed, Action::LongPress(Duration::from_millis(1010)), Action::Released]
+/// );
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct KeyboardJoystick {
+    // When each direction's key went down, and when it last repeated
+    down: [Option<(SystemTime, SystemTime)>; 5],
+}
+
+impl SimulatorDisplay {
+    /// Makes a black matrix, with the default gamma table.
+    pub fn new() -> Self {
+        SimulatorDisplay::default()
+    }
+
+    /// Returns what's on the matrix, row by row from the top left, as it
+    /// appears on the LEDs.
+    pub fn pixels(&self) -> [Pixel; 64] {
+        let state = self.lock();
+        let mut pixels = [(0, 0, 0); 64];
+        for (pixel, value) in pixels.iter_mut().zip(state.frame.chunks(2)) {
+            *pixel = convert_to_pixel(LittleEndian::read_u16(value));
+        }
+        pixels
+    }
+
+    /// Draws what's on the matrix as the window shows it, with
+    /// `rasterize`.
+    pub fn rasterize(&self, layout: &RasterLayout) -> Vec<u32> {
+        rasterize(&self.pixels(), layout)
+    }
+
+    /// Helper function.
+    ///
+    /// Locks the shared state, even if a thread panicked holding it.
+    fn lock(&self) -> MutexGuard<'_, SimulatorState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl LedMatrix for SimulatorDisplay {
+    fn write(&mut self, frame: &[u8; 128], changed: &[Range<usize>]) -> SenseHatResult<()> {
+        let mut state = self.lock();
+        if state.closed {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "simulator window closed").into());
+        }
+        for range in changed {
+            state.frame[range.clone()].copy_from_slice(&frame[range.clone()]);
+        }
+        Ok(())
+    }
+
+    fn gamma(&self) -> [u8; 32] {
+        self.lock().gamma
+    }
+
+    fn set_gamma(&mut self, table: &[u8; 32]) {
+        self.lock().gamma = *table;
+    }
+
+    fn reset_gamma(&mut self, low_light: bool) {
+        self.lock().gamma = if low_light { LOW_LIGHT_GAMMA } else { DEFAULT_GAMMA };
+    }
+}
+
+impl SimulatorWindow {
+    /// Opens a window showing `display`, drawn with `layout`. Returns an
+    /// error if there's no desktop to open it on.
+    pub fn open(
+        title: &str,
+        display: &SimulatorDisplay,
+        layout: RasterLayout,
+    ) -> SenseHatResult<Self> {
+        let size = layout.size();
+        let mut window = Window::new(title, size, size, WindowOptions::default())
+            .map_err(|err| window_error("open", err))?;
+        window.set_target_fps(WINDOW_FPS);
+        Ok(SimulatorWindow {
+            window,
+            display: display.clone(),
+            layout,
+            keys: KeyboardJoystick::new(),
+            joystick: None,
+        })
+    }
+
+    /// Returns a joystick worked by the arrow keys and Enter, as
+    /// `KeyboardJoystick` turns them into events, in place of any joystick
+    /// this returned before.
+    pub fn joystick(&mut self) -> SenseHatResult<Joystick> {
+        let (joystick, sender) = MockJoystick::channel()?;
+        self.joystick = Some(sender);
+        Ok(joystick)
+    }
+
+    /// Redraws the window and handles its events, waiting long enough to
+    /// keep to 60 frames a second. Returns false once the window has been
+    /// closed.
+    pub fn update(&mut self) -> SenseHatResult<bool> {
+        if !self.window.is_open() {
+            self.display.lock().closed = true;
+            return Ok(false);
+        }
+        let size = self.layout.size();
+        let image = self.display.rasterize(&self.layout);
+        self.window
+            .update_with_buffer(&image, size, size)
+            .map_err(|err| window_error("draw", err))?;
+        let events = self.keys.update(&self.window.get_keys(), SystemTime::now());
+        if let Some(ref sender) = self.joystick {
+            for event in events {
+                // The joystick may have been dropped, which is fine.
+                let _ = sender.send(event);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Keeps the window up to date until it's closed.
+    pub fn run(mut self) -> SenseHatResult<()> {
+        while self.update()? {}
+        Ok(())
+    }
+}
+
+impl KeyboardJoystick {
+    /// Makes a mapping with no keys down.
+    pub fn new() -> Self {
+        KeyboardJoystick::default()
+    }
+
+    /// Returns the direction a key works, if any.
+    pub fn direction(key: Key) -> Option<Direction> {
+        match key {
+            Key::Up => Some(Direction::Up),
+            Key::Down => Some(Direction::Down),
+            Key::Left => Some(Direction::Left),
+            Key::Right => Some(Direction::Right),
+            Key::Enter | Key::NumPadEnter => Some(Direction::Enter),
+            _ => None,
+        }
+    }
+
+    /// Returns the joystick events since the last call, given the keys
+    /// which are down at `now`, in the order of `Direction`.
+    pub fn update(&mut self, keys: &[Key], now: SystemTime) -> Vec<JoystickEvent> {
+        let mut events = Vec::new();
+        for &direction in DIRECTIONS.iter() {
+            let down = keys.iter().any(|&key| KeyboardJoystick::direction(key) == Some(direction));
+            let slot = &mut self.down[direction.index()];
+            let action = match (*slot, down) {
+                (None, true) => {
+                    *slot = Some((now, now));
+                    Action::Pressed
+                }
+                (Some(_), false) => {
+                    *slot = None;
+                    Action::Released
+                }
+                (Some((pressed, repeated)), true) => {
+                    let since = |time: SystemTime| now.duration_since(time).unwrap_or_default();
+                    let due = if repeated == pressed { REPEAT_DELAY } else { REPEAT_PERIOD };
+                    if since(repeated) < due {
+                        continue;
+                    }
+                    *slot = Some((pressed, now));
+                    Action::Held(since(pressed))
+                }
+                (None, false) => continue,
+            };
+            events.push(JoystickEvent {
+                direction,
+                action,
+                timestamp: now,
+            });
+        }
+        events
+    }
+}
+
+impl Drop for SimulatorWindow {
+    fn drop(&mut self) {
+        self.display.lock().closed = true;
+    }
+}
+
+/// Helper function.
+///
+/// Makes the error for the window failing to do `what`.
+fn window_error(what: &str, err: minifb::Error) -> io::Error {
+    io::Error::other(format!("couldn't {} the simulator window: {}", what, err))
+}
@@ -0,0 +1,251 @@
+use fusion::true_heading;
+use {SenseHatError, SenseHatResult};
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// An exponentially weighted moving average, for smoothing noisy
+/// readings, as used by `SenseHat::set_smoothing`.
+///
+/// Each new value moves the average `alpha` of the way towards it, so an
+/// `alpha` of 1 doesn't smooth at all, and smaller ones smooth more. The
+/// first value starts the average off. If a maximum age is set, a value
+/// coming more than that long after the last one starts it off again
+/// rather than being averaged with stale readings. Nothing here reads the
+/// clock, so the times are whatever the caller passes in.
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use sensehat::Ewma;
+///
+/// let start = Instant::now();
+/// let at = |s| start + Duration::from_secs(s);
+///
+/// let mut ewma = Ewma::new(0.5).unwrap();
+/// assert_eq!(ewma.value(), None);
+/// assert_eq!(ewma.update(10.0, at(0)), 10.0);
+/// assert_eq!(ewma.update(20.0, at(1)), 15.0);
+/// assert_eq!(ewma.update(20.0, at(2)), 17.5);
+///
+/// // A long gap starts it off again.
+/// ewma.set_max_age(Some(Duration::from_secs(60)));
+/// assert_eq!(ewma.update(0.0, at(3)), 8.75);
+/// assert_eq!(ewma.update(30.0, at(100)), 30.0);
+///
+/// assert!(Ewma::new(0.0).is_err());
+/// assert!(Ewma::new(1.5).is_err());
+/// assert!(Ewma::new(f64::NAN).is_err());
+/// assert!(Ewma::new(1.0).is_ok());
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ewma {
+    alpha: f64,
+    max_age: Option<Duration>,
+    /// The average, and when it was last updated.
+    last: Option<(f64, Instant)>,
+}
+
+impl Ewma {
+    /// Makes an average with nothing in it yet. Returns `InvalidSmoothing`
+    /// unless `alpha` is more than 0 and no more than 1.
+    pub fn new(alpha: f64) -> SenseHatResult<Self> {
+        if !(alpha > 0.0 && alpha <= 1.0) {
+            return Err(SenseHatError::InvalidSmoothing(alpha));
+        }
+        Ok(Ewma {
+            alpha,
+            max_age: None,
+            last: None,
+        })
+    }
+
+    /// Returns how far each new value moves the average.
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    /// Returns how long a gap between values starts the average off again,
+    /// if it does.
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age
+    }
+
+    /// Changes how long a gap between values starts the average off again.
+    /// None means never.
+    pub fn set_max_age(&mut self, max_age: Option<Duration>) {
+        self.max_age = max_age;
+    }
+
+    /// Adds a value taken at `now`, and returns the new average.
+    pub fn update(&mut self, value: f64, now: Instant) -> f64 {
+        let average = match self.last {
+            Some((average, then)) if !self.is_stale(then, now) => {
+                average + self.alpha * (value - average)
+            }
+            _ => value,
+        };
+        self.last = Some((average, now));
+        average
+    }
+
+    /// Returns the average so far, or None if there have been no values
+    /// since it was made or reset.
+    pub fn value(&self) -> Option<f64> {
+        self.last.map(|(average, _)| average)
+    }
+
+    /// Helper function.
+    ///
+    /// Returns true if a value at `now` is too long after one at `then` to
+    /// be averaged with it.
+    fn is_stale(&self, then: Instant, now: Instant) -> bool {
+        self.max_age
+            .is_some_and(|max_age| now.saturating_duration_since(then) > max_age)
+    }
+
+    /// Forgets the values so far, so the next one starts it off again.
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+/// Smooths compass headings by averaging the last few, as used by
+/// `Imu::set_heading_smoothing`.
+///
+/// Headings are averaged with `circular_mean`, so ones either side of
+/// north average to north rather than south. Only the last `window`
+/// headings are kept, and if a maximum age is set, headings older than
+/// that are dropped too, so the average doesn't lag behind after a gap.
+/// Nothing here reads the clock, so the times are whatever the caller
+/// passes in.
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use sensehat::HeadingSmoother;
+///
+/// let start = Instant::now();
+/// let at = |s| start + Duration::from_secs(s);
+///
+/// let mut smoother = HeadingSmoother::new(3, Some(Duration::from_secs(10)));
+/// assert_eq!(smoother.update(350.0, at(0)).round(), 350.0);
+/// assert_eq!(smoother.update(10.0, at(1)).round(), 0.0);
+/// assert_eq!(smoother.update(30.0, at(2)).round(), 10.0);
+/// // Only the last three count.
+/// assert_eq!(smoother.update(50.0, at(3)).round(), 30.0);
+///
+/// // After a long gap the old headings are dropped.
+/// assert_eq!(smoother.update(180.0, at(100)).round(), 180.0);
+/// assert_eq!(smoother.len(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadingSmoother {
+    window: usize,
+    max_age: Option<Duration>,
+    /// The headings kept, oldest first, with when they were taken.
+    headings: VecDeque<(f64, Instant)>,
+}
+
+impl HeadingSmoother {
+    /// Makes a smoother averaging up to `window` headings, at least one,
+    /// none older than `max_age` if that's set.
+    pub fn new(window: usize, max_age: Option<Duration>) -> Self {
+        HeadingSmoother {
+            window: window.max(1),
+            max_age,
+            headings: VecDeque::new(),
+        }
+    }
+
+    /// Returns how many headings are averaged, at most.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Returns how old a heading can be and still be averaged, if there's
+    /// a limit.
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age
+    }
+
+    /// Changes how old a heading can be and still be averaged. None means
+    /// any age.
+    pub fn set_max_age(&mut self, max_age: Option<Duration>) {
+        self.max_age = max_age;
+    }
+
+    /// Returns how many headings are kept.
+    pub fn len(&self) -> usize {
+        self.headings.len()
+    }
+
+    /// Returns true if no headings are kept.
+    pub fn is_empty(&self) -> bool {
+        self.headings.is_empty()
+    }
+
+    /// Adds a heading in degrees taken at `now`, and returns the average
+    /// of the headings kept, from 0 up to 360. If they cancel out, so
+    /// there's no average, the heading just added is returned.
+    pub fn update(&mut self, heading: f64, now: Instant) -> f64 {
+        if let Some(max_age) = self.max_age {
+            while self
+                .headings
+                .front()
+                .is_some_and(|&(_, then)| now.saturating_duration_since(then) > max_age)
+            {
+                self.headings.pop_front();
+            }
+        }
+        self.headings.push_back((heading, now));
+        while self.headings.len() > self.window {
+            self.headings.pop_front();
+        }
+        let headings: Vec<f64> = self.headings.iter().map(|&(heading, _)| heading).collect();
+        circular_mean(&headings).unwrap_or_else(|| true_heading(heading, 0.0))
+    }
+
+    /// Forgets the headings so far.
+    pub fn reset(&mut self) {
+        self.headings.clear();
+    }
+}
+
+/// Returns the average of some angles in degrees, from 0 up to 360,
+/// worked out from the average of their sines and cosines so it's right
+/// either side of 0.
+///
+/// Returns None if there are no angles, or they cancel out, like 0 and
+/// 180, so there's no direction to give.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::circular_mean;
+///
+/// let near = |mean: Option<f64>, expected: f64| (mean.unwrap() - expected).abs() < 1e-9;
+///
+/// assert!(near(circular_mean(&[10.0, 20.0, 30.0]), 20.0));
+/// // Averaging plainly would give 180.
+/// assert!(near(circular_mean(&[359.0, 1.0]), 0.0));
+/// assert_eq!(circular_mean(&[350.0, 355.0, 0.0, 5.0, 20.0]).unwrap().round(), 2.0);
+/// assert!(near(circular_mean(&[-90.0, 270.0, 630.0]), 270.0));
+///
+/// assert_eq!(circular_mean(&[]), None);
+/// assert_eq!(circular_mean(&[0.0, 180.0]), None);
+/// assert_eq!(circular_mean(&[0.0, 90.0, 180.0, 270.0]), None);
+/// ```
+pub fn circular_mean(degrees: &[f64]) -> Option<f64> {
+    let (sin, cos) = degrees.iter().fold((0.0, 0.0), |(sin, cos), angle: &f64| {
+        let (s, c) = angle.to_radians().sin_cos();
+        (sin + s, cos + c)
+    });
+    // Rounding leaves angles which cancel out a little way off 0.
+    if degrees.is_empty() || sin.hypot(cos) < 1e-9 * degrees.len() as f64 {
+        return None;
+    }
+    Some(true_heading(sin.atan2(cos).to_degrees(), 0.0))
+}
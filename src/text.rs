@@ -0,0 +1,740 @@
+use animation::Animation;
+use display::{Display, Pixel};
+use SenseHatResult;
+
+use std::time::{Duration, Instant};
+
+/// How many columns wide each character of the font is.
+const GLYPH_WIDTH: usize = 5;
+
+/// How many columns wide a space is in proportional text, since it has no
+/// columns to measure.
+const SPACE_WIDTH: usize = 3;
+
+/// The character drawn for characters the font doesn't have.
+const REPLACEMENT: char = '?';
+
+/// A 5x7 font for the printable ASCII characters, from space to `~`. Each
+/// character is five columns, left to right, with the top row in the
+/// lowest bit.
+const FONT: [[u8; GLYPH_WIDTH]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x00, 0x00, 0x5f, 0x00, 0x00], // '!'
+    [0x00, 0x07, 0x00, 0x07, 0x00], // '"'
+    [0x14, 0x7f, 0x14, 0x7f, 0x14], // '#'
+    [0x24, 0x2a, 0x7f, 0x2a, 0x12], // '$'
+    [0x23, 0x13, 0x08, 0x64, 0x62], // '%'
+    [0x36, 0x49, 0x55, 0x22, 0x50], // '&'
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '\''
+    [0x00, 0x1c, 0x22, 0x41, 0x00], // '('
+    [0x00, 0x41, 0x22, 0x1c, 0x00], // ')'
+    [0x08, 0x2a, 0x1c, 0x2a, 0x08], // '*'
+    [0x08, 0x08, 0x3e, 0x08, 0x08], // '+'
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ','
+    [0x08, 0x08, 0x08, 0x08, 0x08], // '-'
+    [0x00, 0x60, 0x60, 0x00, 0x00], // '.'
+    [0x20, 0x10, 0x08, 0x04, 0x02], // '/'
+    [0x3e, 0x51, 0x49, 0x45, 0x3e], // '0'
+    [0x00, 0x42, 0x7f, 0x40, 0x00], // '1'
+    [0x42, 0x61, 0x51, 0x49, 0x46], // '2'
+    [0x21, 0x41, 0x45, 0x4b, 0x31], // '3'
+    [0x18, 0x14, 0x12, 0x7f, 0x10], // '4'
+    [0x27, 0x45, 0x45, 0x45, 0x39], // '5'
+    [0x3c, 0x4a, 0x49, 0x49, 0x30], // '6'
+    [0x01, 0x71, 0x09, 0x05, 0x03], // '7'
+    [0x36, 0x49, 0x49, 0x49, 0x36], // '8'
+    [0x06, 0x49, 0x49, 0x29, 0x1e], // '9'
+    [0x00, 0x36, 0x36, 0x00, 0x00], // ':'
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ';'
+    [0x08, 0x14, 0x22, 0x41, 0x00], // '<'
+    [0x14, 0x14, 0x14, 0x14, 0x14], // '='
+    [0x00, 0x41, 0x22, 0x14, 0x08], // '>'
+    [0x02, 0x01, 0x51, 0x09, 0x06], // '?'
+    [0x32, 0x49, 0x79, 0x41, 0x3e], // '@'
+    [0x7e, 0x11, 0x11, 0x11, 0x7e], // 'A'
+    [0x7f, 0x49, 0x49, 0x49, 0x36], // 'B'
+    [0x3e, 0x41, 0x41, 0x41, 0x22], // 'C'
+    [0x7f, 0x41, 0x41, 0x22, 0x1c], // 'D'
+    [0x7f, 0x49, 0x49, 0x49, 0x41], // 'E'
+    [0x7f, 0x09, 0x09, 0x01, 0x01], // 'F'
+    [0x3e, 0x41, 0x41, 0x51, 0x32], // 'G'
+    [0x7f, 0x08, 0x08, 0x08, 0x7f], // 'H'
+    [0x00, 0x41, 0x7f, 0x41, 0x00], // 'I'
+    [0x20, 0x40, 0x41, 0x3f, 0x01], // 'J'
+    [0x7f, 0x08, 0x14, 0x22, 0x41], // 'K'
+    [0x7f, 0x40, 0x40, 0x40, 0x40], // 'L'
+    [0x7f, 0x02, 0x04, 0x02, 0x7f], // 'M'
+    [0x7f, 0x04, 0x08, 0x10, 0x7f], // 'N'
+    [0x3e, 0x41, 0x41, 0x41, 0x3e], // 'O'
+    [0x7f, 0x09, 0x09, 0x09, 0x06], // 'P'
+    [0x3e, 0x41, 0x51, 0x21, 0x5e], // 'Q'
+    [0x7f, 0x09, 0x19, 0x29, 0x46], // 'R'
+    [0x46, 0x49, 0x49, 0x49, 0x31], // 'S'
+    [0x01, 0x01, 0x7f, 0x01, 0x01], // 'T'
+    [0x3f, 0x40, 0x40, 0x40, 0x3f], // 'U'
+    [0x1f, 0x20, 0x40, 0x20, 0x1f], // 'V'
+    [0x7f, 0x20, 0x18, 0x20, 0x7f], // 'W'
+    [0x63, 0x14, 0x08, 0x14, 0x63], // 'X'
+    [0x03, 0x04, 0x78, 0x04, 0x03], // 'Y'
+    [0x61, 0x51, 0x49, 0x45, 0x43], // 'Z'
+    [0x00, 0x7f, 0x41, 0x41, 0x00], // '['
+    [0x02, 0x04, 0x08, 0x10, 0x20], // '\\'
+    [0x00, 0x41, 0x41, 0x7f, 0x00], // ']'
+    [0x04, 0x02, 0x01, 0x02, 0x04], // '^'
+    [0x40, 0x40, 0x40, 0x40, 0x40], // '_'
+    [0x00, 0x01, 0x02, 0x04, 0x00], // '`'
+    [0x20, 0x54, 0x54, 0x54, 0x78], // 'a'
+    [0x7f, 0x48, 0x44, 0x44, 0x38], // 'b'
+    [0x38, 0x44, 0x44, 0x44, 0x20], // 'c'
+    [0x38, 0x44, 0x44, 0x48, 0x7f], // 'd'
+    [0x38, 0x54, 0x54, 0x54, 0x18], // 'e'
+    [0x08, 0x7e, 0x09, 0x01, 0x02], // 'f'
+    [0x08, 0x54, 0x54, 0x54, 0x3c], // 'g'
+    [0x7f, 0x08, 0x04, 0x04, 0x78], // 'h'
+    [0x00, 0x44, 0x7d, 0x40, 0x00], // 'i'
+    [0x20, 0x40, 0x44, 0x3d, 0x00], // 'j'
+    [0x7f, 0x10, 0x28, 0x44, 0x00], // 'k'
+    [0x00, 0x41, 0x7f, 0x40, 0x00], // 'l'
+    [0x7c, 0x04, 0x18, 0x04, 0x78], // 'm'
+    [0x7c, 0x08, 0x04, 0x04, 0x78], // 'n'
+    [0x38, 0x44, 0x44, 0x44, 0x38], // 'o'
+    [0x7c, 0x14, 0x14, 0x14, 0x08], // 'p'
+    [0x08, 0x14, 0x14, 0x18, 0x7c], // 'q'
+    [0x7c, 0x08, 0x04, 0x04, 0x08], // 'r'
+    [0x48, 0x54, 0x54, 0x54, 0x20], // 's'
+    [0x04, 0x3f, 0x44, 0x40, 0x20], // 't'
+    [0x3c, 0x40, 0x40, 0x20, 0x7c], // 'u'
+    [0x1c, 0x20, 0x40, 0x20, 0x1c], // 'v'
+    [0x3c, 0x40, 0x30, 0x40, 0x3c], // 'w'
+    [0x44, 0x28, 0x10, 0x28, 0x44], // 'x'
+    [0x0c, 0x50, 0x50, 0x50, 0x3c], // 'y'
+    [0x44, 0x64, 0x54, 0x4c, 0x44], // 'z'
+    [0x00, 0x08, 0x36, 0x41, 0x00], // '{'
+    [0x00, 0x00, 0x7f, 0x00, 0x00], // '|'
+    [0x00, 0x41, 0x36, 0x08, 0x00], // '}'
+    [0x02, 0x01, 0x02, 0x04, 0x02], // '~'
+];
+
+/// Which way `show_message` scrolls text.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ScrollDirection {
+    /// Entering from the right and leaving to the left.
+    Left,
+    /// Entering from the left and leaving to the right. The characters
+    /// aren't mirrored, and the text still reads left to right.
+    Right,
+}
+
+/// What `show_message` shows behind text.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TextBackground {
+    /// A single colour behind the text.
+    Solid(Pixel),
+    /// Whatever is on the LED matrix already, with only the text drawn
+    /// over it, so the image shows round the text and comes back once the
+    /// text has moved on.
+    Transparent,
+}
+
+/// How `show_message` lays out and scrolls text.
+///
+/// The defaults match the Python Sense HAT library: a column every 100ms,
+/// one blank column between characters, and a screen's width of blank
+/// columns before and after, so the message scrolls in from the right and
+/// all the way off to the left.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TextStyle {
+    /// How long each step of the scroll, one column, is shown for.
+    pub speed: Duration,
+    /// How many blank columns go between characters.
+    pub char_spacing: u8,
+    /// How many blank columns go before the message.
+    pub leading_cols: u8,
+    /// How many blank columns go after the message.
+    pub trailing_cols: u8,
+    /// Which way the message scrolls.
+    pub direction: ScrollDirection,
+    /// Whether each character only takes the columns it uses, so narrow
+    /// ones like `i` scroll past quicker, rather than the font's full
+    /// width, leaving `char_spacing` as the only gap between them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Instant;
+    /// use sensehat::{measure_message, text_columns, Display, MemoryMatrix, TextStyle};
+    ///
+    /// let style = TextStyle { proportional: true, leading_cols: 0, ..TextStyle::default() };
+    /// let glyph = TextStyle { trailing_cols: 0, ..style };
+    /// // An i is three columns wide and a j four, where both are five
+    /// // columns without it.
+    /// assert_eq!(text_columns("i", &glyph).len(), 3);
+    /// assert_eq!(text_columns("j", &glyph).len(), 4);
+    /// // So "ij" with one column between them, and 8 trailing.
+    /// assert_eq!(measure_message("ij", &style).columns, 3 + 1 + 4 + 8);
+    ///
+    /// // The first frame shows both characters, lit where their columns are.
+    /// let matrix = MemoryMatrix::new();
+    /// let mut display = Display::with_matrix(matrix.clone());
+    /// let now = Instant::now();
+    /// let handle = display.start_message("ij", (255, 255, 255), &style, now);
+    /// assert!(display.update_message(&handle, now).unwrap());
+    /// let pixels = matrix.pixels();
+    /// let columns = text_columns("ij", &glyph);
+    /// for x in 0..8 {
+    ///     for y in 0..8 {
+    ///         let lit = (columns[x] >> y) & 1 == 1;
+    ///         assert_eq!(pixels[y * 8 + x] == (255, 255, 255), lit, "at ({}, {})", x, y);
+    ///     }
+    /// }
+    /// // Every column is lit apart from the one between the characters.
+    /// let lit = |x: usize| (0..8).any(|y| pixels[y * 8 + x] != (0, 0, 0));
+    /// let lit: Vec<bool> = (0..8).map(lit).collect();
+    /// assert_eq!(lit, [true, true, true, false, true, true, true, true]);
+    /// ```
+    pub proportional: bool,
+    /// What `show_message` shows behind the text.
+    pub background: TextBackground,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        TextStyle {
+            speed: Duration::from_millis(100),
+            char_spacing: 1,
+            leading_cols: 8,
+            trailing_cols: 8,
+            direction: ScrollDirection::Left,
+            proportional: false,
+            background: TextBackground::Solid((0, 0, 0)),
+        }
+    }
+}
+
+/// How big a scrolled message is, as returned by `measure_message` and
+/// `show_message`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MessageMetrics {
+    /// How many columns the message is laid out in, including the blank
+    /// columns before and after it.
+    pub columns: usize,
+    /// How long the message takes to scroll.
+    pub duration: Duration,
+}
+
+/// Works out how many columns `text` takes, and how long `show_message`
+/// takes to scroll it, without showing it.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use sensehat::{measure_message, TextStyle};
+///
+/// let metrics = measure_message("Hi", &TextStyle::default());
+/// assert_eq!(metrics.columns, 27);
+/// // A frame for each of the 20 positions of the screen along the strip.
+/// assert_eq!(metrics.duration, Duration::from_millis(2000));
+/// ```
+pub fn measure_message(text: &str, style: &TextStyle) -> MessageMetrics {
+    metrics(&text_columns(text, style), style)
+}
+
+/// Formats a number for showing on the LED matrix, rounded to `decimals`
+/// places, with `suffix` after it. If `strip_zeros` is set, zeros at the
+/// end of the decimals are left off, and the point too if they all are.
+/// NaN and infinite values come out as `--`, so a bad reading doesn't stop
+/// a display, and a value which rounds to zero never has a minus sign.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::format_value;
+///
+/// assert_eq!(format_value(21.345, 1, false, "C"), "21.3C");
+/// assert_eq!(format_value(21.35, 1, false, "C"), "21.4C");
+/// assert_eq!(format_value(1013.25, 0, false, " hPa"), "1013 hPa");
+/// assert_eq!(format_value(9.5, 0, false, ""), "10");
+/// assert_eq!(format_value(21.0, 2, false, ""), "21.00");
+/// assert_eq!(format_value(21.0, 2, true, ""), "21");
+/// assert_eq!(format_value(21.50, 2, true, "%"), "21.5%");
+/// assert_eq!(format_value(100.0, 0, true, ""), "100");
+/// assert_eq!(format_value(-0.04, 1, false, ""), "0.0");
+/// assert_eq!(format_value(-3.25, 1, false, ""), "-3.2");
+/// assert_eq!(format_value(f64::NAN, 1, false, "C"), "--C");
+/// assert_eq!(format_value(f64::NEG_INFINITY, 0, true, " hPa"), "-- hPa");
+/// ```
+pub fn format_value(value: f64, decimals: u8, strip_zeros: bool, suffix: &str) -> String {
+    if !value.is_finite() {
+        return format!("--{}", suffix);
+    }
+    let mut number = format!("{:.*}", decimals as usize, value);
+    if strip_zeros && number.contains('.') {
+        let kept = number.trim_end_matches('0').trim_end_matches('.').len();
+        number.truncate(kept);
+    }
+    if number.starts_with('-') && number[1..].chars().all(|c| c == '0' || c == '.') {
+        number.remove(0);
+    }
+    number + suffix
+}
+
+/// Lays out `text` as a strip of columns, left to right, ready to scroll
+/// across the LED matrix. Each column has the top row in the lowest bit.
+///
+/// Characters the font doesn't have are drawn as `?`.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{measure_message, text_columns, TextStyle};
+///
+/// let style = TextStyle::default();
+/// // 8 leading, 5 + 1 + 5 for the two characters, and 8 trailing.
+/// assert_eq!(text_columns("Hi", &style).len(), 27);
+///
+/// let tight = TextStyle { char_spacing: 0, leading_cols: 0, trailing_cols: 0, ..style };
+/// let columns = text_columns("Hi", &tight);
+/// assert_eq!(columns.len(), 10);
+/// // The left edge of the H is a full height line.
+/// assert_eq!(columns[0], 0b1111_1110);
+///
+/// // Proportional characters are only as wide as they need to be, with
+/// // one blank column between them, and spaces three columns wide.
+/// let proportional = TextStyle { proportional: true, char_spacing: 1, ..tight };
+/// assert_eq!(text_columns("ill", &proportional).len(), 3 + 1 + 3 + 1 + 3);
+/// assert_eq!(text_columns("WWW", &proportional).len(), 5 + 1 + 5 + 1 + 5);
+/// assert_eq!(text_columns("a b", &proportional).len(), 5 + 1 + 3 + 1 + 5);
+/// assert_eq!(measure_message("ill", &proportional).columns, 11);
+/// ```
+pub fn text_columns(text: &str, style: &TextStyle) -> Vec<u8> {
+    let mut columns = vec![0; style.leading_cols as usize];
+    for (i, c) in text.chars().enumerate() {
+        if i > 0 {
+            columns.extend((0..style.char_spacing).map(|_| 0));
+        }
+        let glyph = glyph(c);
+        let used = if !style.proportional {
+            &glyph[..]
+        } else if c == ' ' {
+            &glyph[..SPACE_WIDTH]
+        } else {
+            trim_glyph(glyph)
+        };
+        // Leave the top row blank, like the Python library.
+        columns.extend(used.iter().map(|&column| column << 1));
+    }
+    columns.extend((0..style.trailing_cols).map(|_| 0));
+    columns
+}
+
+/// Renders the whole scroll of `text` as an animation, in `fg` on `bg`,
+/// with a frame for each step of the scroll shown for the style's speed.
+/// These are the frames `show_message` plays, so a message can be
+/// rendered once and played, or saved, as often as it's wanted.
+///
+/// There's a frame for each place the screen can be along the laid out
+/// columns, so with the default style the first and last frames are
+/// blank, as the message scrolls in and out.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{measure_message, render_message, ScrollDirection, TextStyle};
+///
+/// let style = TextStyle::default();
+/// let (fg, bg) = ((255, 255, 255), (0, 0, 64));
+/// let animation = render_message("Hi", &style, fg, bg);
+/// let metrics = measure_message("Hi", &style);
+///
+/// assert_eq!(animation.len(), metrics.columns - 7);
+/// assert_eq!(animation.total_duration(), metrics.duration);
+/// assert_eq!(animation.duration(0), Some(style.speed));
+/// assert_eq!(animation.frame(0), Some([bg; 64]));
+/// assert_eq!(animation.frame(animation.len() - 1), Some([bg; 64]));
+/// // The H is on the screen on its way across.
+/// assert!(animation.frame(8).unwrap().contains(&fg));
+///
+/// // Scrolling right shows the same frames the other way round.
+/// let right = TextStyle { direction: ScrollDirection::Right, ..style };
+/// let mut frames: Vec<_> = animation.iter().collect();
+/// frames.reverse();
+/// assert_eq!(render_message("Hi", &right, fg, bg).iter().collect::<Vec<_>>(), frames);
+/// ```
+pub fn render_message(text: &str, style: &TextStyle, fg: Pixel, bg: Pixel) -> Animation {
+    let frames = message_indices(text, style);
+    let durations = vec![style.speed; frames.len()];
+    Animation::from_paletted(frames, vec![bg, fg], durations)
+        .expect("message frames only use the two colours")
+}
+
+/// Renders the whole scroll of `text` as an animation, as
+/// `render_message` does, but drawn in `fg` over `base` rather than on a
+/// solid background. Every pixel the text isn't on is `base`'s at every
+/// step, so the image is put back as the text moves on.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{render_message_over, TextStyle};
+///
+/// let mut base = [(0, 0, 0); 64];
+/// for (i, pixel) in base.iter_mut().enumerate() {
+///     *pixel = (0, i as u8 * 4, 255 - i as u8 * 4);
+/// }
+/// let fg = (255, 255, 255);
+/// let animation = render_message_over("Hi ho", &TextStyle::default(), fg, &base);
+///
+/// for (frame, _) in animation.iter() {
+///     for (pixel, original) in frame.iter().zip(base.iter()) {
+///         assert!(pixel == original || *pixel == fg);
+///     }
+/// }
+/// assert!(animation.iter().any(|(frame, _)| frame.contains(&fg)));
+/// // Once the message has gone, the image is as it was.
+/// assert_eq!(animation.frame(animation.len() - 1), Some(base));
+/// ```
+pub fn render_message_over(
+    text: &str,
+    style: &TextStyle,
+    fg: Pixel,
+    base: &[Pixel; 64],
+) -> Animation {
+    let mut animation = Animation::new();
+    for glyphs in message_indices(text, style) {
+        let mut frame = *base;
+        for (pixel, &lit) in frame.iter_mut().zip(glyphs.iter()) {
+            if lit == 1 {
+                *pixel = fg;
+            }
+        }
+        animation
+            .push(&frame, style.speed)
+            .expect("an image and a text colour fit in a palette");
+    }
+    animation
+}
+
+/// A message scrolling across the LED matrix without blocking, for
+/// `Display::update_message` to draw.
+///
+/// It plays the animation `render_message` makes, or any other, working
+/// out which frame to show from the times it's given, so it can be
+/// paused, say while the joystick is held, and resumed from the same
+/// column without jumping ahead. Nothing here reads the clock.
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use sensehat::{render_message, MessageHandle, TextStyle};
+///
+/// let style = TextStyle::default();
+/// let start = Instant::now();
+/// let at = |ms| start + Duration::from_millis(ms);
+///
+/// let mut handle = MessageHandle::new("Hi", (255, 0, 0), &style, start);
+/// assert_eq!(handle.column_at(at(250)), 2);
+///
+/// // Nothing moves while it's paused.
+/// handle.pause(at(250));
+/// assert!(handle.is_paused());
+/// assert_eq!(handle.column_at(at(5000)), 2);
+///
+/// // And it carries on from the same place, 50ms into column 2.
+/// handle.resume(at(5000));
+/// assert_eq!(handle.column_at(at(5049)), 2);
+/// assert_eq!(handle.column_at(at(5050)), 3);
+/// let animation = render_message("Hi", &style, (255, 0, 0), (0, 0, 0));
+/// assert_eq!(handle.frame_at(at(5050)), animation.frame(3));
+///
+/// // It finishes 2 seconds of scrolling after it started.
+/// assert!(!handle.is_finished(at(6749)));
+/// assert!(handle.is_finished(at(6750)));
+/// assert_eq!(handle.frame_at(at(6750)), None);
+///
+/// // Cancelling works while paused too.
+/// let mut handle = MessageHandle::new("Hi", (255, 0, 0), &style, start);
+/// handle.pause(at(100));
+/// handle.cancel();
+/// assert!(handle.is_finished(at(100)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageHandle {
+    animation: Animation,
+    start: Instant,
+    /// When the pause in progress started.
+    paused_at: Option<Instant>,
+    /// How long it was paused for before, not counting a pause in progress.
+    paused_for: Duration,
+    cancelled: bool,
+}
+
+impl MessageHandle {
+    /// Starts scrolling `text` in the given color at `now`, laid out and
+    /// scrolled the way `style` says, on its background. A transparent
+    /// background needs the image underneath, which `Display::start_message`
+    /// has, so here the text is drawn over black.
+    pub fn new(text: &str, color: Pixel, style: &TextStyle, now: Instant) -> Self {
+        let bg = match style.background {
+            TextBackground::Solid(bg) => bg,
+            TextBackground::Transparent => (0, 0, 0),
+        };
+        MessageHandle::from_animation(render_message(text, style, color, bg), now)
+    }
+
+    /// Starts playing `animation` at `now`, one frame after another, such
+    /// as a message from `render_message_over`.
+    pub fn from_animation(animation: Animation, now: Instant) -> Self {
+        MessageHandle {
+            animation,
+            start: now,
+            paused_at: None,
+            paused_for: Duration::from_secs(0),
+            cancelled: false,
+        }
+    }
+
+    /// Returns how many columns the message has scrolled by at `now`, which
+    /// is which frame of the animation it's on, not counting time spent
+    /// paused. Stops going up once it's finished.
+    pub fn column_at(&self, now: Instant) -> usize {
+        let now = self.paused_at.unwrap_or(now);
+        let elapsed = now
+            .saturating_duration_since(self.start)
+            .checked_sub(self.paused_for)
+            .unwrap_or_default();
+        self.animation.frame_at(elapsed).unwrap_or_else(|| self.animation.len())
+    }
+
+    /// Returns the frame to show at `now`, or None if the message has
+    /// finished or been cancelled.
+    pub fn frame_at(&self, now: Instant) -> Option<[Pixel; 64]> {
+        if self.cancelled {
+            return None;
+        }
+        self.animation.frame(self.column_at(now))
+    }
+
+    /// Returns true if the message has finished scrolling at `now`, or has
+    /// been cancelled.
+    pub fn is_finished(&self, now: Instant) -> bool {
+        self.frame_at(now).is_none()
+    }
+
+    /// Stops the message moving at `now`, keeping the frame it's on. Does
+    /// nothing if it's already paused.
+    pub fn pause(&mut self, now: Instant) {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(now);
+        }
+    }
+
+    /// Starts the message moving again at `now`, from where it was paused.
+    /// Does nothing if it isn't paused.
+    pub fn resume(&mut self, now: Instant) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused_for += now.saturating_duration_since(paused_at);
+        }
+    }
+
+    /// Returns true if the message is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Stops the message, whether or not it's paused.
+    pub fn cancel(&mut self) {
+        self.cancelled = true;
+    }
+}
+
+/// Helper function.
+///
+/// Returns how many steps it takes to scroll a strip of columns across the
+/// LED matrix, one a column, starting with its first eight columns.
+fn scroll_steps(columns: &[u8]) -> usize {
+    columns.len().saturating_sub(8) + 1
+}
+
+/// Helper function.
+///
+/// Returns the metrics of a laid out strip of columns.
+fn metrics(columns: &[u8], style: &TextStyle) -> MessageMetrics {
+    MessageMetrics {
+        columns: columns.len(),
+        duration: style.speed * scroll_steps(columns) as u32,
+    }
+}
+
+/// Helper function.
+///
+/// Returns the font's columns for a character.
+fn glyph(c: char) -> &'static [u8; GLYPH_WIDTH] {
+    let index = match c {
+        ' '..='~' => c as usize - ' ' as usize,
+        _ => REPLACEMENT as usize - ' ' as usize,
+    };
+    &FONT[index]
+}
+
+/// Helper function.
+///
+/// Returns the columns of a glyph without the blank ones either side.
+fn trim_glyph(glyph: &[u8; GLYPH_WIDTH]) -> &[u8] {
+    let start = glyph.iter().position(|&column| column != 0).unwrap_or(0);
+    let end = glyph.iter().rposition(|&column| column != 0).map_or(start, |end| end + 1);
+    &glyph[start..end]
+}
+
+/// Helper function.
+///
+/// Returns each step of scrolling `text`, in order, as 1 for each lit
+/// pixel and 0 for the rest.
+fn message_indices(text: &str, style: &TextStyle) -> Vec<[u8; 64]> {
+    let columns = text_columns(text, style);
+    let mut offsets: Vec<usize> = (0..scroll_steps(&columns)).collect();
+    if style.direction == ScrollDirection::Right {
+        offsets.reverse();
+    }
+    offsets.iter().map(|&offset| indices_at(&columns, offset)).collect()
+}
+
+/// Helper function.
+///
+/// Returns the 8x8 frame showing the eight columns starting at `offset`,
+/// as 1 for each lit pixel and 0 for the rest.
+fn indices_at(columns: &[u8], offset: usize) -> [u8; 64] {
+    let mut indices = [0; 64];
+    for x in 0..8 {
+        let column = columns.get(offset + x).cloned().unwrap_or(0);
+        for y in 0..8 {
+            if column & (1 << y) != 0 {
+                indices[x + 8 * y] = 1;
+            }
+        }
+    }
+    indices
+}
+
+impl Display {
+    /// Scrolls `text` across the LED matrix in the given color, laid out
+    /// and scrolled the way `style` says. Blocks until the scroll is finished,
+    /// then returns how big the message was, the same as
+    /// `measure_message`.
+    ///
+    /// This plays the animation `render_message` makes, on the style's
+    /// solid background, or `render_message_over` makes over the image
+    /// on the LED matrix if the background is transparent.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sensehat::{Display, TextBackground, TextStyle};
+    ///
+    /// let mut display = Display::new().unwrap();
+    /// display.show_message("Hello!", (255, 255, 0), &TextStyle::default()).unwrap();
+    ///
+    /// // Over a picture instead.
+    /// display.clear(Some((0, 0, 128))).unwrap();
+    /// let over = TextStyle { background: TextBackground::Transparent, ..TextStyle::default() };
+    /// display.show_message("21.5C", (255, 255, 0), &over).unwrap();
+    /// ```
+    pub fn show_message(
+        &mut self,
+        text: &str,
+        color: Pixel,
+        style: &TextStyle,
+    ) -> SenseHatResult<MessageMetrics> {
+        let animation = self.render_message(text, color, style);
+        self.play_animation(&animation)?;
+        Ok(measure_message(text, style))
+    }
+
+    /// Starts scrolling `text` at `now` without blocking, for
+    /// `update_message` to draw, with the same frames `show_message`
+    /// plays, over the image on the LED matrix if the background is
+    /// transparent.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use sensehat::{Display, MemoryMatrix, TextBackground, TextStyle};
+    ///
+    /// let matrix = MemoryMatrix::new();
+    /// let mut display = Display::with_matrix(matrix.clone());
+    /// let start = Instant::now();
+    /// let at = |ms| start + Duration::from_millis(ms);
+    ///
+    /// // The style's background is behind the text.
+    /// let background = TextBackground::Solid((0, 0, 255));
+    /// let style = TextStyle { background, ..TextStyle::default() };
+    /// let handle = display.start_message("Hi", (255, 255, 0), &style, start);
+    /// assert!(display.update_message(&handle, at(850)).unwrap());
+    /// assert!(matrix.pixels().contains(&(255, 255, 0)));
+    /// assert!(matrix.pixels().iter().all(|&p| p == (255, 255, 0) || p == (0, 0, 255)));
+    ///
+    /// // A transparent one shows the image round the text.
+    /// display.clear(Some((255, 0, 0))).unwrap();
+    /// let over = TextStyle { background: TextBackground::Transparent, ..style };
+    /// let handle = display.start_message("Hi", (255, 255, 0), &over, start);
+    /// assert!(display.update_message(&handle, at(850)).unwrap());
+    /// assert!(matrix.pixels().iter().all(|&p| p == (255, 255, 0) || p == (255, 0, 0)));
+    ///
+    /// // The last frame is the image, which is left showing.
+    /// assert!(display.update_message(&handle, at(1950)).unwrap());
+    /// assert!(!display.update_message(&handle, at(2000)).unwrap());
+    /// assert_eq!(matrix.pixels(), [(255, 0, 0); 64]);
+    /// ```
+    pub fn start_message(
+        &self,
+        text: &str,
+        color: Pixel,
+        style: &TextStyle,
+        now: Instant,
+    ) -> MessageHandle {
+        MessageHandle::from_animation(self.render_message(text, color, style), now)
+    }
+
+    /// Draws the frame `handle` should be showing at `now`. Returns false,
+    /// and leaves the display alone, once it's finished or been cancelled.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::thread::sleep;
+    /// use std::time::{Duration, Instant};
+    /// use sensehat::{Display, TextStyle};
+    ///
+    /// let mut display = Display::new().unwrap();
+    /// let style = TextStyle::default();
+    /// let handle = display.start_message("Hello!", (255, 255, 0), &style, Instant::now());
+    /// while display.update_message(&handle, Instant::now()).unwrap() {
+    ///     // Pause and resume the handle here, say from the joystick.
+    ///     sleep(Duration::from_millis(10));
+    /// }
+    /// ```
+    pub fn update_message(&mut self, handle: &MessageHandle, now: Instant) -> SenseHatResult<bool> {
+        match handle.frame_at(now) {
+            Some(frame) => {
+                self.set_pixels(&frame)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Helper function.
+    ///
+    /// Renders the frames of scrolling `text`, on the style's background or
+    /// over the image on the LED matrix.
+    fn render_message(&self, text: &str, color: Pixel, style: &TextStyle) -> Animation {
+        match style.background {
+            TextBackground::Solid(bg) => render_message(text, style, color, bg),
+            TextBackground::Transparent => {
+                render_message_over(text, style, color, &self.get_pixels())
+            }
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! Rotating, flipping and mirroring 8x8 images, without the LED matrix.
+//!
+//! The functions here take an image as `Display::set_pixels` does, row by
+//! row from the top left, and return a new one. Rotations are clockwise,
+//! as `Orientation` turns the image.
+//!
+//! # Example
+//!
+//! ```
+//! use sensehat::transform::{self, Transform};
+//!
+//! // Every pixel different, so nothing can hide a mistake.
+//! let mut image = [(0, 0, 0); 64];
+//! for (i, pixel) in image.iter_mut().enumerate() {
+//!     *pixel = (i as u8, 0, 0);
+//! }
+//!
+//! let turned = transform::rotate90(&image);
+//! assert_eq!(transform::rotate90(&transform::rotate90(&transform::rotate90(&turned))), image);
+//! assert_eq!(transform::flip_h(&transform::flip_v(&image)), transform::rotate180(&image));
+//! assert_eq!(transform::flip_h(&transform::flip_h(&image)), image);
+//! assert_eq!(transform::flip_v(&transform::flip_v(&image)), image);
+//! assert_eq!(transform::transpose(&transform::transpose(&image)), image);
+//! assert_eq!(transform::rotate270(&turned), image);
+//! // Transposing is flipping about the diagonal from the top left.
+//! assert_eq!(transform::transpose(&image), transform::flip_h(&turned));
+//!
+//! // The top left corner goes to the top right, then the bottom right.
+//! assert_eq!(turned[7], image[0]);
+//! assert_eq!(transform::rotate180(&image)[63], image[0]);
+//! // The top row goes to the bottom.
+//! assert_eq!(transform::flip_v(&image)[56..], image[..8]);
+//!
+//! // Composing gives one transform, which does the same as each in turn.
+//! let all = Transform::ALL;
+//! for &first in &all {
+//!     for &second in &all {
+//!         let both = transform::compose(&[first, second]);
+//!         assert_eq!(both.apply(&image), second.apply(&first.apply(&image)));
+//!     }
+//!     assert_eq!(transform::compose(&[first, first.inverse()]), Transform::Identity);
+//! }
+//! assert_eq!(transform::compose(&[Transform::Rotate90; 4]), Transform::Identity);
+//! assert_eq!(transform::compose(&[Transform::FlipH, Transform::FlipV]), Transform::Rotate180);
+//! assert_eq!(transform::compose(&[]), Transform::Identity);
+//! ```
+
+use display::{Orientation, Pixel};
+
+/// A way of turning or flipping an 8x8 image over onto itself. There are
+/// eight, and doing one after another always gives one of them, which
+/// `compose` works out.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Transform {
+    /// Leaves the image as it is.
+    #[default]
+    Identity,
+    /// Turns the image a quarter turn clockwise.
+    Rotate90,
+    /// Turns the image upside down.
+    Rotate180,
+    /// Turns the image a quarter turn anticlockwise.
+    Rotate270,
+    /// Mirrors the image left to right.
+    FlipH,
+    /// Mirrors the image top to bottom.
+    FlipV,
+    /// Mirrors the image about the diagonal from the top left to the
+    /// bottom right, swapping rows and columns.
+    Transpose,
+    /// Mirrors the image about the diagonal from the top right to the
+    /// bottom left.
+    AntiTranspose,
+}
+
+impl Transform {
+    /// Every transform.
+    pub const ALL: [Transform; 8] = [
+        Transform::Identity,
+        Transform::Rotate90,
+        Transform::Rotate180,
+        Transform::Rotate270,
+        Transform::FlipH,
+        Transform::FlipV,
+        Transform::Transpose,
+        Transform::AntiTranspose,
+    ];
+
+    /// Returns where the pixel at (x, y) ends up.
+    pub fn map(self, x: usize, y: usize) -> (usize, usize) {
+        match self {
+            Transform::Identity => (x, y),
+            Transform::Rotate90 => Orientation::Deg90.map(x, y, false),
+            Transform::Rotate180 => Orientation::Deg180.map(x, y, false),
+            Transform::Rotate270 => Orientation::Deg270.map(x, y, false),
+            Transform::FlipH => (7 - x, y),
+            Transform::FlipV => (x, 7 - y),
+            Transform::Transpose => (y, x),
+            Transform::AntiTranspose => (7 - y, 7 - x),
+        }
+    }
+
+    /// Returns the transform which undoes this one.
+    pub fn inverse(self) -> Transform {
+        match self {
+            Transform::Rotate90 => Transform::Rotate270,
+            Transform::Rotate270 => Transform::Rotate90,
+            other => other,
+        }
+    }
+
+    /// Returns the image transformed.
+    pub fn apply(self, pixels: &[Pixel; 64]) -> [Pixel; 64] {
+        let mut out = [(0, 0, 0); 64];
+        for (i, &pixel) in pixels.iter().enumerate() {
+            let (x, y) = self.map(i % 8, i / 8);
+            out[x + 8 * y] = pixel;
+        }
+        out
+    }
+}
+
+/// Turns the image a quarter turn clockwise.
+pub fn rotate90(pixels: &[Pixel; 64]) -> [Pixel; 64] {
+    Transform::Rotate90.apply(pixels)
+}
+
+/// Turns the image upside down.
+pub fn rotate180(pixels: &[Pixel; 64]) -> [Pixel; 64] {
+    Transform::Rotate180.apply(pixels)
+}
+
+/// Turns the image a quarter turn anticlockwise.
+pub fn rotate270(pixels: &[Pixel; 64]) -> [Pixel; 64] {
+    Transform::Rotate270.apply(pixels)
+}
+
+/// Mirrors the image left to right.
+pub fn flip_h(pixels: &[Pixel; 64]) -> [Pixel; 64] {
+    Transform::FlipH.apply(pixels)
+}
+
+/// Mirrors the image top to bottom.
+pub fn flip_v(pixels: &[Pixel; 64]) -> [Pixel; 64] {
+    Transform::FlipV.apply(pixels)
+}
+
+/// Swaps the image's rows and columns.
+pub fn transpose(pixels: &[Pixel; 64]) -> [Pixel; 64] {
+    Transform::Transpose.apply(pixels)
+}
+
+/// Returns the transform which does each of `transforms` in turn, first
+/// to last, so an image only has to be transformed once.
+pub fn compose(transforms: &[Transform]) -> Transform {
+    transforms.iter().fold(Transform::Identity, |so_far, &next| {
+        // (1, 2) isn't on any line a transform mirrors about, so each
+        // transform moves it somewhere different.
+        let (x, y) = so_far.map(1, 2);
+        let target = next.map(x, y);
+        Transform::ALL
+            .iter()
+            .cloned()
+            .find(|candidate| candidate.map(1, 2) == target)
+            .expect("transforms of the square always compose to one of the eight")
+    })
+}
+
+/// A quarter of the 8x8 image, four pixels by four.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Quadrant {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Makes a symmetrical image out of a 4x4 quadrant, row by row from the
+/// top left, by putting it in the top left corner and mirroring it into
+/// the other three.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::transform::{self, mirror_quadrant};
+///
+/// let mut quadrant = [(0, 0, 0); 16];
+/// for (i, pixel) in quadrant.iter_mut().enumerate() {
+///     *pixel = (i as u8 * 16, 255 - i as u8 * 16, 0);
+/// }
+/// let image = mirror_quadrant(&quadrant);
+/// assert_eq!(transform::flip_h(&image), image);
+/// assert_eq!(transform::flip_v(&image), image);
+/// assert_eq!(image[..4], quadrant[..4]);
+/// assert_eq!(image[7], quadrant[0]);
+/// assert_eq!(image[63], quadrant[0]);
+/// assert_eq!(image[8 * 4 + 4], quadrant[15]);
+/// ```
+pub fn mirror_quadrant(quadrant: &[Pixel; 16]) -> [Pixel; 64] {
+    let mut out = [(0, 0, 0); 64];
+    for (i, pixel) in out.iter_mut().enumerate() {
+        let (x, y) = (i % 8, i / 8);
+        *pixel = quadrant[x.min(7 - x) + 4 * y.min(7 - y)];
+    }
+    out
+}
+
+/// Makes a symmetrical image out of one quadrant of `frame`, leaving that
+/// quadrant where it is and mirroring it across both middles of the image
+/// into the other three. Goes well with `Display::rotate_hue` for cheap
+/// patterns.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::transform::{self, kaleidoscope, Quadrant};
+///
+/// let mut frame = [(0, 0, 0); 64];
+/// for (i, pixel) in frame.iter_mut().enumerate() {
+///     *pixel = (i as u8, 0, 255 - i as u8);
+/// }
+/// for &source in &[Quadrant::TopLeft, Quadrant::TopRight, Quadrant::BottomLeft, Quadrant::BottomRight] {
+///     let image = kaleidoscope(&frame, source);
+///     assert_eq!(transform::flip_h(&image), image);
+///     assert_eq!(transform::flip_v(&image), image);
+/// }
+///
+/// // The source quadrant is left as it was.
+/// let image = kaleidoscope(&frame, Quadrant::BottomRight);
+/// assert_eq!(image[8 * 7 + 7], frame[8 * 7 + 7]);
+/// assert_eq!(image[8 * 4 + 4], frame[8 * 4 + 4]);
+/// assert_eq!(image[0], frame[8 * 7 + 7]);
+/// ```
+pub fn kaleidoscope(frame: &[Pixel; 64], source: Quadrant) -> [Pixel; 64] {
+    let (left, top) = match source {
+        Quadrant::TopLeft => (true, true),
+        Quadrant::TopRight => (false, true),
+        Quadrant::BottomLeft => (true, false),
+        Quadrant::BottomRight => (false, false),
+    };
+    let mut out = [(0, 0, 0); 64];
+    for (i, pixel) in out.iter_mut().enumerate() {
+        let (x, y) = (i % 8, i / 8);
+        let x = if (x < 4) == left { x } else { 7 - x };
+        let y = if (y < 4) == top { y } else { 7 - y };
+        *pixel = frame[x + 8 * y];
+    }
+    out
+}
@@ -0,0 +1,302 @@
+use measurements::Measurement;
+
+use std::fmt;
+
+/// The `Angle` struct holds an angle, like the pitch of the Sense Hat.
+///
+/// It's stored in degrees, which is what the IMU works in, so
+/// `from_degrees` and `as_degrees` round trip exactly. Radians are
+/// converted with `f64::to_radians` and `f64::to_degrees`, which are exact
+/// to within the precision of an `f64`.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::Angle;
+///
+/// let right_angle = Angle::from_degrees(90.0);
+/// assert_eq!(right_angle.as_degrees(), 90.0);
+/// assert_eq!(right_angle.as_radians(), std::f64::consts::FRAC_PI_2);
+/// assert_eq!(format!("{}", right_angle), "90.0°");
+/// ```
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Angle {
+    degrees: f64,
+}
+
+impl Angle {
+    pub fn from_degrees(degrees: f64) -> Self {
+        Angle { degrees }
+    }
+
+    pub fn from_radians(radians: f64) -> Self {
+        Self::from_degrees(radians.to_degrees())
+    }
+
+    pub fn as_degrees(&self) -> f64 {
+        self.degrees
+    }
+
+    pub fn as_radians(&self) -> f64 {
+        self.degrees.to_radians()
+    }
+}
+
+impl Measurement for Angle {
+    fn get_base_units(&self) -> f64 {
+        self.degrees
+    }
+
+    fn from_base_units(units: f64) -> Self {
+        Self::from_degrees(units)
+    }
+}
+
+implement_measurement! { Angle }
+
+impl fmt::Display for Angle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.1}\u{00B0}", self.as_degrees())
+    }
+}
+
+/// The `AngularVelocity` struct holds a rate of rotation, like a gyroscope
+/// reading.
+///
+/// It's stored in degrees per second, which is what the gyroscope reports,
+/// and converted to radians per second the same way as `Angle`.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::AngularVelocity;
+///
+/// let rate = AngularVelocity::from_degrees_per_second(123.4);
+/// assert_eq!(rate.as_degrees_per_second(), 123.4);
+/// assert_eq!(format!("{}", rate), "123.4 °/s");
+///
+/// let half_turn = AngularVelocity::from_radians_per_second(std::f64::consts::PI);
+/// assert_eq!(half_turn.as_degrees_per_second(), 180.0);
+/// assert_eq!(half_turn.as_radians_per_second(), std::f64::consts::PI);
+/// ```
+#[derive(Copy, Clone, Debug, Default)]
+pub struct AngularVelocity {
+    degrees_per_second: f64,
+}
+
+impl AngularVelocity {
+    pub fn from_degrees_per_second(degrees_per_second: f64) -> Self {
+        AngularVelocity { degrees_per_second }
+    }
+
+    pub fn from_radians_per_second(radians_per_second: f64) -> Self {
+        Self::from_degrees_per_second(radians_per_second.to_degrees())
+    }
+
+    pub fn as_degrees_per_second(&self) -> f64 {
+        self.degrees_per_second
+    }
+
+    pub fn as_radians_per_second(&self) -> f64 {
+        self.degrees_per_second.to_radians()
+    }
+}
+
+impl Measurement for AngularVelocity {
+    fn get_base_units(&self) -> f64 {
+        self.degrees_per_second
+    }
+
+    fn from_base_units(units: f64) -> Self {
+        Self::from_degrees_per_second(units)
+    }
+}
+
+implement_measurement! { AngularVelocity }
+
+impl fmt::Display for AngularVelocity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.1} \u{00B0}/s", self.as_degrees_per_second())
+    }
+}
+
+/// The angular velocity around each of the X, Y and Z axes, as returned
+/// by `Imu::get_gyroscope`.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{AngularVelocity, AngularVelocity3d};
+///
+/// let rates = AngularVelocity3d::from_degrees_per_second([123.4, -5.0, 0.0]);
+/// assert_eq!(rates.x, AngularVelocity::from_degrees_per_second(123.4));
+/// assert_eq!(rates.degrees_per_second(), [123.4, -5.0, 0.0]);
+/// assert_eq!(format!("{}", rates), "x: 123.4 °/s, y: -5.0 °/s, z: 0.0 °/s");
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct AngularVelocity3d {
+    pub x: AngularVelocity,
+    pub y: AngularVelocity,
+    pub z: AngularVelocity,
+}
+
+impl AngularVelocity3d {
+    pub fn from_degrees_per_second(rates: [f64; 3]) -> Self {
+        AngularVelocity3d {
+            x: AngularVelocity::from_degrees_per_second(rates[0]),
+            y: AngularVelocity::from_degrees_per_second(rates[1]),
+            z: AngularVelocity::from_degrees_per_second(rates[2]),
+        }
+    }
+
+    /// Returns the X, Y and Z rates in degrees per second.
+    pub fn degrees_per_second(&self) -> [f64; 3] {
+        [
+            self.x.as_degrees_per_second(),
+            self.y.as_degrees_per_second(),
+            self.z.as_degrees_per_second(),
+        ]
+    }
+
+    /// Returns the X, Y and Z rates in radians per second.
+    pub fn radians_per_second(&self) -> [f64; 3] {
+        [
+            self.x.as_radians_per_second(),
+            self.y.as_radians_per_second(),
+            self.z.as_radians_per_second(),
+        ]
+    }
+}
+
+impl fmt::Display for AngularVelocity3d {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "x: {}, y: {}, z: {}", self.x, self.y, self.z)
+    }
+}
+
+/// Standard gravity, in metres per second squared per g.
+const STANDARD_GRAVITY: f64 = 9.80665;
+
+/// The `Acceleration` struct holds an acceleration, like an accelerometer
+/// reading.
+///
+/// It's stored in g, which is what the accelerometer reports, and
+/// converted to metres per second squared using standard gravity,
+/// 9.80665 m/s² per g.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::Acceleration;
+///
+/// let gravity = Acceleration::from_g(1.0);
+/// assert_eq!(gravity.as_g(), 1.0);
+/// assert_eq!(gravity.as_meters_per_second_squared(), 9.80665);
+/// assert_eq!(format!("{}", gravity), "1.00 g");
+///
+/// let half = Acceleration::from_meters_per_second_squared(4.903325);
+/// assert_eq!(half.as_g(), 0.5);
+/// ```
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Acceleration {
+    g: f64,
+}
+
+impl Acceleration {
+    pub fn from_g(g: f64) -> Self {
+        Acceleration { g }
+    }
+
+    pub fn from_meters_per_second_squared(meters_per_second_squared: f64) -> Self {
+        Self::from_g(meters_per_second_squared / STANDARD_GRAVITY)
+    }
+
+    pub fn as_g(&self) -> f64 {
+        self.g
+    }
+
+    pub fn as_meters_per_second_squared(&self) -> f64 {
+        self.g * STANDARD_GRAVITY
+    }
+}
+
+impl Measurement for Acceleration {
+    fn get_base_units(&self) -> f64 {
+        self.g
+    }
+
+    fn from_base_units(units: f64) -> Self {
+        Self::from_g(units)
+    }
+}
+
+implement_measurement! { Acceleration }
+
+impl fmt::Display for Acceleration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.2} g", self.as_g())
+    }
+}
+
+/// The acceleration along each of the X, Y and Z axes, as returned by
+/// `Imu::get_accelerometer`.
+///
+/// # Example
+///
+/// ```
+/// use sensehat::{Acceleration, Acceleration3d};
+///
+/// let accel = Acceleration3d::from_g([0.0, 0.6, 0.8]);
+/// assert_eq!(accel.y, Acceleration::from_g(0.6));
+/// assert_eq!(accel.g(), [0.0, 0.6, 0.8]);
+/// assert_eq!(accel.magnitude().as_g(), 1.0);
+/// assert_eq!(Acceleration3d::from_g([3.0, 4.0, 12.0]).magnitude().as_g(), 13.0);
+/// assert_eq!(Acceleration3d::default().magnitude().as_g(), 0.0);
+///
+/// let [_, _, z] = Acceleration3d::from_g([0.0, 0.0, 2.0]).meters_per_second_squared();
+/// assert_eq!(z, 19.6133);
+/// assert_eq!(format!("{}", accel), "x: 0.00 g, y: 0.60 g, z: 0.80 g");
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Acceleration3d {
+    pub x: Acceleration,
+    pub y: Acceleration,
+    pub z: Acceleration,
+}
+
+impl Acceleration3d {
+    pub fn from_g(accel: [f64; 3]) -> Self {
+        Acceleration3d {
+            x: Acceleration::from_g(accel[0]),
+            y: Acceleration::from_g(accel[1]),
+            z: Acceleration::from_g(accel[2]),
+        }
+    }
+
+    /// Returns the X, Y and Z accelerations in g.
+    pub fn g(&self) -> [f64; 3] {
+        [self.x.as_g(), self.y.as_g(), self.z.as_g()]
+    }
+
+    /// Returns the X, Y and Z accelerations in metres per second squared.
+    pub fn meters_per_second_squared(&self) -> [f64; 3] {
+        [
+            self.x.as_meters_per_second_squared(),
+            self.y.as_meters_per_second_squared(),
+            self.z.as_meters_per_second_squared(),
+        ]
+    }
+
+    /// Returns the total acceleration, whichever way it's pointing. It's
+    /// about 1 g at rest, from gravity, and about 0 in free fall.
+    pub fn magnitude(&self) -> Acceleration {
+        let [x, y, z] = self.g();
+        Acceleration::from_g((x * x + y * y + z * z).sqrt())
+    }
+}
+
+impl fmt::Display for Acceleration3d {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "x: {}, y: {}, z: {}", self.x, self.y, self.z)
+    }
+}